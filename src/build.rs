@@ -1,4 +1,11 @@
 fn main() -> std::io::Result<()> {
-    prost_build::compile_protos(&["src/protos/component.proto"], &["src/protos"])?;
+    prost_build::compile_protos(
+        &[
+            "src/protos/component.proto",
+            "src/protos/component_v2.proto",
+            "src/protos/snapshot.proto",
+        ],
+        &["src/protos"],
+    )?;
     Ok(())
 }
@@ -0,0 +1,599 @@
+use nom::{
+    error::{Error, ErrorKind},
+    number::complete::{be_i16, be_i32, be_i8, be_u16, be_u8},
+    IResult,
+};
+
+/// Mnemonics of the JVM instruction set indexed by opcode (JVMS chapter 6.5), up to and
+/// including `jsr_w`.
+const MNEMONICS: [&str; 202] = [
+    "nop",
+    "aconst_null",
+    "iconst_m1",
+    "iconst_0",
+    "iconst_1",
+    "iconst_2",
+    "iconst_3",
+    "iconst_4",
+    "iconst_5",
+    "lconst_0",
+    "lconst_1",
+    "fconst_0",
+    "fconst_1",
+    "fconst_2",
+    "dconst_0",
+    "dconst_1",
+    "bipush",
+    "sipush",
+    "ldc",
+    "ldc_w",
+    "ldc2_w",
+    "iload",
+    "lload",
+    "fload",
+    "dload",
+    "aload",
+    "iload_0",
+    "iload_1",
+    "iload_2",
+    "iload_3",
+    "lload_0",
+    "lload_1",
+    "lload_2",
+    "lload_3",
+    "fload_0",
+    "fload_1",
+    "fload_2",
+    "fload_3",
+    "dload_0",
+    "dload_1",
+    "dload_2",
+    "dload_3",
+    "aload_0",
+    "aload_1",
+    "aload_2",
+    "aload_3",
+    "iaload",
+    "laload",
+    "faload",
+    "daload",
+    "aaload",
+    "baload",
+    "caload",
+    "saload",
+    "istore",
+    "lstore",
+    "fstore",
+    "dstore",
+    "astore",
+    "istore_0",
+    "istore_1",
+    "istore_2",
+    "istore_3",
+    "lstore_0",
+    "lstore_1",
+    "lstore_2",
+    "lstore_3",
+    "fstore_0",
+    "fstore_1",
+    "fstore_2",
+    "fstore_3",
+    "dstore_0",
+    "dstore_1",
+    "dstore_2",
+    "dstore_3",
+    "astore_0",
+    "astore_1",
+    "astore_2",
+    "astore_3",
+    "iastore",
+    "lastore",
+    "fastore",
+    "dastore",
+    "aastore",
+    "bastore",
+    "castore",
+    "sastore",
+    "pop",
+    "pop2",
+    "dup",
+    "dup_x1",
+    "dup_x2",
+    "dup2",
+    "dup2_x1",
+    "dup2_x2",
+    "swap",
+    "iadd",
+    "ladd",
+    "fadd",
+    "dadd",
+    "isub",
+    "lsub",
+    "fsub",
+    "dsub",
+    "imul",
+    "lmul",
+    "fmul",
+    "dmul",
+    "idiv",
+    "ldiv",
+    "fdiv",
+    "ddiv",
+    "irem",
+    "lrem",
+    "frem",
+    "drem",
+    "ineg",
+    "lneg",
+    "fneg",
+    "dneg",
+    "ishl",
+    "lshl",
+    "ishr",
+    "lshr",
+    "iushr",
+    "lushr",
+    "iand",
+    "land",
+    "ior",
+    "lor",
+    "ixor",
+    "lxor",
+    "iinc",
+    "i2l",
+    "i2f",
+    "i2d",
+    "l2i",
+    "l2f",
+    "l2d",
+    "f2i",
+    "f2l",
+    "f2d",
+    "d2i",
+    "d2l",
+    "d2f",
+    "i2b",
+    "i2c",
+    "i2s",
+    "lcmp",
+    "fcmpl",
+    "fcmpg",
+    "dcmpl",
+    "dcmpg",
+    "ifeq",
+    "ifne",
+    "iflt",
+    "ifge",
+    "ifgt",
+    "ifle",
+    "if_icmpeq",
+    "if_icmpne",
+    "if_icmplt",
+    "if_icmpge",
+    "if_icmpgt",
+    "if_icmple",
+    "if_acmpeq",
+    "if_acmpne",
+    "goto",
+    "jsr",
+    "ret",
+    "tableswitch",
+    "lookupswitch",
+    "ireturn",
+    "lreturn",
+    "freturn",
+    "dreturn",
+    "areturn",
+    "return",
+    "getstatic",
+    "putstatic",
+    "getfield",
+    "putfield",
+    "invokevirtual",
+    "invokespecial",
+    "invokestatic",
+    "invokeinterface",
+    "invokedynamic",
+    "new",
+    "newarray",
+    "anewarray",
+    "arraylength",
+    "athrow",
+    "checkcast",
+    "instanceof",
+    "monitorenter",
+    "monitorexit",
+    "wide",
+    "multianewarray",
+    "ifnull",
+    "ifnonnull",
+    "goto_w",
+    "jsr_w",
+];
+
+pub mod opcodes {
+    pub const LDC: u8 = 0x12;
+    pub const LDC_W: u8 = 0x13;
+    pub const LDC2_W: u8 = 0x14;
+    pub const IINC: u8 = 0x84;
+    pub const GOTO: u8 = 0xa7;
+    pub const JSR: u8 = 0xa8;
+    pub const RET: u8 = 0xa9;
+    pub const TABLESWITCH: u8 = 0xaa;
+    pub const LOOKUPSWITCH: u8 = 0xab;
+    pub const IRETURN: u8 = 0xac;
+    pub const RETURN: u8 = 0xb1;
+    pub const GETSTATIC: u8 = 0xb2;
+    pub const PUTSTATIC: u8 = 0xb3;
+    pub const GETFIELD: u8 = 0xb4;
+    pub const PUTFIELD: u8 = 0xb5;
+    pub const INVOKEVIRTUAL: u8 = 0xb6;
+    pub const INVOKESPECIAL: u8 = 0xb7;
+    pub const INVOKESTATIC: u8 = 0xb8;
+    pub const INVOKEINTERFACE: u8 = 0xb9;
+    pub const INVOKEDYNAMIC: u8 = 0xba;
+    pub const NEW: u8 = 0xbb;
+    pub const ANEWARRAY: u8 = 0xbd;
+    pub const ATHROW: u8 = 0xbf;
+    pub const CHECKCAST: u8 = 0xc0;
+    pub const INSTANCEOF: u8 = 0xc1;
+    pub const WIDE: u8 = 0xc4;
+    pub const MULTIANEWARRAY: u8 = 0xc5;
+    pub const GOTO_W: u8 = 0xc8;
+    pub const JSR_W: u8 = 0xc9;
+}
+
+/// A decoded instruction of a `Code` attribute.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    /// Offset of the opcode from the start of the code array
+    pub offset: u32,
+    pub opcode: u8,
+    /// Whether the instruction was modified by a preceding `wide`
+    pub wide: bool,
+    pub operands: Operands,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operands {
+    None,
+    /// `bipush` and `sipush`
+    Immediate(i16),
+    /// Loads, stores and `ret`
+    Local(u16),
+    Iinc {
+        index: u16,
+        value: i16,
+    },
+    /// Constant pool index of `ldc`, field access, invocation, `new` and type check instructions
+    ConstantPool(u16),
+    InvokeInterface {
+        index: u16,
+        count: u8,
+    },
+    /// Branch offset relative to the opcode
+    Branch(i32),
+    TableSwitch {
+        default: i32,
+        low: i32,
+        high: i32,
+        offsets: Vec<i32>,
+    },
+    LookupSwitch {
+        default: i32,
+        pairs: Vec<(i32, i32)>,
+    },
+    /// Array type code of `newarray`
+    NewArray(u8),
+    MultiANewArray {
+        index: u16,
+        dimensions: u8,
+    },
+}
+
+impl Instruction {
+    pub fn mnemonic(&self) -> &'static str {
+        mnemonic(self.opcode)
+    }
+
+    /// Returns the constant pool index referenced by this instruction, if any.
+    pub fn constant_pool_index(&self) -> Option<u16> {
+        match self.operands {
+            Operands::ConstantPool(index)
+            | Operands::InvokeInterface { index, .. }
+            | Operands::MultiANewArray { index, .. } => Some(index),
+            _ => None,
+        }
+    }
+
+    /// Returns the absolute offsets this instruction may jump to, excluding the fall through.
+    pub fn branch_targets(&self) -> Vec<u32> {
+        let target = |relative: i32| (self.offset as i64 + relative as i64) as u32;
+        match &self.operands {
+            Operands::Branch(relative) => vec![target(*relative)],
+            Operands::TableSwitch {
+                default, offsets, ..
+            } => std::iter::once(default)
+                .chain(offsets)
+                .map(|x| target(*x))
+                .collect(),
+            Operands::LookupSwitch { default, pairs } => std::iter::once(default)
+                .chain(pairs.iter().map(|(_, x)| x))
+                .map(|x| target(*x))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Returns the mnemonic of an opcode, e.g. `invokevirtual`.
+pub fn mnemonic(opcode: u8) -> &'static str {
+    match opcode {
+        0xca => "breakpoint",
+        0xfe => "impdep1",
+        0xff => "impdep2",
+        _ => MNEMONICS
+            .get(opcode as usize)
+            .copied()
+            .unwrap_or("<invalid>"),
+    }
+}
+
+/// Decodes the code array of a `Code` attribute into its instructions.
+pub fn parse_instructions(code: &[u8]) -> IResult<&[u8], Vec<Instruction>> {
+    let mut input = code;
+    let mut instructions = Vec::new();
+    while !input.is_empty() {
+        let offset = (code.len() - input.len()) as u32;
+        let (i, instruction) = parse_instruction(input, offset)?;
+        input = i;
+        instructions.push(instruction);
+    }
+    Ok((input, instructions))
+}
+
+fn parse_instruction(input: &[u8], offset: u32) -> IResult<&[u8], Instruction> {
+    let (input, opcode) = be_u8(input)?;
+    if opcode == opcodes::WIDE {
+        return parse_wide_instruction(input, offset);
+    }
+
+    let (input, operands) = match opcode {
+        0x00..=0x0f | 0x1a..=0x35 | 0x3b..=0x83 | 0x85..=0x98 | 0xac..=0xb1 => {
+            (input, Operands::None)
+        }
+        0xbe | 0xbf | 0xc2 | 0xc3 | 0xca | 0xfe | 0xff => (input, Operands::None),
+        0x10 => {
+            let (input, value) = be_i8(input)?;
+            (input, Operands::Immediate(value as i16))
+        }
+        0x11 => {
+            let (input, value) = be_i16(input)?;
+            (input, Operands::Immediate(value))
+        }
+        opcodes::LDC => {
+            let (input, index) = be_u8(input)?;
+            (input, Operands::ConstantPool(index as u16))
+        }
+        0x13 | 0x14 | 0xb2..=0xb8 | 0xbb | 0xbd | 0xc0 | 0xc1 => {
+            let (input, index) = be_u16(input)?;
+            (input, Operands::ConstantPool(index))
+        }
+        0x15..=0x19 | 0x36..=0x3a | opcodes::RET => {
+            let (input, index) = be_u8(input)?;
+            (input, Operands::Local(index as u16))
+        }
+        opcodes::IINC => {
+            let (input, index) = be_u8(input)?;
+            let (input, value) = be_i8(input)?;
+            (
+                input,
+                Operands::Iinc {
+                    index: index as u16,
+                    value: value as i16,
+                },
+            )
+        }
+        0x99..=0xa8 | 0xc6 | 0xc7 => {
+            let (input, branch) = be_i16(input)?;
+            (input, Operands::Branch(branch as i32))
+        }
+        opcodes::GOTO_W | opcodes::JSR_W => {
+            let (input, branch) = be_i32(input)?;
+            (input, Operands::Branch(branch))
+        }
+        opcodes::TABLESWITCH => {
+            let (input, _) = nom::bytes::complete::take(switch_padding(offset))(input)?;
+            let (input, default) = be_i32(input)?;
+            let (input, low) = be_i32(input)?;
+            let (input, high) = be_i32(input)?;
+            // the operands are untrusted, so the table must fit in the remaining code before
+            // anything is allocated for it
+            let len = high as i64 - low as i64 + 1;
+            if high < low || len > (input.len() / 4) as i64 {
+                return Err(nom::Err::Error(Error::new(input, ErrorKind::Verify)));
+            }
+            let mut input = input;
+            let mut offsets = Vec::with_capacity(len as usize);
+            for _ in low..=high {
+                let (i, offset) = be_i32(input)?;
+                input = i;
+                offsets.push(offset);
+            }
+            (
+                input,
+                Operands::TableSwitch {
+                    default,
+                    low,
+                    high,
+                    offsets,
+                },
+            )
+        }
+        opcodes::LOOKUPSWITCH => {
+            let (input, _) = nom::bytes::complete::take(switch_padding(offset))(input)?;
+            let (input, default) = be_i32(input)?;
+            let (input, npairs) = be_i32(input)?;
+            if npairs < 0 || npairs as usize > input.len() / 8 {
+                return Err(nom::Err::Error(Error::new(input, ErrorKind::Verify)));
+            }
+            let mut input = input;
+            let mut pairs = Vec::with_capacity(npairs as usize);
+            for _ in 0..npairs {
+                let (i, key) = be_i32(input)?;
+                let (i, offset) = be_i32(i)?;
+                input = i;
+                pairs.push((key, offset));
+            }
+            (input, Operands::LookupSwitch { default, pairs })
+        }
+        opcodes::INVOKEINTERFACE => {
+            let (input, index) = be_u16(input)?;
+            let (input, count) = be_u8(input)?;
+            let (input, _) = be_u8(input)?;
+            (input, Operands::InvokeInterface { index, count })
+        }
+        opcodes::INVOKEDYNAMIC => {
+            let (input, index) = be_u16(input)?;
+            let (input, _) = be_u16(input)?;
+            (input, Operands::ConstantPool(index))
+        }
+        0xbc => {
+            let (input, atype) = be_u8(input)?;
+            (input, Operands::NewArray(atype))
+        }
+        opcodes::MULTIANEWARRAY => {
+            let (input, index) = be_u16(input)?;
+            let (input, dimensions) = be_u8(input)?;
+            (input, Operands::MultiANewArray { index, dimensions })
+        }
+        _ => return Err(nom::Err::Error(Error::new(input, ErrorKind::Tag))),
+    };
+
+    Ok((
+        input,
+        Instruction {
+            offset,
+            opcode,
+            wide: false,
+            operands,
+        },
+    ))
+}
+
+fn parse_wide_instruction(input: &[u8], offset: u32) -> IResult<&[u8], Instruction> {
+    let (input, opcode) = be_u8(input)?;
+    let (input, operands) = match opcode {
+        0x15..=0x19 | 0x36..=0x3a | opcodes::RET => {
+            let (input, index) = be_u16(input)?;
+            (input, Operands::Local(index))
+        }
+        opcodes::IINC => {
+            let (input, index) = be_u16(input)?;
+            let (input, value) = be_i16(input)?;
+            (input, Operands::Iinc { index, value })
+        }
+        _ => return Err(nom::Err::Error(Error::new(input, ErrorKind::Tag))),
+    };
+
+    Ok((
+        input,
+        Instruction {
+            offset,
+            opcode,
+            wide: true,
+            operands,
+        },
+    ))
+}
+
+/// Switch operands start at the next offset which is a multiple of four.
+fn switch_padding(offset: u32) -> usize {
+    ((4 - (offset + 1) % 4) % 4) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_instructions() {
+        #[rustfmt::skip]
+        let code = [
+            0x2a,                               // 0: aload_0
+            0xb6, 0x00, 0x07,                   // 1: invokevirtual #7
+            0xaa, 0x00, 0x00, 0x00,             // 4: tableswitch, padded to 8
+            0x00, 0x00, 0x00, 0x1c,             //    default: +28
+            0x00, 0x00, 0x00, 0x01,             //    low: 1
+            0x00, 0x00, 0x00, 0x02,             //    high: 2
+            0x00, 0x00, 0x00, 0x1a,             //    1: +26
+            0x00, 0x00, 0x00, 0x1b,             //    2: +27
+            0xc4, 0x84, 0x01, 0x00, 0xff, 0xff, // 28: wide iinc 256, -1
+            0xb1,                               // 34: return
+        ];
+
+        let (rest, instructions) = parse_instructions(&code).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(
+            instructions
+                .iter()
+                .map(|x| x.mnemonic())
+                .collect::<Vec<_>>(),
+            vec!["aload_0", "invokevirtual", "tableswitch", "iinc", "return"]
+        );
+        assert_eq!(instructions[1].constant_pool_index(), Some(7));
+        assert_eq!(instructions[2].branch_targets(), vec![32, 30, 31]);
+        assert_eq!(
+            instructions[3],
+            Instruction {
+                offset: 28,
+                opcode: opcodes::IINC,
+                wide: true,
+                operands: Operands::Iinc {
+                    index: 256,
+                    value: -1
+                },
+            }
+        );
+        assert_eq!(instructions[4].offset, 34);
+    }
+
+    #[test]
+    fn test_parse_switch_bounds() {
+        #[rustfmt::skip]
+        let tableswitch = |low: i32, high: i32| {
+            let mut code = vec![
+                0xaa, 0x00, 0x00, 0x00,             // 0: tableswitch, padded to 4
+                0x00, 0x00, 0x00, 0x10,             //    default: +16
+            ];
+            code.extend(low.to_be_bytes());
+            code.extend(high.to_be_bytes());
+            code.extend([0x00, 0x00, 0x00, 0x10]);  //    low: +16, the only offset
+            code
+        };
+        assert!(parse_instructions(&tableswitch(0, 0)).is_ok());
+        // high < low
+        assert!(parse_instructions(&tableswitch(1, 0)).is_err());
+        // more offsets than remaining bytes
+        assert!(parse_instructions(&tableswitch(0, 1)).is_err());
+        assert!(parse_instructions(&tableswitch(i32::MIN, i32::MAX)).is_err());
+
+        #[rustfmt::skip]
+        let lookupswitch = |npairs: i32| {
+            let mut code = vec![
+                0xab, 0x00, 0x00, 0x00,             // 0: lookupswitch, padded to 4
+                0x00, 0x00, 0x00, 0x10,             //    default: +16
+            ];
+            code.extend(npairs.to_be_bytes());
+            code.extend([
+                0x00, 0x00, 0x00, 0x01,             //    key: 1
+                0x00, 0x00, 0x00, 0x10,             //    +16
+            ]);
+            code
+        };
+        assert!(parse_instructions(&lookupswitch(1)).is_ok());
+        assert!(parse_instructions(&lookupswitch(-1)).is_err());
+        assert!(parse_instructions(&lookupswitch(2)).is_err());
+        assert!(parse_instructions(&lookupswitch(i32::MAX)).is_err());
+    }
+}
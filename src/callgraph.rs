@@ -0,0 +1,736 @@
+use std::{
+    collections::{BTreeSet, HashMap, HashSet, VecDeque},
+    fmt,
+};
+
+use serde::Serialize;
+
+use crate::{
+    bytecode::{opcodes, parse_instructions},
+    class_file::{
+        AccessFlags, AttributeKind, BootstrapMethod, ClassFile, ConstantPoolInfo, MethodAccessFlags,
+    },
+    constant_pool::{ConstantPool, MemberRef},
+    hierarchy::TypeHierarchy,
+};
+
+const LAMBDA_METAFACTORY: &str = "java.lang.invoke.LambdaMetafactory";
+
+/// A method identified by its declaring (or referenced) class, name and descriptor.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct MethodRef {
+    pub class_name: String,
+    pub name: String,
+    pub descriptor: String,
+}
+
+impl MethodRef {
+    pub fn new(class_name: &str, name: &str, descriptor: &str) -> Self {
+        Self {
+            class_name: class_name.to_string(),
+            name: name.to_string(),
+            descriptor: descriptor.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for MethodRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}{}", self.class_name, self.name, self.descriptor)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum CallKind {
+    Virtual,
+    Special,
+    Static,
+    Interface,
+    Dynamic,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CallEdge {
+    pub caller: MethodRef,
+    pub callee: MethodRef,
+    pub kind: CallKind,
+    /// Offset of the invoke instruction in the caller's code
+    pub offset: u32,
+    /// Whether the edge was added by class hierarchy analysis rather than read from the code
+    pub dispatched: bool,
+}
+
+/// A call graph over the methods of a set of class files.
+///
+/// Edges initially point at the method named by each invoke instruction. `invokedynamic` sites
+/// bootstrapped by `LambdaMetafactory` point at the lambda body or referenced method, other
+/// `invokedynamic` sites at their bootstrap method.
+#[derive(Debug, Default, Serialize)]
+pub struct CallGraph {
+    /// Methods declared by the analyzed class files
+    pub methods: BTreeSet<MethodRef>,
+    pub edges: Vec<CallEdge>,
+    #[serde(skip)]
+    abstract_methods: HashSet<MethodRef>,
+    #[serde(skip)]
+    abstract_classes: HashSet<String>,
+}
+
+impl CallGraph {
+    pub fn new(class_files: &[ClassFile]) -> Result<Self, String> {
+        let mut graph = Self::default();
+        for class_file in class_files {
+            graph.insert(class_file)?;
+        }
+        Ok(graph)
+    }
+
+    /// Adds the methods of a class file and the calls made by their code.
+    pub fn insert(&mut self, class_file: &ClassFile) -> Result<(), String> {
+        let pool = class_file.pool();
+        let Some(class_name) = class_name(pool, class_file.this_class) else {
+            return Err("Class file has no this_class indexing into constant pool".to_string());
+        };
+        if class_file.access_flags.contains(AccessFlags::ABSTRACT) {
+            self.abstract_classes.insert(class_name.clone());
+        }
+
+        let bootstrap_methods = class_file
+            .attributes
+            .iter()
+            .find_map(|x| match &x.kind {
+                AttributeKind::BootstrapMethods {
+                    bootstrap_methods, ..
+                } => Some(bootstrap_methods.as_slice()),
+                _ => None,
+            })
+            .unwrap_or_default();
+
+        for method in &class_file.methods {
+            let caller = MethodRef::new(
+                &class_name,
+                method.get_name(pool.entries()),
+                method.get_descriptor(pool.entries()),
+            );
+            if method.access_flags.contains(MethodAccessFlags::ABSTRACT) {
+                self.abstract_methods.insert(caller.clone());
+            }
+            self.methods.insert(caller.clone());
+
+            let Some(code) = method.attributes.iter().find_map(|x| match &x.kind {
+                AttributeKind::Code { code, .. } => Some(code),
+                _ => None,
+            }) else {
+                continue;
+            };
+
+            let (_, instructions) =
+                parse_instructions(code).map_err(|e| format!("{caller}: {e}"))?;
+            for instruction in instructions {
+                let Some(index) = instruction.constant_pool_index() else {
+                    continue;
+                };
+                let call = match instruction.opcode {
+                    opcodes::INVOKEVIRTUAL => {
+                        member_ref(pool, index).map(|x| (x, CallKind::Virtual))
+                    }
+                    opcodes::INVOKESPECIAL => {
+                        member_ref(pool, index).map(|x| (x, CallKind::Special))
+                    }
+                    opcodes::INVOKESTATIC => member_ref(pool, index).map(|x| (x, CallKind::Static)),
+                    opcodes::INVOKEINTERFACE => {
+                        member_ref(pool, index).map(|x| (x, CallKind::Interface))
+                    }
+                    opcodes::INVOKEDYNAMIC => dynamic_target(pool, bootstrap_methods, index)
+                        .map(|x| (x, CallKind::Dynamic)),
+                    _ => continue,
+                };
+                let Some((callee, kind)) = call else {
+                    return Err(format!(
+                        "{caller}: invalid constant pool index {index} at offset {}",
+                        instruction.offset
+                    ));
+                };
+
+                self.edges.push(CallEdge {
+                    caller: caller.clone(),
+                    callee,
+                    kind,
+                    offset: instruction.offset,
+                    dispatched: false,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Expands every virtual and interface call with an edge to each method it may dispatch to
+    /// according to class hierarchy analysis: the implementation selected for the receiver
+    /// type and each of its concrete subclasses.
+    pub fn expand_virtual_calls(&mut self, hierarchy: &TypeHierarchy) {
+        let mut existing = self
+            .edges
+            .iter()
+            .map(|x| (x.caller.clone(), x.callee.clone()))
+            .collect::<HashSet<_>>();
+
+        // many call sites share a callee, so the dispatch targets are computed once per callee
+        let mut targets: HashMap<&MethodRef, Vec<MethodRef>> = HashMap::new();
+        let mut dispatched = Vec::new();
+        for edge in &self.edges {
+            if edge.dispatched || !matches!(edge.kind, CallKind::Virtual | CallKind::Interface) {
+                continue;
+            }
+
+            let targets = targets
+                .entry(&edge.callee)
+                .or_insert_with(|| self.dispatch_targets(hierarchy, &edge.callee));
+            for target in targets.iter() {
+                if existing.insert((edge.caller.clone(), target.clone())) {
+                    dispatched.push(CallEdge {
+                        caller: edge.caller.clone(),
+                        callee: target.clone(),
+                        kind: edge.kind,
+                        offset: edge.offset,
+                        dispatched: true,
+                    });
+                }
+            }
+        }
+
+        self.edges.extend(dispatched);
+    }
+
+    /// Returns the methods a virtual call of `callee` may dispatch to, for the receiver type and
+    /// each of its concrete subclasses.
+    fn dispatch_targets(&self, hierarchy: &TypeHierarchy, callee: &MethodRef) -> Vec<MethodRef> {
+        let receiver = callee.class_name.as_str();
+        let mut targets = BTreeSet::new();
+        for ty in std::iter::once(receiver).chain(hierarchy.subtypes(receiver)) {
+            if hierarchy.is_interface(ty) || self.abstract_classes.contains(ty) {
+                continue;
+            }
+            if let Some(target) =
+                self.select_method(hierarchy, ty, &callee.name, &callee.descriptor)
+            {
+                targets.insert(target);
+            }
+        }
+        targets.into_iter().collect()
+    }
+
+    /// Returns the concrete method invoked on an instance of `class_name`, searching the
+    /// analyzed methods in method resolution order.
+    pub fn select_method(
+        &self,
+        hierarchy: &TypeHierarchy,
+        class_name: &str,
+        name: &str,
+        descriptor: &str,
+    ) -> Option<MethodRef> {
+        hierarchy
+            .method_resolution_order(class_name)
+            .into_iter()
+            .map(|ty| MethodRef::new(ty, name, descriptor))
+            .find(|x| self.methods.contains(x) && !self.abstract_methods.contains(x))
+    }
+
+    /// Resolves a referenced method to the analyzed method it names, which may be declared by a
+    /// supertype of the referenced class (JVMS 5.4.3.3). Returns `None` for methods outside the
+    /// analyzed class files.
+    pub fn resolve_method(
+        &self,
+        hierarchy: &TypeHierarchy,
+        method: &MethodRef,
+    ) -> Option<MethodRef> {
+        if self.methods.contains(method) {
+            return Some(method.clone());
+        }
+        hierarchy
+            .method_resolution_order(&method.class_name)
+            .into_iter()
+            .map(|ty| MethodRef::new(ty, &method.name, &method.descriptor))
+            .find(|x| self.methods.contains(x))
+    }
+
+    /// Returns the methods called by the given method.
+    pub fn callees(&self, method: &MethodRef) -> Vec<&MethodRef> {
+        self.edges
+            .iter()
+            .filter(|x| &x.caller == method)
+            .map(|x| &x.callee)
+            .collect()
+    }
+
+    /// Returns the methods calling the given method.
+    pub fn callers(&self, method: &MethodRef) -> Vec<&MethodRef> {
+        self.edges
+            .iter()
+            .filter(|x| &x.callee == method)
+            .map(|x| &x.caller)
+            .collect()
+    }
+
+    /// Returns every method transitively reachable from the entry points, including the entry
+    /// points themselves.
+    pub fn reachable_from<'a, I>(&self, entry_points: I) -> BTreeSet<MethodRef>
+    where
+        I: IntoIterator<Item = &'a MethodRef>,
+    {
+        let mut adjacency: HashMap<&MethodRef, Vec<&MethodRef>> = HashMap::new();
+        for edge in &self.edges {
+            adjacency
+                .entry(&edge.caller)
+                .or_default()
+                .push(&edge.callee);
+        }
+
+        let mut reachable = BTreeSet::new();
+        let mut queue = entry_points.into_iter().collect::<VecDeque<_>>();
+        while let Some(method) = queue.pop_front() {
+            if !reachable.insert(method.clone()) {
+                continue;
+            }
+            if let Some(callees) = adjacency.get(method) {
+                queue.extend(callees.iter().copied());
+            }
+        }
+        reachable
+    }
+}
+
+/// The name of a `Class` entry, in binary form (`java.lang.Object`).
+pub(crate) fn class_name(pool: ConstantPool, index: u16) -> Option<String> {
+    pool.class_name(index).ok().map(|x| x.replace('/', "."))
+}
+
+/// Resolves a `Methodref` or `InterfaceMethodref` entry, or a `MethodHandle` pointing to one.
+fn member_ref(pool: ConstantPool, index: u16) -> Option<MethodRef> {
+    let member = match pool.get(index).ok()? {
+        ConstantPoolInfo::MethodHandle { .. } => pool.method_handle(index).ok()?.reference,
+        _ => pool.member_ref(index).ok()?,
+    };
+    // Method handles may also refer to fields
+    member.descriptor.starts_with('(').then(|| member.into())
+}
+
+impl From<MemberRef<'_>> for MethodRef {
+    fn from(member: MemberRef<'_>) -> Self {
+        MethodRef::new(
+            &member.class_name.replace('/', "."),
+            member.name,
+            member.descriptor,
+        )
+    }
+}
+
+/// Resolves the target of an `invokedynamic` site: the implementation method for lambdas and
+/// method references, the bootstrap method otherwise.
+fn dynamic_target(
+    pool: ConstantPool,
+    bootstrap_methods: &[BootstrapMethod],
+    index: u16,
+) -> Option<MethodRef> {
+    let ConstantPoolInfo::InvokeDynamic {
+        bootstrap_method_attr_index,
+        ..
+    } = pool.get(index).ok()?
+    else {
+        return None;
+    };
+    let bootstrap_method = bootstrap_methods.get(*bootstrap_method_attr_index as usize)?;
+    let bootstrap = member_ref(pool, bootstrap_method.bootstrap_method_ref)?;
+
+    if bootstrap.class_name == LAMBDA_METAFACTORY {
+        // (samMethodType, implMethod, instantiatedMethodType, ...)
+        if let Some(implementation) = bootstrap_method
+            .bootstrap_arguments
+            .get(1)
+            .and_then(|x| member_ref(pool, *x))
+        {
+            return Some(implementation);
+        }
+    }
+    Some(bootstrap)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        class_file::Attribute,
+        component::{extract_component, AccessModifier, ExtractorContext},
+        test_util::ConstantPoolBuilder,
+    };
+
+    const BOOTSTRAP_DESCRIPTOR: &str = "(Ljava/lang/invoke/MethodHandles$Lookup;Ljava/lang/String;Ljava/lang/invoke/MethodType;)Ljava/lang/invoke/CallSite;";
+    const METAFACTORY_DESCRIPTOR: &str = "(Ljava/lang/invoke/MethodHandles$Lookup;Ljava/lang/String;Ljava/lang/invoke/MethodType;Ljava/lang/invoke/MethodType;Ljava/lang/invoke/MethodHandle;Ljava/lang/invoke/MethodType;)Ljava/lang/invoke/CallSite;";
+
+    const PUBLIC: MethodAccessFlags = MethodAccessFlags::PUBLIC;
+    const ABSTRACT: MethodAccessFlags = PUBLIC.union(MethodAccessFlags::ABSTRACT);
+    const STATIC: MethodAccessFlags = PUBLIC.union(MethodAccessFlags::STATIC);
+    const CLASS: AccessFlags = AccessFlags::PUBLIC.union(AccessFlags::SUPER);
+
+    /// `a.Shape` is abstract, `a.Circle` and `a.Square` override `area` and `a.Box` inherits it
+    /// from `a.Square`. `a.Circle` also implements `a.Named`.
+    fn shapes() -> Vec<ClassFile> {
+        let mut pool = ConstantPoolBuilder::new();
+        let name = pool.method("name", "()Ljava/lang/String;", ABSTRACT, Vec::new());
+        let named = pool.class_file(
+            AccessFlags::PUBLIC | AccessFlags::INTERFACE | AccessFlags::ABSTRACT,
+            "a/Named",
+            "java/lang/Object",
+            &[],
+            vec![name],
+            Vec::new(),
+        );
+
+        let mut pool = ConstantPoolBuilder::new();
+        let area = pool.method("area", "()D", ABSTRACT, Vec::new());
+        let shape = pool.class_file(
+            CLASS | AccessFlags::ABSTRACT,
+            "a/Shape",
+            "java/lang/Object",
+            &[],
+            vec![area],
+            Vec::new(),
+        );
+
+        let mut pool = ConstantPoolBuilder::new();
+        let methods = vec![
+            // return
+            pool.method("<init>", "()V", PUBLIC, vec![0xb1]),
+            // dconst_0, dreturn
+            pool.method("area", "()D", PUBLIC, vec![0x0e, 0xaf]),
+            // aconst_null, areturn
+            pool.method("name", "()Ljava/lang/String;", PUBLIC, vec![0x01, 0xb0]),
+        ];
+        let circle = pool.class_file(
+            CLASS,
+            "a/Circle",
+            "a/Shape",
+            &["a/Named"],
+            methods,
+            Vec::new(),
+        );
+
+        let mut pool = ConstantPoolBuilder::new();
+        let area = pool.method("area", "()D", PUBLIC, vec![0x0e, 0xaf]);
+        let square = pool.class_file(CLASS, "a/Square", "a/Shape", &[], vec![area], Vec::new());
+
+        let pool = ConstantPoolBuilder::new();
+        let r#box = pool.class_file(CLASS, "a/Box", "a/Square", &[], Vec::new(), Vec::new());
+
+        vec![named, shape, circle, square, r#box]
+    }
+
+    /// `a.Main.main` creates an `a.Circle` and calls it through `a.Shape` and `a.Named`.
+    fn main_class() -> ClassFile {
+        let mut pool = ConstantPoolBuilder::new();
+        let circle = pool.class("a/Circle").to_be_bytes();
+        let init = pool.method_ref("a/Circle", "<init>", "()V").to_be_bytes();
+        let area = pool.method_ref("a/Shape", "area", "()D").to_be_bytes();
+        let name = pool
+            .interface_method_ref("a/Named", "name", "()Ljava/lang/String;")
+            .to_be_bytes();
+        let helper = pool.method_ref("a/Main", "helper", "()V").to_be_bytes();
+        #[rustfmt::skip]
+        let code = vec![
+            0xbb, circle[0], circle[1],    // 0: new a/Circle
+            0x59,                          // 3: dup
+            0xb7, init[0], init[1],        // 4: invokespecial a/Circle.<init>
+            0xb6, area[0], area[1],        // 7: invokevirtual a/Shape.area
+            0x58,                          // 10: pop2
+            0x01,                          // 11: aconst_null
+            0xb9, name[0], name[1], 1, 0,  // 12: invokeinterface a/Named.name
+            0x57,                          // 17: pop
+            0xb8, helper[0], helper[1],    // 18: invokestatic a/Main.helper
+            0xb1,                          // 21: return
+        ];
+        let methods = vec![
+            pool.method("main", "()V", STATIC, code),
+            pool.method("helper", "()V", STATIC, vec![0xb1]),
+        ];
+        pool.class_file(
+            CLASS,
+            "a/Main",
+            "java/lang/Object",
+            &[],
+            methods,
+            Vec::new(),
+        )
+    }
+
+    fn hierarchy(class_files: &[ClassFile]) -> TypeHierarchy {
+        let context = ExtractorContext {
+            target_access_modifiers: AccessModifier::all(),
+        };
+        let components = class_files
+            .iter()
+            .map(|x| extract_component(x, &context))
+            .collect::<Vec<_>>();
+        TypeHierarchy::new(&components)
+    }
+
+    fn method(class_name: &str, name: &str, descriptor: &str) -> MethodRef {
+        MethodRef::new(class_name, name, descriptor)
+    }
+
+    #[test]
+    fn records_invoke_instructions() {
+        let mut class_files = shapes();
+        class_files.push(main_class());
+        let graph = CallGraph::new(&class_files).unwrap();
+
+        assert_eq!(graph.methods.len(), 8);
+        let main = method("a.Main", "main", "()V");
+        let edges = graph
+            .edges
+            .iter()
+            .map(|x| (x.kind, x.offset, x.callee.to_string(), x.dispatched))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            edges,
+            vec![
+                (
+                    CallKind::Special,
+                    4,
+                    "a.Circle.<init>()V".to_string(),
+                    false
+                ),
+                (CallKind::Virtual, 7, "a.Shape.area()D".to_string(), false),
+                (
+                    CallKind::Interface,
+                    12,
+                    "a.Named.name()Ljava/lang/String;".to_string(),
+                    false
+                ),
+                (CallKind::Static, 18, "a.Main.helper()V".to_string(), false),
+            ]
+        );
+        assert!(graph.edges.iter().all(|x| x.caller == main));
+        assert_eq!(graph.callees(&main).len(), 4);
+        assert_eq!(
+            graph.callers(&method("a.Main", "helper", "()V")),
+            vec![&main]
+        );
+        assert!(graph.callers(&method("a.Circle", "area", "()D")).is_empty());
+    }
+
+    #[test]
+    fn reports_invalid_constant_pool_indices() {
+        let mut pool = ConstantPoolBuilder::new();
+        let index = pool.utf8("a/Main").to_be_bytes();
+        let run = pool.method("run", "()V", STATIC, vec![0xb8, index[0], index[1], 0xb1]);
+        let class_file = pool.class_file(
+            CLASS,
+            "a/Main",
+            "java/lang/Object",
+            &[],
+            vec![run],
+            Vec::new(),
+        );
+
+        let error = CallGraph::new(&[class_file]).unwrap_err();
+        assert_eq!(
+            error,
+            "a.Main.run()V: invalid constant pool index 1 at offset 0"
+        );
+    }
+
+    #[test]
+    fn resolves_lambdas_to_their_implementation() {
+        let mut pool = ConstantPoolBuilder::new();
+        let metafactory = pool.static_method_handle(
+            "java/lang/invoke/LambdaMetafactory",
+            "metafactory",
+            METAFACTORY_DESCRIPTOR,
+        );
+        let void_descriptor = pool.utf8("()V");
+        let method_type = pool.add(ConstantPoolInfo::MethodType {
+            descriptor_index: void_descriptor,
+        });
+        let lambda_body = pool.static_method_handle("a/Main", "lambda$run$0", "()V");
+        let bootstrap = pool.static_method_handle("a/Main", "bootstrap", BOOTSTRAP_DESCRIPTOR);
+
+        let lambda = pool
+            .invoke_dynamic(0, "run", "()Ljava/lang/Runnable;")
+            .to_be_bytes();
+        let custom = pool
+            .invoke_dynamic(1, "make", "()Ljava/lang/Object;")
+            .to_be_bytes();
+        let truncated = pool
+            .invoke_dynamic(2, "run", "()Ljava/lang/Runnable;")
+            .to_be_bytes();
+        #[rustfmt::skip]
+        let code = vec![
+            0xba, lambda[0], lambda[1], 0, 0,        // 0: invokedynamic run
+            0x57,                                    // 5: pop
+            0xba, custom[0], custom[1], 0, 0,        // 6: invokedynamic make
+            0x57,                                    // 11: pop
+            0xba, truncated[0], truncated[1], 0, 0,  // 12: invokedynamic run
+            0x57,                                    // 17: pop
+            0xb1,                                    // 18: return
+        ];
+        let methods = vec![
+            pool.method("run", "()V", STATIC, code),
+            pool.method("lambda$run$0", "()V", STATIC, vec![0xb1]),
+            pool.method("bootstrap", BOOTSTRAP_DESCRIPTOR, STATIC, vec![0x01, 0xb0]),
+        ];
+        let bootstrap_methods = vec![
+            BootstrapMethod::new(metafactory, vec![method_type, lambda_body, method_type]),
+            BootstrapMethod::new(bootstrap, Vec::new()),
+            // without an implementation method the bootstrap method is the target
+            BootstrapMethod::new(metafactory, vec![method_type]),
+        ];
+        let name_index = pool.utf8("BootstrapMethods");
+        let attributes = vec![Attribute::new(
+            name_index,
+            AttributeKind::BootstrapMethods {
+                num_bootstrap_methods: bootstrap_methods.len() as u16,
+                bootstrap_methods,
+            },
+        )];
+        let class_file = pool.class_file(
+            CLASS,
+            "a/Main",
+            "java/lang/Object",
+            &[],
+            methods,
+            attributes,
+        );
+
+        let graph = CallGraph::new(&[class_file]).unwrap();
+        let lambda = method("a.Main", "lambda$run$0", "()V");
+        let edges = graph
+            .edges
+            .iter()
+            .map(|x| (x.kind, x.offset, &x.callee))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            edges,
+            vec![
+                (CallKind::Dynamic, 0, &lambda),
+                (
+                    CallKind::Dynamic,
+                    6,
+                    &method("a.Main", "bootstrap", BOOTSTRAP_DESCRIPTOR)
+                ),
+                (
+                    CallKind::Dynamic,
+                    12,
+                    &method(LAMBDA_METAFACTORY, "metafactory", METAFACTORY_DESCRIPTOR)
+                ),
+            ]
+        );
+        assert_eq!(
+            graph.callers(&lambda),
+            vec![&method("a.Main", "run", "()V")]
+        );
+    }
+
+    #[test]
+    fn selects_the_most_specific_concrete_method() {
+        let class_files = shapes();
+        let hierarchy = hierarchy(&class_files);
+        let graph = CallGraph::new(&class_files).unwrap();
+
+        let select = |class_name: &str| graph.select_method(&hierarchy, class_name, "area", "()D");
+        assert_eq!(select("a.Box"), Some(method("a.Square", "area", "()D")));
+        assert_eq!(select("a.Circle"), Some(method("a.Circle", "area", "()D")));
+        // the only declaration is abstract
+        assert_eq!(select("a.Shape"), None);
+        assert_eq!(select("a.Unknown"), None);
+    }
+
+    #[test]
+    fn resolves_methods_declared_by_supertypes() {
+        let class_files = shapes();
+        let hierarchy = hierarchy(&class_files);
+        let graph = CallGraph::new(&class_files).unwrap();
+
+        let resolve = |x: MethodRef| graph.resolve_method(&hierarchy, &x);
+        assert_eq!(
+            resolve(method("a.Box", "area", "()D")),
+            Some(method("a.Square", "area", "()D"))
+        );
+        // resolution stops at abstract declarations
+        assert_eq!(
+            resolve(method("a.Shape", "area", "()D")),
+            Some(method("a.Shape", "area", "()D"))
+        );
+        assert_eq!(resolve(method("a.Box", "size", "()I")), None);
+        assert_eq!(resolve(method("java.lang.Object", "hashCode", "()I")), None);
+    }
+
+    #[test]
+    fn expands_virtual_calls_to_concrete_subtypes() {
+        let mut class_files = shapes();
+        class_files.push(main_class());
+        let hierarchy = hierarchy(&class_files);
+        let mut graph = CallGraph::new(&class_files).unwrap();
+        graph.expand_virtual_calls(&hierarchy);
+
+        // a.Shape is abstract and a.Box selects a.Square's implementation
+        let dispatched = graph
+            .edges
+            .iter()
+            .filter(|x| x.dispatched)
+            .map(|x| (x.kind, x.offset, x.callee.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            dispatched,
+            vec![
+                (CallKind::Virtual, 7, "a.Circle.area()D".to_string()),
+                (CallKind::Virtual, 7, "a.Square.area()D".to_string()),
+                (
+                    CallKind::Interface,
+                    12,
+                    "a.Circle.name()Ljava/lang/String;".to_string()
+                ),
+            ]
+        );
+
+        // expanding again adds nothing
+        let edges = graph.edges.len();
+        graph.expand_virtual_calls(&hierarchy);
+        assert_eq!(graph.edges.len(), edges);
+    }
+
+    #[test]
+    fn finds_reachable_methods() {
+        let mut class_files = shapes();
+        class_files.push(main_class());
+        let mut graph = CallGraph::new(&class_files).unwrap();
+        let main = method("a.Main", "main", "()V");
+
+        let reachable = graph.reachable_from([&main]);
+        assert_eq!(reachable.len(), 5);
+        assert!(!reachable.contains(&method("a.Circle", "area", "()D")));
+
+        graph.expand_virtual_calls(&hierarchy(&class_files));
+        let reachable = graph
+            .reachable_from([&main])
+            .into_iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            reachable,
+            vec![
+                "a.Circle.<init>()V",
+                "a.Circle.area()D",
+                "a.Circle.name()Ljava/lang/String;",
+                "a.Main.helper()V",
+                "a.Main.main()V",
+                "a.Named.name()Ljava/lang/String;",
+                "a.Shape.area()D",
+                "a.Square.area()D",
+            ]
+        );
+        assert_eq!(
+            graph.reachable_from([&method("a.Main", "helper", "()V")]),
+            BTreeSet::from([method("a.Main", "helper", "()V")])
+        );
+    }
+}
@@ -6,6 +6,9 @@ use nom::{
     number::complete::{be_u16, be_u32, be_u8},
     IResult,
 };
+use serde::{Deserialize, Serialize};
+
+use crate::constant_pool::ConstantPool;
 
 #[derive(Debug)]
 pub struct ClassFile {
@@ -26,6 +29,13 @@ pub struct ClassFile {
     pub attributes: Vec<Attribute>,
 }
 
+impl ClassFile {
+    /// A checked view of the constant pool.
+    pub fn pool(&self) -> ConstantPool<'_> {
+        ConstantPool::new(&self.constant_pool)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum ConstantPoolInfo {
     Class {
@@ -94,7 +104,8 @@ pub enum ConstantPoolInfo {
 }
 
 bitflags! {
-    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(transparent)]
     pub struct AccessFlags: u16 {
         const NONE = 0x0000;
         const PUBLIC = 0x0001;
@@ -110,7 +121,8 @@ bitflags! {
 }
 
 bitflags! {
-    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(transparent)]
     pub struct FieldAccessFlags: u16 {
         const NONE = 0x0000;
         const PUBLIC = 0x0001;
@@ -163,7 +175,8 @@ impl FieldInfo {
 }
 
 bitflags! {
-    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(transparent)]
     pub struct MethodAccessFlags: u16 {
         const NONE = 0x0000;
         const PUBLIC = 0x0001;
@@ -227,14 +240,16 @@ pub struct AttributeInfo {
 
 #[derive(Debug)]
 pub struct ExceptionTableEntry {
-    start_pc: u16,
-    end_pc: u16,
-    handler_pc: u16,
-    catch_type: u16,
+    pub start_pc: u16,
+    pub end_pc: u16,
+    pub handler_pc: u16,
+    /// A `Class` entry, or 0 for handlers that catch everything
+    pub catch_type: u16,
 }
 
-#[derive(Debug)]
-enum VerificationTypeInfo {
+/// A verification type as stored in a `StackMapTable` frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerificationTypeInfo {
     Top,
     Integer,
     Float,
@@ -242,12 +257,19 @@ enum VerificationTypeInfo {
     Double,
     Null,
     UninitializedThis,
-    Object { cpool_index: u16 },
-    Uninitialized { offset: u16 },
+    Object {
+        cpool_index: u16,
+    },
+    /// An object created by the `new` instruction at `offset`
+    Uninitialized {
+        offset: u16,
+    },
 }
 
-#[derive(Debug)]
-enum StackMapFrameKind {
+/// A compressed `StackMapTable` frame. Use [`crate::stack_map`] for frames with absolute
+/// offsets and expanded locals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StackMapFrameKind {
     Same, // frame_type:0-63
     SameLocals1StackItem {
         stack: VerificationTypeInfo,
@@ -275,17 +297,44 @@ enum StackMapFrameKind {
     }, // frame_type:255
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct StackMapFrame {
-    frame_type: u8,
-    kind: StackMapFrameKind,
+    pub frame_type: u8,
+    pub kind: StackMapFrameKind,
+}
+
+impl StackMapFrame {
+    /// The offset of this frame relative to the previous one, which is encoded in the frame
+    /// type for `same` and `same_locals_1_stack_item` frames.
+    pub fn offset_delta(&self) -> u16 {
+        match &self.kind {
+            StackMapFrameKind::Same => self.frame_type as u16,
+            StackMapFrameKind::SameLocals1StackItem { .. } => self.frame_type as u16 - 64,
+            StackMapFrameKind::SameLocals1StackItemExtended { offset_delta, .. }
+            | StackMapFrameKind::Chop { offset_delta }
+            | StackMapFrameKind::SameExtended { offset_delta }
+            | StackMapFrameKind::Append { offset_delta, .. }
+            | StackMapFrameKind::Full { offset_delta, .. } => *offset_delta,
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct BootstrapMethod {
-    bootstrap_method_ref: u16,
+    pub bootstrap_method_ref: u16,
     num_bootstrap_arguments: u16,
-    bootstrap_arguments: Vec<u16>,
+    pub bootstrap_arguments: Vec<u16>,
+}
+
+#[cfg(test)]
+impl BootstrapMethod {
+    pub(crate) fn new(bootstrap_method_ref: u16, bootstrap_arguments: Vec<u16>) -> Self {
+        Self {
+            bootstrap_method_ref,
+            num_bootstrap_arguments: bootstrap_arguments.len() as u16,
+            bootstrap_arguments,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -296,12 +345,26 @@ pub struct Parameter {
 
 #[derive(Debug)]
 pub struct InnerClass {
-    inner_class_info_index: u16,
-    outer_class_info_index: u16,
-    inner_name_index: u16,
+    pub inner_class_info_index: u16,
+    /// Zero for top-level, local and anonymous classes
+    pub outer_class_info_index: u16,
+    /// Zero for anonymous classes
+    pub inner_name_index: u16,
     inner_class_access_flags: InnerClassAccessFlagsKind,
 }
 
+impl InnerClass {
+    /// The access flags, with undefined bits dropped.
+    pub fn access_flags(&self) -> InnerClassAccessFlags {
+        match self.inner_class_access_flags {
+            InnerClassAccessFlagsKind::Valid(flags) => flags,
+            InnerClassAccessFlagsKind::Invalid(bits) => {
+                InnerClassAccessFlags::from_bits_truncate(bits)
+            }
+        }
+    }
+}
+
 #[derive(Debug)]
 enum InnerClassAccessFlagsKind {
     Invalid(u16),
@@ -309,8 +372,9 @@ enum InnerClassAccessFlagsKind {
 }
 
 bitflags! {
-    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-    struct InnerClassAccessFlags: u16 {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(transparent)]
+    pub struct InnerClassAccessFlags: u16 {
         const NONE = 0x0000;
         const PUBLIC = 0x0001;
         const PRIVATE = 0x0002;
@@ -361,13 +425,13 @@ pub struct LocalVariableTypeTableEntry {
 pub struct Annotation {
     pub type_index: u16,
     num_element_value_pairs: u16,
-    element_value_pairs: Vec<ElementValuePair>,
+    pub element_value_pairs: Vec<ElementValuePair>,
 }
 
 #[derive(Debug)]
-struct ElementValuePair {
-    element_name_index: u16,
-    value: ElementValue,
+pub struct ElementValuePair {
+    pub element_name_index: u16,
+    pub value: ElementValue,
 }
 
 #[derive(Debug)]
@@ -428,7 +492,7 @@ pub struct TypeAnnotation {
     target_path: TypePath,
     pub type_index: u16,
     num_element_value_pairs: u16,
-    element_value_pairs: Vec<ElementValuePair>,
+    pub element_value_pairs: Vec<ElementValuePair>,
 }
 
 #[derive(Debug)]
@@ -523,11 +587,23 @@ pub struct ModuleProvides {
 
 #[derive(Debug)]
 pub struct Attribute {
-    attribute_name_index: u16,
-    attribute_length: u32,
+    pub attribute_name_index: u16,
+    pub attribute_length: u32,
     pub kind: AttributeKind,
 }
 
+#[cfg(test)]
+impl Attribute {
+    /// An attribute as parsed, for class files built by hand in tests.
+    pub(crate) fn new(attribute_name_index: u16, kind: AttributeKind) -> Self {
+        Self {
+            attribute_name_index,
+            attribute_length: 0,
+            kind,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum AttributeKind {
     // Critical to correct interpretation
@@ -802,8 +878,7 @@ fn parse_constant_pool_info(input: &[u8]) -> IResult<&[u8], ConstantPoolInfo> {
                 ConstantPoolInfo::Utf8 {
                     length,
                     bytes: bytes.to_vec(),
-                    // UNSAFE: in the future, unsafe utf8 str will be discriminated
-                    utf8_str: unsafe { String::from_utf8_unchecked(bytes.to_vec()) },
+                    utf8_str: decode_modified_utf8(bytes),
                 },
             ))
         }
@@ -856,6 +931,37 @@ fn parse_constant_pool_info(input: &[u8]) -> IResult<&[u8], ConstantPoolInfo> {
     }
 }
 
+/// Decodes the modified UTF-8 of `CONSTANT_Utf8_info` (JVMS 4.4.7), which encodes NUL in two
+/// bytes and supplementary characters as two three-byte surrogates. Unpaired surrogates and
+/// malformed bytes become U+FFFD.
+fn decode_modified_utf8(bytes: &[u8]) -> String {
+    if let Ok(s) = std::str::from_utf8(bytes) {
+        return s.to_string();
+    }
+
+    let continuation = |i: usize| bytes.get(i).is_some_and(|x| x & 0xC0 == 0x80);
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let x = u16::from(bytes[i]);
+        let (unit, length) = if x & 0x80 == 0 {
+            (x, 1)
+        } else if x & 0xE0 == 0xC0 && continuation(i + 1) {
+            (((x & 0x1F) << 6) | (u16::from(bytes[i + 1]) & 0x3F), 2)
+        } else if x & 0xF0 == 0xE0 && continuation(i + 1) && continuation(i + 2) {
+            let unit = ((x & 0x0F) << 12)
+                | ((u16::from(bytes[i + 1]) & 0x3F) << 6)
+                | (u16::from(bytes[i + 2]) & 0x3F);
+            (unit, 3)
+        } else {
+            (0xFFFD, 1)
+        };
+        units.push(unit);
+        i += length;
+    }
+    String::from_utf16_lossy(&units)
+}
+
 fn parse_access_flags(input: &[u8]) -> IResult<&[u8], AccessFlags> {
     let (input, access_flags) = be_u16(input)?;
     Ok((input, AccessFlags::from_bits(access_flags).unwrap()))
@@ -1531,13 +1637,13 @@ fn parse_provides_info(input: &[u8]) -> IResult<&[u8], ModuleProvides> {
 }
 
 pub fn parse_class_file(input: &[u8]) -> IResult<&[u8], ClassFile> {
-    let (input, _) = parse_magic_number(input).unwrap();
+    let (input, _) = parse_magic_number(input)?;
     let (input, minor_version) = be_u16(input)?;
     let (input, major_version) = be_u16(input)?;
 
     let (input, constant_pool_count) = be_u16(input)?;
 
-    let (input, constant_pool) = parse_constant_pool(input, constant_pool_count).unwrap();
+    let (input, constant_pool) = parse_constant_pool(input, constant_pool_count)?;
 
     let parser = ClassFileParser {
         constant_pool: &constant_pool,
@@ -0,0 +1,671 @@
+use std::{collections::HashMap, fmt::Display};
+
+use serde::Serialize;
+
+use crate::{
+    class_file::{AccessFlags, FieldAccessFlags, MethodAccessFlags},
+    component::{Component, ConstantValue, Field, Method},
+    hierarchy::TypeHierarchy,
+    members::ClassPath,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum Severity {
+    /// Pre-existing binaries fail to link or silently observe different behavior (JLS 13)
+    Binary,
+    /// Pre-existing sources fail to compile against the new version
+    Source,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ChangeKind {
+    ClassRemoved,
+    ClassAccessNarrowed,
+    ClassKindChanged,
+    ClassMadeFinal,
+    ClassMadeAbstract,
+    ClassMadeSealed,
+    SuperclassChanged,
+    SupertypeRemoved,
+    PermittedSubclassesShrunk,
+    TypeParametersChanged,
+    MethodRemoved,
+    MethodDescriptorChanged,
+    MethodAccessNarrowed,
+    MethodMadeFinal,
+    MethodMadeAbstract,
+    MethodStaticChanged,
+    MethodSignatureChanged,
+    AbstractMethodAdded,
+    FieldRemoved,
+    FieldTypeChanged,
+    FieldAccessNarrowed,
+    FieldMadeFinal,
+    FieldStaticChanged,
+    FieldSignatureChanged,
+    ConstantValueChanged,
+}
+
+impl ChangeKind {
+    pub fn severity(&self) -> Severity {
+        match self {
+            ChangeKind::TypeParametersChanged
+            | ChangeKind::MethodSignatureChanged
+            | ChangeKind::AbstractMethodAdded
+            | ChangeKind::FieldSignatureChanged => Severity::Source,
+            _ => Severity::Binary,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Incompatibility {
+    pub kind: ChangeKind,
+    pub severity: Severity,
+    pub class_name: String,
+    /// `name` + descriptor of the affected member, if any
+    pub member: Option<String>,
+    pub message: String,
+}
+
+impl Display for Incompatibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let severity = match self.severity {
+            Severity::Binary => "binary",
+            Severity::Source => "source",
+        };
+        match &self.member {
+            Some(member) => write!(
+                f,
+                "[{severity}] {}#{member}: {}",
+                self.class_name, self.message
+            ),
+            None => write!(f, "[{severity}] {}: {}", self.class_name, self.message),
+        }
+    }
+}
+
+/// Compares the public API of two versions of a component set following JLS chapter 13 and
+/// returns every change which breaks pre-existing binaries or sources.
+///
+/// Only public classes and their public or protected members are considered.
+pub fn check_compatibility(old: &[Component], new: &[Component]) -> Vec<Incompatibility> {
+    let checker = CompatChecker {
+        old_hierarchy: TypeHierarchy::new(old),
+        new_hierarchy: TypeHierarchy::new(new),
+        new_class_path: ClassPath::new(new),
+        incompatibilities: Vec::new(),
+    };
+    checker.check(old)
+}
+
+struct CompatChecker<'a> {
+    old_hierarchy: TypeHierarchy,
+    new_hierarchy: TypeHierarchy,
+    new_class_path: ClassPath<'a>,
+    incompatibilities: Vec<Incompatibility>,
+}
+
+impl CompatChecker<'_> {
+    fn check(mut self, old: &[Component]) -> Vec<Incompatibility> {
+        for old_component in old {
+            let Some(name) = old_component.qualified_name() else {
+                continue;
+            };
+            let old_flags = old_component.access_flags();
+            if !old_flags.contains(AccessFlags::PUBLIC) {
+                continue;
+            }
+
+            let Some(new_component) = self.new_class_path.get(name) else {
+                self.report(
+                    ChangeKind::ClassRemoved,
+                    name,
+                    None,
+                    "class removed".to_string(),
+                );
+                continue;
+            };
+            self.check_class(name, old_component, new_component);
+        }
+
+        self.incompatibilities
+    }
+
+    fn report(
+        &mut self,
+        kind: ChangeKind,
+        class_name: &str,
+        member: Option<String>,
+        message: String,
+    ) {
+        self.incompatibilities.push(Incompatibility {
+            kind,
+            severity: kind.severity(),
+            class_name: class_name.to_string(),
+            member,
+            message,
+        });
+    }
+
+    fn check_class(&mut self, name: &str, old: &Component, new: &Component) {
+        let old_flags = old.access_flags();
+        let new_flags = new.access_flags();
+
+        if !new_flags.contains(AccessFlags::PUBLIC) {
+            self.report(
+                ChangeKind::ClassAccessNarrowed,
+                name,
+                None,
+                "class is no longer public".to_string(),
+            );
+            return;
+        }
+        if old.is_interface() != new.is_interface() {
+            self.report(
+                ChangeKind::ClassKindChanged,
+                name,
+                None,
+                if old.is_interface() {
+                    "interface changed to class".to_string()
+                } else {
+                    "class changed to interface".to_string()
+                },
+            );
+            return;
+        }
+        if !old_flags.contains(AccessFlags::FINAL) && new_flags.contains(AccessFlags::FINAL) {
+            self.report(
+                ChangeKind::ClassMadeFinal,
+                name,
+                None,
+                "class made final".to_string(),
+            );
+        }
+        if !old.is_interface()
+            && !old_flags.contains(AccessFlags::ABSTRACT)
+            && new_flags.contains(AccessFlags::ABSTRACT)
+        {
+            self.report(
+                ChangeKind::ClassMadeAbstract,
+                name,
+                None,
+                "class made abstract".to_string(),
+            );
+        }
+
+        self.check_supertypes(name);
+        self.check_permitted_subclasses(name, old, new);
+
+        let old_type_parameters = old.signature().and_then(|x| x.type_parameters.as_ref());
+        let new_type_parameters = new.signature().and_then(|x| x.type_parameters.as_ref());
+        if old_type_parameters != new_type_parameters {
+            self.report(
+                ChangeKind::TypeParametersChanged,
+                name,
+                None,
+                "type parameters changed".to_string(),
+            );
+        }
+
+        self.check_methods(name, old, new);
+        self.check_fields(name, old, new);
+    }
+
+    fn check_supertypes(&mut self, name: &str) {
+        let old_super_class = self
+            .old_hierarchy
+            .get(name)
+            .and_then(|x| x.super_class.clone());
+        let new_super_class = self
+            .new_hierarchy
+            .get(name)
+            .and_then(|x| x.super_class.clone());
+        let new_supertypes = self
+            .new_hierarchy
+            .supertypes(name)
+            .into_iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>();
+
+        let removed = self
+            .old_hierarchy
+            .supertypes(name)
+            .into_iter()
+            .filter(|x| !new_supertypes.iter().any(|y| y == x))
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>();
+
+        for super_type in removed {
+            if Some(&super_type) == old_super_class.as_ref() {
+                self.report(
+                    ChangeKind::SuperclassChanged,
+                    name,
+                    None,
+                    format!(
+                        "superclass changed from {super_type} to {}",
+                        new_super_class.as_deref().unwrap_or("<none>")
+                    ),
+                );
+            } else {
+                self.report(
+                    ChangeKind::SupertypeRemoved,
+                    name,
+                    None,
+                    format!("{super_type} is no longer a supertype"),
+                );
+            }
+        }
+    }
+
+    fn check_permitted_subclasses(&mut self, name: &str, old: &Component, new: &Component) {
+        let old_permitted = old.permitted_subclasses();
+        let new_permitted = new.permitted_subclasses();
+
+        if old_permitted.is_empty() {
+            if !new_permitted.is_empty() {
+                self.report(
+                    ChangeKind::ClassMadeSealed,
+                    name,
+                    None,
+                    "class made sealed".to_string(),
+                );
+            }
+            return;
+        }
+
+        let removed = old_permitted
+            .iter()
+            .filter(|x| !new_permitted.contains(x))
+            .cloned()
+            .collect::<Vec<_>>();
+        if !removed.is_empty() {
+            self.report(
+                ChangeKind::PermittedSubclassesShrunk,
+                name,
+                None,
+                format!("permitted subclasses removed: {}", removed.join(", ")),
+            );
+        }
+    }
+
+    fn check_methods(&mut self, name: &str, old: &Component, new: &Component) {
+        let class_is_final = old.access_flags().contains(AccessFlags::FINAL);
+        let new_methods = new
+            .methods()
+            .iter()
+            .map(|x| (method_key(x), x))
+            .collect::<HashMap<_, _>>();
+        let old_methods = old
+            .methods()
+            .iter()
+            .filter(|x| is_api_method(x))
+            .map(|x| (method_key(x), x))
+            .collect::<HashMap<_, _>>();
+        let new_members = self.new_class_path.members_of(name);
+
+        for old_method in old.methods().iter().filter(|x| is_api_method(x)) {
+            let key = method_key(old_method);
+            let inherited = new_members.as_ref().and_then(|members| {
+                members
+                    .methods
+                    .iter()
+                    .map(|x| &x.method)
+                    .find(|x| method_key(x) == key)
+            });
+
+            let Some(new_method) = new_methods.get(&key).copied().or(inherited) else {
+                let replaced = new
+                    .methods()
+                    .iter()
+                    .any(|x| x.name == old_method.name && is_api_method(x));
+                if replaced {
+                    self.report(
+                        ChangeKind::MethodDescriptorChanged,
+                        name,
+                        Some(key),
+                        "method descriptor changed".to_string(),
+                    );
+                } else {
+                    self.report(
+                        ChangeKind::MethodRemoved,
+                        name,
+                        Some(key),
+                        "method removed".to_string(),
+                    );
+                }
+                continue;
+            };
+
+            let old_flags = old_method.access_flags;
+            let new_flags = new_method.access_flags;
+            if method_access_level(new_flags) < method_access_level(old_flags) {
+                self.report(
+                    ChangeKind::MethodAccessNarrowed,
+                    name,
+                    Some(key.clone()),
+                    "method access narrowed".to_string(),
+                );
+            }
+            if !class_is_final
+                && !old_flags.contains(MethodAccessFlags::STATIC)
+                && !old_flags.contains(MethodAccessFlags::FINAL)
+                && new_flags.contains(MethodAccessFlags::FINAL)
+            {
+                self.report(
+                    ChangeKind::MethodMadeFinal,
+                    name,
+                    Some(key.clone()),
+                    "method made final".to_string(),
+                );
+            }
+            if !old_flags.contains(MethodAccessFlags::ABSTRACT)
+                && new_flags.contains(MethodAccessFlags::ABSTRACT)
+            {
+                self.report(
+                    ChangeKind::MethodMadeAbstract,
+                    name,
+                    Some(key.clone()),
+                    "method made abstract".to_string(),
+                );
+            }
+            if old_flags.contains(MethodAccessFlags::STATIC)
+                != new_flags.contains(MethodAccessFlags::STATIC)
+            {
+                self.report(
+                    ChangeKind::MethodStaticChanged,
+                    name,
+                    Some(key.clone()),
+                    if old_flags.contains(MethodAccessFlags::STATIC) {
+                        "static method made non-static".to_string()
+                    } else {
+                        "instance method made static".to_string()
+                    },
+                );
+            }
+            if old_method.signature != new_method.signature {
+                self.report(
+                    ChangeKind::MethodSignatureChanged,
+                    name,
+                    Some(key),
+                    "generic signature changed".to_string(),
+                );
+            }
+        }
+
+        let is_abstract_type =
+            new.is_interface() || new.access_flags().contains(AccessFlags::ABSTRACT);
+        if is_abstract_type {
+            for new_method in new.methods().iter().filter(|x| is_api_method(x)) {
+                let key = method_key(new_method);
+                if new_method
+                    .access_flags
+                    .contains(MethodAccessFlags::ABSTRACT)
+                    && !old_methods.contains_key(&key)
+                {
+                    self.report(
+                        ChangeKind::AbstractMethodAdded,
+                        name,
+                        Some(key),
+                        "abstract method added".to_string(),
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_fields(&mut self, name: &str, old: &Component, new: &Component) {
+        let new_fields = new
+            .fields()
+            .iter()
+            .map(|x| (x.name.as_str(), x))
+            .collect::<HashMap<_, _>>();
+        let new_members = self.new_class_path.members_of(name);
+
+        for old_field in old.fields().iter().filter(|x| is_api_field(x)) {
+            let inherited = new_members.as_ref().and_then(|members| {
+                members
+                    .fields
+                    .iter()
+                    .map(|x| &x.field)
+                    .find(|x| x.name == old_field.name)
+            });
+            let key = Some(old_field.name.clone());
+
+            let Some(new_field) = new_fields
+                .get(old_field.name.as_str())
+                .copied()
+                .or(inherited)
+            else {
+                self.report(
+                    ChangeKind::FieldRemoved,
+                    name,
+                    key,
+                    "field removed".to_string(),
+                );
+                continue;
+            };
+
+            let old_flags = old_field.access_flags;
+            let new_flags = new_field.access_flags;
+            if old_field.descriptor != new_field.descriptor {
+                self.report(
+                    ChangeKind::FieldTypeChanged,
+                    name,
+                    key.clone(),
+                    format!(
+                        "field type changed from {} to {}",
+                        old_field.descriptor, new_field.descriptor
+                    ),
+                );
+            } else if old_field.signature != new_field.signature {
+                self.report(
+                    ChangeKind::FieldSignatureChanged,
+                    name,
+                    key.clone(),
+                    "generic signature changed".to_string(),
+                );
+            }
+            if field_access_level(new_flags) < field_access_level(old_flags) {
+                self.report(
+                    ChangeKind::FieldAccessNarrowed,
+                    name,
+                    key.clone(),
+                    "field access narrowed".to_string(),
+                );
+            }
+            if !old_flags.contains(FieldAccessFlags::FINAL)
+                && new_flags.contains(FieldAccessFlags::FINAL)
+            {
+                self.report(
+                    ChangeKind::FieldMadeFinal,
+                    name,
+                    key.clone(),
+                    "field made final".to_string(),
+                );
+            }
+            if old_flags.contains(FieldAccessFlags::STATIC)
+                != new_flags.contains(FieldAccessFlags::STATIC)
+            {
+                self.report(
+                    ChangeKind::FieldStaticChanged,
+                    name,
+                    key.clone(),
+                    if old_flags.contains(FieldAccessFlags::STATIC) {
+                        "static field made non-static".to_string()
+                    } else {
+                        "instance field made static".to_string()
+                    },
+                );
+            }
+            if let Some(old_value) = &old_field.constant_value {
+                let changed = match &new_field.constant_value {
+                    Some(new_value) => !constant_value_eq(old_value, new_value),
+                    None => true,
+                };
+                if changed {
+                    self.report(
+                        ChangeKind::ConstantValueChanged,
+                        name,
+                        key,
+                        format!(
+                            "constant value changed from {} to {}",
+                            format_constant_value(Some(old_value)),
+                            format_constant_value(new_field.constant_value.as_ref())
+                        ),
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn method_key(method: &Method) -> String {
+    format!("{}{}", method.name, method.descriptor)
+}
+
+fn method_access_level(flags: MethodAccessFlags) -> u8 {
+    if flags.contains(MethodAccessFlags::PUBLIC) {
+        3
+    } else if flags.contains(MethodAccessFlags::PROTECTED) {
+        2
+    } else if flags.contains(MethodAccessFlags::PRIVATE) {
+        0
+    } else {
+        1
+    }
+}
+
+fn field_access_level(flags: FieldAccessFlags) -> u8 {
+    if flags.contains(FieldAccessFlags::PUBLIC) {
+        3
+    } else if flags.contains(FieldAccessFlags::PROTECTED) {
+        2
+    } else if flags.contains(FieldAccessFlags::PRIVATE) {
+        0
+    } else {
+        1
+    }
+}
+
+fn is_api_method(method: &Method) -> bool {
+    method_access_level(method.access_flags) >= 2
+        && !method
+            .access_flags
+            .intersects(MethodAccessFlags::BRIDGE | MethodAccessFlags::SYNTHETIC)
+        && method.name != "<clinit>"
+}
+
+fn is_api_field(field: &Field) -> bool {
+    field_access_level(field.access_flags) >= 2
+        && !field.access_flags.contains(FieldAccessFlags::SYNTHETIC)
+}
+
+pub(crate) fn constant_value_eq(a: &ConstantValue, b: &ConstantValue) -> bool {
+    match (a, b) {
+        (ConstantValue::Float(a), ConstantValue::Float(b)) => a.to_bits() == b.to_bits(),
+        (ConstantValue::Double(a), ConstantValue::Double(b)) => a.to_bits() == b.to_bits(),
+        _ => a == b,
+    }
+}
+
+pub(crate) fn format_constant_value(value: Option<&ConstantValue>) -> String {
+    match value {
+        Some(ConstantValue::Int(v)) => v.to_string(),
+        Some(ConstantValue::Long(v)) => format!("{v}L"),
+        Some(ConstantValue::Float(v)) => format!("{v}f"),
+        Some(ConstantValue::Double(v)) => v.to_string(),
+        Some(ConstantValue::String(v)) => format!("{v:?}"),
+        None => "<not constant>".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        component::{Class, ComponentKind},
+        test_util::{self, component, method},
+    };
+
+    fn field(name: &str, constant_value: Option<ConstantValue>) -> Field {
+        Field {
+            constant_value,
+            ..test_util::field(name, "I", None, 0x0019)
+        }
+    }
+
+    fn class(
+        access_flags: AccessFlags,
+        methods: Vec<Method>,
+        fields: Vec<Field>,
+        permitted_subclasses: &[&str],
+    ) -> Component {
+        component(ComponentKind::Class(Class {
+            methods,
+            fields,
+            access_flags,
+            permitted_subclasses: permitted_subclasses.iter().map(|x| x.to_string()).collect(),
+            ..test_util::class("a.Api")
+        }))
+    }
+
+    #[test]
+    fn test_compatible_change() {
+        let old = class(
+            AccessFlags::PUBLIC,
+            vec![method("run", "()V", None, 0x0001)],
+            Vec::new(),
+            &[],
+        );
+        let new = class(
+            AccessFlags::PUBLIC,
+            vec![
+                method("run", "()V", None, 0x0001),
+                method("stop", "()V", None, 0x0001),
+            ],
+            Vec::new(),
+            &[],
+        );
+        assert!(check_compatibility(&[old], &[new]).is_empty());
+    }
+
+    #[test]
+    fn test_incompatible_changes() {
+        let old = class(
+            AccessFlags::PUBLIC,
+            vec![
+                method("run", "()V", None, 0x0001),
+                method("get", "()I", None, 0x0001),
+                method("hook", "()V", None, 0x0004),
+            ],
+            vec![field("MAX", Some(ConstantValue::Int(1)))],
+            &["a.A", "a.B"],
+        );
+        let new = class(
+            AccessFlags::PUBLIC | AccessFlags::FINAL,
+            vec![
+                method("get", "()J", None, 0x0001),
+                method("hook", "()V", None, 0),
+            ],
+            vec![field("MAX", Some(ConstantValue::Int(2)))],
+            &["a.A"],
+        );
+
+        let kinds = check_compatibility(&[old], &[new])
+            .into_iter()
+            .map(|x| x.kind)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                ChangeKind::ClassMadeFinal,
+                ChangeKind::PermittedSubclassesShrunk,
+                ChangeKind::MethodRemoved,
+                ChangeKind::MethodDescriptorChanged,
+                ChangeKind::MethodAccessNarrowed,
+                ChangeKind::ConstantValueChanged,
+            ]
+        );
+    }
+}
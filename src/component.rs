@@ -1,22 +1,25 @@
 use crate::{
+    bytecode::{opcodes, parse_instructions},
     class_file::{
-        AccessFlags, Attribute, AttributeKind, ClassFile, ConstantPoolInfo, FieldAccessFlags,
-        FieldInfo, MethodAccessFlags, MethodInfo,
+        self, AccessFlags, Attribute, AttributeKind, ClassFile, ConstantPoolInfo, ElementValuePair,
+        FieldAccessFlags, FieldInfo, InnerClassAccessFlags, MethodAccessFlags, MethodInfo,
     },
+    constant_pool::ConstantPoolError,
     descriptor::{
-        parse_field_descriptor, parse_method_descriptor, BaseTy, FieldDescriptor, FieldTy,
+        try_parse_field_descriptor, try_parse_method_descriptor, BaseTy, FieldDescriptor, FieldTy,
         ReturnDescriptor,
     },
+    release::{is_preview, JavaRelease},
     signature::{
         parse_class_signature, parse_field_signature, parse_method_signature, BaseType,
-        ClassSignature, FieldSignature, MethodSignature, ReferenceTypeSignature, Result,
-        TypeSignature,
+        ClassSignature, FieldSignature, MethodSignature, ReferenceTypeSignature,
+        Result as SignatureResult, TypeSignature,
     },
 };
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Component {
     pub minor_version: u16,
     pub major_version: u16,
@@ -24,20 +27,40 @@ pub struct Component {
     pub class_file_name: String,
 }
 
-#[derive(Debug, Serialize)]
+/// A component with where it was read from, as in output aggregating many inputs.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SourcedComponent {
+    /// The input JAR/JMOD file, directory or class file
+    pub source: String,
+    /// The path of the class file within `source`, empty when `source` is the class file
+    pub entry_path: String,
+    #[serde(flatten)]
+    pub component: Component,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub enum ComponentKind {
     Class(Class),
     Interface(Interface),
     Module(Module),
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TyName {
     pub package_name: Option<String>,
     pub name: String,
 }
 
-#[derive(Debug, Serialize)]
+impl TyName {
+    pub fn qualified_name(&self) -> String {
+        match &self.package_name {
+            Some(package_name) => format!("{package_name}.{}", self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Ty {
     Prim(PrimTy),
     Reference(TyName),
@@ -46,7 +69,32 @@ pub enum Ty {
     Void,
 }
 
-#[derive(Debug, Serialize)]
+impl Ty {
+    /// The name of the type as in source, with binary names for classes, e.g. `int[]`, `T` or
+    /// `java.util.Map$Entry`.
+    pub fn name(&self) -> String {
+        match self {
+            Ty::Prim(prim) => match prim {
+                PrimTy::Byte => "byte",
+                PrimTy::Char => "char",
+                PrimTy::Double => "double",
+                PrimTy::Float => "float",
+                PrimTy::Int => "int",
+                PrimTy::Long => "long",
+                PrimTy::Short => "short",
+                PrimTy::Boolean => "boolean",
+                PrimTy::Void => "void",
+            }
+            .to_string(),
+            Ty::Reference(name) => name.qualified_name(),
+            Ty::TyVar(name) => name.clone(),
+            Ty::Array(ty, dimensions) => format!("{}{}", ty.name(), "[]".repeat(*dimensions)),
+            Ty::Void => "void".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum PrimTy {
     Byte = 0,
     Char = 7,
@@ -59,7 +107,7 @@ pub enum PrimTy {
     Void = 8,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Class {
     pub qualified_name: String,
     pub super_class: Option<String>,
@@ -70,11 +118,16 @@ pub struct Class {
     pub annotations: Vec<Annotation>,
     pub is_enum: bool,
     pub is_abstract: bool,
+    pub access_flags: AccessFlags,
+    pub permitted_subclasses: Vec<String>,
+    pub inner_class: Option<InnerClassInfo>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Method {
     pub name: String,
+    pub descriptor: String,
+    pub access_flags: MethodAccessFlags,
     pub signature: Option<MethodSignature>,
     pub modifiers: String,
     pub param_tys: Vec<Ty>,
@@ -82,19 +135,44 @@ pub struct Method {
     pub type_params: Vec<String>,
     pub annotations: Vec<Annotation>,
     pub is_static: bool,
+    /// Binary names of the checked exceptions in the `Exceptions` attribute
+    pub exceptions: Vec<String>,
+    /// The default value of an annotation interface element
+    pub annotation_default: Option<AnnotationValue>,
+    /// For constructors, the constructor called first by `super(...)` or `this(...)`
+    pub constructor_call: Option<ConstructorCall>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConstructorCall {
+    /// Binary name of the superclass or of the class itself
+    pub class_name: String,
+    pub descriptor: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Field {
     pub name: String,
+    pub descriptor: String,
+    pub access_flags: FieldAccessFlags,
     pub ty: Ty,
     pub signature: Option<FieldSignature>,
     pub modifiers: String,
     pub annotations: Vec<Annotation>,
     pub is_static: bool,
+    pub constant_value: Option<ConstantValue>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ConstantValue {
+    Int(i32),
+    Long(i64),
+    Float(#[serde(with = "non_finite::f32")] f32),
+    Double(#[serde(with = "non_finite::f64")] f64),
+    String(String),
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Interface {
     pub is_annotation: bool,
     pub qualified_name: String,
@@ -103,22 +181,66 @@ pub struct Interface {
     pub methods: Vec<Method>,
     pub fields: Vec<Field>,
     pub annotations: Vec<Annotation>,
+    pub access_flags: AccessFlags,
+    pub permitted_subclasses: Vec<String>,
+    pub inner_class: Option<InnerClassInfo>,
 }
 
-#[derive(Debug, Serialize)]
+/// How a nested class is declared, from its own entry in the `InnerClasses` attribute.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InnerClassInfo {
+    /// Binary name of the declaring class, `None` for local and anonymous classes
+    pub outer_class: Option<String>,
+    /// `None` for anonymous classes
+    pub simple_name: Option<String>,
+    /// The flags as declared in source, e.g. `PRIVATE` or `STATIC`
+    pub access_flags: InnerClassAccessFlags,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Module {
     pub name: String,
     pub version: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Annotation {
     pub kind: AnnotationKind,
     pub ty: Ty,
+    /// The explicitly given elements, in class file order
+    pub elements: Vec<AnnotationElement>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnnotationElement {
+    pub name: String,
+    pub value: AnnotationValue,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum AnnotationValue {
+    Byte(i8),
+    /// A UTF-16 code unit
+    Char(u16),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(#[serde(with = "non_finite::f32")] f32),
+    Double(#[serde(with = "non_finite::f64")] f64),
+    Boolean(bool),
+    String(String),
+    Enum {
+        ty: Ty,
+        name: String,
+    },
+    /// A class literal such as `String.class`, or `void.class`
+    Class(Ty),
+    Annotation(Box<Annotation>),
+    Array(Vec<AnnotationValue>),
 }
 
 #[allow(clippy::enum_variant_names)]
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum AnnotationKind {
     RuntimeInvisible,
     RuntimeVisible,
@@ -128,13 +250,150 @@ pub enum AnnotationKind {
     RuntimeVisibleType,
 }
 
+/// Writes NaN and infinite floats as the strings `NaN`, `inf` and `-inf`, which JSON cannot
+/// represent as numbers.
+mod non_finite {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Float {
+        Number(f64),
+        Text(String),
+    }
+
+    pub mod f32 {
+        use super::*;
+
+        pub fn serialize<S: Serializer>(x: &f32, serializer: S) -> Result<S::Ok, S::Error> {
+            if x.is_finite() {
+                serializer.serialize_f32(*x)
+            } else {
+                serializer.collect_str(x)
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+            match Float::deserialize(deserializer)? {
+                Float::Number(x) => Ok(x as f32),
+                Float::Text(x) => x.parse().map_err(D::Error::custom),
+            }
+        }
+    }
+
+    pub mod f64 {
+        use super::*;
+
+        pub fn serialize<S: Serializer>(x: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+            if x.is_finite() {
+                serializer.serialize_f64(*x)
+            } else {
+                serializer.collect_str(x)
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+            match Float::deserialize(deserializer)? {
+                Float::Number(x) => Ok(x),
+                Float::Text(x) => x.parse().map_err(D::Error::custom),
+            }
+        }
+    }
+}
+
+impl Component {
+    /// The Java release the class file targets.
+    pub fn release(&self) -> Option<JavaRelease> {
+        JavaRelease::from_major_version(self.major_version)
+    }
+
+    pub fn is_preview(&self) -> bool {
+        is_preview(self.minor_version)
+    }
+
+    pub fn qualified_name(&self) -> Option<&str> {
+        match &self.kind {
+            ComponentKind::Class(c) => Some(&c.qualified_name),
+            ComponentKind::Interface(i) => Some(&i.qualified_name),
+            ComponentKind::Module(_) => None,
+        }
+    }
+
+    pub fn signature(&self) -> Option<&ClassSignature> {
+        match &self.kind {
+            ComponentKind::Class(c) => c.signature.as_ref(),
+            ComponentKind::Interface(i) => i.signature.as_ref(),
+            ComponentKind::Module(_) => None,
+        }
+    }
+
+    pub fn methods(&self) -> &[Method] {
+        match &self.kind {
+            ComponentKind::Class(c) => &c.methods,
+            ComponentKind::Interface(i) => &i.methods,
+            ComponentKind::Module(_) => &[],
+        }
+    }
+
+    pub fn fields(&self) -> &[Field] {
+        match &self.kind {
+            ComponentKind::Class(c) => &c.fields,
+            ComponentKind::Interface(i) => &i.fields,
+            ComponentKind::Module(_) => &[],
+        }
+    }
+
+    pub fn is_interface(&self) -> bool {
+        matches!(self.kind, ComponentKind::Interface(_))
+    }
+
+    pub fn access_flags(&self) -> AccessFlags {
+        match &self.kind {
+            ComponentKind::Class(c) => c.access_flags,
+            ComponentKind::Interface(i) => i.access_flags,
+            ComponentKind::Module(_) => AccessFlags::MODULE,
+        }
+    }
+
+    pub fn super_class(&self) -> Option<&str> {
+        match &self.kind {
+            ComponentKind::Class(c) => c.super_class.as_deref(),
+            _ => None,
+        }
+    }
+
+    pub fn interfaces(&self) -> &[String] {
+        match &self.kind {
+            ComponentKind::Class(c) => &c.interfaces,
+            ComponentKind::Interface(i) => &i.interfaces,
+            ComponentKind::Module(_) => &[],
+        }
+    }
+
+    pub fn annotations(&self) -> &[Annotation] {
+        match &self.kind {
+            ComponentKind::Class(c) => &c.annotations,
+            ComponentKind::Interface(i) => &i.annotations,
+            ComponentKind::Module(_) => &[],
+        }
+    }
+
+    pub fn permitted_subclasses(&self) -> &[String] {
+        match &self.kind {
+            ComponentKind::Class(c) => &c.permitted_subclasses,
+            ComponentKind::Interface(i) => &i.permitted_subclasses,
+            ComponentKind::Module(_) => &[],
+        }
+    }
+}
+
 struct ComponentExtractor<'a, 'ctxt> {
     class_file: &'a ClassFile,
     context: &'ctxt ExtractorContext,
 }
 
 bitflags::bitflags! {
-    #[derive(Debug, Serialize)]
+    #[derive(Debug, Serialize, Deserialize)]
     #[serde(transparent)]
     pub struct AccessModifier: u16 {
         const PRIVATE = 0x0001;
@@ -155,7 +414,7 @@ enum Kind {
 }
 
 impl<'a> ComponentExtractor<'a, '_> {
-    fn extract_component(&self) -> Component {
+    fn extract_component(&self) -> Result<Component, String> {
         let class_file_name = self.get_source_file_name();
         let class_file = &self.class_file;
 
@@ -166,71 +425,49 @@ impl<'a> ComponentExtractor<'a, '_> {
                 } else {
                     Kind::Interface
                 },
-            )
+            )?
         } else if class_file.access_flags.contains(AccessFlags::MODULE) {
-            self.extract_module_component()
+            self.extract_module_component()?
         } else {
-            self.extract_class_component(Kind::Class)
+            self.extract_class_component(Kind::Class)?
         };
 
-        Component {
+        Ok(Component {
             minor_version: class_file.minor_version,
             major_version: class_file.major_version,
             kind: comp_kind,
             class_file_name: class_file_name.unwrap_or_default().to_string(),
-        }
+        })
     }
 
-    fn extract_class_component(&self, kind: Kind) -> ComponentKind {
-        let ConstantPoolInfo::Class { name_index } =
-            &self.class_file.constant_pool[self.class_file.this_class as usize]
-        else {
-            panic!("Class file has no this_class indexing into constant pool")
-        };
-        let ConstantPoolInfo::Utf8 {
-            bytes: _,
-            length: _,
-            utf8_str: qualified_name,
-        } = &self.class_file.constant_pool[*name_index as usize]
-        else {
-            panic!("Class file has no name")
-        };
+    fn extract_class_component(&self, kind: Kind) -> Result<ComponentKind, String> {
+        let pool = self.class_file.pool();
+        let qualified_name = pool
+            .class_name(self.class_file.this_class)
+            .map_err(|e| format!("Class file has no name: {e}"))?;
 
         let super_class = if self.class_file.super_class == 0 {
             if qualified_name == "java/lang/Object" {
                 None
             } else {
-                panic!("Class file has no super_class")
+                return Err("Class file has no super_class".to_string());
             }
         } else {
-            let ConstantPoolInfo::Class { name_index } =
-                &self.class_file.constant_pool[self.class_file.super_class as usize]
-            else {
-                panic!("Class file has no super_class indexing into constant pool")
-            };
-            if *name_index != 0 {
-                let ConstantPoolInfo::Utf8 {
-                    bytes: _,
-                    length: _,
-                    utf8_str: super_class,
-                } = &self.class_file.constant_pool[*name_index as usize]
-                else {
-                    panic!("Class file has no name")
-                };
-                let super_class = super_class.replace('/', ".");
-                if super_class == "java.lang.Object" {
-                    None
-                } else {
-                    Some(super_class)
-                }
-            } else {
+            let super_class = pool
+                .class_name(self.class_file.super_class)
+                .map_err(|e| format!("Class file has no super_class: {e}"))?
+                .replace('/', ".");
+            if super_class == "java.lang.Object" {
                 None
+            } else {
+                Some(super_class)
             }
         };
 
-        let signature = self.extract_class_signature();
+        let signature = self.extract_class_signature()?;
         let class_sig = if let Some(signature) = signature {
-            let (_, class_signature) = parse_class_signature(&signature).unwrap();
+            let (_, class_signature) = parse_class_signature(&signature)
+                .map_err(|e| format!("Invalid class signature `{signature}`: {e}"))?;
             Some(class_signature)
         } else {
             None
@@ -241,42 +478,34 @@ impl<'a> ComponentExtractor<'a, '_> {
             .interfaces
             .iter()
             .map(|interface| {
-                let ConstantPoolInfo::Class { name_index } =
-                    &self.class_file.constant_pool[*interface as usize]
-                else {
-                    panic!("Class file has no interface indexing into constant pool")
-                };
-                let ConstantPoolInfo::Utf8 {
-                    bytes: _,
-                    length: _,
-                    utf8_str: interface,
-                } = &self.class_file.constant_pool[*name_index as usize]
-                else {
-                    panic!("Class file has no name")
-                };
-                interface.replace('/', ".")
+                pool.class_name(*interface)
+                    .map(|x| x.replace('/', "."))
+                    .map_err(|e| format!("Class file has no interface name: {e}"))
             })
-            .collect();
+            .collect::<Result<Vec<_>, _>>()?;
 
         let methods = self
             .class_file
             .methods
             .iter()
-            .filter_map(|x| self.extract_method_info(x))
-            .collect::<Vec<_>>();
+            .filter_map(|x| self.extract_method_info(x).transpose())
+            .collect::<Result<Vec<_>, _>>()?;
 
         let fields = self
             .class_file
             .fields
             .iter()
-            .filter_map(|x| self.extract_field_info(x))
-            .collect::<Vec<_>>();
+            .filter_map(|x| self.extract_field_info(x).transpose())
+            .collect::<Result<Vec<_>, _>>()?;
 
-        let annotations = self.extract_annotations(&self.class_file.attributes);
+        let annotations = self.extract_annotations(&self.class_file.attributes)?;
         let is_abstract = self.class_file.access_flags.contains(AccessFlags::ABSTRACT);
         let is_enum = self.class_file.access_flags.contains(AccessFlags::ENUM);
+        let access_flags = self.class_file.access_flags;
+        let permitted_subclasses = self.extract_permitted_subclasses()?;
+        let inner_class = self.extract_inner_class()?;
 
-        match kind {
+        Ok(match kind {
             Kind::Class => ComponentKind::Class(Class {
                 qualified_name: qualified_name.replace('/', "."),
                 super_class,
@@ -287,9 +516,12 @@ impl<'a> ComponentExtractor<'a, '_> {
                 annotations,
                 is_abstract,
                 is_enum,
+                access_flags,
+                permitted_subclasses,
+                inner_class,
             }),
             Kind::Interface | Kind::AnnotationInterface if super_class.is_some() => {
-                panic!("Interface has super class: {super_class:?}")
+                return Err(format!("Interface has super class: {super_class:?}"));
             }
             Kind::Interface => ComponentKind::Interface(Interface {
                 is_annotation: false,
@@ -299,6 +531,9 @@ impl<'a> ComponentExtractor<'a, '_> {
                 methods,
                 fields,
                 annotations,
+                access_flags,
+                permitted_subclasses,
+                inner_class: inner_class.clone(),
             }),
             Kind::AnnotationInterface => ComponentKind::Interface(Interface {
                 is_annotation: true,
@@ -308,11 +543,14 @@ impl<'a> ComponentExtractor<'a, '_> {
                 methods,
                 fields,
                 annotations,
+                access_flags,
+                permitted_subclasses,
+                inner_class,
             }),
-        }
+        })
     }
 
-    fn extract_module_component(&self) -> ComponentKind {
+    fn extract_module_component(&self) -> Result<ComponentKind, String> {
         for attr in &self.class_file.attributes {
             if let AttributeKind::Module {
                 module_name_index,
@@ -320,121 +558,101 @@ impl<'a> ComponentExtractor<'a, '_> {
                 ..
             } = attr.kind
             {
-                let ConstantPoolInfo::Module { name_index, .. } =
-                    &self.class_file.constant_pool[module_name_index as usize]
-                else {
-                    panic!("Class file has no this_class indexing into constant pool")
-                };
-
-                let ConstantPoolInfo::Utf8 {
-                    bytes: _,
-                    length: _,
-                    utf8_str: module_name,
-                } = &self.class_file.constant_pool[*name_index as usize]
-                else {
-                    panic!("Class file has no module name")
-                };
+                let pool = self.class_file.pool();
+                let module_name = pool
+                    .module_name(module_name_index)
+                    .map_err(|e| format!("Class file has no module name: {e}"))?;
 
                 let module_version = if module_version_index == 0 {
-                    String::new()
+                    ""
                 } else {
-                    let ConstantPoolInfo::Utf8 {
-                        bytes: _,
-                        length: _,
-                        utf8_str: module_version,
-                    } = &self.class_file.constant_pool[module_version_index as usize]
-                    else {
-                        panic!("Class file has no module version")
-                    };
-
-                    module_version.to_string()
+                    pool.utf8(module_version_index)
+                        .map_err(|e| format!("Class file has no module version: {e}"))?
                 };
 
-                return ComponentKind::Module(Module {
+                return Ok(ComponentKind::Module(Module {
                     name: module_name.to_string(),
                     version: module_version.to_string(),
-                });
+                }));
             }
         }
 
-        panic!("Class file has no module attribute")
+        Err("Class file has no module attribute".to_string())
     }
 
-    fn extract_class_signature(&self) -> Option<String> {
+    fn extract_class_signature(&self) -> Result<Option<String>, String> {
         for attr in &self.class_file.attributes {
             if let AttributeKind::Signature { signature_index } = attr.kind {
-                let ConstantPoolInfo::Utf8 {
-                    bytes: _,
-                    length: _,
-                    utf8_str: signature,
-                } = &self.class_file.constant_pool[signature_index as usize]
-                else {
-                    panic!("Class file has no signature")
-                };
-                return Some(signature.to_string());
+                let signature = self
+                    .class_file
+                    .pool()
+                    .utf8(signature_index)
+                    .map_err(|e| format!("Class file has no signature: {e}"))?;
+                return Ok(Some(signature.to_string()));
             }
         }
-        None
+        Ok(None)
     }
 
-    fn extract_method_signature(&self, method_info: &MethodInfo) -> Option<MethodSignature> {
+    fn extract_method_signature(
+        &self,
+        name: &str,
+        method_info: &MethodInfo,
+    ) -> Result<Option<MethodSignature>, String> {
         for attr in &method_info.attributes {
             if let AttributeKind::Signature { signature_index } = attr.kind {
-                let ConstantPoolInfo::Utf8 {
-                    bytes: _,
-                    length: _,
-                    utf8_str: signature,
-                } = &self.class_file.constant_pool[signature_index as usize]
-                else {
-                    panic!(
-                        "Method `{}` in Class file has no signature",
-                        method_info.get_name(&self.class_file.constant_pool)
-                    )
-                };
-
-                let (_, signature) = parse_method_signature(signature).unwrap();
-                return Some(signature);
+                let signature =
+                    self.class_file.pool().utf8(signature_index).map_err(|e| {
+                        format!("Method `{name}` in Class file has no signature: {e}")
+                    })?;
+
+                let (_, signature) = parse_method_signature(signature)
+                    .map_err(|e| format!("Method `{name}` has invalid signature: {e}"))?;
+                return Ok(Some(signature));
             }
         }
-        None
+        Ok(None)
     }
 
-    fn extract_field_signature(&self, field_info: &FieldInfo) -> Option<FieldSignature> {
+    fn extract_field_signature(
+        &self,
+        name: &str,
+        field_info: &FieldInfo,
+    ) -> Result<Option<FieldSignature>, String> {
         for attr in &field_info.attributes {
             if let AttributeKind::Signature { signature_index } = attr.kind {
-                let ConstantPoolInfo::Utf8 {
-                    bytes: _,
-                    length: _,
-                    utf8_str: signature,
-                } = &self.class_file.constant_pool[signature_index as usize]
-                else {
-                    panic!(
-                        "Field `{}` in Class file has no signature",
-                        field_info.get_name(&self.class_file.constant_pool)
-                    )
-                };
-
-                let (_, signature) = parse_field_signature(signature).unwrap();
-                return Some(signature);
+                let signature =
+                    self.class_file.pool().utf8(signature_index).map_err(|e| {
+                        format!("Field `{name}` in Class file has no signature: {e}")
+                    })?;
+
+                let (_, signature) = parse_field_signature(signature)
+                    .map_err(|e| format!("Field `{name}` has invalid signature: {e}"))?;
+                return Ok(Some(signature));
             }
         }
-        None
+        Ok(None)
     }
 
-    fn extract_method_info(&self, method_info: &MethodInfo) -> Option<Method> {
+    fn extract_method_info(&self, method_info: &MethodInfo) -> Result<Option<Method>, String> {
         if self.is_skippable_method(&method_info.access_flags) {
-            return None;
+            return Ok(None);
         }
 
-        let name = method_info.get_name(&self.class_file.constant_pool);
-        let sig = self.extract_method_signature(method_info);
-        let descriptor = method_info.get_descriptor(&self.class_file.constant_pool);
-        let descriptor = parse_method_descriptor(descriptor);
+        let pool = self.class_file.pool();
+        let name = pool
+            .utf8(method_info.name_index)
+            .map_err(|e| format!("Method has no name: {e}"))?;
+        let sig = self.extract_method_signature(name, method_info)?;
+        let descriptor_str = pool
+            .utf8(method_info.descriptor_index)
+            .map_err(|e| format!("Method `{name}` has no descriptor: {e}"))?;
+        let descriptor = try_parse_method_descriptor(descriptor_str)?;
 
         let ret_ty = if let Some(sig) = &sig {
             match &sig.result {
-                Result::JavaTypeSignature(ty_sig) => ty_sig.into(),
-                Result::VoidDescriptor => Ty::Void,
+                SignatureResult::JavaTypeSignature(ty_sig) => ty_sig.into(),
+                SignatureResult::VoidDescriptor => Ty::Void,
             }
         } else {
             match descriptor.ret_desc {
@@ -472,32 +690,61 @@ impl<'a> ComponentExtractor<'a, '_> {
                 .collect()
         };
 
-        let annotations = self.extract_annotations(&method_info.attributes);
+        let annotations = self.extract_annotations(&method_info.attributes)?;
 
         let is_static = method_info.access_flags.contains(MethodAccessFlags::STATIC);
 
-        Some(Method {
+        let exceptions = self.extract_exceptions(method_info)?;
+
+        let annotation_default = method_info
+            .attributes
+            .iter()
+            .find_map(|x| match &x.kind {
+                AttributeKind::AnnotationDefault { default_value } => Some(
+                    self.extract_annotation_value(&AnnotationKind::RuntimeVisible, default_value),
+                ),
+                _ => None,
+            })
+            .transpose()?;
+
+        let constructor_call = if name == "<init>" {
+            self.extract_constructor_call(method_info)
+        } else {
+            None
+        };
+
+        Ok(Some(Method {
             name: name.to_string(),
+            descriptor: descriptor_str.to_string(),
+            access_flags: method_info.access_flags,
             signature: sig,
-            modifiers: "".to_string(),
+            modifiers: method_modifiers(method_info.access_flags),
             param_tys,
             ret_ty,
             type_params,
             annotations,
             is_static,
-        })
+            exceptions,
+            annotation_default,
+            constructor_call,
+        }))
     }
 
-    fn extract_field_info(&self, field_info: &FieldInfo) -> Option<Field> {
+    fn extract_field_info(&self, field_info: &FieldInfo) -> Result<Option<Field>, String> {
         if self.is_skippable_field(&field_info.access_flags) {
-            return None;
+            return Ok(None);
         }
 
-        let name = field_info.get_name(&self.class_file.constant_pool);
-        let descriptor = field_info.get_descriptor(&self.class_file.constant_pool);
-        let descriptor = parse_field_descriptor(descriptor);
+        let pool = self.class_file.pool();
+        let name = pool
+            .utf8(field_info.name_index)
+            .map_err(|e| format!("Field has no name: {e}"))?;
+        let descriptor_str = pool
+            .utf8(field_info.descriptor_index)
+            .map_err(|e| format!("Field `{name}` has no descriptor: {e}"))?;
+        let descriptor = try_parse_field_descriptor(descriptor_str)?;
 
-        let sig = self.extract_field_signature(field_info);
+        let sig = self.extract_field_signature(name, field_info)?;
 
         let ty = if let Some(sig) = &sig {
             sig.into()
@@ -505,124 +752,345 @@ impl<'a> ComponentExtractor<'a, '_> {
             (&descriptor).into()
         };
 
-        let annotations = self.extract_annotations(&field_info.attributes);
+        let annotations = self.extract_annotations(&field_info.attributes)?;
 
         let is_static = field_info.access_flags.contains(FieldAccessFlags::STATIC);
 
-        Some(Field {
+        let constant_value = self.extract_constant_value(field_info, &descriptor)?;
+
+        Ok(Some(Field {
             name: name.to_string(),
+            descriptor: descriptor_str.to_string(),
+            access_flags: field_info.access_flags,
             ty,
             signature: sig,
-            modifiers: "".to_string(),
+            modifiers: field_modifiers(field_info.access_flags),
             annotations,
             is_static,
-        })
+            constant_value,
+        }))
     }
 
-    fn extract_annotations(&self, attributes: &[Attribute]) -> Vec<Annotation> {
-        attributes
-            .iter()
-            .filter_map(|attr| {
-                match &attr.kind {
-                    t @ (AttributeKind::RuntimeVisibleAnnotations {
-                        num_annotations: _,
-                        annotations,
+    fn extract_constant_value(
+        &self,
+        field_info: &FieldInfo,
+        descriptor: &FieldDescriptor,
+    ) -> Result<Option<ConstantValue>, String> {
+        for attr in &field_info.attributes {
+            if let AttributeKind::ConstantValue {
+                constant_value_index,
+            } = attr.kind
+            {
+                let pool = self.class_file.pool();
+                let value = match pool.get(constant_value_index) {
+                    Ok(ConstantPoolInfo::Integer { .. }) => {
+                        pool.integer(constant_value_index).map(ConstantValue::Int)
                     }
-                    | AttributeKind::RuntimeInvisibleAnnotations {
-                        num_annotations: _,
-                        annotations,
-                    }) => {
-                        Some(annotations.iter().map(move |annotation| {
-                            let ConstantPoolInfo::Utf8 { utf8_str, .. } =
-                                &self.class_file.constant_pool[annotation.type_index as usize]
-                            else {
-                                panic!("type_idex of annotation in {attr:?} indicates invalid constant pool index");
-                            };
-
-                            Annotation {
-                                kind: match t {
-                                    AttributeKind::RuntimeVisibleAnnotations { .. } => {
-                                        AnnotationKind::RuntimeVisible
-                                    }
-                                    AttributeKind::RuntimeInvisibleAnnotations { .. } => {
-                                        AnnotationKind::RuntimeInvisible
-                                    }
-                                    _ => unreachable!(),
-                                },
-                                ty: (&parse_field_descriptor(utf8_str)).into(),
-                            }
-                        }).collect::<Vec<_>>())
+                    Ok(ConstantPoolInfo::Float { .. }) => {
+                        pool.float(constant_value_index).map(ConstantValue::Float)
                     }
-                    t @ (AttributeKind::RuntimeVisibleParameterAnnotations {
-                        num_parameters: _,
-                        parameter_annotations,
+                    Ok(ConstantPoolInfo::Long { .. }) => {
+                        pool.long(constant_value_index).map(ConstantValue::Long)
                     }
-                    | AttributeKind::RuntimeInvisibleParameterAnnotations {
-                        num_parameters: _,
-                        parameter_annotations,
-                    }) => {
-                        Some(parameter_annotations.iter().flat_map(move |annotation| {
-                            annotation
-                                .annotations
-                                .iter()
-                                .map(move |annotation| {
-                                    let ConstantPoolInfo::Utf8 { utf8_str, .. } =
-                                        &self.class_file.constant_pool
-                                            [annotation.type_index as usize]
-                                    else {
-                                        panic!("type_idex of annotation in {attr:?} indicates invalid constant pool index");
-                                    };
-
-                                    Annotation {
-                                        kind: match t {
-                                            AttributeKind::RuntimeVisibleParameterAnnotations {
-                                                ..
-                                            } => AnnotationKind::RuntimeVisibleParameter,
-                                            AttributeKind::RuntimeInvisibleParameterAnnotations {
-                                                ..
-                                            } => AnnotationKind::RuntimeInvisibleParameter,
-                                            _ => unreachable!(),
-                                        },
-                                        ty: (&parse_field_descriptor(utf8_str)).into(),
-                                    }
-                                })
-                                .collect::<Vec<_>>()
-                        }).collect())
-                    },
-                    t @ (AttributeKind::RuntimeVisibleTypeAnnotations {
-                        num_annotations: _,
-                        type_annotations,
+                    Ok(ConstantPoolInfo::Double { .. }) => {
+                        pool.double(constant_value_index).map(ConstantValue::Double)
+                    }
+                    _ => pool
+                        .string(constant_value_index)
+                        .map(|x| ConstantValue::String(x.to_string())),
+                };
+                return value.map(Some).map_err(|e| {
+                    format!(
+                        "ConstantValue of field with descriptor `{}` is invalid: {e}",
+                        descriptor.descriptor
+                    )
+                });
+            }
+        }
+        Ok(None)
+    }
+
+    fn extract_permitted_subclasses(&self) -> Result<Vec<String>, String> {
+        for attr in &self.class_file.attributes {
+            if let AttributeKind::PermittedSubclasses { classes, .. } = &attr.kind {
+                return classes
+                    .iter()
+                    .map(|class| {
+                        self.class_file
+                            .pool()
+                            .class_name(*class)
+                            .map(|x| x.replace('/', "."))
+                            .map_err(|e| format!("Permitted subclass has no name: {e}"))
+                    })
+                    .collect();
+            }
+        }
+        Ok(Vec::new())
+    }
+
+    fn extract_exceptions(&self, method_info: &MethodInfo) -> Result<Vec<String>, String> {
+        for attr in &method_info.attributes {
+            if let AttributeKind::Exceptions {
+                exception_index_table,
+                ..
+            } = &attr.kind
+            {
+                return exception_index_table
+                    .iter()
+                    .map(|class| {
+                        self.class_file
+                            .pool()
+                            .class_name(*class)
+                            .map(|x| x.replace('/', "."))
+                            .map_err(|e| format!("Thrown exception has no name: {e}"))
+                    })
+                    .collect();
+            }
+        }
+        Ok(Vec::new())
+    }
+
+    /// Finds the `invokespecial` that initializes `this`, skipping the initialization of new
+    /// instances of the class or its superclass created for the arguments.
+    fn extract_constructor_call(&self, method_info: &MethodInfo) -> Option<ConstructorCall> {
+        let code = method_info.attributes.iter().find_map(|x| match &x.kind {
+            AttributeKind::Code { code, .. } => Some(code),
+            _ => None,
+        })?;
+        let pool = self.class_file.pool();
+        let this_class = pool.class_name(self.class_file.this_class).ok()?;
+        let super_class = pool.class_name(self.class_file.super_class).ok()?;
+        let (_, instructions) = parse_instructions(code).ok()?;
+
+        let mut new_instances = 0;
+        for instruction in instructions {
+            let Some(index) = instruction.constant_pool_index() else {
+                continue;
+            };
+            match instruction.opcode {
+                opcodes::NEW
+                    if pool
+                        .class_name(index)
+                        .is_ok_and(|x| x == this_class || x == super_class) =>
+                {
+                    new_instances += 1;
+                }
+                opcodes::INVOKESPECIAL => {
+                    let method = pool.member_ref(index).ok()?;
+                    if method.name != "<init>"
+                        || (method.class_name != this_class && method.class_name != super_class)
+                    {
+                        continue;
+                    }
+                    if new_instances > 0 {
+                        new_instances -= 1;
+                    } else {
+                        return Some(ConstructorCall {
+                            class_name: method.class_name.replace('/', "."),
+                            descriptor: method.descriptor.to_string(),
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+
+    fn extract_inner_class(&self) -> Result<Option<InnerClassInfo>, String> {
+        let pool = self.class_file.pool();
+        for attr in &self.class_file.attributes {
+            if let AttributeKind::InnerClasses { classes, .. } = &attr.kind {
+                let Some(inner_class) = classes
+                    .iter()
+                    .find(|x| x.inner_class_info_index == self.class_file.this_class)
+                else {
+                    return Ok(None);
+                };
+                let outer_class = (inner_class.outer_class_info_index != 0)
+                    .then(|| {
+                        pool.class_name(inner_class.outer_class_info_index)
+                            .map(|x| x.replace('/', "."))
+                            .map_err(|e| format!("Outer class has no name: {e}"))
+                    })
+                    .transpose()?;
+                let simple_name = (inner_class.inner_name_index != 0)
+                    .then(|| {
+                        pool.utf8(inner_class.inner_name_index)
+                            .map(|x| x.to_string())
+                            .map_err(|e| format!("Inner class has no name: {e}"))
+                    })
+                    .transpose()?;
+                return Ok(Some(InnerClassInfo {
+                    outer_class,
+                    simple_name,
+                    access_flags: inner_class.access_flags(),
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    fn extract_annotations(&self, attributes: &[Attribute]) -> Result<Vec<Annotation>, String> {
+        let mut result = Vec::new();
+        for attr in attributes {
+            match &attr.kind {
+                AttributeKind::RuntimeVisibleAnnotations { annotations, .. }
+                | AttributeKind::RuntimeInvisibleAnnotations { annotations, .. } => {
+                    let kind = match &attr.kind {
+                        AttributeKind::RuntimeVisibleAnnotations { .. } => {
+                            AnnotationKind::RuntimeVisible
+                        }
+                        _ => AnnotationKind::RuntimeInvisible,
+                    };
+                    for annotation in annotations {
+                        result.push(self.extract_annotation(
+                            kind.clone(),
+                            annotation.type_index,
+                            &annotation.element_value_pairs,
+                        )?);
                     }
-                    | AttributeKind::RuntimeInvisibleTypeAnnotations {
-                        num_annotations: _,
-                        type_annotations,
-                    }) => {
-                        Some(type_annotations.iter().map(move |annotation| {
-                            let ConstantPoolInfo::Utf8 { utf8_str, .. } =
-                                &self.class_file.constant_pool[annotation.type_index as usize]
-                            else {
-                                panic!("type_idex of annotation in {attr:?} indicates invalid constant pool index");
-                            };
-
-                            Annotation {
-                                kind: match t {
-                                    AttributeKind::RuntimeVisibleTypeAnnotations { .. } => {
-                                        AnnotationKind::RuntimeVisibleType
-                                    }
-                                    AttributeKind::RuntimeInvisibleTypeAnnotations { .. } => {
-                                        AnnotationKind::RuntimeInvisibleType
-                                    }
-                                    _ => unreachable!(),
-                                },
-                                ty: (&parse_field_descriptor(utf8_str)).into(),
-                            }
-                        }).collect::<Vec<_>>())
+                }
+                AttributeKind::RuntimeVisibleParameterAnnotations {
+                    parameter_annotations,
+                    ..
+                }
+                | AttributeKind::RuntimeInvisibleParameterAnnotations {
+                    parameter_annotations,
+                    ..
+                } => {
+                    let kind = match &attr.kind {
+                        AttributeKind::RuntimeVisibleParameterAnnotations { .. } => {
+                            AnnotationKind::RuntimeVisibleParameter
+                        }
+                        _ => AnnotationKind::RuntimeInvisibleParameter,
+                    };
+                    for annotation in parameter_annotations.iter().flat_map(|x| &x.annotations) {
+                        result.push(self.extract_annotation(
+                            kind.clone(),
+                            annotation.type_index,
+                            &annotation.element_value_pairs,
+                        )?);
                     }
-                    _ => None,
                 }
+                AttributeKind::RuntimeVisibleTypeAnnotations {
+                    type_annotations, ..
+                }
+                | AttributeKind::RuntimeInvisibleTypeAnnotations {
+                    type_annotations, ..
+                } => {
+                    let kind = match &attr.kind {
+                        AttributeKind::RuntimeVisibleTypeAnnotations { .. } => {
+                            AnnotationKind::RuntimeVisibleType
+                        }
+                        _ => AnnotationKind::RuntimeInvisibleType,
+                    };
+                    for annotation in type_annotations {
+                        result.push(self.extract_annotation(
+                            kind.clone(),
+                            annotation.type_index,
+                            &annotation.element_value_pairs,
+                        )?);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(result)
+    }
+
+    fn extract_annotation(
+        &self,
+        kind: AnnotationKind,
+        type_index: u16,
+        pairs: &[ElementValuePair],
+    ) -> Result<Annotation, String> {
+        let pool = self.class_file.pool();
+        let descriptor = pool
+            .utf8(type_index)
+            .map_err(|e| format!("type_index of annotation is invalid: {e}"))?;
+        let elements = pairs
+            .iter()
+            .map(|pair| {
+                Ok(AnnotationElement {
+                    name: pool
+                        .utf8(pair.element_name_index)
+                        .map_err(|e| format!("Annotation element has no name: {e}"))?
+                        .to_string(),
+                    value: self.extract_annotation_value(&kind, &pair.value)?,
+                })
             })
-            .flatten()
-            .collect()
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(Annotation {
+            kind,
+            ty: (&try_parse_field_descriptor(descriptor)?).into(),
+            elements,
+        })
+    }
+
+    fn extract_annotation_value(
+        &self,
+        kind: &AnnotationKind,
+        value: &class_file::ElementValue,
+    ) -> Result<AnnotationValue, String> {
+        use class_file::ElementValue as E;
+
+        let pool = self.class_file.pool();
+        fn invalid(e: ConstantPoolError) -> String {
+            format!("Annotation element value is invalid: {e}")
+        }
+
+        let int = |index: u16| pool.integer(index).map_err(invalid);
+        let utf8 = |index: u16| pool.utf8(index).map_err(invalid);
+        Ok(match value {
+            E::Byte { const_value_index } => AnnotationValue::Byte(int(*const_value_index)? as i8),
+            E::Char { const_value_index } => AnnotationValue::Char(int(*const_value_index)? as u16),
+            E::Short { const_value_index } => {
+                AnnotationValue::Short(int(*const_value_index)? as i16)
+            }
+            E::Int { const_value_index } => AnnotationValue::Int(int(*const_value_index)?),
+            E::Boolean { const_value_index } => {
+                AnnotationValue::Boolean(int(*const_value_index)? != 0)
+            }
+            E::Long { const_value_index } => {
+                AnnotationValue::Long(pool.long(*const_value_index).map_err(invalid)?)
+            }
+            E::Float { const_value_index } => {
+                AnnotationValue::Float(pool.float(*const_value_index).map_err(invalid)?)
+            }
+            E::Double { const_value_index } => {
+                AnnotationValue::Double(pool.double(*const_value_index).map_err(invalid)?)
+            }
+            E::String { const_value_index } => {
+                AnnotationValue::String(utf8(*const_value_index)?.to_string())
+            }
+            E::EnumConst {
+                type_name_index,
+                const_name_index,
+            } => AnnotationValue::Enum {
+                ty: (&try_parse_field_descriptor(utf8(*type_name_index)?)?).into(),
+                name: utf8(*const_name_index)?.to_string(),
+            },
+            E::ClassInfoIndex { class_info_index } => match utf8(*class_info_index)? {
+                "V" => AnnotationValue::Class(Ty::Void),
+                descriptor => {
+                    AnnotationValue::Class((&try_parse_field_descriptor(descriptor)?).into())
+                }
+            },
+            E::AnnotationValue { annotation } => {
+                AnnotationValue::Annotation(Box::new(self.extract_annotation(
+                    kind.clone(),
+                    annotation.type_index,
+                    &annotation.element_value_pairs,
+                )?))
+            }
+            E::ArrayValue { values, .. } => AnnotationValue::Array(
+                values
+                    .iter()
+                    .map(|x| self.extract_annotation_value(kind, x))
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+        })
     }
 
     fn is_skippable_field(&self, access_flag: &FieldAccessFlags) -> bool {
@@ -651,14 +1119,8 @@ impl<'a> ComponentExtractor<'a, '_> {
         let mut class_file_name = None;
         for attr in &self.class_file.attributes {
             if let AttributeKind::SourceFile { sourcefile_index } = attr.kind {
-                let name_info = &self.class_file.constant_pool[sourcefile_index as usize];
-                if let ConstantPoolInfo::Utf8 {
-                    bytes: _,
-                    length: _,
-                    utf8_str,
-                } = name_info
-                {
-                    class_file_name = Some(utf8_str as &str);
+                if let Ok(name) = self.class_file.pool().utf8(sourcefile_index) {
+                    class_file_name = Some(name);
                 }
             }
         }
@@ -667,7 +1129,71 @@ impl<'a> ComponentExtractor<'a, '_> {
     }
 }
 
+/// Returns the Java source modifiers of a class, e.g. `public abstract`.
+pub fn class_modifiers(flags: AccessFlags) -> String {
+    let mut modifiers = Vec::new();
+    if flags.contains(AccessFlags::PUBLIC) {
+        modifiers.push("public");
+    }
+    if flags.contains(AccessFlags::ABSTRACT) && !flags.contains(AccessFlags::INTERFACE) {
+        modifiers.push("abstract");
+    }
+    if flags.contains(AccessFlags::FINAL) {
+        modifiers.push("final");
+    }
+    modifiers.join(" ")
+}
+
+/// Returns the Java source modifiers of a method, e.g. `public static final`.
+pub fn method_modifiers(flags: MethodAccessFlags) -> String {
+    let mut modifiers = Vec::new();
+    for (flag, modifier) in [
+        (MethodAccessFlags::PUBLIC, "public"),
+        (MethodAccessFlags::PROTECTED, "protected"),
+        (MethodAccessFlags::PRIVATE, "private"),
+        (MethodAccessFlags::ABSTRACT, "abstract"),
+        (MethodAccessFlags::STATIC, "static"),
+        (MethodAccessFlags::FINAL, "final"),
+        (MethodAccessFlags::SYNCHRONIZED, "synchronized"),
+        (MethodAccessFlags::NATIVE, "native"),
+        (MethodAccessFlags::STRICT, "strictfp"),
+    ] {
+        if flags.contains(flag) {
+            modifiers.push(modifier);
+        }
+    }
+    modifiers.join(" ")
+}
+
+/// Returns the Java source modifiers of a field, e.g. `private static final`.
+pub fn field_modifiers(flags: FieldAccessFlags) -> String {
+    let mut modifiers = Vec::new();
+    for (flag, modifier) in [
+        (FieldAccessFlags::PUBLIC, "public"),
+        (FieldAccessFlags::PROTECTED, "protected"),
+        (FieldAccessFlags::PRIVATE, "private"),
+        (FieldAccessFlags::STATIC, "static"),
+        (FieldAccessFlags::FINAL, "final"),
+        (FieldAccessFlags::TRANSIENT, "transient"),
+        (FieldAccessFlags::VOLATILE, "volatile"),
+    ] {
+        if flags.contains(flag) {
+            modifiers.push(modifier);
+        }
+    }
+    modifiers.join(" ")
+}
+
 pub fn extract_component(class_file: &ClassFile, context: &ExtractorContext) -> Component {
+    try_extract_component(class_file, context).unwrap_or_else(|e| panic!("{e}"))
+}
+
+/// Extracts a component, returning an error instead of panicking if the class file is
+/// malformed, e.g. when it has an invalid constant pool index or descriptor.
+pub fn try_extract_component(
+    class_file: &ClassFile,
+    context: &ExtractorContext,
+) -> Result<Component, String> {
     let extractor = ComponentExtractor {
         class_file,
         context,
@@ -800,3 +1326,42 @@ impl From<&MethodAccessFlags> for AccessModifier {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::ConstantPoolBuilder;
+
+    #[test]
+    fn reports_malformed_class_files() {
+        let context = ExtractorContext {
+            target_access_modifiers: AccessModifier::empty(),
+        };
+        let class_file = |descriptor: &str, interfaces: &[&str]| {
+            let mut pool = ConstantPoolBuilder::new();
+            let run = pool.method("run", descriptor, MethodAccessFlags::PUBLIC, vec![0xb1]);
+            pool.class_file(
+                AccessFlags::PUBLIC | AccessFlags::SUPER,
+                "a/Main",
+                "java/lang/Object",
+                interfaces,
+                vec![run],
+                Vec::new(),
+            )
+        };
+
+        let component = try_extract_component(&class_file("()V", &["a/Named"]), &context);
+        assert_eq!(component.unwrap().qualified_name(), Some("a.Main"));
+
+        assert_eq!(
+            try_extract_component(&class_file("(V)V", &[]), &context).unwrap_err(),
+            "Invalid descriptor: (V)V"
+        );
+
+        let mut invalid_interface = class_file("()V", &["a/Named"]);
+        invalid_interface.interfaces = vec![0];
+        assert!(try_extract_component(&invalid_interface, &context)
+            .unwrap_err()
+            .starts_with("Class file has no interface name: "));
+    }
+}
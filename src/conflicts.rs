@@ -0,0 +1,389 @@
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap},
+    error::Error,
+    hash::{Hash, Hasher},
+    io::Read,
+    path::Path,
+};
+
+use serde::Serialize;
+
+use crate::{
+    class_file::{parse_class_file, AccessFlags},
+    component::{try_extract_component, AccessModifier, Component, ExtractorContext},
+    dependency::package_name,
+    diff::diff_components,
+};
+
+/// A class read from one element (a JAR file or a class directory) of a classpath.
+#[derive(Debug)]
+pub struct ClasspathEntry {
+    /// The path of the JAR file or directory containing the class
+    pub source: String,
+    pub component: Component,
+    /// Hash of the class file bytes
+    pub digest: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum DuplicateKind {
+    /// Every copy has the same bytes
+    Identical,
+    /// The copies differ but have the same public API
+    SameApi,
+    /// The public API of the copies differs
+    DifferentApi,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DuplicateClass {
+    pub qualified_name: String,
+    /// The classpath elements containing the class, in classpath order
+    pub sources: Vec<String>,
+    pub kind: DuplicateKind,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SplitPackage {
+    pub package: String,
+    pub sources: Vec<String>,
+}
+
+/// Classes defined by several classpath elements, where the first one wins, and packages
+/// split across elements, which the module system rejects.
+#[derive(Debug, Default, Serialize)]
+pub struct ConflictReport {
+    pub duplicate_classes: Vec<DuplicateClass>,
+    pub split_packages: Vec<SplitPackage>,
+}
+
+impl ConflictReport {
+    pub fn is_empty(&self) -> bool {
+        self.duplicate_classes.is_empty() && self.split_packages.is_empty()
+    }
+
+    pub fn to_table(&self) -> String {
+        let mut out = String::new();
+        if !self.duplicate_classes.is_empty() {
+            let width = self
+                .duplicate_classes
+                .iter()
+                .map(|x| x.qualified_name.len())
+                .max()
+                .unwrap_or_default();
+            out.push_str("Duplicate classes:\n");
+            for duplicate in &self.duplicate_classes {
+                let kind = match duplicate.kind {
+                    DuplicateKind::Identical => "identical",
+                    DuplicateKind::SameApi => "same API",
+                    DuplicateKind::DifferentApi => "different API",
+                };
+                out.push_str(&format!(
+                    "  {:width$}  {:13}  {}\n",
+                    duplicate.qualified_name,
+                    kind,
+                    duplicate.sources.join(", ")
+                ));
+            }
+        }
+
+        if !self.split_packages.is_empty() {
+            let width = self
+                .split_packages
+                .iter()
+                .map(|x| x.package.len().max("<unnamed>".len()))
+                .max()
+                .unwrap_or_default();
+            out.push_str("Split packages:\n");
+            for split in &self.split_packages {
+                let package = if split.package.is_empty() {
+                    "<unnamed>"
+                } else {
+                    &split.package
+                };
+                out.push_str(&format!(
+                    "  {:width$}  {}\n",
+                    package,
+                    split.sources.join(", ")
+                ));
+            }
+        }
+
+        let different_api = self
+            .duplicate_classes
+            .iter()
+            .filter(|x| x.kind == DuplicateKind::DifferentApi)
+            .count();
+        out.push_str(&format!(
+            "{} duplicate classes ({} with different API), {} split packages\n",
+            self.duplicate_classes.len(),
+            different_api,
+            self.split_packages.len()
+        ));
+        out
+    }
+}
+
+/// The classes read from one element of a classpath.
+#[derive(Debug, Default)]
+pub struct ClasspathElement {
+    pub entries: Vec<ClasspathEntry>,
+    /// Class files which failed to parse and were skipped
+    pub errors: Vec<String>,
+}
+
+/// Reads the classes of a JAR file, or of every class file below a directory. `module-info`
+/// and the versioned classes of multi-release JARs are skipped, and so are class files which
+/// fail to parse.
+pub fn read_classpath_element<P: AsRef<Path>>(path: P) -> Result<ClasspathElement, Box<dyn Error>> {
+    let path = path.as_ref();
+    let source = path.display().to_string();
+
+    let mut class_bytes = Vec::new();
+    if path.is_dir() {
+        for entry in walkdir::WalkDir::new(path)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().is_file())
+        {
+            if entry.path().extension().unwrap_or_default() == "class" {
+                let name = entry.path().display().to_string();
+                class_bytes.push((name, std::fs::read(entry.path())?));
+            }
+        }
+    } else {
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(path)?)?;
+        for i in 0..archive.len() {
+            let mut file = archive.by_index(i)?;
+            if !file.name().ends_with(".class") || file.name().starts_with("META-INF/") {
+                continue;
+            }
+
+            let mut buf = Vec::new();
+            file.read_to_end(&mut buf)?;
+            class_bytes.push((format!("{} in {source}", file.name()), buf));
+        }
+    }
+
+    let context = ExtractorContext {
+        target_access_modifiers: AccessModifier::empty(),
+    };
+    let mut element = ClasspathElement::default();
+    for (name, bytes) in class_bytes {
+        let class_file = match parse_class_file(&bytes) {
+            Ok((_, class_file)) => class_file,
+            Err(e) => {
+                element
+                    .errors
+                    .push(format!("Error parsing class file {name}: {e}"));
+                continue;
+            }
+        };
+        if class_file.access_flags.contains(AccessFlags::MODULE) {
+            continue;
+        }
+        let component = match try_extract_component(&class_file, &context) {
+            Ok(component) => component,
+            Err(e) => {
+                element
+                    .errors
+                    .push(format!("Error parsing class file {name}: {e}"));
+                continue;
+            }
+        };
+
+        let mut hasher = DefaultHasher::new();
+        bytes.hash(&mut hasher);
+        element.entries.push(ClasspathEntry {
+            source: source.clone(),
+            component,
+            digest: hasher.finish(),
+        });
+    }
+
+    Ok(element)
+}
+
+/// Finds duplicate classes and split packages among classpath entries given in classpath
+/// order.
+pub fn find_conflicts(entries: &[ClasspathEntry]) -> ConflictReport {
+    let mut classes: BTreeMap<&str, Vec<&ClasspathEntry>> = BTreeMap::new();
+    let mut packages: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for entry in entries {
+        let Some(name) = entry.component.qualified_name() else {
+            continue;
+        };
+        classes.entry(name).or_default().push(entry);
+
+        let sources = packages.entry(package_name(name)).or_default();
+        if !sources.contains(&entry.source.as_str()) {
+            sources.push(&entry.source);
+        }
+    }
+
+    let mut report = ConflictReport::default();
+    for (name, copies) in classes {
+        let mut sources = Vec::new();
+        for copy in &copies {
+            if !sources.contains(&copy.source) {
+                sources.push(copy.source.clone());
+            }
+        }
+        if sources.len() < 2 {
+            continue;
+        }
+
+        let first = copies[0];
+        let kind = if copies.iter().all(|x| x.digest == first.digest) {
+            DuplicateKind::Identical
+        } else if copies.iter().all(|x| {
+            diff_components(
+                std::slice::from_ref(&first.component),
+                std::slice::from_ref(&x.component),
+            )
+            .is_empty()
+        }) {
+            DuplicateKind::SameApi
+        } else {
+            DuplicateKind::DifferentApi
+        };
+
+        report.duplicate_classes.push(DuplicateClass {
+            qualified_name: name.to_string(),
+            sources,
+            kind,
+        });
+    }
+
+    for (package, sources) in packages {
+        if sources.len() > 1 {
+            report.split_packages.push(SplitPackage {
+                package: package.to_string(),
+                sources: sources.into_iter().map(|x| x.to_string()).collect(),
+            });
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        component::{Class, ComponentKind},
+        test_util::{self, component, method},
+    };
+
+    fn entry(source: &str, name: &str, methods: &[&str], digest: u64) -> ClasspathEntry {
+        ClasspathEntry {
+            source: source.to_string(),
+            component: component(ComponentKind::Class(Class {
+                methods: methods
+                    .iter()
+                    .map(|x| method(x, "()V", None, 0x0001))
+                    .collect(),
+                ..test_util::class(name)
+            })),
+            digest,
+        }
+    }
+
+    fn classpath() -> Vec<ClasspathEntry> {
+        vec![
+            entry("a.jar", "a.Api", &["run"], 1),
+            entry("a.jar", "a.Util", &["help"], 2),
+            entry("a.jar", "c.Model", &["get"], 3),
+            entry("b.jar", "a.Api", &["run"], 1),
+            entry("b.jar", "b.Helper", &[], 4),
+            // a recompiled copy with the same API
+            entry("c.jar", "c.Model", &["get"], 5),
+            entry("c.jar", "a.Util", &["help", "assist"], 6),
+            entry("c.jar", "b.Other", &[], 7),
+        ]
+    }
+
+    #[test]
+    fn classifies_duplicate_classes() {
+        let report = find_conflicts(&classpath());
+
+        let duplicates = report
+            .duplicate_classes
+            .iter()
+            .map(|x| (x.qualified_name.as_str(), x.sources.join(", "), x.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            duplicates,
+            vec![
+                (
+                    "a.Api",
+                    "a.jar, b.jar".to_string(),
+                    DuplicateKind::Identical
+                ),
+                (
+                    "a.Util",
+                    "a.jar, c.jar".to_string(),
+                    DuplicateKind::DifferentApi
+                ),
+                (
+                    "c.Model",
+                    "a.jar, c.jar".to_string(),
+                    DuplicateKind::SameApi
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_split_packages() {
+        let report = find_conflicts(&classpath());
+
+        let split_packages = report
+            .split_packages
+            .iter()
+            .map(|x| (x.package.as_str(), x.sources.join(", ")))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            split_packages,
+            vec![
+                ("a", "a.jar, b.jar, c.jar".to_string()),
+                ("b", "b.jar, c.jar".to_string()),
+                ("c", "a.jar, c.jar".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_conflicts_as_a_table() {
+        let report = find_conflicts(&classpath());
+        // the conflicts subcommand exits with status 1 unless the report is empty
+        assert!(!report.is_empty());
+        assert_eq!(
+            report.to_table(),
+            "Duplicate classes:
+  a.Api    identical      a.jar, b.jar
+  a.Util   different API  a.jar, c.jar
+  c.Model  same API       a.jar, c.jar
+Split packages:
+  a          a.jar, b.jar, c.jar
+  b          b.jar, c.jar
+  c          a.jar, c.jar
+3 duplicate classes (1 with different API), 3 split packages
+"
+        );
+    }
+
+    #[test]
+    fn reports_nothing_for_disjoint_elements() {
+        let report = find_conflicts(&[
+            entry("a.jar", "a.Api", &["run"], 1),
+            entry("a.jar", "a.Util", &[], 2),
+            entry("b.jar", "b.Other", &[], 3),
+        ]);
+        assert!(report.is_empty());
+        assert_eq!(
+            report.to_table(),
+            "0 duplicate classes (0 with different API), 0 split packages\n"
+        );
+    }
+}
@@ -0,0 +1,291 @@
+use std::{error::Error, fmt};
+
+use crate::class_file::ConstantPoolInfo;
+
+/// A checked view of a class file's constant pool. Every accessor validates the index and the
+/// tag of the entries it follows and returns an error instead of panicking.
+#[derive(Debug, Clone, Copy)]
+pub struct ConstantPool<'a> {
+    entries: &'a [ConstantPoolInfo],
+}
+
+/// A resolved `Fieldref`, `Methodref` or `InterfaceMethodref` entry. Class names are in
+/// internal form (`java/lang/Object`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemberRef<'a> {
+    pub class_name: &'a str,
+    pub name: &'a str,
+    pub descriptor: &'a str,
+}
+
+/// A resolved `MethodHandle` entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MethodHandle<'a> {
+    /// One of the `REF_*` kinds, e.g. 6 for `REF_invokeStatic`
+    pub reference_kind: u8,
+    pub reference: MemberRef<'a>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConstantPoolError {
+    /// The index is zero, out of bounds, or the second slot of a `Long` or `Double`
+    InvalidIndex(u16),
+    UnexpectedTag {
+        index: u16,
+        expected: &'static str,
+        found: &'static str,
+    },
+}
+
+impl fmt::Display for ConstantPoolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConstantPoolError::InvalidIndex(index) => {
+                write!(f, "invalid constant pool index {index}")
+            }
+            ConstantPoolError::UnexpectedTag {
+                index,
+                expected,
+                found,
+            } => write!(
+                f,
+                "constant pool entry {index} is {found}, expected {expected}"
+            ),
+        }
+    }
+}
+
+impl Error for ConstantPoolError {}
+
+type Result<T> = std::result::Result<T, ConstantPoolError>;
+
+impl<'a> ConstantPool<'a> {
+    pub fn new(entries: &'a [ConstantPoolInfo]) -> Self {
+        Self { entries }
+    }
+
+    /// The raw entries, including the unused entry at index 0.
+    pub fn entries(&self) -> &'a [ConstantPoolInfo] {
+        self.entries
+    }
+
+    pub fn get(&self, index: u16) -> Result<&'a ConstantPoolInfo> {
+        match self.entries.get(index as usize) {
+            None | Some(ConstantPoolInfo::Empty) => Err(ConstantPoolError::InvalidIndex(index)),
+            Some(entry) => Ok(entry),
+        }
+    }
+
+    pub fn utf8(&self, index: u16) -> Result<&'a str> {
+        match self.get(index)? {
+            ConstantPoolInfo::Utf8 { utf8_str, .. } => Ok(utf8_str),
+            other => Err(unexpected(index, "Utf8", other)),
+        }
+    }
+
+    /// The name of a `Class` entry, in internal form.
+    pub fn class_name(&self, index: u16) -> Result<&'a str> {
+        match self.get(index)? {
+            ConstantPoolInfo::Class { name_index } => self.utf8(*name_index),
+            other => Err(unexpected(index, "Class", other)),
+        }
+    }
+
+    /// The name and descriptor of a `NameAndType` entry.
+    pub fn name_and_type(&self, index: u16) -> Result<(&'a str, &'a str)> {
+        match self.get(index)? {
+            ConstantPoolInfo::NameAndType {
+                name_index,
+                descriptor_index,
+            } => Ok((self.utf8(*name_index)?, self.utf8(*descriptor_index)?)),
+            other => Err(unexpected(index, "NameAndType", other)),
+        }
+    }
+
+    /// Resolves a `Fieldref`, `Methodref` or `InterfaceMethodref` entry.
+    pub fn member_ref(&self, index: u16) -> Result<MemberRef<'a>> {
+        match self.get(index)? {
+            ConstantPoolInfo::Fieldref {
+                class_index,
+                name_and_type_index,
+            }
+            | ConstantPoolInfo::Methodref {
+                class_index,
+                name_and_type_index,
+            }
+            | ConstantPoolInfo::InterfaceMethodref {
+                class_index,
+                name_and_type_index,
+            } => {
+                let (name, descriptor) = self.name_and_type(*name_and_type_index)?;
+                Ok(MemberRef {
+                    class_name: self.class_name(*class_index)?,
+                    name,
+                    descriptor,
+                })
+            }
+            other => Err(unexpected(index, "Fieldref or Methodref", other)),
+        }
+    }
+
+    pub fn method_handle(&self, index: u16) -> Result<MethodHandle<'a>> {
+        match self.get(index)? {
+            ConstantPoolInfo::MethodHandle {
+                reference_kind,
+                reference_index,
+            } => Ok(MethodHandle {
+                reference_kind: *reference_kind,
+                reference: self.member_ref(*reference_index)?,
+            }),
+            other => Err(unexpected(index, "MethodHandle", other)),
+        }
+    }
+
+    /// The value of a `String` entry.
+    pub fn string(&self, index: u16) -> Result<&'a str> {
+        match self.get(index)? {
+            ConstantPoolInfo::String { string_index } => self.utf8(*string_index),
+            other => Err(unexpected(index, "String", other)),
+        }
+    }
+
+    /// The name of a `Module` entry.
+    pub fn module_name(&self, index: u16) -> Result<&'a str> {
+        match self.get(index)? {
+            ConstantPoolInfo::Module { name_index } => self.utf8(*name_index),
+            other => Err(unexpected(index, "Module", other)),
+        }
+    }
+
+    pub fn integer(&self, index: u16) -> Result<i32> {
+        match self.get(index)? {
+            ConstantPoolInfo::Integer { bytes } => Ok(*bytes as i32),
+            other => Err(unexpected(index, "Integer", other)),
+        }
+    }
+
+    pub fn long(&self, index: u16) -> Result<i64> {
+        match self.get(index)? {
+            ConstantPoolInfo::Long {
+                high_bytes,
+                low_bytes,
+            } => Ok(((*high_bytes as u64) << 32 | *low_bytes as u64) as i64),
+            other => Err(unexpected(index, "Long", other)),
+        }
+    }
+
+    pub fn float(&self, index: u16) -> Result<f32> {
+        match self.get(index)? {
+            ConstantPoolInfo::Float { bytes } => Ok(f32::from_bits(*bytes)),
+            other => Err(unexpected(index, "Float", other)),
+        }
+    }
+
+    pub fn double(&self, index: u16) -> Result<f64> {
+        match self.get(index)? {
+            ConstantPoolInfo::Double {
+                high_bytes,
+                low_bytes,
+            } => Ok(f64::from_bits(
+                (*high_bytes as u64) << 32 | *low_bytes as u64,
+            )),
+            other => Err(unexpected(index, "Double", other)),
+        }
+    }
+}
+
+fn unexpected(index: u16, expected: &'static str, found: &ConstantPoolInfo) -> ConstantPoolError {
+    ConstantPoolError::UnexpectedTag {
+        index,
+        expected,
+        found: tag_name(found),
+    }
+}
+
+fn tag_name(entry: &ConstantPoolInfo) -> &'static str {
+    match entry {
+        ConstantPoolInfo::Class { .. } => "Class",
+        ConstantPoolInfo::Fieldref { .. } => "Fieldref",
+        ConstantPoolInfo::Methodref { .. } => "Methodref",
+        ConstantPoolInfo::InterfaceMethodref { .. } => "InterfaceMethodref",
+        ConstantPoolInfo::String { .. } => "String",
+        ConstantPoolInfo::Integer { .. } => "Integer",
+        ConstantPoolInfo::Float { .. } => "Float",
+        ConstantPoolInfo::Long { .. } => "Long",
+        ConstantPoolInfo::Double { .. } => "Double",
+        ConstantPoolInfo::NameAndType { .. } => "NameAndType",
+        ConstantPoolInfo::Utf8 { .. } => "Utf8",
+        ConstantPoolInfo::MethodHandle { .. } => "MethodHandle",
+        ConstantPoolInfo::MethodType { .. } => "MethodType",
+        ConstantPoolInfo::Dynamic { .. } => "Dynamic",
+        ConstantPoolInfo::InvokeDynamic { .. } => "InvokeDynamic",
+        ConstantPoolInfo::Module { .. } => "Module",
+        ConstantPoolInfo::Package { .. } => "Package",
+        ConstantPoolInfo::Empty => "Empty",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::utf8;
+
+    #[test]
+    fn resolves_entries_and_reports_errors() {
+        let entries = vec![
+            ConstantPoolInfo::Empty,
+            utf8("a/b/Main"),
+            ConstantPoolInfo::Class { name_index: 1 },
+            utf8("run"),
+            utf8("()V"),
+            ConstantPoolInfo::NameAndType {
+                name_index: 3,
+                descriptor_index: 4,
+            },
+            ConstantPoolInfo::Methodref {
+                class_index: 2,
+                name_and_type_index: 5,
+            },
+            ConstantPoolInfo::MethodHandle {
+                reference_kind: 6,
+                reference_index: 6,
+            },
+            ConstantPoolInfo::Long {
+                high_bytes: 0xffff_ffff,
+                low_bytes: 0xffff_fffe,
+            },
+            ConstantPoolInfo::Empty,
+            ConstantPoolInfo::String { string_index: 3 },
+        ];
+        let pool = ConstantPool::new(&entries);
+
+        let run = MemberRef {
+            class_name: "a/b/Main",
+            name: "run",
+            descriptor: "()V",
+        };
+        assert_eq!(pool.class_name(2), Ok("a/b/Main"));
+        assert_eq!(pool.member_ref(6), Ok(run));
+        assert_eq!(
+            pool.method_handle(7),
+            Ok(MethodHandle {
+                reference_kind: 6,
+                reference: run
+            })
+        );
+        assert_eq!(pool.long(8), Ok(-2));
+        assert_eq!(pool.string(10), Ok("run"));
+
+        assert_eq!(pool.utf8(0), Err(ConstantPoolError::InvalidIndex(0)));
+        assert_eq!(pool.double(9), Err(ConstantPoolError::InvalidIndex(9)));
+        assert_eq!(pool.utf8(11), Err(ConstantPoolError::InvalidIndex(11)));
+        assert_eq!(
+            pool.utf8(2),
+            Err(ConstantPoolError::UnexpectedTag {
+                index: 2,
+                expected: "Utf8",
+                found: "Class"
+            })
+        );
+    }
+}
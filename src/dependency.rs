@@ -0,0 +1,530 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+
+use crate::{
+    class_file::{
+        AccessFlags, Annotation, Attribute, AttributeKind, ClassFile, ConstantPoolInfo,
+        ElementValue,
+    },
+    constant_pool::ConstantPool,
+    descriptor::{
+        try_parse_field_descriptor, try_parse_method_descriptor, FieldTy, ReturnDescriptor,
+    },
+    signature::{
+        parse_class_signature, parse_field_signature, parse_method_signature, ClassTypeSignature,
+        ReferenceTypeSignature, Result as SignatureResult, ThrowsSignature, TypeArgument,
+        TypeParameter, TypeSignature,
+    },
+};
+
+/// Packages whose classes are internal to the JDK and not part of the Java SE API.
+const JDK_INTERNAL_PACKAGES: &[&str] = &["sun", "jdk.internal", "com.sun.proxy"];
+
+/// Class and package level dependencies of a set of class files, similar to `jdeps`.
+///
+/// Dependencies are collected from the constant pool (class entries, name-and-type and method
+/// type descriptors), field and method descriptors, generic signatures and annotations.
+/// Instructions which invoke methods or access fields always reference their owner through a
+/// constant pool entry, so those are covered without decoding the code.
+#[derive(Debug, Default, Serialize)]
+pub struct DependencyGraph {
+    /// Referenced classes keyed by the qualified name of the referencing class
+    pub classes: BTreeMap<String, BTreeSet<String>>,
+    /// Referenced packages keyed by the referencing package, excluding the package itself
+    pub packages: BTreeMap<String, BTreeSet<String>>,
+    /// References to JDK-internal classes keyed by the referencing class
+    pub jdk_internal: BTreeMap<String, BTreeSet<String>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Granularity {
+    Class,
+    Package,
+}
+
+impl DependencyGraph {
+    pub fn new(class_files: &[ClassFile]) -> Self {
+        let mut graph = Self::default();
+        for class_file in class_files {
+            graph.insert(class_file);
+        }
+        graph
+    }
+
+    /// Adds the dependencies of a class file. `module-info` class files are ignored.
+    pub fn insert(&mut self, class_file: &ClassFile) {
+        let Some((class_name, dependencies)) = class_dependencies(class_file) else {
+            return;
+        };
+
+        let package = package_name(&class_name).to_string();
+        let packages = self.packages.entry(package.clone()).or_default();
+        for dependency in &dependencies {
+            let dependency_package = package_name(dependency);
+            if dependency_package != package {
+                packages.insert(dependency_package.to_string());
+            }
+        }
+
+        let internal = dependencies
+            .iter()
+            .filter(|x| is_jdk_internal(package_name(x)))
+            .cloned()
+            .collect::<BTreeSet<_>>();
+        if !internal.is_empty() {
+            self.jdk_internal
+                .entry(class_name.clone())
+                .or_default()
+                .extend(internal);
+        }
+
+        self.classes
+            .entry(class_name)
+            .or_default()
+            .extend(dependencies);
+    }
+
+    fn edges(&self, granularity: Granularity) -> &BTreeMap<String, BTreeSet<String>> {
+        match granularity {
+            Granularity::Class => &self.classes,
+            Granularity::Package => &self.packages,
+        }
+    }
+
+    fn is_internal(&self, name: &str, granularity: Granularity) -> bool {
+        match granularity {
+            Granularity::Class => is_jdk_internal(package_name(name)),
+            Granularity::Package => is_jdk_internal(name),
+        }
+    }
+
+    /// Renders the graph in Graphviz DOT format, with JDK-internal dependencies in red.
+    pub fn to_dot(&self, granularity: Granularity) -> String {
+        let mut out = String::from("digraph \"dependencies\" {\n");
+        for (from, dependencies) in self.edges(granularity) {
+            for to in dependencies {
+                out.push_str(&format!(
+                    "  \"{}\" -> \"{}\"",
+                    display_name(from),
+                    display_name(to)
+                ));
+                if self.is_internal(to, granularity) {
+                    out.push_str(" [color=red]");
+                }
+                out.push_str(";\n");
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Renders the graph as a plain text table with one dependency per line, in the style of
+    /// `jdeps -verbose`.
+    pub fn to_table(&self, granularity: Granularity) -> String {
+        let edges = self.edges(granularity);
+        let width = edges
+            .keys()
+            .map(|x| display_name(x).len())
+            .max()
+            .unwrap_or_default();
+
+        let mut out = String::new();
+        for (from, dependencies) in edges {
+            for to in dependencies {
+                let line = format!("{:width$} -> {}", display_name(from), display_name(to));
+                if self.is_internal(to, granularity) {
+                    out.push_str(&format!("{line}  JDK internal API\n"));
+                } else {
+                    out.push_str(&format!("{line}\n"));
+                }
+            }
+        }
+
+        let internal_count = self.jdk_internal.values().map(|x| x.len()).sum::<usize>();
+        out.push_str(&format!(
+            "{} classes, {} packages, {} dependencies on JDK internal APIs\n",
+            self.classes.len(),
+            self.packages.len(),
+            internal_count
+        ));
+        out
+    }
+}
+
+fn display_name(name: &str) -> &str {
+    if name.is_empty() {
+        "<unnamed>"
+    } else {
+        name
+    }
+}
+
+/// Returns the package of a qualified class name, or an empty string for the unnamed package.
+pub fn package_name(qualified_name: &str) -> &str {
+    qualified_name
+        .rsplit_once('.')
+        .map(|(package, _)| package)
+        .unwrap_or_default()
+}
+
+/// Returns whether a package is internal to the JDK, such as `sun.misc` or
+/// `jdk.internal.misc`.
+pub fn is_jdk_internal(package: &str) -> bool {
+    let is_under = |prefix: &str| {
+        package
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+    };
+    JDK_INTERNAL_PACKAGES.iter().any(|x| is_under(x))
+        || (is_under("com.sun") && package.split('.').any(|x| x == "internal"))
+}
+
+/// Returns the qualified name of a class file and every other class it references.
+/// Returns `None` for `module-info` class files.
+pub fn class_dependencies(class_file: &ClassFile) -> Option<(String, BTreeSet<String>)> {
+    if class_file.access_flags.contains(AccessFlags::MODULE) {
+        return None;
+    }
+
+    let mut collector = DependencyCollector {
+        pool: class_file.pool(),
+        names: BTreeSet::new(),
+    };
+    let class_name = collector
+        .pool
+        .class_name(class_file.this_class)
+        .ok()?
+        .replace('/', ".");
+
+    for entry in &class_file.constant_pool {
+        match entry {
+            ConstantPoolInfo::Class { name_index } => {
+                if let Ok(name) = collector.pool.utf8(*name_index) {
+                    collector.add_internal_name(name);
+                }
+            }
+            ConstantPoolInfo::NameAndType {
+                descriptor_index, ..
+            }
+            | ConstantPoolInfo::MethodType { descriptor_index } => {
+                if let Ok(descriptor) = collector.pool.utf8(*descriptor_index) {
+                    collector.add_descriptor(descriptor);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for field in &class_file.fields {
+        collector.add_descriptor(field.get_descriptor(&class_file.constant_pool));
+        collector.add_attributes(&field.attributes, Owner::Field);
+    }
+    for method in &class_file.methods {
+        collector.add_descriptor(method.get_descriptor(&class_file.constant_pool));
+        collector.add_attributes(&method.attributes, Owner::Method);
+    }
+    collector.add_attributes(&class_file.attributes, Owner::Class);
+
+    let mut names = collector.names;
+    names.remove(&class_name);
+    Some((class_name, names))
+}
+
+/// Returns the classes named by a field or method descriptor.
+pub(crate) fn descriptor_class_names(descriptor: &str) -> BTreeSet<String> {
+    let mut collector = DependencyCollector {
+        pool: ConstantPool::new(&[]),
+        names: BTreeSet::new(),
+    };
+    collector.add_descriptor(descriptor);
+    collector.names
+}
+
+#[derive(Clone, Copy)]
+enum Owner {
+    Class,
+    Field,
+    Method,
+}
+
+struct DependencyCollector<'a> {
+    pool: ConstantPool<'a>,
+    names: BTreeSet<String>,
+}
+
+impl<'a> DependencyCollector<'a> {
+    /// Adds a class named in internal form, which is an array descriptor for array classes.
+    fn add_internal_name(&mut self, name: &str) {
+        if name.starts_with('[') {
+            self.add_descriptor(name);
+        } else {
+            self.names.insert(name.replace('/', "."));
+        }
+    }
+
+    /// Adds the classes named by a descriptor. Malformed descriptors name no classes; reporting
+    /// them is left to `validate`.
+    fn add_descriptor(&mut self, descriptor: &str) {
+        if descriptor.starts_with('(') {
+            let Ok(descriptor) = try_parse_method_descriptor(descriptor) else {
+                return;
+            };
+            for param in &descriptor.param_descs {
+                self.add_field_ty(&param.ty);
+            }
+            if let ReturnDescriptor::TyDesc(ret) = &descriptor.ret_desc {
+                self.add_field_ty(&ret.ty);
+            }
+        } else if let Ok(descriptor) = try_parse_field_descriptor(descriptor) {
+            self.add_field_ty(&descriptor.ty);
+        }
+    }
+
+    fn add_field_ty(&mut self, ty: &FieldTy) {
+        match ty {
+            FieldTy::Base(_) => {}
+            FieldTy::Obj(obj) => self.add_internal_name(&obj.class_name),
+            FieldTy::Array(array) => self.add_field_ty(&array.ty),
+        }
+    }
+
+    fn add_class_type_signature(&mut self, signature: &ClassTypeSignature) {
+        self.names.insert(signature.qualified_name());
+        for simple in std::iter::once(&signature.simple_class_type_signature)
+            .chain(&signature.class_type_signature_suffixes)
+        {
+            for argument in simple.type_arguments.iter().flatten() {
+                if let TypeArgument::ReferenceType(_, reference) = argument {
+                    self.add_reference_type_signature(reference);
+                }
+            }
+        }
+    }
+
+    fn add_reference_type_signature(&mut self, signature: &ReferenceTypeSignature) {
+        match signature {
+            ReferenceTypeSignature::TypeVariable(_) => {}
+            ReferenceTypeSignature::Class(class) => self.add_class_type_signature(class),
+            ReferenceTypeSignature::Array(array) => {
+                self.add_type_signature(&array.java_type_signature)
+            }
+        }
+    }
+
+    fn add_type_signature(&mut self, signature: &TypeSignature) {
+        if let TypeSignature::Reference(reference) = signature {
+            self.add_reference_type_signature(reference);
+        }
+    }
+
+    fn add_type_parameters(&mut self, type_parameters: Option<&Vec<TypeParameter>>) {
+        for type_parameter in type_parameters.into_iter().flatten() {
+            for bound in type_parameter
+                .class_bound
+                .iter()
+                .chain(&type_parameter.interface_bounds)
+            {
+                self.add_reference_type_signature(bound);
+            }
+        }
+    }
+
+    fn add_signature(&mut self, signature: &str, owner: Owner) {
+        match owner {
+            Owner::Class => {
+                if let Ok((_, signature)) = parse_class_signature(signature) {
+                    self.add_type_parameters(signature.type_parameters.as_ref());
+                    self.add_class_type_signature(&signature.superclass_signature);
+                    for interface in &signature.superinterface_signatures {
+                        self.add_class_type_signature(interface);
+                    }
+                }
+            }
+            Owner::Field => {
+                if let Ok((_, signature)) = parse_field_signature(signature) {
+                    self.add_reference_type_signature(&signature.reference_type_signature);
+                }
+            }
+            Owner::Method => {
+                if let Ok((_, signature)) = parse_method_signature(signature) {
+                    self.add_type_parameters(signature.type_parameters.as_ref());
+                    for parameter in &signature.parameters {
+                        self.add_type_signature(parameter);
+                    }
+                    if let SignatureResult::JavaTypeSignature(result) = &signature.result {
+                        self.add_type_signature(result);
+                    }
+                    for throws in &signature.throws_signatures {
+                        if let ThrowsSignature::ClassTypeSignature(class) = throws {
+                            self.add_class_type_signature(class);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn add_annotation(&mut self, annotation: &Annotation) {
+        if let Ok(descriptor) = self.pool.utf8(annotation.type_index) {
+            self.add_descriptor(descriptor);
+        }
+        for pair in &annotation.element_value_pairs {
+            self.add_element_value(&pair.value);
+        }
+    }
+
+    fn add_element_value(&mut self, value: &ElementValue) {
+        match value {
+            ElementValue::EnumConst {
+                type_name_index, ..
+            } => {
+                if let Ok(descriptor) = self.pool.utf8(*type_name_index) {
+                    self.add_descriptor(descriptor);
+                }
+            }
+            ElementValue::ClassInfoIndex { class_info_index } => {
+                // a return descriptor, so `V` stands for `void.class`
+                if let Ok(descriptor) = self.pool.utf8(*class_info_index) {
+                    if descriptor != "V" {
+                        self.add_descriptor(descriptor);
+                    }
+                }
+            }
+            ElementValue::AnnotationValue { annotation } => self.add_annotation(annotation),
+            ElementValue::ArrayValue { values, .. } => {
+                for value in values {
+                    self.add_element_value(value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn add_attributes(&mut self, attributes: &[Attribute], owner: Owner) {
+        for attribute in attributes {
+            match &attribute.kind {
+                AttributeKind::Signature { signature_index } => {
+                    if let Ok(signature) = self.pool.utf8(*signature_index) {
+                        self.add_signature(signature, owner);
+                    }
+                }
+                AttributeKind::RuntimeVisibleAnnotations { annotations, .. }
+                | AttributeKind::RuntimeInvisibleAnnotations { annotations, .. } => {
+                    for annotation in annotations {
+                        self.add_annotation(annotation);
+                    }
+                }
+                AttributeKind::RuntimeVisibleParameterAnnotations {
+                    parameter_annotations,
+                    ..
+                }
+                | AttributeKind::RuntimeInvisibleParameterAnnotations {
+                    parameter_annotations,
+                    ..
+                } => {
+                    for annotation in parameter_annotations.iter().flat_map(|x| &x.annotations) {
+                        self.add_annotation(annotation);
+                    }
+                }
+                AttributeKind::RuntimeVisibleTypeAnnotations {
+                    type_annotations, ..
+                }
+                | AttributeKind::RuntimeInvisibleTypeAnnotations {
+                    type_annotations, ..
+                } => {
+                    for annotation in type_annotations {
+                        if let Ok(descriptor) = self.pool.utf8(annotation.type_index) {
+                            self.add_descriptor(descriptor);
+                        }
+                        for pair in &annotation.element_value_pairs {
+                            self.add_element_value(&pair.value);
+                        }
+                    }
+                }
+                AttributeKind::AnnotationDefault { default_value } => {
+                    self.add_element_value(default_value)
+                }
+                AttributeKind::Code { attributes, .. } => self.add_attributes(attributes, owner),
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::utf8;
+
+    fn class_file(constant_pool: Vec<ConstantPoolInfo>) -> ClassFile {
+        ClassFile {
+            minor_version: 0,
+            major_version: 52,
+            constant_pool_count: constant_pool.len() as u16,
+            constant_pool,
+            access_flags: AccessFlags::PUBLIC | AccessFlags::SUPER,
+            this_class: 1,
+            super_class: 3,
+            interfaces_count: 0,
+            interfaces: Vec::new(),
+            fields_count: 0,
+            fields: Vec::new(),
+            methods_count: 0,
+            methods: Vec::new(),
+            attributes_count: 0,
+            attributes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_class_dependencies() {
+        let class_file = class_file(vec![
+            ConstantPoolInfo::Empty,
+            ConstantPoolInfo::Class { name_index: 2 },
+            utf8("a/b/Main"),
+            ConstantPoolInfo::Class { name_index: 4 },
+            utf8("java/lang/Object"),
+            ConstantPoolInfo::Class { name_index: 6 },
+            utf8("[Lsun/misc/Unsafe;"),
+            ConstantPoolInfo::NameAndType {
+                name_index: 8,
+                descriptor_index: 9,
+            },
+            utf8("run"),
+            utf8("(Ljava/util/List;[I)La/b/Main;"),
+        ]);
+
+        let (name, dependencies) = class_dependencies(&class_file).unwrap();
+        assert_eq!(name, "a.b.Main");
+        assert_eq!(
+            dependencies.into_iter().collect::<Vec<_>>(),
+            vec!["java.lang.Object", "java.util.List", "sun.misc.Unsafe"]
+        );
+
+        let graph = DependencyGraph::new(&[class_file]);
+        assert_eq!(
+            graph.packages["a.b"].iter().collect::<Vec<_>>(),
+            vec!["java.lang", "java.util", "sun.misc"]
+        );
+        assert_eq!(
+            graph.jdk_internal["a.b.Main"].iter().collect::<Vec<_>>(),
+            vec!["sun.misc.Unsafe"]
+        );
+        assert!(graph
+            .to_table(Granularity::Package)
+            .contains("a.b -> sun.misc  JDK internal API"));
+    }
+
+    #[test]
+    fn test_is_jdk_internal() {
+        assert!(is_jdk_internal("sun.misc"));
+        assert!(is_jdk_internal("jdk.internal.misc"));
+        assert!(is_jdk_internal(
+            "com.sun.org.apache.xerces.internal.parsers"
+        ));
+        assert!(!is_jdk_internal("sunny.days"));
+        assert!(!is_jdk_internal("com.sun.net.httpserver"));
+        assert!(!is_jdk_internal("jdk.jfr"));
+    }
+}
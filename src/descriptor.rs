@@ -1,17 +1,19 @@
-#[derive(Debug, Clone, PartialEq, Eq)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct FieldDescriptor {
     pub descriptor: String,
     pub ty: FieldTy,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum FieldTy {
     Base(BaseTy),
     Obj(ObjTy),
     Array(ArrayTy),
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BaseTy {
     Byte,
     Char,
@@ -24,30 +26,74 @@ pub enum BaseTy {
     Void,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ObjTy {
     pub class_name: String,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ArrayTy {
     pub ty: Box<FieldTy>,
     pub dims: usize,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct MethodDescriptor {
     pub descriptor: String,
     pub param_descs: Vec<FieldDescriptor>,
     pub ret_desc: ReturnDescriptor,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ReturnDescriptor {
     TyDesc(FieldDescriptor),
     Void,
 }
 
+impl FieldDescriptor {
+    /// Renders the descriptor from its type, e.g. `[[Ljava/lang/String;`.
+    pub fn to_descriptor_string(&self) -> String {
+        self.ty.to_descriptor_string()
+    }
+}
+
+impl FieldTy {
+    pub fn to_descriptor_string(&self) -> String {
+        match self {
+            FieldTy::Base(x) => match x {
+                BaseTy::Byte => "B",
+                BaseTy::Char => "C",
+                BaseTy::Double => "D",
+                BaseTy::Float => "F",
+                BaseTy::Int => "I",
+                BaseTy::Long => "J",
+                BaseTy::Short => "S",
+                BaseTy::Boolean => "Z",
+                BaseTy::Void => "V",
+            }
+            .to_string(),
+            FieldTy::Obj(x) => format!("L{};", x.class_name),
+            FieldTy::Array(x) => format!("{}{}", "[".repeat(x.dims), x.ty.to_descriptor_string()),
+        }
+    }
+}
+
+impl MethodDescriptor {
+    /// Renders the descriptor from its parameter and return types, e.g. `(I[J)Ljava/lang/Object;`.
+    pub fn to_descriptor_string(&self) -> String {
+        let mut out = "(".to_string();
+        for x in &self.param_descs {
+            out.push_str(&x.to_descriptor_string());
+        }
+        out.push(')');
+        match &self.ret_desc {
+            ReturnDescriptor::TyDesc(x) => out.push_str(&x.to_descriptor_string()),
+            ReturnDescriptor::Void => out.push('V'),
+        }
+        out
+    }
+}
+
 pub fn parse_field_descriptor(descriptor: &str) -> FieldDescriptor {
     let mut chars = descriptor.chars();
     let mut ty = FieldTy::Base(BaseTy::Void);
@@ -143,9 +189,82 @@ pub fn parse_method_descriptor(descriptor: &str) -> MethodDescriptor {
     }
 }
 
+/// Parses a field descriptor, returning an error instead of panicking if it is malformed, e.g.
+/// when it comes from an unvalidated constant pool.
+pub fn try_parse_field_descriptor(descriptor: &str) -> Result<FieldDescriptor, String> {
+    match field_ty(descriptor) {
+        Some((ty, len)) if len == descriptor.len() => Ok(FieldDescriptor {
+            descriptor: descriptor.to_string(),
+            ty,
+        }),
+        _ => Err(format!("Invalid descriptor: {}", descriptor)),
+    }
+}
+
+/// Parses a method descriptor, returning an error instead of panicking if it is malformed.
+pub fn try_parse_method_descriptor(descriptor: &str) -> Result<MethodDescriptor, String> {
+    let invalid = || format!("Invalid descriptor: {}", descriptor);
+    let mut rest = descriptor.strip_prefix('(').ok_or_else(invalid)?;
+    let mut param_descs = Vec::new();
+    while !rest.starts_with(')') {
+        let (ty, len) = field_ty(rest).ok_or_else(invalid)?;
+        param_descs.push(FieldDescriptor {
+            descriptor: rest[..len].to_string(),
+            ty,
+        });
+        rest = &rest[len..];
+    }
+
+    let ret_desc = match &rest[1..] {
+        "V" => ReturnDescriptor::Void,
+        ret => ReturnDescriptor::TyDesc(try_parse_field_descriptor(ret).map_err(|_| invalid())?),
+    };
+    Ok(MethodDescriptor {
+        descriptor: descriptor.to_string(),
+        param_descs,
+        ret_desc,
+    })
+}
+
+/// Returns the type of the field descriptor at the start of `descriptor` and its length in bytes.
+fn field_ty(descriptor: &str) -> Option<(FieldTy, usize)> {
+    let dims = descriptor.bytes().take_while(|x| *x == b'[').count();
+    let (ty, len) = match descriptor.as_bytes().get(dims)? {
+        b'B' => (FieldTy::Base(BaseTy::Byte), dims + 1),
+        b'C' => (FieldTy::Base(BaseTy::Char), dims + 1),
+        b'D' => (FieldTy::Base(BaseTy::Double), dims + 1),
+        b'F' => (FieldTy::Base(BaseTy::Float), dims + 1),
+        b'I' => (FieldTy::Base(BaseTy::Int), dims + 1),
+        b'J' => (FieldTy::Base(BaseTy::Long), dims + 1),
+        b'S' => (FieldTy::Base(BaseTy::Short), dims + 1),
+        b'Z' => (FieldTy::Base(BaseTy::Boolean), dims + 1),
+        b'L' => {
+            let class_name = &descriptor[dims + 1..descriptor[dims + 1..].find(';')? + dims + 1];
+            if class_name.is_empty() {
+                return None;
+            }
+            let ty = FieldTy::Obj(ObjTy {
+                class_name: class_name.to_string(),
+            });
+            (ty, dims + class_name.len() + 2)
+        }
+        _ => return None,
+    };
+
+    if dims == 0 {
+        return Some((ty, len));
+    }
+    let ty = FieldTy::Array(ArrayTy {
+        ty: Box::new(ty),
+        dims,
+    });
+    Some((ty, len))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use quickcheck::{Arbitrary, Gen, QuickCheck};
 
     #[test]
     fn test_field_descriptor() {
@@ -213,4 +332,138 @@ mod tests {
             assert_eq!(actual.ret_desc, expected[i].ret_desc);
         }
     }
+
+    #[test]
+    fn test_to_descriptor_string() {
+        for input in ["I", "Ljava/lang/Object;", "[[[D", "[Ljava/util/Map$Entry;"] {
+            assert_eq!(parse_field_descriptor(input).to_descriptor_string(), input);
+        }
+        for input in [
+            "()V",
+            "(IDLjava/lang/Thread;)Ljava/lang/Object;",
+            "([[J[Ljava/lang/String;)[B",
+        ] {
+            assert_eq!(parse_method_descriptor(input).to_descriptor_string(), input);
+        }
+    }
+
+    #[test]
+    fn test_try_parse_descriptor() {
+        for input in ["I", "[Ljava/lang/Object;", "[[[D"] {
+            assert_eq!(
+                try_parse_field_descriptor(input),
+                Ok(parse_field_descriptor(input))
+            );
+        }
+        for input in [
+            "()V",
+            "(IDLjava/lang/Thread;)Ljava/lang/Object;",
+            "([[J[La/B;)[B",
+        ] {
+            assert_eq!(
+                try_parse_method_descriptor(input),
+                Ok(parse_method_descriptor(input))
+            );
+        }
+        let descriptor = try_parse_method_descriptor("(La/é;)V").unwrap();
+        assert_eq!(descriptor.param_descs[0].descriptor, "La/é;");
+        for input in ["", "V", "L;", "Ljava/lang/Object", "II", "[", "X"] {
+            assert!(try_parse_field_descriptor(input).is_err(), "{input}");
+        }
+        for input in [
+            "",
+            "I",
+            "(",
+            "(I",
+            "()",
+            "()VV",
+            "(V)V",
+            "(X)V",
+            "()Ljava;I",
+        ] {
+            assert!(try_parse_method_descriptor(input).is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_descriptor_round_trip() {
+        fn field_descriptor(value: FieldDescriptor) -> bool {
+            parse_field_descriptor(&value.to_descriptor_string()) == value
+        }
+        fn method_descriptor(value: MethodDescriptor) -> bool {
+            parse_method_descriptor(&value.to_descriptor_string()) == value
+        }
+
+        let mut qc = QuickCheck::new().tests(500);
+        qc.quickcheck(field_descriptor as fn(FieldDescriptor) -> bool);
+        qc.quickcheck(method_descriptor as fn(MethodDescriptor) -> bool);
+    }
+
+    // Arrays are flattened by the parser, so the element type of an array is never an array and
+    // `V` only appears as a return type.
+    fn element_ty(g: &mut Gen) -> FieldTy {
+        if bool::arbitrary(g) {
+            let segment = |g: &mut Gen| {
+                let chars = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz_$0123456789";
+                (0..1 + usize::arbitrary(g) % 6)
+                    .map(|_| *g.choose(chars.as_bytes()).unwrap() as char)
+                    .collect::<String>()
+            };
+            FieldTy::Obj(ObjTy {
+                class_name: (0..1 + usize::arbitrary(g) % 4)
+                    .map(|_| segment(g))
+                    .collect::<Vec<_>>()
+                    .join("/"),
+            })
+        } else {
+            FieldTy::Base(
+                g.choose(&[
+                    BaseTy::Byte,
+                    BaseTy::Char,
+                    BaseTy::Double,
+                    BaseTy::Float,
+                    BaseTy::Int,
+                    BaseTy::Long,
+                    BaseTy::Short,
+                    BaseTy::Boolean,
+                ])
+                .unwrap()
+                .clone(),
+            )
+        }
+    }
+
+    impl Arbitrary for FieldDescriptor {
+        fn arbitrary(g: &mut Gen) -> Self {
+            let ty = match usize::arbitrary(g) % 4 {
+                0 => element_ty(g),
+                dims => FieldTy::Array(ArrayTy {
+                    ty: Box::new(element_ty(g)),
+                    dims,
+                }),
+            };
+            FieldDescriptor {
+                descriptor: ty.to_descriptor_string(),
+                ty,
+            }
+        }
+    }
+
+    impl Arbitrary for MethodDescriptor {
+        fn arbitrary(g: &mut Gen) -> Self {
+            let mut value = MethodDescriptor {
+                descriptor: String::new(),
+                param_descs: (0..usize::arbitrary(g) % 5)
+                    .map(|_| FieldDescriptor::arbitrary(g))
+                    .collect(),
+                ret_desc: if bool::arbitrary(g) {
+                    ReturnDescriptor::TyDesc(FieldDescriptor::arbitrary(g))
+                } else {
+                    ReturnDescriptor::Void
+                },
+            };
+            value.descriptor = value.to_descriptor_string();
+            value
+        }
+    }
 }
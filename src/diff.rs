@@ -0,0 +1,585 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+
+use crate::{
+    class_file::{AccessFlags, FieldAccessFlags, MethodAccessFlags},
+    compat::{constant_value_eq, format_constant_value},
+    component::{
+        class_modifiers, AccessModifier, Annotation, AnnotationKind, Component, ConstantValue,
+        Field, Method, Ty,
+    },
+    signature::{ClassSignature, FieldSignature, MethodSignature},
+};
+
+/// The structural difference between the public APIs of two component sets.
+#[derive(Debug, Default, Serialize)]
+pub struct ApiDiff {
+    pub added_classes: Vec<String>,
+    pub removed_classes: Vec<String>,
+    pub changed_classes: Vec<ClassDiff>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ClassDiff {
+    pub qualified_name: String,
+    pub changes: Vec<ClassChange>,
+    pub added_methods: Vec<String>,
+    pub removed_methods: Vec<String>,
+    pub changed_methods: Vec<MemberDiff>,
+    pub added_fields: Vec<String>,
+    pub removed_fields: Vec<String>,
+    pub changed_fields: Vec<MemberDiff>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MemberDiff {
+    /// `name` + descriptor for methods, `name` for fields
+    pub name: String,
+    pub changes: Vec<MemberChange>,
+}
+
+#[derive(Debug, Serialize)]
+pub enum ClassChange {
+    Kind {
+        old: String,
+        new: String,
+    },
+    Modifiers {
+        old: String,
+        new: String,
+    },
+    Superclass {
+        old: Option<String>,
+        new: Option<String>,
+    },
+    Interfaces {
+        added: Vec<String>,
+        removed: Vec<String>,
+    },
+    PermittedSubclasses {
+        added: Vec<String>,
+        removed: Vec<String>,
+    },
+    Annotations {
+        added: Vec<String>,
+        removed: Vec<String>,
+    },
+    Signature {
+        old: Option<Box<ClassSignature>>,
+        new: Option<Box<ClassSignature>>,
+    },
+}
+
+#[derive(Debug, Serialize)]
+pub enum MemberChange {
+    Modifiers {
+        old: String,
+        new: String,
+    },
+    Annotations {
+        added: Vec<String>,
+        removed: Vec<String>,
+    },
+    MethodSignature {
+        old: Option<MethodSignature>,
+        new: Option<MethodSignature>,
+    },
+    FieldType {
+        old: String,
+        new: String,
+    },
+    FieldSignature {
+        old: Option<FieldSignature>,
+        new: Option<FieldSignature>,
+    },
+    ConstantValue {
+        old: Option<ConstantValue>,
+        new: Option<ConstantValue>,
+    },
+}
+
+impl ApiDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_classes.is_empty()
+            && self.removed_classes.is_empty()
+            && self.changed_classes.is_empty()
+    }
+
+    /// Renders the diff as a Markdown document suitable for release notes.
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("# API changes\n");
+        if self.is_empty() {
+            out.push_str("\nNo public API changes.\n");
+            return out;
+        }
+
+        push_list(&mut out, "## Added classes", &self.added_classes);
+        push_list(&mut out, "## Removed classes", &self.removed_classes);
+
+        if !self.changed_classes.is_empty() {
+            out.push_str("\n## Changed classes\n");
+        }
+        for class in &self.changed_classes {
+            out.push_str(&format!("\n### `{}`\n\n", class.qualified_name));
+            for change in &class.changes {
+                out.push_str(&format!("- {}\n", describe_class_change(change)));
+            }
+            for method in &class.added_methods {
+                out.push_str(&format!("- Added method `{method}`\n"));
+            }
+            for method in &class.removed_methods {
+                out.push_str(&format!("- Removed method `{method}`\n"));
+            }
+            for method in &class.changed_methods {
+                for change in &method.changes {
+                    out.push_str(&format!(
+                        "- Changed method `{}`: {}\n",
+                        method.name,
+                        describe_member_change(change)
+                    ));
+                }
+            }
+            for field in &class.added_fields {
+                out.push_str(&format!("- Added field `{field}`\n"));
+            }
+            for field in &class.removed_fields {
+                out.push_str(&format!("- Removed field `{field}`\n"));
+            }
+            for field in &class.changed_fields {
+                for change in &field.changes {
+                    out.push_str(&format!(
+                        "- Changed field `{}`: {}\n",
+                        field.name,
+                        describe_member_change(change)
+                    ));
+                }
+            }
+        }
+
+        out
+    }
+}
+
+fn push_list(out: &mut String, title: &str, items: &[String]) {
+    if items.is_empty() {
+        return;
+    }
+    out.push('\n');
+    out.push_str(title);
+    out.push_str("\n\n");
+    for item in items {
+        out.push_str(&format!("- `{item}`\n"));
+    }
+}
+
+fn describe_names(added: &[String], removed: &[String]) -> String {
+    let mut parts = Vec::new();
+    if !added.is_empty() {
+        parts.push(format!("added {}", quote_all(added)));
+    }
+    if !removed.is_empty() {
+        parts.push(format!("removed {}", quote_all(removed)));
+    }
+    parts.join(", ")
+}
+
+fn quote_all(names: &[String]) -> String {
+    names
+        .iter()
+        .map(|x| format!("`{x}`"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn describe_class_change(change: &ClassChange) -> String {
+    match change {
+        ClassChange::Kind { old, new } => format!("Kind changed from {old} to {new}"),
+        ClassChange::Modifiers { old, new } => {
+            format!("Modifiers changed from `{old}` to `{new}`")
+        }
+        ClassChange::Superclass { old, new } => format!(
+            "Superclass changed from `{}` to `{}`",
+            old.as_deref().unwrap_or("java.lang.Object"),
+            new.as_deref().unwrap_or("java.lang.Object")
+        ),
+        ClassChange::Interfaces { added, removed } => {
+            format!("Interfaces: {}", describe_names(added, removed))
+        }
+        ClassChange::PermittedSubclasses { added, removed } => {
+            format!("Permitted subclasses: {}", describe_names(added, removed))
+        }
+        ClassChange::Annotations { added, removed } => {
+            format!("Annotations: {}", describe_names(added, removed))
+        }
+        ClassChange::Signature { .. } => "Generic signature changed".to_string(),
+    }
+}
+
+fn describe_member_change(change: &MemberChange) -> String {
+    match change {
+        MemberChange::Modifiers { old, new } => {
+            format!("modifiers changed from `{old}` to `{new}`")
+        }
+        MemberChange::Annotations { added, removed } => {
+            format!("annotations {}", describe_names(added, removed))
+        }
+        MemberChange::MethodSignature { .. } | MemberChange::FieldSignature { .. } => {
+            "generic signature changed".to_string()
+        }
+        MemberChange::FieldType { old, new } => {
+            format!("type changed from `{old}` to `{new}`")
+        }
+        MemberChange::ConstantValue { old, new } => format!(
+            "constant value changed from `{}` to `{}`",
+            format_constant_value(old.as_ref()),
+            format_constant_value(new.as_ref())
+        ),
+    }
+}
+
+/// Computes the difference between the public APIs (public classes and their public or
+/// protected members) of two component sets.
+pub fn diff_components(old: &[Component], new: &[Component]) -> ApiDiff {
+    let old = api_classes(old);
+    let new = api_classes(new);
+
+    let mut diff = ApiDiff::default();
+    for (name, new_component) in &new {
+        match old.get(name) {
+            None => diff.added_classes.push(name.to_string()),
+            Some(old_component) => {
+                let class_diff = diff_class(name, old_component, new_component);
+                if !class_diff.is_empty() {
+                    diff.changed_classes.push(class_diff);
+                }
+            }
+        }
+    }
+    for name in old.keys() {
+        if !new.contains_key(name) {
+            diff.removed_classes.push(name.to_string());
+        }
+    }
+
+    diff
+}
+
+fn api_classes(components: &[Component]) -> BTreeMap<&str, &Component> {
+    components
+        .iter()
+        .filter(|x| x.access_flags().contains(AccessFlags::PUBLIC))
+        .filter_map(|x| x.qualified_name().map(|name| (name, x)))
+        .collect()
+}
+
+impl ClassDiff {
+    fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+            && self.added_methods.is_empty()
+            && self.removed_methods.is_empty()
+            && self.changed_methods.is_empty()
+            && self.added_fields.is_empty()
+            && self.removed_fields.is_empty()
+            && self.changed_fields.is_empty()
+    }
+}
+
+fn kind_name(component: &Component) -> String {
+    let flags = component.access_flags();
+    if flags.contains(AccessFlags::ANNOTATION) {
+        "annotation".to_string()
+    } else if component.is_interface() {
+        "interface".to_string()
+    } else if flags.contains(AccessFlags::ENUM) {
+        "enum".to_string()
+    } else {
+        "class".to_string()
+    }
+}
+
+fn diff_class(name: &str, old: &Component, new: &Component) -> ClassDiff {
+    let mut diff = ClassDiff {
+        qualified_name: name.to_string(),
+        ..Default::default()
+    };
+
+    let (old_kind, new_kind) = (kind_name(old), kind_name(new));
+    if old_kind != new_kind {
+        diff.changes.push(ClassChange::Kind {
+            old: old_kind,
+            new: new_kind,
+        });
+    }
+    let old_modifiers = class_modifiers(old.access_flags());
+    let new_modifiers = class_modifiers(new.access_flags());
+    if old_modifiers != new_modifiers {
+        diff.changes.push(ClassChange::Modifiers {
+            old: old_modifiers,
+            new: new_modifiers,
+        });
+    }
+    if old.super_class() != new.super_class() {
+        diff.changes.push(ClassChange::Superclass {
+            old: old.super_class().map(|x| x.to_string()),
+            new: new.super_class().map(|x| x.to_string()),
+        });
+    }
+    let (added, removed) = added_removed(old.interfaces(), new.interfaces());
+    if !added.is_empty() || !removed.is_empty() {
+        diff.changes
+            .push(ClassChange::Interfaces { added, removed });
+    }
+    let (added, removed) = added_removed(old.permitted_subclasses(), new.permitted_subclasses());
+    if !added.is_empty() || !removed.is_empty() {
+        diff.changes
+            .push(ClassChange::PermittedSubclasses { added, removed });
+    }
+    let (added, removed) = added_removed(
+        &annotation_names(old.annotations()),
+        &annotation_names(new.annotations()),
+    );
+    if !added.is_empty() || !removed.is_empty() {
+        diff.changes
+            .push(ClassChange::Annotations { added, removed });
+    }
+    if old.signature() != new.signature() {
+        diff.changes.push(ClassChange::Signature {
+            old: old.signature().cloned().map(Box::new),
+            new: new.signature().cloned().map(Box::new),
+        });
+    }
+
+    let old_methods = api_methods(old.methods());
+    let new_methods = api_methods(new.methods());
+    for (key, new_method) in &new_methods {
+        match old_methods.get(key) {
+            None => diff.added_methods.push(key.clone()),
+            Some(old_method) => {
+                let changes = diff_method(old_method, new_method);
+                if !changes.is_empty() {
+                    diff.changed_methods.push(MemberDiff {
+                        name: key.clone(),
+                        changes,
+                    });
+                }
+            }
+        }
+    }
+    diff.removed_methods = old_methods
+        .keys()
+        .filter(|x| !new_methods.contains_key(*x))
+        .cloned()
+        .collect();
+
+    let old_fields = api_fields(old.fields());
+    let new_fields = api_fields(new.fields());
+    for (key, new_field) in &new_fields {
+        match old_fields.get(key) {
+            None => diff.added_fields.push(key.clone()),
+            Some(old_field) => {
+                let changes = diff_field(old_field, new_field);
+                if !changes.is_empty() {
+                    diff.changed_fields.push(MemberDiff {
+                        name: key.clone(),
+                        changes,
+                    });
+                }
+            }
+        }
+    }
+    diff.removed_fields = old_fields
+        .keys()
+        .filter(|x| !new_fields.contains_key(*x))
+        .cloned()
+        .collect();
+
+    diff
+}
+
+fn is_api(modifier: AccessModifier) -> bool {
+    modifier.intersects(AccessModifier::PUBLIC | AccessModifier::PROTECTED)
+}
+
+fn api_methods(methods: &[Method]) -> BTreeMap<String, &Method> {
+    methods
+        .iter()
+        .filter(|x| is_api((&x.access_flags).into()))
+        .filter(|x| {
+            !x.access_flags
+                .intersects(MethodAccessFlags::BRIDGE | MethodAccessFlags::SYNTHETIC)
+        })
+        .map(|x| (format!("{}{}", x.name, x.descriptor), x))
+        .collect()
+}
+
+fn api_fields(fields: &[Field]) -> BTreeMap<String, &Field> {
+    fields
+        .iter()
+        .filter(|x| is_api((&x.access_flags).into()))
+        .filter(|x| !x.access_flags.contains(FieldAccessFlags::SYNTHETIC))
+        .map(|x| (x.name.clone(), x))
+        .collect()
+}
+
+fn diff_method(old: &Method, new: &Method) -> Vec<MemberChange> {
+    let mut changes = Vec::new();
+    if old.modifiers != new.modifiers {
+        changes.push(MemberChange::Modifiers {
+            old: old.modifiers.clone(),
+            new: new.modifiers.clone(),
+        });
+    }
+    let (added, removed) = added_removed(
+        &annotation_names(&old.annotations),
+        &annotation_names(&new.annotations),
+    );
+    if !added.is_empty() || !removed.is_empty() {
+        changes.push(MemberChange::Annotations { added, removed });
+    }
+    if old.signature != new.signature {
+        changes.push(MemberChange::MethodSignature {
+            old: old.signature.clone(),
+            new: new.signature.clone(),
+        });
+    }
+    changes
+}
+
+fn diff_field(old: &Field, new: &Field) -> Vec<MemberChange> {
+    let mut changes = Vec::new();
+    if old.modifiers != new.modifiers {
+        changes.push(MemberChange::Modifiers {
+            old: old.modifiers.clone(),
+            new: new.modifiers.clone(),
+        });
+    }
+    let (added, removed) = added_removed(
+        &annotation_names(&old.annotations),
+        &annotation_names(&new.annotations),
+    );
+    if !added.is_empty() || !removed.is_empty() {
+        changes.push(MemberChange::Annotations { added, removed });
+    }
+    if old.descriptor != new.descriptor {
+        changes.push(MemberChange::FieldType {
+            old: old.descriptor.clone(),
+            new: new.descriptor.clone(),
+        });
+    } else if old.signature != new.signature {
+        changes.push(MemberChange::FieldSignature {
+            old: old.signature.clone(),
+            new: new.signature.clone(),
+        });
+    }
+    let constant_changed = match (&old.constant_value, &new.constant_value) {
+        (Some(a), Some(b)) => !constant_value_eq(a, b),
+        (a, b) => a.is_some() != b.is_some(),
+    };
+    if constant_changed {
+        changes.push(MemberChange::ConstantValue {
+            old: old.constant_value.clone(),
+            new: new.constant_value.clone(),
+        });
+    }
+    changes
+}
+
+/// Returns the qualified names of the declaration annotations (not parameter or type
+/// annotations).
+fn annotation_names(annotations: &[Annotation]) -> Vec<String> {
+    annotations
+        .iter()
+        .filter(|x| {
+            matches!(
+                x.kind,
+                AnnotationKind::RuntimeVisible | AnnotationKind::RuntimeInvisible
+            )
+        })
+        .filter_map(|x| match &x.ty {
+            Ty::Reference(name) => Some(name.qualified_name()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn added_removed(old: &[String], new: &[String]) -> (Vec<String>, Vec<String>) {
+    let old = old.iter().collect::<BTreeSet<_>>();
+    let new = new.iter().collect::<BTreeSet<_>>();
+    (
+        new.difference(&old).map(|x| x.to_string()).collect(),
+        old.difference(&new).map(|x| x.to_string()).collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        component::{Class, ComponentKind},
+        test_util::{self, component, field, reference},
+    };
+
+    fn method(name: &str, flags: u16) -> Method {
+        test_util::method(name, "()V", None, flags)
+    }
+
+    fn class(name: &str, methods: Vec<Method>, annotations: &[&str]) -> Component {
+        component(ComponentKind::Class(Class {
+            methods,
+            fields: vec![field("hidden", "I", None, 0x0002)],
+            annotations: annotations
+                .iter()
+                .map(|x| Annotation {
+                    kind: AnnotationKind::RuntimeVisible,
+                    ty: reference("java.lang", x),
+                    elements: Vec::new(),
+                })
+                .collect(),
+            ..test_util::class(name)
+        }))
+    }
+
+    #[test]
+    fn test_diff_components() {
+        let old = vec![
+            class(
+                "a.Api",
+                vec![
+                    method("run", 0x0001),
+                    method("stop", 0x0001),
+                    method("internal", 0x0002),
+                ],
+                &[],
+            ),
+            class("a.Old", Vec::new(), &[]),
+        ];
+        let new = vec![
+            class(
+                "a.Api",
+                vec![method("run", 0x0011), method("start", 0x0001)],
+                &["Deprecated"],
+            ),
+            class("a.New", Vec::new(), &[]),
+        ];
+
+        let diff = diff_components(&old, &new);
+        assert_eq!(diff.added_classes, vec!["a.New"]);
+        assert_eq!(diff.removed_classes, vec!["a.Old"]);
+        assert_eq!(diff.changed_classes.len(), 1);
+
+        let class = &diff.changed_classes[0];
+        assert_eq!(class.added_methods, vec!["start()V"]);
+        assert_eq!(class.removed_methods, vec!["stop()V"]);
+        assert_eq!(class.changed_methods.len(), 1);
+        assert_eq!(class.changed_methods[0].name, "run()V");
+        assert!(matches!(
+            &class.changes[..],
+            [ClassChange::Annotations { added, .. }] if added == &vec!["java.lang.Deprecated".to_string()]
+        ));
+
+        let markdown = diff.to_markdown();
+        assert!(markdown.contains("- Removed method `stop()V`"));
+        assert!(markdown.contains("modifiers changed from `public` to `public final`"));
+    }
+}
@@ -0,0 +1,654 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::{
+    class_file::{AccessFlags, FieldAccessFlags, MethodAccessFlags},
+    component::{Component, ComponentKind, Field, InnerClassInfo, Method, PrimTy, Ty},
+    signature::{
+        BaseType, ClassTypeSignature, ReferenceTypeSignature, Result, TypeArgument, TypeParameter,
+        TypeSignature, WildcardIndicator,
+    },
+};
+
+const INDENT: &str = "    ";
+
+/// Reserved words of TypeScript that are not Java keywords, and so can appear in Java names.
+const RESERVED: &[&str] = &[
+    "debugger", "delete", "export", "function", "in", "let", "typeof", "var", "with", "yield",
+];
+
+/// Renders the public classes and interfaces of the components as TypeScript declarations,
+/// for Java objects exposed to JavaScript. Packages become namespaces and member classes are
+/// nested in a namespace merged with their declaring class. Primitives map to `number`,
+/// `boolean` and `string` (for `char`), `java.lang.String` to `string`, and `java.lang.Object`
+/// and classes outside the components to `any`. Static members of interfaces are functions and
+/// constants of the merged namespace, and classes extend the interfaces they implement through a
+/// merged interface.
+pub fn dts(components: &[Component]) -> String {
+    let declarations = Declarations::new(components);
+
+    let mut packages: BTreeMap<&str, Vec<&Component>> = BTreeMap::new();
+    for (name, component) in &declarations.classes {
+        if declarations.names.contains_key(name) && inner_class(component).is_none() {
+            let package = name.rsplit_once('.').map_or("", |x| x.0);
+            packages.entry(package).or_default().push(component);
+        }
+    }
+
+    let mut out = String::new();
+    for (package, mut classes) in packages {
+        classes.sort_by_key(|x| x.qualified_name());
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        if package.is_empty() {
+            declarations.declarations(&classes, 0, &mut out);
+        } else {
+            let package = package
+                .split('.')
+                .map(identifier)
+                .collect::<Vec<_>>()
+                .join(".");
+            out.push_str(&format!("declare namespace {package} {{\n"));
+            declarations.declarations(&classes, 1, &mut out);
+            out.push_str("}\n");
+        }
+    }
+    out
+}
+
+/// The names of type variables in scope.
+type Scope = HashSet<String>;
+
+struct Declarations<'a> {
+    /// Classes and interfaces by binary name
+    classes: HashMap<&'a str, &'a Component>,
+    /// Declared member classes by the binary name of their declaring class
+    members: HashMap<&'a str, Vec<&'a Component>>,
+    /// Qualified TypeScript names of the declared classes and interfaces, by binary name
+    names: HashMap<&'a str, String>,
+}
+
+impl<'a> Declarations<'a> {
+    fn new(components: &'a [Component]) -> Self {
+        let mut classes = HashMap::new();
+        for component in components {
+            if let Some(name) = component.qualified_name() {
+                classes.entry(name).or_insert(component);
+            }
+        }
+
+        let mut declarations = Declarations {
+            classes,
+            members: HashMap::new(),
+            names: HashMap::new(),
+        };
+        let names = declarations.classes.keys().copied().collect::<Vec<_>>();
+        for name in names {
+            declarations.name(name);
+        }
+        for name in declarations.names.keys() {
+            let component = declarations.classes[name];
+            if let Some(InnerClassInfo {
+                outer_class: Some(outer_class),
+                ..
+            }) = inner_class(component)
+            {
+                let outer_class = declarations.classes[outer_class.as_str()]
+                    .qualified_name()
+                    .unwrap();
+                declarations
+                    .members
+                    .entry(outer_class)
+                    .or_default()
+                    .push(component);
+            }
+        }
+        declarations
+    }
+
+    /// Computes the TypeScript name of a class if it is declared: public, and top-level or a
+    /// member of a declared class.
+    fn name(&mut self, binary_name: &'a str) -> Option<String> {
+        if let Some(name) = self.names.get(binary_name) {
+            return Some(name.clone());
+        }
+        let component = *self.classes.get(binary_name)?;
+        let flags = component.access_flags();
+        let is_annotation =
+            matches!(&component.kind, ComponentKind::Interface(x) if x.is_annotation);
+        if matches!(component.kind, ComponentKind::Module(_))
+            || is_annotation
+            || !flags.contains(AccessFlags::PUBLIC)
+            || flags.contains(AccessFlags::SYNTHETIC)
+            || binary_name.ends_with("package-info")
+        {
+            return None;
+        }
+
+        let name = match inner_class(component) {
+            None => binary_name
+                .split('.')
+                .map(identifier)
+                .collect::<Vec<_>>()
+                .join("."),
+            Some(InnerClassInfo {
+                outer_class: Some(outer_class),
+                simple_name: Some(simple_name),
+                ..
+            }) => {
+                let outer_class = *self.classes.get_key_value(outer_class.as_str())?.0;
+                format!("{}.{}", self.name(outer_class)?, identifier(simple_name))
+            }
+            Some(_) => return None,
+        };
+        self.names.insert(binary_name, name.clone());
+        Some(name)
+    }
+
+    /// Declares classes and interfaces, separated by blank lines.
+    fn declarations(&self, classes: &[&Component], depth: usize, out: &mut String) {
+        let declare = if depth == 0 { "declare " } else { "" };
+        for (i, component) in classes.iter().enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+            if component.is_interface() {
+                self.interface(component, depth, declare, out);
+            } else {
+                self.class(component, depth, declare, out);
+            }
+            self.namespace(component, depth, declare, out);
+        }
+    }
+
+    fn class(&self, component: &Component, depth: usize, declare: &str, out: &mut String) {
+        let indent = INDENT.repeat(depth);
+        let member_indent = INDENT.repeat(depth + 1);
+        let name = self.simple_name(component);
+        let scope = type_variables(component);
+        let type_params = self.class_type_parameters(component);
+
+        let signature = component.signature();
+        let super_class = match signature {
+            Some(signature) => self.class_type(&signature.superclass_signature, &scope),
+            None => component
+                .super_class()
+                .map_or("any".to_string(), |x| self.named(x, None)),
+        };
+        let extends = if super_class == "any" {
+            String::new()
+        } else {
+            format!(" extends {super_class}")
+        };
+        let modifier = if component.access_flags().contains(AccessFlags::ABSTRACT) {
+            "abstract "
+        } else {
+            ""
+        };
+
+        let methods = public_methods(component).collect::<Vec<_>>();
+        let method_names = methods
+            .iter()
+            .map(|x| x.name.as_str())
+            .collect::<HashSet<_>>();
+        let mut members = Vec::new();
+        for field in public_fields(component) {
+            // A property cannot share its name with a method
+            if !method_names.contains(field.name.as_str()) {
+                let is_static = field.access_flags.contains(FieldAccessFlags::STATIC);
+                let prefix = if is_static { "static " } else { "" };
+                members.push(self.field(field, prefix, &scope));
+            }
+        }
+        for method in methods {
+            let is_static = method.access_flags.contains(MethodAccessFlags::STATIC);
+            let prefix = if is_static { "static " } else { "" };
+            let scope = if is_static {
+                Scope::new()
+            } else {
+                scope.clone()
+            };
+            members.push(self.method(method, prefix, &scope));
+        }
+
+        out.push_str(&format!(
+            "{indent}{declare}{modifier}class {name}{type_params}{extends} {{\n"
+        ));
+        for member in members {
+            out.push_str(&format!("{member_indent}{member}\n"));
+        }
+        out.push_str(&format!("{indent}}}\n"));
+
+        // Merged into the class, so that it need not declare the members of its interfaces
+        let interfaces = self.interfaces(component, &scope);
+        if !interfaces.is_empty() {
+            out.push_str(&format!(
+                "{indent}{declare}interface {name}{type_params} extends {} {{}}\n",
+                interfaces.join(", ")
+            ));
+        }
+    }
+
+    fn interface(&self, component: &Component, depth: usize, declare: &str, out: &mut String) {
+        let indent = INDENT.repeat(depth);
+        let member_indent = INDENT.repeat(depth + 1);
+        let name = self.simple_name(component);
+        let scope = type_variables(component);
+        let type_params = self.class_type_parameters(component);
+
+        let interfaces = self.interfaces(component, &scope);
+        let extends = if interfaces.is_empty() {
+            String::new()
+        } else {
+            format!(" extends {}", interfaces.join(", "))
+        };
+
+        let methods = public_methods(component)
+            .filter(|x| !x.access_flags.contains(MethodAccessFlags::STATIC))
+            .collect::<Vec<_>>();
+        if methods.is_empty() {
+            out.push_str(&format!(
+                "{indent}{declare}interface {name}{type_params}{extends} {{}}\n"
+            ));
+            return;
+        }
+        out.push_str(&format!(
+            "{indent}{declare}interface {name}{type_params}{extends} {{\n"
+        ));
+        for method in methods {
+            out.push_str(&format!(
+                "{member_indent}{}\n",
+                self.method(method, "", &scope)
+            ));
+        }
+        out.push_str(&format!("{indent}}}\n"));
+    }
+
+    /// Declares the namespace merged with a class or interface, holding its member classes and
+    /// the static members of an interface.
+    fn namespace(&self, component: &Component, depth: usize, declare: &str, out: &mut String) {
+        let mut items = Vec::new();
+        if component.is_interface() {
+            for field in public_fields(component) {
+                items.push(self.field(field, "const ", &Scope::new()));
+            }
+            for method in public_methods(component) {
+                if method.access_flags.contains(MethodAccessFlags::STATIC) {
+                    items.push(self.method(method, "function ", &Scope::new()));
+                }
+            }
+        }
+        let mut members = self
+            .members
+            .get(component.qualified_name().unwrap())
+            .cloned()
+            .unwrap_or_default();
+        if items.is_empty() && members.is_empty() {
+            return;
+        }
+
+        let indent = INDENT.repeat(depth);
+        let member_indent = INDENT.repeat(depth + 1);
+        out.push_str(&format!(
+            "{indent}{declare}namespace {} {{\n",
+            self.simple_name(component)
+        ));
+        for item in &items {
+            out.push_str(&format!("{member_indent}{item}\n"));
+        }
+        if !members.is_empty() {
+            if !items.is_empty() {
+                out.push('\n');
+            }
+            members.sort_by_key(|x| x.qualified_name());
+            self.declarations(&members, depth + 1, out);
+        }
+        out.push_str(&format!("{indent}}}\n"));
+    }
+
+    fn field(&self, field: &Field, prefix: &str, scope: &Scope) -> String {
+        let readonly = if prefix != "const " && field.access_flags.contains(FieldAccessFlags::FINAL)
+        {
+            "readonly "
+        } else {
+            ""
+        };
+        let ty = match &field.signature {
+            Some(signature) => self.reference(&signature.reference_type_signature, scope),
+            None => self.ty(&field.ty),
+        };
+        format!("{prefix}{readonly}{}: {ty};", field.name)
+    }
+
+    /// Declares a method or constructor, with `prefix` being `static ` or `function `.
+    fn method(&self, method: &Method, prefix: &str, scope: &Scope) -> String {
+        let mut scope = scope.clone();
+        let type_parameters = method
+            .signature
+            .as_ref()
+            .and_then(|x| x.type_parameters.as_deref())
+            .unwrap_or_default();
+        scope.extend(type_parameters.iter().map(|x| x.identifier.clone()));
+        let type_params = self.type_parameters(type_parameters, &scope, false);
+
+        let mut params = match &method.signature {
+            Some(signature) if signature.parameters.len() == method.param_tys.len() => signature
+                .parameters
+                .iter()
+                .map(|x| self.type_signature(x, &scope))
+                .collect::<Vec<_>>(),
+            _ => method.param_tys.iter().map(|x| self.ty(x)).collect(),
+        }
+        .into_iter()
+        .enumerate()
+        .map(|(i, ty)| format!("arg{i}: {ty}"))
+        .collect::<Vec<_>>();
+        if method.access_flags.contains(MethodAccessFlags::VARARGS) {
+            if let Some(last) = params.last_mut() {
+                if last.ends_with("[]") {
+                    *last = format!("...{last}");
+                }
+            }
+        }
+        let params = params.join(", ");
+
+        if method.name == "<init>" {
+            return format!("constructor({params});");
+        }
+        let ret = match method.signature.as_ref().map(|x| &x.result) {
+            Some(Result::JavaTypeSignature(x)) => self.type_signature(x, &scope),
+            Some(Result::VoidDescriptor) => "void".to_string(),
+            None => self.ty(&method.ret_ty),
+        };
+        // A method named `constructor` would declare a constructor
+        let name = if method.name == "constructor" {
+            "\"constructor\""
+        } else {
+            &method.name
+        };
+        format!("{prefix}{name}{type_params}({params}): {ret};")
+    }
+
+    fn simple_name(&self, component: &Component) -> &str {
+        let name = &self.names[component.qualified_name().unwrap()];
+        name.rsplit('.').next().unwrap()
+    }
+
+    /// The type parameters of a class or interface, defaulting to `any` so that raw references
+    /// remain valid.
+    fn class_type_parameters(&self, component: &Component) -> String {
+        let type_parameters = component
+            .signature()
+            .and_then(|x| x.type_parameters.as_deref())
+            .unwrap_or_default();
+        self.type_parameters(type_parameters, &type_variables(component), true)
+    }
+
+    fn type_parameters(
+        &self,
+        type_parameters: &[TypeParameter],
+        scope: &Scope,
+        default: bool,
+    ) -> String {
+        if type_parameters.is_empty() {
+            return String::new();
+        }
+        let params = type_parameters
+            .iter()
+            .map(|param| {
+                let bounds = param
+                    .class_bound
+                    .iter()
+                    .chain(&param.interface_bounds)
+                    .map(|x| self.reference(x, scope))
+                    .filter(|x| x != "any")
+                    .collect::<Vec<_>>();
+                let mut out = param.identifier.clone();
+                if !bounds.is_empty() {
+                    out.push_str(&format!(" extends {}", bounds.join(" & ")));
+                }
+                if default {
+                    out.push_str(" = any");
+                }
+                out
+            })
+            .collect::<Vec<_>>();
+        format!("<{}>", params.join(", "))
+    }
+
+    /// The declared interfaces among the direct superinterfaces.
+    fn interfaces(&self, component: &Component, scope: &Scope) -> Vec<String> {
+        let interfaces = match component.signature() {
+            Some(signature) => signature
+                .superinterface_signatures
+                .iter()
+                .map(|x| self.class_type(x, scope))
+                .collect::<Vec<_>>(),
+            None => component
+                .interfaces()
+                .iter()
+                .map(|x| self.named(x, None))
+                .collect(),
+        };
+        interfaces.into_iter().filter(|x| x != "any").collect()
+    }
+
+    fn ty(&self, ty: &Ty) -> String {
+        match ty {
+            Ty::Prim(prim) => prim_name(prim).to_string(),
+            Ty::Void => "void".to_string(),
+            Ty::TyVar(_) => "any".to_string(),
+            Ty::Reference(name) => self.named(&name.qualified_name(), None),
+            Ty::Array(element, dimensions) => {
+                format!("{}{}", self.ty(element), "[]".repeat(*dimensions))
+            }
+        }
+    }
+
+    fn type_signature(&self, signature: &TypeSignature, scope: &Scope) -> String {
+        match signature {
+            TypeSignature::Base(base) => base_name(base).to_string(),
+            TypeSignature::Reference(reference) => self.reference(reference, scope),
+        }
+    }
+
+    fn reference(&self, signature: &ReferenceTypeSignature, scope: &Scope) -> String {
+        match signature {
+            ReferenceTypeSignature::TypeVariable(x) if scope.contains(&x.identifier) => {
+                x.identifier.clone()
+            }
+            ReferenceTypeSignature::TypeVariable(_) => "any".to_string(),
+            ReferenceTypeSignature::Class(x) => self.class_type(x, scope),
+            ReferenceTypeSignature::Array(x) => {
+                format!("{}[]", self.type_signature(&x.java_type_signature, scope))
+            }
+        }
+    }
+
+    fn class_type(&self, signature: &ClassTypeSignature, scope: &Scope) -> String {
+        let args = signature
+            .class_type_signature_suffixes
+            .last()
+            .unwrap_or(&signature.simple_class_type_signature)
+            .type_arguments
+            .as_ref()
+            .map(|args| {
+                args.iter()
+                    .map(|x| match x {
+                        TypeArgument::Any
+                        | TypeArgument::ReferenceType(Some(WildcardIndicator::Minus), _) => {
+                            "any".to_string()
+                        }
+                        TypeArgument::ReferenceType(_, x) => self.reference(x, scope),
+                    })
+                    .collect::<Vec<_>>()
+            });
+        self.named(&signature.qualified_name(), args)
+    }
+
+    /// The TypeScript type of a class by binary name. Raw types use the default arguments.
+    fn named(&self, name: &str, args: Option<Vec<String>>) -> String {
+        match name {
+            "java.lang.Object" => return "any".to_string(),
+            "java.lang.String" => return "string".to_string(),
+            _ => {}
+        }
+        let (Some(ts_name), Some(component)) = (self.names.get(name), self.classes.get(name))
+        else {
+            return "any".to_string();
+        };
+        let count = component
+            .signature()
+            .and_then(|x| x.type_parameters.as_ref())
+            .map_or(0, |x| x.len());
+        match args {
+            Some(args) if count > 0 && args.len() == count => {
+                format!("{ts_name}<{}>", args.join(", "))
+            }
+            _ => ts_name.clone(),
+        }
+    }
+}
+
+fn inner_class(component: &Component) -> Option<&InnerClassInfo> {
+    match &component.kind {
+        ComponentKind::Class(x) => x.inner_class.as_ref(),
+        ComponentKind::Interface(x) => x.inner_class.as_ref(),
+        ComponentKind::Module(_) => None,
+    }
+}
+
+fn type_variables(component: &Component) -> Scope {
+    component
+        .signature()
+        .and_then(|x| x.type_parameters.as_ref())
+        .into_iter()
+        .flatten()
+        .map(|x| x.identifier.clone())
+        .collect()
+}
+
+fn public_fields(component: &Component) -> impl Iterator<Item = &Field> {
+    component.fields().iter().filter(|x| {
+        x.access_flags.contains(FieldAccessFlags::PUBLIC)
+            && !x.access_flags.contains(FieldAccessFlags::SYNTHETIC)
+    })
+}
+
+fn public_methods(component: &Component) -> impl Iterator<Item = &Method> {
+    component.methods().iter().filter(|x| {
+        x.access_flags.contains(MethodAccessFlags::PUBLIC)
+            && !x
+                .access_flags
+                .intersects(MethodAccessFlags::SYNTHETIC | MethodAccessFlags::BRIDGE)
+            && x.name != "<clinit>"
+    })
+}
+
+fn prim_name(prim: &PrimTy) -> &'static str {
+    match prim {
+        PrimTy::Boolean => "boolean",
+        PrimTy::Char => "string",
+        PrimTy::Void => "void",
+        PrimTy::Byte
+        | PrimTy::Short
+        | PrimTy::Int
+        | PrimTy::Long
+        | PrimTy::Float
+        | PrimTy::Double => "number",
+    }
+}
+
+fn base_name(base: &BaseType) -> &'static str {
+    match base {
+        BaseType::Boolean => "boolean",
+        BaseType::Char => "string",
+        BaseType::Byte
+        | BaseType::Short
+        | BaseType::Int
+        | BaseType::Long
+        | BaseType::Float
+        | BaseType::Double => "number",
+    }
+}
+
+/// Escapes a Java name that is reserved in TypeScript by appending `_`.
+fn identifier(name: &str) -> String {
+    if RESERVED.contains(&name) {
+        format!("{name}_")
+    } else {
+        name.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        component::{Class, Interface},
+        signature::parse_class_signature,
+        test_util::{self, component, field, method},
+    };
+
+    fn components() -> Vec<Component> {
+        let named = Interface {
+            signature: Some(
+                parse_class_signature("<T:Ljava/lang/Object;>Ljava/lang/Object;")
+                    .unwrap()
+                    .1,
+            ),
+            methods: vec![
+                method("nameOf", "(I)Ljava/lang/Object;", Some("(I)TT;"), 0x0401),
+                method("describe", "()Ljava/lang/String;", None, 0x0009),
+            ],
+            fields: vec![field("SEPARATOR", "Ljava/lang/String;", None, 0x0019)],
+            ..test_util::interface("a.Named")
+        };
+        let boxed = Class {
+            super_class: Some("java.lang.Object".to_string()),
+            interfaces: vec!["a.Named".to_string()],
+            signature: Some(
+                parse_class_signature("<T:Ljava/lang/Object;>Ljava/lang/Object;La/Named<TT;>;")
+                    .unwrap()
+                    .1,
+            ),
+            methods: vec![
+                method("<init>", "(Ljava/lang/String;)V", None, 0x0001),
+                method("add", "([C)V", None, 0x0001),
+                method("add", "(J)Z", None, 0x0001),
+                method("hidden", "()V", None, 0x0002),
+            ],
+            fields: vec![field("itemCount", "J", None, 0x0011)],
+            ..test_util::class("a.Box")
+        };
+        vec![
+            component(ComponentKind::Interface(named)),
+            component(ComponentKind::Class(boxed)),
+        ]
+    }
+
+    #[test]
+    fn renders_namespaces_and_overloads() {
+        assert_eq!(
+            dts(&components()),
+            "declare namespace a {
+    class Box<T = any> {
+        readonly itemCount: number;
+        constructor(arg0: string);
+        add(arg0: string[]): void;
+        add(arg0: number): boolean;
+    }
+    interface Box<T = any> extends a.Named<T> {}
+
+    interface Named<T = any> {
+        nameOf(arg0: number): T;
+    }
+    namespace Named {
+        const SEPARATOR: string;
+        function describe(): string;
+    }
+}
+"
+        );
+    }
+}
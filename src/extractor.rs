@@ -1,11 +1,37 @@
 use std::{error::Error, io::Read, path::Path};
 
+use prost::Message;
+
 use crate::{
-    class_file::parse_class_file,
-    component::{extract_component, AccessModifier, Component, ExtractorContext},
+    class_file::{parse_class_file, ClassFile},
+    component::{try_extract_component, AccessModifier, Component, ExtractorContext},
+    proto, snapshot,
 };
 
+/// Extracts the components of a JAR/JMOD file. Entries which fail to parse are reported on
+/// stderr and skipped.
 pub fn extract_members_from_jar<P: AsRef<Path>>(path: P) -> Result<Vec<Component>, Box<dyn Error>> {
+    let jar = extract_entries_from_jar(path)?;
+    jar.errors.iter().for_each(|x| eprintln!("{x}"));
+    Ok(jar
+        .entries
+        .into_iter()
+        .map(|(_, component)| component)
+        .collect())
+}
+
+/// The components extracted from a JAR/JMOD file.
+#[derive(Debug, Default)]
+pub struct JarEntries {
+    /// The components along with the paths of their class files
+    pub entries: Vec<(String, Component)>,
+    /// Entries which failed to parse and were skipped
+    pub errors: Vec<String>,
+}
+
+/// Extracts the components of a JAR/JMOD file along with the paths of their class files.
+/// Entries which fail to parse are skipped and listed in the result.
+pub fn extract_entries_from_jar<P: AsRef<Path>>(path: P) -> Result<JarEntries, Box<dyn Error>> {
     let path = path.as_ref();
 
     if let Some(ext) = path.extension() {
@@ -36,7 +62,7 @@ pub fn extract_members_from_jar<P: AsRef<Path>>(path: P) -> Result<Vec<Component
         }
     };
 
-    let mut components = Vec::new();
+    let mut jar = JarEntries::default();
     for i in 0..archive.len() {
         let mut file = archive.by_index(i).unwrap();
         if !file.name().ends_with(".class") {
@@ -46,24 +72,215 @@ pub fn extract_members_from_jar<P: AsRef<Path>>(path: P) -> Result<Vec<Component
         let mut buf = Vec::new();
         file.read_to_end(&mut buf).unwrap();
 
-        let component = parse_class_file(&buf).map(|(_, c)| {
-            extract_component(
-                &c,
+        let component = parse_class_file(&buf)
+            .map_err(|e| e.to_string())
+            .and_then(|(_, c)| {
+                try_extract_component(
+                    &c,
+                    &ExtractorContext {
+                        target_access_modifiers: AccessModifier::empty(),
+                    },
+                )
+            });
+
+        match component {
+            Ok(component) => jar.entries.push((file.name().to_string(), component)),
+            Err(e) => jar.errors.push(format!(
+                "Error parsing class file {} in {}: {}",
+                file.name(),
+                path.display(),
+                e
+            )),
+        }
+    }
+
+    Ok(jar)
+}
+
+/// Extracts components from a class file, a JAR/JMOD file or every such file below a directory,
+/// or loads them from a snapshot written by the JSON or Protocol Buffers output. Fails if an
+/// archive entry fails to parse, since comparing a partial API would report its classes as
+/// removed.
+pub fn extract_members_from_path<P: AsRef<Path>>(
+    path: P,
+) -> Result<Vec<Component>, Box<dyn Error>> {
+    let ext = path.as_ref().extension().unwrap_or_default();
+    if path.as_ref().is_file() && (ext == "json" || ext == "ndjson" || ext == "pb") {
+        return load_components(path);
+    }
+
+    let read = read_class_files_from_path(path)?;
+    if let Some(err) = read.errors.into_iter().next() {
+        return Err(err.into());
+    }
+    let components = read
+        .class_files
+        .iter()
+        .map(|class_file| {
+            try_extract_component(
+                class_file,
                 &ExtractorContext {
                     target_access_modifiers: AccessModifier::empty(),
                 },
             )
-        });
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(components)
+}
 
-        let comp = if let Err(e) = component {
-            println!("Error parsing class file: {:?}", e);
-            continue;
+/// Loads the components of a snapshot: a `.json` file with a component or a list of them, a
+/// `.ndjson` file with a component per line, or a `.pb` file. Files without a snapshot header
+/// hold a `ComponentList`, or a `Component` when named `<class file>.class.pb`. Schema version 1
+/// lacks what `component.proto` does not carry, such as exceptions.
+pub fn load_components<P: AsRef<Path>>(path: P) -> Result<Vec<Component>, Box<dyn Error>> {
+    let path = path.as_ref();
+    let buf = std::fs::read(path)?;
+
+    if path.extension().unwrap_or_default() == "pb" {
+        if let Some(snapshot) = snapshot::decode_snapshot(&buf)? {
+            return Ok(snapshot
+                .components
+                .into_iter()
+                .map(|x| x.component)
+                .collect());
+        }
+        let is_class = path
+            .file_stem()
+            .is_some_and(|x| Path::new(x).extension().unwrap_or_default() == "class");
+        return if is_class {
+            let component = proto::component::Component::decode(buf.as_slice())?;
+            Ok(vec![component.try_into()?])
         } else {
-            component.unwrap()
+            let components = proto::component::ComponentList::decode(buf.as_slice())?;
+            Ok(components.try_into()?)
         };
+    }
 
-        components.push(comp);
+    if path.extension().unwrap_or_default() == "ndjson" {
+        return serde_json::Deserializer::from_slice(&buf)
+            .into_iter()
+            .map(|x| Ok(x?))
+            .collect();
     }
 
-    Ok(components)
+    let is_list = buf.iter().find(|x| !x.is_ascii_whitespace()) == Some(&b'[');
+    if is_list {
+        Ok(serde_json::from_slice(&buf)?)
+    } else {
+        Ok(vec![serde_json::from_slice(&buf)?])
+    }
+}
+
+/// The class files read from a path.
+#[derive(Debug, Default)]
+pub struct ClassFiles {
+    pub class_files: Vec<ClassFile>,
+    /// Entries of archives which failed to parse and were skipped
+    pub errors: Vec<String>,
+}
+
+/// Parses a class file, every class file in a JAR/JMOD file or every such file below a
+/// directory. Entries of an archive which fail to parse are skipped and listed in the result.
+pub fn read_class_files_from_path<P: AsRef<Path>>(path: P) -> Result<ClassFiles, Box<dyn Error>> {
+    let path = path.as_ref();
+    let mut read = ClassFiles::default();
+
+    if path.is_dir() {
+        for entry in walkdir::WalkDir::new(path)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().is_file())
+        {
+            let ext = entry.path().extension().unwrap_or_default();
+            if ext == "class" || ext == "jar" || ext == "jmod" {
+                let entry = read_class_files_from_path(entry.path())?;
+                read.class_files.extend(entry.class_files);
+                read.errors.extend(entry.errors);
+            }
+        }
+        return Ok(read);
+    }
+
+    let ext = path.extension().unwrap_or_default();
+    if ext == "jar" || ext == "jmod" {
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(path)?)?;
+        for i in 0..archive.len() {
+            let mut file = archive.by_index(i)?;
+            if !file.name().ends_with(".class") {
+                continue;
+            }
+
+            let mut buf = Vec::new();
+            file.read_to_end(&mut buf)?;
+            match parse_class_file(&buf) {
+                Ok((_, class_file)) => read.class_files.push(class_file),
+                Err(e) => read.errors.push(format!(
+                    "Error parsing class file {} in {}: {}",
+                    file.name(),
+                    path.display(),
+                    e
+                )),
+            }
+        }
+        return Ok(read);
+    }
+
+    let buf = std::fs::read(path)?;
+    let (_, class_file) = parse_class_file(&buf).map_err(|e| e.to_string())?;
+    read.class_files.push(class_file);
+    Ok(read)
+}
+
+/// Reads the provider classes registered for `ServiceLoader` in `META-INF/services` of a JAR
+/// file or of every JAR file and exploded class directory below a directory.
+pub fn read_service_providers_from_path<P: AsRef<Path>>(
+    path: P,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let path = path.as_ref();
+    let mut providers = Vec::new();
+
+    if path.is_dir() {
+        for entry in walkdir::WalkDir::new(path)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().is_file())
+        {
+            let ext = entry.path().extension().unwrap_or_default();
+            if ext == "jar" {
+                providers.extend(read_service_providers_from_path(entry.path())?);
+            } else if entry
+                .path()
+                .parent()
+                .is_some_and(|x| x.ends_with("META-INF/services"))
+            {
+                providers.extend(parse_service_providers(&std::fs::read_to_string(
+                    entry.path(),
+                )?));
+            }
+        }
+    } else if path.extension().unwrap_or_default() == "jar" {
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(path)?)?;
+        for i in 0..archive.len() {
+            let mut file = archive.by_index(i)?;
+            if !file.name().starts_with("META-INF/services/") || file.is_dir() {
+                continue;
+            }
+
+            let mut content = String::new();
+            file.read_to_string(&mut content)?;
+            providers.extend(parse_service_providers(&content));
+        }
+    }
+
+    Ok(providers)
+}
+
+/// Parses a provider-configuration file: one class name per line, `#` starts a comment.
+fn parse_service_providers(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| line.to_string())
+        .collect()
 }
@@ -0,0 +1,386 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
+
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+
+use crate::component::{Component, ComponentKind};
+
+const JAVA_LANG_OBJECT: &str = "java.lang.Object";
+
+/// An index over the supertype relations of a set of components.
+///
+/// Types which are only referenced (e.g. JDK classes when indexing a single JAR) are kept as
+/// names, so queries still report them but cannot walk past them.
+#[derive(Debug, Default)]
+pub struct TypeHierarchy {
+    pub types: BTreeMap<String, TypeNode>,
+    direct_subtypes: BTreeMap<String, BTreeSet<String>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TypeNode {
+    pub qualified_name: String,
+    pub is_interface: bool,
+    pub super_class: Option<String>,
+    pub interfaces: Vec<String>,
+}
+
+/// Serialises like the protobuf `TypeHierarchy`: every node carries its direct subtypes.
+impl Serialize for TypeHierarchy {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Node<'a> {
+            #[serde(flatten)]
+            node: &'a TypeNode,
+            direct_subtypes: Vec<&'a str>,
+        }
+
+        let types: BTreeMap<_, _> = self
+            .types
+            .iter()
+            .map(|(name, node)| {
+                let node = Node {
+                    node,
+                    direct_subtypes: self.direct_subtypes(name),
+                };
+                (name, node)
+            })
+            .collect();
+        let mut state = serializer.serialize_struct("TypeHierarchy", 1)?;
+        state.serialize_field("types", &types)?;
+        state.end()
+    }
+}
+
+impl TypeHierarchy {
+    pub fn new(components: &[Component]) -> Self {
+        let mut hierarchy = Self::default();
+        for component in components {
+            hierarchy.insert(component);
+        }
+        hierarchy
+    }
+
+    pub fn insert(&mut self, component: &Component) {
+        let node = match &component.kind {
+            ComponentKind::Class(c) => TypeNode {
+                qualified_name: c.qualified_name.clone(),
+                is_interface: false,
+                super_class: c.super_class.clone().or_else(|| {
+                    if c.qualified_name == JAVA_LANG_OBJECT {
+                        None
+                    } else {
+                        Some(JAVA_LANG_OBJECT.to_string())
+                    }
+                }),
+                interfaces: c.interfaces.clone(),
+            },
+            ComponentKind::Interface(i) => TypeNode {
+                qualified_name: i.qualified_name.clone(),
+                is_interface: true,
+                super_class: None,
+                interfaces: i.interfaces.clone(),
+            },
+            ComponentKind::Module(_) => return,
+        };
+
+        for super_type in node.super_class.iter().chain(node.interfaces.iter()) {
+            self.direct_subtypes
+                .entry(super_type.clone())
+                .or_default()
+                .insert(node.qualified_name.clone());
+        }
+        self.types.insert(node.qualified_name.clone(), node);
+    }
+
+    pub fn get(&self, qualified_name: &str) -> Option<&TypeNode> {
+        self.types.get(qualified_name)
+    }
+
+    pub fn is_interface(&self, qualified_name: &str) -> bool {
+        self.get(qualified_name).is_some_and(|x| x.is_interface)
+    }
+
+    /// Returns the direct supertypes of a type, superclass first.
+    pub fn direct_supertypes(&self, qualified_name: &str) -> Vec<&str> {
+        match self.get(qualified_name) {
+            Some(node) => node
+                .super_class
+                .iter()
+                .chain(node.interfaces.iter())
+                .map(|x| x.as_str())
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns every transitive supertype of a type in breadth-first order. Interfaces have
+    /// `java.lang.Object` as their last supertype.
+    pub fn supertypes(&self, qualified_name: &str) -> Vec<&str> {
+        let mut visited = HashSet::new();
+        let mut result = Vec::new();
+        let mut queue = VecDeque::from(self.direct_supertypes(qualified_name));
+
+        while let Some(ty) = queue.pop_front() {
+            if !visited.insert(ty) {
+                continue;
+            }
+            result.push(ty);
+            queue.extend(self.direct_supertypes(ty));
+        }
+        if self.is_interface(qualified_name) && !visited.contains(JAVA_LANG_OBJECT) {
+            result.push(JAVA_LANG_OBJECT);
+        }
+        result
+    }
+
+    pub fn is_subtype_of(&self, qualified_name: &str, super_type: &str) -> bool {
+        qualified_name == super_type || self.supertypes(qualified_name).contains(&super_type)
+    }
+
+    /// Returns the types which directly extend or implement the given type.
+    pub fn direct_subtypes(&self, qualified_name: &str) -> Vec<&str> {
+        self.direct_subtypes
+            .get(qualified_name)
+            .map(|x| x.iter().map(|x| x.as_str()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns the classes whose superclass is the given class.
+    pub fn direct_subclasses(&self, qualified_name: &str) -> Vec<&str> {
+        self.direct_subtypes(qualified_name)
+            .into_iter()
+            .filter(|x| {
+                self.get(x)
+                    .is_some_and(|x| x.super_class.as_deref() == Some(qualified_name))
+            })
+            .collect()
+    }
+
+    /// Returns every class which transitively extends the given class.
+    pub fn subclasses(&self, qualified_name: &str) -> Vec<&str> {
+        let mut visited = HashSet::new();
+        let mut result = Vec::new();
+        let mut queue = VecDeque::from(self.direct_subclasses(qualified_name));
+        while let Some(ty) = queue.pop_front() {
+            if !visited.insert(ty) {
+                continue;
+            }
+            result.push(ty);
+            queue.extend(self.direct_subclasses(ty));
+        }
+        result
+    }
+
+    /// Returns every transitive subtype (classes and interfaces) of the given type.
+    pub fn subtypes(&self, qualified_name: &str) -> Vec<&str> {
+        let mut visited = HashSet::new();
+        let mut result = Vec::new();
+        let mut queue = VecDeque::from(self.direct_subtypes(qualified_name));
+
+        while let Some(ty) = queue.pop_front() {
+            if !visited.insert(ty) {
+                continue;
+            }
+            result.push(ty);
+            queue.extend(self.direct_subtypes(ty));
+        }
+        result
+    }
+
+    /// Returns every class implementing the given interface, directly or through superclasses
+    /// and subinterfaces. Abstract classes are included.
+    pub fn implementors(&self, interface: &str) -> Vec<&str> {
+        self.subtypes(interface)
+            .into_iter()
+            .filter(|x| !self.is_interface(x))
+            .collect()
+    }
+
+    /// Returns the order in which method resolution (JVMS 5.4.3.3) searches the type: the type
+    /// itself, its superclass chain and then its superinterfaces, each interface listed once and
+    /// after all of its subinterfaces. Interfaces are followed by `java.lang.Object`.
+    pub fn method_resolution_order<'a>(&'a self, qualified_name: &'a str) -> Vec<&'a str> {
+        let mut class_chain = Vec::new();
+        let mut current = Some(qualified_name);
+        while let Some(ty) = current {
+            if class_chain.contains(&ty) {
+                break;
+            }
+            class_chain.push(ty);
+            current = self.get(ty).and_then(|x| x.super_class.as_deref());
+        }
+
+        let mut roots: Vec<&str> = Vec::new();
+        for ty in &class_chain {
+            let super_class = self.get(ty).and_then(|x| x.super_class.as_deref());
+            for interface in self.direct_supertypes(ty) {
+                if Some(interface) != super_class && !roots.contains(&interface) {
+                    roots.push(interface);
+                }
+            }
+        }
+
+        // reversed post-order keeps declaration order while placing every superinterface
+        // after the interfaces extending it
+        let mut seen = HashSet::new();
+        let mut visited = Vec::new();
+        for interface in roots.into_iter().rev() {
+            self.visit_interfaces_post_order(interface, &mut seen, &mut visited);
+        }
+        visited.reverse();
+
+        let mut order = class_chain.into_iter().chain(visited).collect::<Vec<_>>();
+        if self.is_interface(qualified_name) && !order.contains(&JAVA_LANG_OBJECT) {
+            order.push(JAVA_LANG_OBJECT);
+        }
+        order
+    }
+
+    // `seen` is marked before recursing so a cyclic (invalid) interface graph terminates
+    fn visit_interfaces_post_order<'a>(
+        &'a self,
+        interface: &'a str,
+        seen: &mut HashSet<&'a str>,
+        visited: &mut Vec<&'a str>,
+    ) {
+        if !seen.insert(interface) {
+            return;
+        }
+        for super_interface in self.direct_supertypes(interface).into_iter().rev() {
+            self.visit_interfaces_post_order(super_interface, seen, visited);
+        }
+        visited.push(interface);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        component::{Class, Interface},
+        test_util::{self, component},
+    };
+
+    fn class(name: &str, super_class: Option<&str>, interfaces: &[&str]) -> Component {
+        component(ComponentKind::Class(Class {
+            super_class: super_class.map(|x| x.to_string()),
+            interfaces: interfaces.iter().map(|x| x.to_string()).collect(),
+            ..test_util::class(name)
+        }))
+    }
+
+    fn interface(name: &str, interfaces: &[&str]) -> Component {
+        component(ComponentKind::Interface(Interface {
+            interfaces: interfaces.iter().map(|x| x.to_string()).collect(),
+            ..test_util::interface(name)
+        }))
+    }
+
+    fn hierarchy() -> TypeHierarchy {
+        TypeHierarchy::new(&[
+            interface("a.Collection", &[]),
+            interface("a.List", &["a.Collection"]),
+            class("a.AbstractList", None, &["a.List"]),
+            class(
+                "a.ArrayList",
+                Some("a.AbstractList"),
+                &["a.List", "a.RandomAccess"],
+            ),
+            class("a.Stack", Some("a.ArrayList"), &[]),
+            class("a.Other", None, &[]),
+        ])
+    }
+
+    #[test]
+    fn test_supertypes() {
+        let h = hierarchy();
+        assert_eq!(
+            h.supertypes("a.Stack"),
+            vec![
+                "a.ArrayList",
+                "a.AbstractList",
+                "a.List",
+                "a.RandomAccess",
+                "java.lang.Object",
+                "a.Collection"
+            ]
+        );
+        assert!(h.is_subtype_of("a.Stack", "a.Collection"));
+        assert!(!h.is_subtype_of("a.Other", "a.Collection"));
+    }
+
+    #[test]
+    fn test_interfaces_extend_object() {
+        let h = hierarchy();
+        assert_eq!(
+            h.supertypes("a.List"),
+            vec!["a.Collection", "java.lang.Object"]
+        );
+        assert_eq!(h.supertypes("a.Collection"), vec!["java.lang.Object"]);
+        assert!(h.is_subtype_of("a.List", "java.lang.Object"));
+        assert_eq!(
+            h.method_resolution_order("a.List"),
+            vec!["a.List", "a.Collection", "java.lang.Object"]
+        );
+        // unknown types are not known to be interfaces
+        assert_eq!(h.supertypes("a.RandomAccess"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_subtypes() {
+        let h = hierarchy();
+        assert_eq!(h.direct_subclasses("a.AbstractList"), vec!["a.ArrayList"]);
+        assert_eq!(
+            h.subclasses("a.AbstractList"),
+            vec!["a.ArrayList", "a.Stack"]
+        );
+        assert_eq!(
+            h.implementors("a.Collection"),
+            vec!["a.AbstractList", "a.ArrayList", "a.Stack"]
+        );
+    }
+
+    #[test]
+    fn test_cyclic_hierarchy() {
+        let h = TypeHierarchy::new(&[
+            interface("q.I", &["q.J"]),
+            interface("q.J", &["q.I"]),
+            class("q.A", Some("q.B"), &["q.I"]),
+            class("q.B", Some("q.A"), &[]),
+        ]);
+        assert_eq!(h.subclasses("q.A"), vec!["q.B", "q.A"]);
+        assert_eq!(
+            h.method_resolution_order("q.A"),
+            vec!["q.A", "q.B", "q.I", "q.J"]
+        );
+    }
+
+    #[test]
+    fn test_serialize_direct_subtypes() {
+        let json = serde_json::to_value(hierarchy()).unwrap();
+        assert_eq!(
+            json["types"]["a.AbstractList"]["direct_subtypes"],
+            serde_json::json!(["a.ArrayList"])
+        );
+        assert_eq!(
+            json["types"]["a.List"]["super_class"],
+            serde_json::Value::Null
+        );
+    }
+
+    #[test]
+    fn test_method_resolution_order() {
+        let h = hierarchy();
+        assert_eq!(
+            h.method_resolution_order("a.ArrayList"),
+            vec![
+                "a.ArrayList",
+                "a.AbstractList",
+                "java.lang.Object",
+                "a.List",
+                "a.Collection",
+                "a.RandomAccess"
+            ]
+        );
+    }
+}
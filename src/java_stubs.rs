@@ -0,0 +1,1088 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    class_file::{AccessFlags, FieldAccessFlags, InnerClassAccessFlags, MethodAccessFlags},
+    component::{
+        Annotation, AnnotationKind, AnnotationValue, Component, ComponentKind, ConstantValue,
+        Field, InnerClassInfo, Method, PrimTy, Ty,
+    },
+    descriptor::parse_method_descriptor,
+    members::{bind, substitute_method},
+    pretty::source_name,
+    signature::{
+        BaseType, ClassTypeSignature, ReferenceTypeSignature, Result, SimpleClassTypeSignature,
+        ThrowsSignature, TypeArgument, TypeParameter, TypeSignature, WildcardIndicator,
+    },
+};
+
+const INDENT: &str = "    ";
+const UNSUPPORTED: &str = "throw new java.lang.UnsupportedOperationException();";
+
+/// A Java source file declaring one top-level class and its member classes.
+#[derive(Debug, Clone)]
+pub struct JavaStub {
+    /// Relative path following the package, e.g. `com/example/Api.java`
+    pub path: String,
+    pub source: String,
+}
+
+/// Renders components as compilable Java source, with method and constructor bodies replaced
+/// by `throw new UnsupportedOperationException()`. Member classes are nested in their declaring
+/// class and left out when it is not among the components. Private fields and methods, synthetic
+/// members, local and anonymous classes, and type and parameter annotations are skipped.
+pub fn java_stubs(components: &[Component]) -> Vec<JavaStub> {
+    let renderer = Renderer::new(components);
+    let mut paths = HashSet::new();
+    let mut stubs = Vec::new();
+    for component in components {
+        let Some(name) = component.qualified_name() else {
+            continue;
+        };
+        if inner_class(component).is_some() || !renderer.rendered.contains(name) {
+            continue;
+        }
+        let path = format!("{}.java", name.replace('.', "/"));
+        if !paths.insert(path.clone()) {
+            continue;
+        }
+
+        let mut source = String::new();
+        if let Some((package, _)) = name.rsplit_once('.') {
+            source.push_str(&format!("package {package};\n\n"));
+        }
+        renderer.class(component, 0, &mut source);
+        stubs.push(JavaStub { path, source });
+    }
+    stubs
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Class,
+    Enum,
+    Record,
+    Interface,
+    Annotation,
+}
+
+struct Renderer<'a> {
+    /// Classes and interfaces by binary name
+    classes: HashMap<&'a str, &'a Component>,
+    /// Member classes by the binary name of their declaring class, in input order
+    members: HashMap<&'a str, Vec<&'a Component>>,
+    /// Binary names of the classes that appear in the stubs
+    rendered: HashSet<&'a str>,
+}
+
+impl<'a> Renderer<'a> {
+    fn new(components: &'a [Component]) -> Self {
+        let mut classes = HashMap::new();
+        for component in components {
+            let Some(name) = component.qualified_name() else {
+                continue;
+            };
+            if component.access_flags().contains(AccessFlags::SYNTHETIC)
+                || name.ends_with("package-info")
+            {
+                continue;
+            }
+            classes.entry(name).or_insert(component);
+        }
+
+        let mut members: HashMap<&str, Vec<&Component>> = HashMap::new();
+        for component in components {
+            let Some(name) = component.qualified_name() else {
+                continue;
+            };
+            if let Some(InnerClassInfo {
+                outer_class: Some(outer_class),
+                simple_name: Some(_),
+                ..
+            }) = inner_class(component)
+            {
+                if classes
+                    .get(name)
+                    .is_some_and(|x| std::ptr::eq(*x, component))
+                {
+                    members.entry(outer_class).or_default().push(component);
+                }
+            }
+        }
+
+        let mut renderer = Renderer {
+            classes,
+            members,
+            rendered: HashSet::new(),
+        };
+        let mut pending = renderer
+            .classes
+            .iter()
+            .filter(|(_, x)| inner_class(x).is_none())
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>();
+        while let Some(name) = pending.pop() {
+            renderer.rendered.insert(name);
+            for member in renderer.members.get(name).into_iter().flatten() {
+                let info = inner_class(member).unwrap();
+                if !info.access_flags.contains(InnerClassAccessFlags::SYNTHETIC) {
+                    pending.push(member.qualified_name().unwrap());
+                }
+            }
+        }
+        renderer
+    }
+
+    /// The name of a class in source, `a.Outer.Inner` for the binary name `a.Outer$Inner`.
+    fn source_name(&self, binary_name: &str) -> String {
+        if let Some(component) = self.classes.get(binary_name) {
+            if let Some(InnerClassInfo {
+                outer_class: Some(outer_class),
+                simple_name: Some(simple_name),
+                ..
+            }) = inner_class(component)
+            {
+                return format!("{}.{simple_name}", self.source_name(outer_class));
+            }
+            return binary_name.to_string();
+        }
+
+        // Outside the input, fall back to guessing from the `$` separators
+        source_name(binary_name)
+    }
+
+    fn class(&self, component: &Component, depth: usize, out: &mut String) {
+        let indent = INDENT.repeat(depth);
+        let name = component.qualified_name().unwrap();
+        let kind = kind(component);
+        let simple_name = match inner_class(component) {
+            Some(InnerClassInfo {
+                simple_name: Some(simple_name),
+                ..
+            }) => simple_name.as_str(),
+            _ => name.rsplit('.').next().unwrap(),
+        };
+        let flags = match inner_class(component) {
+            Some(info) => info.access_flags,
+            None => InnerClassAccessFlags::from_bits_truncate(component.access_flags().bits()),
+        };
+
+        self.annotations(component.annotations(), &indent, out);
+        out.push_str(&indent);
+        out.push_str(access(
+            flags.contains(InnerClassAccessFlags::PUBLIC),
+            flags.contains(InnerClassAccessFlags::PROTECTED),
+            flags.contains(InnerClassAccessFlags::PRIVATE),
+        ));
+        if kind == Kind::Class {
+            if flags.contains(InnerClassAccessFlags::STATIC) {
+                out.push_str("static ");
+            }
+            if flags.contains(InnerClassAccessFlags::ABSTRACT) {
+                out.push_str("abstract ");
+            }
+            if flags.contains(InnerClassAccessFlags::FINAL) {
+                out.push_str("final ");
+            }
+        }
+        let permitted = self.permitted_subclasses(component);
+        if !permitted.is_empty() {
+            out.push_str("sealed ");
+        } else if self.is_non_sealed(component, kind, flags) {
+            out.push_str("non-sealed ");
+        }
+        out.push_str(match kind {
+            Kind::Class => "class ",
+            Kind::Enum => "enum ",
+            Kind::Record => "record ",
+            Kind::Interface => "interface ",
+            Kind::Annotation => "@interface ",
+        });
+        out.push_str(simple_name);
+
+        let signature = component.signature();
+        if let Some(signature) = signature {
+            out.push_str(&self.type_parameters(signature.type_parameters.as_deref()));
+        }
+        let record_components = component
+            .fields()
+            .iter()
+            .filter(|x| kind == Kind::Record && !x.is_static)
+            .collect::<Vec<_>>();
+        if kind == Kind::Record {
+            let params = record_components
+                .iter()
+                .map(|x| format!("{} {}", self.field_type(x), x.name))
+                .collect::<Vec<_>>();
+            out.push_str(&format!("({})", params.join(", ")));
+        }
+
+        if kind == Kind::Class {
+            let super_class = match signature {
+                Some(signature) => self.class_type(&signature.superclass_signature),
+                None => component
+                    .super_class()
+                    .map_or("java.lang.Object".to_string(), |x| self.source_name(x)),
+            };
+            if super_class != "java.lang.Object" {
+                out.push_str(&format!(" extends {super_class}"));
+            }
+        }
+        let interfaces = match signature {
+            Some(signature) => signature
+                .superinterface_signatures
+                .iter()
+                .map(|x| self.class_type(x))
+                .collect::<Vec<_>>(),
+            None => component
+                .interfaces()
+                .iter()
+                .map(|x| self.source_name(x))
+                .collect(),
+        };
+        if !interfaces.is_empty() && kind != Kind::Annotation {
+            let keyword = if kind == Kind::Interface {
+                "extends"
+            } else {
+                "implements"
+            };
+            out.push_str(&format!(" {keyword} {}", interfaces.join(", ")));
+        }
+        if !permitted.is_empty() {
+            out.push_str(&format!(" permits {}", permitted.join(", ")));
+        }
+        out.push_str(" {\n");
+
+        let member_indent = INDENT.repeat(depth + 1);
+        let mut members = Vec::new();
+        if kind == Kind::Enum {
+            let body = if component.access_flags().contains(AccessFlags::ABSTRACT) {
+                self.constant_body(name, depth + 2)
+            } else {
+                None
+            };
+            let constants = component
+                .fields()
+                .iter()
+                .filter(|x| x.access_flags.contains(FieldAccessFlags::ENUM))
+                .map(|x| {
+                    let mut constant = String::new();
+                    self.annotations(&x.annotations, &member_indent, &mut constant);
+                    constant.push_str(&member_indent);
+                    constant.push_str(&x.name);
+                    if let Some(body) = &body {
+                        constant.push_str(&format!(" {{\n{body}{member_indent}}}"));
+                    }
+                    constant
+                })
+                .collect::<Vec<_>>();
+            members.push(format!("{};\n", constants.join(",\n")));
+        }
+        for field in component.fields() {
+            if field.access_flags.intersects(
+                FieldAccessFlags::PRIVATE | FieldAccessFlags::SYNTHETIC | FieldAccessFlags::ENUM,
+            ) || (kind == Kind::Record && !field.is_static)
+            {
+                continue;
+            }
+            let mut member = String::new();
+            self.field(field, kind, &member_indent, &mut member);
+            members.push(member);
+        }
+        let record_descriptor = format!(
+            "({})V",
+            record_components
+                .iter()
+                .map(|x| x.descriptor.as_str())
+                .collect::<String>()
+        );
+        // Other constructors of records must delegate to the canonical one
+        let canonical_call = format!(
+            "this({});",
+            record_components
+                .iter()
+                .map(|x| default_argument(&x.ty, Some(&self.field_type(x))))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        for method in component.methods() {
+            let is_constructor = method.name == "<init>";
+            let skip = method
+                .access_flags
+                .intersects(MethodAccessFlags::SYNTHETIC | MethodAccessFlags::BRIDGE)
+                || method.name == "<clinit>"
+                || (method.access_flags.contains(MethodAccessFlags::PRIVATE) && !is_constructor)
+                || (kind == Kind::Enum
+                    && (is_constructor
+                        || (method.is_static
+                            && method.name == "values"
+                            && method.descriptor.starts_with("()"))
+                        || (method.is_static
+                            && method.name == "valueOf"
+                            && method.descriptor.starts_with("(Ljava/lang/String;)"))))
+                || (kind == Kind::Record
+                    && is_constructor
+                    && method.descriptor == record_descriptor);
+            if skip {
+                continue;
+            }
+            let constructor_call = match kind {
+                Kind::Record if is_constructor => Some(canonical_call.clone()),
+                Kind::Class if is_constructor => self.super_constructor_call(component, method),
+                _ => None,
+            };
+            let mut member = String::new();
+            self.method(
+                component,
+                kind,
+                simple_name,
+                method,
+                constructor_call.as_deref(),
+                &member_indent,
+                &mut member,
+            );
+            members.push(member);
+        }
+        for member_class in self.members.get(name).into_iter().flatten() {
+            if self
+                .rendered
+                .contains(member_class.qualified_name().unwrap())
+            {
+                let mut member = String::new();
+                self.class(member_class, depth + 1, &mut member);
+                members.push(member);
+            }
+        }
+
+        out.push_str(&members.join("\n"));
+        out.push_str(&indent);
+        out.push_str("}\n");
+    }
+
+    fn field(&self, field: &Field, kind: Kind, indent: &str, out: &mut String) {
+        self.annotations(&field.annotations, indent, out);
+        out.push_str(indent);
+        let in_interface = matches!(kind, Kind::Interface | Kind::Annotation);
+        if !in_interface {
+            let flags = field.access_flags;
+            out.push_str(access(
+                flags.contains(FieldAccessFlags::PUBLIC),
+                flags.contains(FieldAccessFlags::PROTECTED),
+                flags.contains(FieldAccessFlags::PRIVATE),
+            ));
+            for (flag, keyword) in [
+                (FieldAccessFlags::STATIC, "static "),
+                (FieldAccessFlags::FINAL, "final "),
+                (FieldAccessFlags::TRANSIENT, "transient "),
+                (FieldAccessFlags::VOLATILE, "volatile "),
+            ] {
+                if flags.contains(flag) {
+                    out.push_str(keyword);
+                }
+            }
+        }
+        out.push_str(&format!("{} {}", self.field_type(field), field.name));
+
+        // Final fields without a constant get an initializer that is not a constant
+        // expression, so that compiling against the stub does not inline a made-up value
+        if let Some(value) = &field.constant_value {
+            out.push_str(&format!(" = {}", constant(value, &field.ty)));
+        } else if in_interface || field.access_flags.contains(FieldAccessFlags::FINAL) {
+            out.push_str(&format!(" = {}", placeholder(&field.ty)));
+        }
+        out.push_str(";\n");
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn method(
+        &self,
+        component: &Component,
+        kind: Kind,
+        simple_name: &str,
+        method: &Method,
+        constructor_call: Option<&str>,
+        indent: &str,
+        out: &mut String,
+    ) {
+        let flags = method.access_flags;
+        let is_constructor = method.name == "<init>";
+        let in_interface = matches!(kind, Kind::Interface | Kind::Annotation);
+        let has_body = !flags.intersects(MethodAccessFlags::ABSTRACT | MethodAccessFlags::NATIVE)
+            || kind == Kind::Enum;
+
+        self.annotations(&method.annotations, indent, out);
+        out.push_str(indent);
+        if !in_interface {
+            out.push_str(access(
+                flags.contains(MethodAccessFlags::PUBLIC),
+                flags.contains(MethodAccessFlags::PROTECTED),
+                flags.contains(MethodAccessFlags::PRIVATE),
+            ));
+        }
+        if flags.contains(MethodAccessFlags::STATIC) {
+            out.push_str("static ");
+        } else if in_interface && has_body {
+            out.push_str("default ");
+        }
+        if kind == Kind::Class && flags.contains(MethodAccessFlags::ABSTRACT) {
+            out.push_str("abstract ");
+        }
+        for (flag, keyword) in [
+            (MethodAccessFlags::FINAL, "final "),
+            (MethodAccessFlags::SYNCHRONIZED, "synchronized "),
+            (MethodAccessFlags::NATIVE, "native "),
+        ] {
+            if flags.contains(flag) {
+                out.push_str(keyword);
+            }
+        }
+
+        let signature = method.signature.as_ref();
+        if let Some(signature) = signature {
+            let type_parameters = self.type_parameters(signature.type_parameters.as_deref());
+            if !type_parameters.is_empty() {
+                out.push_str(&type_parameters);
+                out.push(' ');
+            }
+        }
+        if is_constructor {
+            out.push_str(simple_name);
+        } else {
+            let ret = match signature.map(|x| &x.result) {
+                Some(Result::JavaTypeSignature(ty)) => self.type_signature(ty),
+                _ => self.ty(&method.ret_ty),
+            };
+            out.push_str(&format!("{ret} {}", method.name));
+        }
+
+        let mut params = match signature {
+            Some(signature) => signature
+                .parameters
+                .iter()
+                .map(|x| self.type_signature(x))
+                .collect::<Vec<_>>(),
+            None => {
+                // Constructors of inner classes take the enclosing instance first
+                let skip = usize::from(is_constructor && is_inner(component));
+                method
+                    .param_tys
+                    .iter()
+                    .skip(skip)
+                    .map(|x| self.ty(x))
+                    .collect()
+            }
+        };
+        if flags.contains(MethodAccessFlags::VARARGS) {
+            if let Some(last) = params.last_mut().filter(|x| x.ends_with("[]")) {
+                last.truncate(last.len() - 2);
+                last.push_str("...");
+            }
+        }
+        let params = params
+            .iter()
+            .enumerate()
+            .map(|(i, x)| format!("{x} arg{i}"))
+            .collect::<Vec<_>>();
+        out.push_str(&format!("({})", params.join(", ")));
+
+        if let Some(value) = &method.annotation_default {
+            out.push_str(&format!(" default {}", self.annotation_value(value)));
+        }
+        let throws = match signature {
+            Some(signature) if !signature.throws_signatures.is_empty() => signature
+                .throws_signatures
+                .iter()
+                .map(|x| match x {
+                    ThrowsSignature::ClassTypeSignature(x) => self.class_type(x),
+                    ThrowsSignature::TypeVariableSignature(x) => x.identifier.clone(),
+                })
+                .collect(),
+            _ => method
+                .exceptions
+                .iter()
+                .map(|x| self.source_name(x))
+                .collect::<Vec<_>>(),
+        };
+        if !throws.is_empty() {
+            out.push_str(&format!(" throws {}", throws.join(", ")));
+        }
+
+        if !has_body {
+            out.push_str(";\n");
+            return;
+        }
+        out.push_str(" {\n");
+        if let Some(call) = constructor_call.filter(|_| is_constructor) {
+            out.push_str(&format!("{indent}{INDENT}{call}\n"));
+        }
+        out.push_str(&format!("{indent}{INDENT}{UNSUPPORTED}\n{indent}}}\n"));
+    }
+
+    /// The methods of the classes of enum constants with bodies, which implement the abstract
+    /// methods of an abstract enum. Every constant gets all of them.
+    fn constant_body(&self, enum_name: &str, depth: usize) -> Option<String> {
+        let mut constant_classes = self
+            .classes
+            .values()
+            .filter(|x| {
+                x.super_class() == Some(enum_name)
+                    && inner_class(x).is_some_and(|x| x.simple_name.is_none())
+            })
+            .collect::<Vec<_>>();
+        constant_classes.sort_by_key(|x| x.qualified_name());
+
+        let indent = INDENT.repeat(depth);
+        let mut seen = HashSet::new();
+        let mut methods = Vec::new();
+        for class in constant_classes {
+            for method in class.methods() {
+                if method.name.starts_with('<')
+                    || method.access_flags.intersects(
+                        MethodAccessFlags::PRIVATE
+                            | MethodAccessFlags::SYNTHETIC
+                            | MethodAccessFlags::BRIDGE,
+                    )
+                    || !seen.insert((&method.name, &method.descriptor))
+                {
+                    continue;
+                }
+                let mut member = String::new();
+                self.method(class, Kind::Class, "", method, None, &indent, &mut member);
+                methods.push(member);
+            }
+        }
+        (!methods.is_empty()).then(|| methods.join("\n"))
+    }
+
+    /// The `super(...)` call of a constructor, for the superclass constructor the original
+    /// calls directly or through `this(...)`. Calls without arguments are left implicit.
+    fn super_constructor_call(&self, component: &Component, method: &Method) -> Option<String> {
+        let this_class = component.qualified_name()?;
+        let super_class = component.super_class()?;
+        let mut call = method.constructor_call.as_ref()?;
+        let mut visited = HashSet::new();
+        while call.class_name == this_class && visited.insert(&call.descriptor) {
+            call = component
+                .methods()
+                .iter()
+                .find(|x| x.name == "<init>" && x.descriptor == call.descriptor)?
+                .constructor_call
+                .as_ref()?;
+        }
+        if call.class_name != super_class {
+            return None;
+        }
+        // Arguments are cast to the parameter types to select the overload. The generic
+        // parameter types are known for superclasses among the components. Otherwise the
+        // erasure of a type variable cannot be cast to, so arguments of generic superclasses
+        // and `Object` arguments are left uncast.
+        let super_component = self.classes.get(super_class);
+        let super_constructor = super_component.and_then(|x| {
+            x.methods()
+                .iter()
+                .find(|x| x.name == "<init>" && x.descriptor == call.descriptor)
+        });
+        let is_generic_super = component
+            .signature()
+            .is_some_and(|x| x.superclass_signature.type_arguments().is_some());
+        let params = match (super_component, super_constructor) {
+            (Some(super_component), Some(constructor)) if constructor.signature.is_some() => {
+                let type_arguments = component
+                    .signature()
+                    .and_then(|x| x.superclass_signature.type_arguments());
+                let constructor_type_parameters = constructor
+                    .signature
+                    .iter()
+                    .flat_map(|x| x.type_parameters.iter().flatten())
+                    .map(|x| x.identifier.clone())
+                    .collect::<Vec<_>>();
+                let constructor =
+                    substitute_method(constructor, &bind(super_component, type_arguments));
+                constructor
+                    .signature
+                    .unwrap()
+                    .parameters
+                    .iter()
+                    .map(|x| {
+                        // Type parameters of the constructor itself are not in scope
+                        let cast =
+                            match x {
+                                TypeSignature::Reference(ReferenceTypeSignature::TypeVariable(
+                                    x,
+                                )) if constructor_type_parameters.contains(&x.identifier) => None,
+                                _ => Some(self.type_signature(x)),
+                            };
+                        (Ty::from(x), cast)
+                    })
+                    .collect::<Vec<_>>()
+            }
+            _ => {
+                let mut params = parse_method_descriptor(&call.descriptor).param_descs;
+                // Constructors of inner classes take the enclosing instance first, which is
+                // passed implicitly when the subclass is nested in the same class
+                if super_component.is_some_and(|x| is_inner(x)) && !params.is_empty() {
+                    params.remove(0);
+                }
+                params
+                    .iter()
+                    .map(|x| {
+                        let ty = Ty::from(&x.ty);
+                        let cast = Some(self.ty(&ty)).filter(|x| {
+                            super_component.is_some()
+                                || (!is_generic_super && x != "java.lang.Object")
+                        });
+                        (ty, cast)
+                    })
+                    .collect()
+            }
+        };
+        if params.is_empty() {
+            return None;
+        }
+        let args = params
+            .iter()
+            .map(|(ty, cast)| default_argument(ty, cast.as_deref()))
+            .collect::<Vec<_>>();
+        Some(format!("super({});", args.join(", ")))
+    }
+
+    /// The permitted subclasses that exist in the stubs or outside the components.
+    fn permitted_subclasses(&self, component: &Component) -> Vec<String> {
+        if kind(component) == Kind::Enum {
+            return Vec::new();
+        }
+        component
+            .permitted_subclasses()
+            .iter()
+            .filter(|x| {
+                !self.classes.contains_key(x.as_str()) || self.rendered.contains(x.as_str())
+            })
+            .map(|x| self.source_name(x))
+            .collect()
+    }
+
+    fn is_non_sealed(
+        &self,
+        component: &Component,
+        kind: Kind,
+        flags: InnerClassAccessFlags,
+    ) -> bool {
+        let name = component.qualified_name().unwrap_or_default();
+        matches!(kind, Kind::Class | Kind::Interface)
+            && !flags.contains(InnerClassAccessFlags::FINAL)
+            && component
+                .super_class()
+                .into_iter()
+                .chain(component.interfaces().iter().map(|x| x.as_str()))
+                .filter_map(|x| self.classes.get(x))
+                .any(|x| x.permitted_subclasses().iter().any(|x| x == name))
+    }
+
+    fn annotations(&self, annotations: &[Annotation], indent: &str, out: &mut String) {
+        for annotation in annotations {
+            if matches!(
+                annotation.kind,
+                AnnotationKind::RuntimeVisible | AnnotationKind::RuntimeInvisible
+            ) {
+                out.push_str(&format!("{indent}{}\n", self.annotation(annotation)));
+            }
+        }
+    }
+
+    fn annotation(&self, annotation: &Annotation) -> String {
+        let mut out = format!("@{}", self.ty(&annotation.ty));
+        if !annotation.elements.is_empty() {
+            let elements = annotation
+                .elements
+                .iter()
+                .map(|x| format!("{} = {}", x.name, self.annotation_value(&x.value)))
+                .collect::<Vec<_>>();
+            out.push_str(&format!("({})", elements.join(", ")));
+        }
+        out
+    }
+
+    fn annotation_value(&self, value: &AnnotationValue) -> String {
+        match value {
+            AnnotationValue::Byte(x) => format!("(byte) {x}"),
+            AnnotationValue::Char(x) => char_literal(*x),
+            AnnotationValue::Short(x) => format!("(short) {x}"),
+            AnnotationValue::Int(x) => x.to_string(),
+            AnnotationValue::Long(x) => format!("{x}L"),
+            AnnotationValue::Float(x) => float_literal(*x),
+            AnnotationValue::Double(x) => double_literal(*x),
+            AnnotationValue::Boolean(x) => x.to_string(),
+            AnnotationValue::String(x) => string_literal(x),
+            AnnotationValue::Enum { ty, name } => format!("{}.{name}", self.ty(ty)),
+            AnnotationValue::Class(ty) => format!("{}.class", self.ty(ty)),
+            AnnotationValue::Annotation(x) => self.annotation(x),
+            AnnotationValue::Array(values) => {
+                let values = values
+                    .iter()
+                    .map(|x| self.annotation_value(x))
+                    .collect::<Vec<_>>();
+                format!("{{{}}}", values.join(", "))
+            }
+        }
+    }
+
+    fn field_type(&self, field: &Field) -> String {
+        match &field.signature {
+            Some(signature) => self.reference_type(&signature.reference_type_signature),
+            None => self.ty(&field.ty),
+        }
+    }
+
+    fn ty(&self, ty: &Ty) -> String {
+        match ty {
+            Ty::Prim(prim) => prim_name(prim).to_string(),
+            Ty::Reference(name) => self.source_name(&name.qualified_name()),
+            Ty::TyVar(name) => name.clone(),
+            Ty::Array(ty, dims) => format!("{}{}", self.ty(ty), "[]".repeat(*dims)),
+            Ty::Void => "void".to_string(),
+        }
+    }
+
+    fn type_parameters(&self, type_parameters: Option<&[TypeParameter]>) -> String {
+        let Some(type_parameters) = type_parameters.filter(|x| !x.is_empty()) else {
+            return String::new();
+        };
+        let type_parameters = type_parameters
+            .iter()
+            .map(|x| {
+                let bounds = x
+                    .class_bound
+                    .iter()
+                    .chain(&x.interface_bounds)
+                    .map(|x| self.reference_type(x))
+                    .collect::<Vec<_>>();
+                if bounds.is_empty() || bounds == ["java.lang.Object"] {
+                    x.identifier.clone()
+                } else {
+                    format!("{} extends {}", x.identifier, bounds.join(" & "))
+                }
+            })
+            .collect::<Vec<_>>();
+        format!("<{}>", type_parameters.join(", "))
+    }
+
+    fn type_signature(&self, signature: &TypeSignature) -> String {
+        match signature {
+            TypeSignature::Base(base) => base_name(base).to_string(),
+            TypeSignature::Reference(reference) => self.reference_type(reference),
+        }
+    }
+
+    fn reference_type(&self, signature: &ReferenceTypeSignature) -> String {
+        match signature {
+            ReferenceTypeSignature::TypeVariable(x) => x.identifier.clone(),
+            ReferenceTypeSignature::Class(x) => self.class_type(x),
+            ReferenceTypeSignature::Array(x) => {
+                format!("{}[]", self.type_signature(&x.java_type_signature))
+            }
+        }
+    }
+
+    fn class_type(&self, signature: &ClassTypeSignature) -> String {
+        let simple = &signature.simple_class_type_signature;
+        let binary_name = match &signature.package_specifier {
+            Some(package) => format!("{package}.{}", simple.identifier),
+            None => simple.identifier.clone(),
+        };
+        let mut out = self.source_name(&binary_name);
+        out.push_str(&self.type_arguments(simple));
+        for suffix in &signature.class_type_signature_suffixes {
+            out.push('.');
+            out.push_str(&suffix.identifier);
+            out.push_str(&self.type_arguments(suffix));
+        }
+        out
+    }
+
+    fn type_arguments(&self, signature: &SimpleClassTypeSignature) -> String {
+        let Some(type_arguments) = &signature.type_arguments else {
+            return String::new();
+        };
+        let type_arguments = type_arguments
+            .iter()
+            .map(|x| match x {
+                TypeArgument::Any => "?".to_string(),
+                TypeArgument::ReferenceType(None, ty) => self.reference_type(ty),
+                TypeArgument::ReferenceType(Some(WildcardIndicator::Plus), ty) => {
+                    format!("? extends {}", self.reference_type(ty))
+                }
+                TypeArgument::ReferenceType(Some(WildcardIndicator::Minus), ty) => {
+                    format!("? super {}", self.reference_type(ty))
+                }
+            })
+            .collect::<Vec<_>>();
+        format!("<{}>", type_arguments.join(", "))
+    }
+}
+
+fn kind(component: &Component) -> Kind {
+    match &component.kind {
+        ComponentKind::Interface(x) if x.is_annotation => Kind::Annotation,
+        ComponentKind::Interface(_) | ComponentKind::Module(_) => Kind::Interface,
+        ComponentKind::Class(x) if x.is_enum => Kind::Enum,
+        ComponentKind::Class(x) if x.super_class.as_deref() == Some("java.lang.Record") => {
+            Kind::Record
+        }
+        ComponentKind::Class(_) => Kind::Class,
+    }
+}
+
+fn inner_class(component: &Component) -> Option<&InnerClassInfo> {
+    match &component.kind {
+        ComponentKind::Class(x) => x.inner_class.as_ref(),
+        ComponentKind::Interface(x) => x.inner_class.as_ref(),
+        ComponentKind::Module(_) => None,
+    }
+}
+
+/// Whether the component is a member class with an enclosing instance.
+fn is_inner(component: &Component) -> bool {
+    kind(component) == Kind::Class
+        && inner_class(component).is_some_and(|x| {
+            x.outer_class.is_some() && !x.access_flags.contains(InnerClassAccessFlags::STATIC)
+        })
+}
+
+fn access(public: bool, protected: bool, private: bool) -> &'static str {
+    if public {
+        "public "
+    } else if protected {
+        "protected "
+    } else if private {
+        "private "
+    } else {
+        ""
+    }
+}
+
+pub(crate) fn prim_name(prim: &PrimTy) -> &'static str {
+    match prim {
+        PrimTy::Byte => "byte",
+        PrimTy::Char => "char",
+        PrimTy::Double => "double",
+        PrimTy::Float => "float",
+        PrimTy::Int => "int",
+        PrimTy::Long => "long",
+        PrimTy::Short => "short",
+        PrimTy::Boolean => "boolean",
+        PrimTy::Void => "void",
+    }
+}
+
+pub(crate) fn base_name(base: &BaseType) -> &'static str {
+    match base {
+        BaseType::Byte => "byte",
+        BaseType::Char => "char",
+        BaseType::Double => "double",
+        BaseType::Float => "float",
+        BaseType::Int => "int",
+        BaseType::Long => "long",
+        BaseType::Short => "short",
+        BaseType::Boolean => "boolean",
+    }
+}
+
+pub(crate) fn constant(value: &ConstantValue, ty: &Ty) -> String {
+    match (value, ty) {
+        (ConstantValue::Int(x), Ty::Prim(PrimTy::Boolean)) => (*x != 0).to_string(),
+        (ConstantValue::Int(x), Ty::Prim(PrimTy::Char)) => char_literal(*x as u16),
+        (ConstantValue::Int(x), _) => x.to_string(),
+        (ConstantValue::Long(x), _) => format!("{x}L"),
+        (ConstantValue::Float(x), _) => float_literal(*x),
+        (ConstantValue::Double(x), _) => double_literal(*x),
+        (ConstantValue::String(x), _) => string_literal(x),
+    }
+}
+
+/// A value of the type that is not a constant expression.
+fn placeholder(ty: &Ty) -> &'static str {
+    match ty {
+        Ty::Prim(PrimTy::Boolean) => "java.lang.Boolean.valueOf(false)",
+        Ty::Prim(PrimTy::Byte) => "java.lang.Byte.valueOf((byte) 0)",
+        Ty::Prim(PrimTy::Char) => "java.lang.Character.valueOf('\\0')",
+        Ty::Prim(PrimTy::Short) => "java.lang.Short.valueOf((short) 0)",
+        Ty::Prim(PrimTy::Int) => "java.lang.Integer.valueOf(0)",
+        Ty::Prim(PrimTy::Long) => "java.lang.Long.valueOf(0L)",
+        Ty::Prim(PrimTy::Float) => "java.lang.Float.valueOf(0.0f)",
+        Ty::Prim(PrimTy::Double) => "java.lang.Double.valueOf(0.0)",
+        _ => "null",
+    }
+}
+
+/// The zero value of the type, as a constructor argument. References are cast when `cast` is
+/// given, to select between overloads.
+fn default_argument(ty: &Ty, cast: Option<&str>) -> String {
+    match ty {
+        Ty::Prim(PrimTy::Boolean) => "false".to_string(),
+        Ty::Prim(PrimTy::Byte) => "(byte) 0".to_string(),
+        Ty::Prim(PrimTy::Char) => "'\\0'".to_string(),
+        Ty::Prim(PrimTy::Short) => "(short) 0".to_string(),
+        Ty::Prim(PrimTy::Long) => "0L".to_string(),
+        Ty::Prim(PrimTy::Float) => "0.0f".to_string(),
+        Ty::Prim(PrimTy::Double) => "0.0".to_string(),
+        Ty::Prim(_) => "0".to_string(),
+        _ => match cast {
+            Some(cast) => format!("({cast}) null"),
+            None => "null".to_string(),
+        },
+    }
+}
+
+pub(crate) fn float_literal(x: f32) -> String {
+    if x.is_nan() {
+        "0.0f / 0.0f".to_string()
+    } else if x.is_infinite() {
+        format!("{}1.0f / 0.0f", if x < 0.0 { "-" } else { "" })
+    } else {
+        format!("{x:?}f")
+    }
+}
+
+pub(crate) fn double_literal(x: f64) -> String {
+    if x.is_nan() {
+        "0.0 / 0.0".to_string()
+    } else if x.is_infinite() {
+        format!("{}1.0 / 0.0", if x < 0.0 { "-" } else { "" })
+    } else {
+        format!("{x:?}")
+    }
+}
+
+pub(crate) fn char_literal(unit: u16) -> String {
+    let mut out = String::from("'");
+    escape(unit, '\'', &mut out);
+    out.push('\'');
+    out
+}
+
+pub(crate) fn string_literal(s: &str) -> String {
+    let mut out = String::from("\"");
+    for unit in s.encode_utf16() {
+        escape(unit, '"', &mut out);
+    }
+    out.push('"');
+    out
+}
+
+/// Escapes a UTF-16 code unit in a character or string literal. Line terminators must not be
+/// written as Unicode escapes, which are translated before literals are read.
+fn escape(unit: u16, quote: char, out: &mut String) {
+    match unit {
+        0x08 => out.push_str("\\b"),
+        0x09 => out.push_str("\\t"),
+        0x0a => out.push_str("\\n"),
+        0x0c => out.push_str("\\f"),
+        0x0d => out.push_str("\\r"),
+        0x5c => out.push_str("\\\\"),
+        _ if unit == quote as u16 => {
+            out.push('\\');
+            out.push(quote);
+        }
+        0x20..=0x7e => out.push(unit as u8 as char),
+        _ => out.push_str(&format!("\\u{unit:04x}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        component::{AnnotationElement, Class, Interface},
+        signature::parse_class_signature,
+        test_util::{self, component, field, method, reference},
+    };
+
+    #[test]
+    fn renders_generic_classes_with_members() {
+        let mut get = method(
+            "get",
+            "(I)Ljava/lang/Object;",
+            Some("(I)TT;^Ljava/io/IOException;"),
+            0x0001,
+        );
+        get.annotations.push(Annotation {
+            kind: AnnotationKind::RuntimeVisible,
+            ty: reference("java.lang", "Deprecated"),
+            elements: vec![AnnotationElement {
+                name: "since".to_string(),
+                value: AnnotationValue::String("1.2\n".to_string()),
+            }],
+        });
+        let mut add = method(
+            "add",
+            "([Ljava/lang/Object;)V",
+            Some("([TT;)V"),
+            0x0401 | 0x0080,
+        );
+        add.exceptions.push("a.Box$Error".to_string());
+
+        let outer = component(ComponentKind::Class(Class {
+            interfaces: vec!["java.lang.Iterable".to_string()],
+            signature: Some(
+                parse_class_signature(
+                    "<T::Ljava/lang/Comparable<-TT;>;>Ljava/lang/Object;Ljava/lang/Iterable<TT;>;",
+                )
+                .unwrap()
+                .1,
+            ),
+            methods: vec![
+                method("<init>", "(Ljava/lang/String;)V", None, 0x0004),
+                get,
+                add,
+                method("bridge", "()V", None, 0x0041),
+            ],
+            fields: vec![Field {
+                constant_value: Some(ConstantValue::Int('\'' as i32)),
+                ..field("SEPARATOR", "C", None, 0x0019)
+            }],
+            is_abstract: true,
+            access_flags: AccessFlags::PUBLIC | AccessFlags::ABSTRACT,
+            ..test_util::class("a.Box")
+        }));
+        let inner = component(ComponentKind::Interface(Interface {
+            methods: vec![method("code", "()V", None, 0x0401)],
+            access_flags: AccessFlags::INTERFACE | AccessFlags::ABSTRACT,
+            inner_class: Some(InnerClassInfo {
+                outer_class: Some("a.Box".to_string()),
+                simple_name: Some("Error".to_string()),
+                access_flags: InnerClassAccessFlags::PUBLIC
+                    | InnerClassAccessFlags::STATIC
+                    | InnerClassAccessFlags::INTERFACE
+                    | InnerClassAccessFlags::ABSTRACT,
+            }),
+            ..test_util::interface("a.Box$Error")
+        }));
+
+        let stubs = java_stubs(&[inner, outer]);
+        assert_eq!(stubs.len(), 1);
+        assert_eq!(stubs[0].path, "a/Box.java");
+        assert_eq!(
+            stubs[0].source,
+            r#"package a;
+
+public abstract class Box<T extends java.lang.Comparable<? super T>> implements java.lang.Iterable<T> {
+    public static final char SEPARATOR = '\'';
+
+    protected Box(java.lang.String arg0) {
+        throw new java.lang.UnsupportedOperationException();
+    }
+
+    @java.lang.Deprecated(since = "1.2\n")
+    public T get(int arg0) throws java.io.IOException {
+        throw new java.lang.UnsupportedOperationException();
+    }
+
+    public abstract void add(T... arg0) throws a.Box.Error;
+
+    public interface Error {
+        void code();
+    }
+}
+"#
+        );
+    }
+}
@@ -1,9 +1,34 @@
+pub mod bytecode;
+pub mod callgraph;
 pub mod class_file;
+pub mod compat;
 pub mod component;
+pub mod conflicts;
+pub mod constant_pool;
+pub mod dependency;
 pub mod descriptor;
+pub mod diff;
+pub mod dts;
 pub mod extractor;
+pub mod hierarchy;
+pub mod java_stubs;
+pub mod members;
+pub mod pretty;
 pub mod proto;
+pub mod proto_schema;
+pub mod proto_v2;
+pub mod query;
+pub mod release;
+pub mod rust_bindings;
 pub mod signature;
+pub mod snapshot;
+pub mod sqlite;
+pub mod stack_map;
+#[cfg(test)]
+mod test_util;
+pub mod type_check;
+pub mod usage;
+pub mod validate;
 
 pub use prost::bytes;
 
@@ -23,6 +48,8 @@ pub mod jni {
     struct ClassReader;
 
     impl ClassReader {
+        /// Returns a bare version 1 `ComponentList` without a snapshot header, which
+        /// `decode_component_list` and `decode_components` read as such.
         #[allow(deprecated)]
         #[allow(clippy::needless_borrow)]
         pub extern "jni" fn extractFromJarPath(jar_path: String) -> Vec<i8> {
@@ -49,6 +76,37 @@ pub mod jni {
     }
 }
 
-pub fn decode_component_list<B: Buf>(buf: B) -> Result<proto::component::ComponentList, String> {
-    proto::component::ComponentList::decode(buf).map_err(|e| e.to_string())
+/// Decodes a `.pb` file as a version 1 `ComponentList`. A snapshot with a header is decoded per
+/// its schema version and converted, dropping what version 1 cannot carry; use
+/// `decode_components` to keep it.
+pub fn decode_component_list<B: Buf>(
+    mut buf: B,
+) -> Result<proto::component::ComponentList, String> {
+    let buf = buf.copy_to_bytes(buf.remaining());
+    match snapshot::decode_snapshot(&buf)? {
+        Some(snapshot) => {
+            let components = snapshot
+                .components
+                .into_iter()
+                .map(|x| x.component)
+                .collect::<Vec<_>>();
+            Ok((&components).into())
+        }
+        None => proto::component::ComponentList::decode(buf).map_err(|e| e.to_string()),
+    }
+}
+
+/// Decodes the components of a `.pb` file: a snapshot of any supported schema version, or a bare
+/// `ComponentList` written before snapshots had headers.
+pub fn decode_components(buf: &[u8]) -> Result<Vec<component::Component>, String> {
+    match snapshot::decode_snapshot(buf)? {
+        Some(snapshot) => Ok(snapshot
+            .components
+            .into_iter()
+            .map(|x| x.component)
+            .collect()),
+        None => proto::component::ComponentList::decode(buf)
+            .map_err(|e| e.to_string())?
+            .try_into(),
+    }
 }
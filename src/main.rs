@@ -1,31 +1,54 @@
 use std::{
     error::Error,
     fs::File,
-    io::Write,
+    io::{BufWriter, Write},
     path::{Path, PathBuf},
     process::exit,
+    sync::Mutex,
 };
 
-use class_file::parse_class_file;
-use component::extract_component;
-use extractor::extract_members_from_jar;
+use classreader::{
+    class_file::parse_class_file,
+    compat::{check_compatibility, Severity},
+    component::{
+        try_extract_component, AccessModifier, Component, ExtractorContext, SourcedComponent,
+    },
+    conflicts::{find_conflicts, read_classpath_element},
+    dependency::{DependencyGraph, Granularity},
+    diff::diff_components,
+    dts::dts,
+    extractor::{
+        extract_entries_from_jar, extract_members_from_path, read_class_files_from_path,
+        read_service_providers_from_path,
+    },
+    hierarchy::TypeHierarchy,
+    java_stubs::java_stubs,
+    proto_schema::{proto_schema, FieldNumbering, ProtoSchemaOptions},
+    query::parse_query,
+    release::{releases_to_table, summarize_releases, JavaRelease},
+    rust_bindings::{rust_bindings, RustBindingOptions},
+    snapshot::{encode_snapshot, SnapshotWriter},
+    sqlite::SqliteWriter,
+    usage::{find_unused, EntryPoints},
+};
 
-use clap::{Args, Parser};
+use clap::{Args, Parser, Subcommand};
 use prost::Message;
 use rayon::prelude::*;
 
-use crate::component::{AccessModifier, ExtractorContext};
-
-mod class_file;
-mod component;
-mod descriptor;
-mod extractor;
-mod proto;
-mod signature;
-
 #[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+#[command(
+    author,
+    version,
+    about,
+    long_about = None,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     #[command(flatten)]
     output_kind: OutputKind,
 
@@ -45,6 +68,220 @@ struct Cli {
     /// Whether to parse files in parallel (default: true)
     #[arg(short = 'r', long, default_value_t = true)]
     parallel: bool,
+
+    /// Whether to write the components of all inputs to a single `components.json` or
+    /// `components.pb`, each tagged with its source and entry path, instead of one file per
+    /// input (default: false)
+    #[arg(
+        long,
+        default_value_t = false,
+        conflicts_with_all = ["java_stubs", "rust", "dts", "proto_schema"]
+    )]
+    single_file: bool,
+
+    /// Whether to also write the type hierarchy of all inputs to `hierarchy.json` or `hierarchy.pb` (default: false)
+    #[arg(long, default_value_t = false)]
+    hierarchy: bool,
+
+    /// Whether the Rust bindings call the Java methods through the `jni` crate (default: false)
+    #[arg(long, default_value_t = false, requires = "rust")]
+    jni: bool,
+
+    /// The version of the component schema of the Protocol Buffers output: 1, or 2, which carries
+    /// everything in the JSON output (default: 1)
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..=2))]
+    proto_version: u32,
+
+    /// How to number the fields of the protobuf messages
+    #[arg(long, value_enum, default_value_t = Numbering::Declaration, requires = "proto_schema")]
+    field_numbering: Numbering,
+
+    /// The number of the first field of each protobuf message (default: 1)
+    #[arg(
+        long,
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u32).range(1..),
+        requires = "proto_schema"
+    )]
+    first_field_number: u32,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Check binary and source compatibility (JLS chapter 13) between two versions of a library
+    Compat(CompatArgs),
+    /// Report the added, removed and changed public API between two versions of a library
+    Diff(DiffArgs),
+    /// Report class or package dependencies, flagging uses of JDK-internal APIs
+    Deps(DepsArgs),
+    /// Report classes, methods and fields unreachable from the given entry points
+    Usage(UsageArgs),
+    /// Report classes defined by several classpath elements and packages split across them
+    Conflicts(ConflictsArgs),
+    /// Report the Java releases targeted by the classes of each JAR file or directory
+    Versions(VersionsArgs),
+    /// List the classes, methods or fields matching a query
+    Query(QueryArgs),
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy)]
+enum Numbering {
+    /// In declaration order, inherited fields first
+    Declaration,
+    /// In alphabetical order of the field names
+    Alphabetical,
+}
+
+#[derive(Args, Debug)]
+struct CompatArgs {
+    /// The old version: a class file, JAR file, directory or `.json`/`.ndjson`/`.pb` snapshot
+    old_path: String,
+
+    /// The new version: a class file, JAR file, directory or `.json`/`.ndjson`/`.pb` snapshot
+    new_path: String,
+
+    /// Also write the report as JSON to the given path
+    #[arg(long)]
+    json: Option<String>,
+
+    /// Whether source-incompatible changes also fail the check (default: false)
+    #[arg(long, default_value_t = false)]
+    fail_on_source: bool,
+}
+
+#[derive(Args, Debug)]
+struct DiffArgs {
+    /// The old version: a class file, JAR file, directory or `.json`/`.ndjson`/`.pb` snapshot
+    old_path: String,
+
+    /// The new version: a class file, JAR file, directory or `.json`/`.ndjson`/`.pb` snapshot
+    new_path: String,
+
+    /// The report format
+    #[arg(short, long, value_enum, default_value_t = DiffFormat::Markdown)]
+    format: DiffFormat,
+
+    /// Write the report to the given path instead of standard output
+    #[arg(short, long)]
+    output_path: Option<String>,
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy)]
+enum DiffFormat {
+    Markdown,
+    Json,
+}
+
+#[derive(Args, Debug)]
+struct DepsArgs {
+    /// The class files, JAR files or directories to analyze
+    #[arg(required = true)]
+    input_paths: Vec<String>,
+
+    /// The report format
+    #[arg(short, long, value_enum, default_value_t = DepsFormat::Table)]
+    format: DepsFormat,
+
+    /// Whether to report dependencies between classes instead of packages (default: false)
+    #[arg(long, default_value_t = false)]
+    classes: bool,
+
+    /// Write the report to the given path instead of standard output
+    #[arg(short, long)]
+    output_path: Option<String>,
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy)]
+enum DepsFormat {
+    Table,
+    Dot,
+    Json,
+}
+
+#[derive(Args, Debug)]
+struct UsageArgs {
+    /// The class files, JAR files or directories to analyze. Providers registered in
+    /// `META-INF/services` are entry points.
+    #[arg(required = true)]
+    input_paths: Vec<String>,
+
+    /// A class whose `main` method is an entry point
+    #[arg(long = "main")]
+    main_classes: Vec<String>,
+
+    /// Keep a class and all its members; `*` matches within a package and `**` across packages
+    #[arg(long)]
+    keep: Vec<String>,
+
+    /// Keep classes and members annotated with the given annotation
+    #[arg(long = "keep-annotation")]
+    keep_annotations: Vec<String>,
+
+    /// The report format
+    #[arg(short, long, value_enum, default_value_t = UsageFormat::Text)]
+    format: UsageFormat,
+
+    /// Write the report to the given path instead of standard output
+    #[arg(short, long)]
+    output_path: Option<String>,
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy)]
+enum UsageFormat {
+    Text,
+    Json,
+}
+
+#[derive(Args, Debug)]
+struct ConflictsArgs {
+    /// The JAR files or class directories of the classpath, in classpath order
+    #[arg(required = true)]
+    input_paths: Vec<String>,
+
+    /// Also write the report as JSON to the given path
+    #[arg(long)]
+    json: Option<String>,
+}
+
+#[derive(Args, Debug)]
+struct VersionsArgs {
+    /// The JAR files or class directories to check
+    #[arg(required = true)]
+    input_paths: Vec<String>,
+
+    /// Fail if any class requires a newer Java release than this one, e.g. `11` or `1.8`
+    #[arg(long)]
+    max_release: Option<JavaRelease>,
+
+    /// Also write the report as JSON to the given path
+    #[arg(long)]
+    json: Option<String>,
+}
+
+#[derive(Args, Debug)]
+struct QueryArgs {
+    /// The query, e.g. `methods where is public and is static and returns = java.util.Optional`
+    /// or `classes where supertype = java.io.Serializable and not has field serialVersionUID
+    /// select name`
+    expression: String,
+
+    /// The class files, JAR files, directories or `.json`/`.ndjson`/`.pb` snapshots to query
+    #[arg(required = true)]
+    input_paths: Vec<String>,
+
+    /// The report format
+    #[arg(short, long, value_enum, default_value_t = QueryFormat::Table)]
+    format: QueryFormat,
+
+    /// Write the report to the given path instead of standard output
+    #[arg(short, long)]
+    output_path: Option<String>,
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy)]
+enum QueryFormat {
+    Table,
+    Json,
 }
 
 #[derive(Args, Debug)]
@@ -57,20 +294,83 @@ struct OutputKind {
     /// Whether to output the parsed class files as Protocol Buffers (default: true, conflicts with json)
     #[arg(short, long, default_value_t = true)]
     proto: bool,
+
+    /// Whether to output the parsed class files as newline-delimited JSON to `components.ndjson`,
+    /// one component per line tagged with its source and entry path, as they are parsed
+    /// (default: false)
+    #[arg(long, default_value_t = false)]
+    ndjson: bool,
+
+    /// The path of a SQLite database to output the parsed class files to, in tables of classes,
+    /// members, parameters, annotations, supertypes and type references, as they are parsed
+    #[arg(long, value_name = "PATH")]
+    sqlite: Option<String>,
+
+    /// Whether to output Java source stubs of the parsed classes, with bodies that throw
+    /// `UnsupportedOperationException`, to `<package path>/<class>.java` (default: false)
+    #[arg(long, default_value_t = false)]
+    java_stubs: bool,
+
+    /// Whether to output Rust bindings of the public classes and interfaces to `bindings.rs`
+    /// (default: false)
+    #[arg(long, default_value_t = false)]
+    rust: bool,
+
+    /// Whether to output TypeScript declarations of the public classes and interfaces to
+    /// `index.d.ts` (default: false)
+    #[arg(long, default_value_t = false)]
+    dts: bool,
+
+    /// Whether to output protobuf messages of the classes and records, and protobuf enums of
+    /// the enums, to `<package path>.proto` (default: false)
+    #[arg(long, default_value_t = false)]
+    proto_schema: bool,
 }
 
 #[derive(Debug, Clone, Copy)]
 enum OKind {
     Json,
-    Proto,
+    Ndjson,
+    Sqlite,
+    /// With the component schema version
+    Proto(u32),
+    JavaStubs,
+    Rust,
+    Dts,
+    ProtoSchema,
 }
 
 fn main() {
     let args = Cli::parse();
+    if let Some(command) = args.command {
+        let code = match command {
+            Command::Compat(compat_args) => run_compat(compat_args),
+            Command::Diff(diff_args) => run_diff(diff_args),
+            Command::Deps(deps_args) => run_deps(deps_args),
+            Command::Usage(usage_args) => run_usage(usage_args),
+            Command::Conflicts(conflicts_args) => run_conflicts(conflicts_args),
+            Command::Versions(versions_args) => run_versions(versions_args),
+            Command::Query(query_args) => run_query(query_args),
+        };
+        exit(code);
+    }
+
     let output_kind = if args.output_kind.json {
         OKind::Json
+    } else if args.output_kind.ndjson {
+        OKind::Ndjson
+    } else if args.output_kind.sqlite.is_some() {
+        OKind::Sqlite
+    } else if args.output_kind.java_stubs {
+        OKind::JavaStubs
+    } else if args.output_kind.rust {
+        OKind::Rust
+    } else if args.output_kind.dts {
+        OKind::Dts
+    } else if args.output_kind.proto_schema {
+        OKind::ProtoSchema
     } else {
-        OKind::Proto
+        OKind::Proto(args.proto_version)
     };
 
     let start_time = if args.time {
@@ -79,13 +379,14 @@ fn main() {
         None
     };
 
+    // Each file to extract, with the input path it was found under
     let paths = args
         .input_paths
         .into_iter()
         .flat_map(|x| {
-            let p = Path::new(&x);
+            let p = PathBuf::from(&x);
             if p.is_dir() {
-                walkdir::WalkDir::new(p)
+                walkdir::WalkDir::new(&p)
                     .into_iter()
                     .filter_map(|e| e.ok())
                     .filter(|e| e.path().is_file())
@@ -93,10 +394,10 @@ fn main() {
                         let ext = e.path().extension().unwrap();
                         ext == "class" || ext == "jar" || ext == "jmod"
                     })
-                    .map(|e| e.path().to_path_buf())
+                    .map(|e| (p.clone(), e.path().to_path_buf()))
                     .collect::<Vec<_>>()
             } else {
-                vec![x.into()]
+                vec![(p.clone(), p)]
             }
         })
         .collect::<Vec<_>>();
@@ -116,25 +417,101 @@ fn main() {
         exit(1);
     }
 
-    if args.parallel {
+    // NDJSON and SQLite are always written to a single file, streamed as the inputs are extracted
+    let aggregate = match (&args.output_kind.sqlite, output_kind) {
+        (Some(path), _) => Some(SqliteWriter::create(path).map(Aggregate::Sqlite)),
+        (None, OKind::Ndjson) => Some(Aggregate::create(output_dir, output_kind)),
+        (None, _) if args.single_file => Some(Aggregate::create(output_dir, output_kind)),
+        _ => None,
+    };
+    let aggregate = match aggregate.transpose() {
+        Ok(aggregate) => aggregate.map(Mutex::new),
+        Err(err) => {
+            println!("Error: {}", err);
+            exit(1);
+        }
+    };
+    // Only the hierarchy and the generators need every component at once; other outputs are
+    // written per input, so its components are dropped once written
+    let keep_components = args.hierarchy
+        || matches!(
+            output_kind,
+            OKind::JavaStubs | OKind::Rust | OKind::Dts | OKind::ProtoSchema
+        );
+    let extract = |(source, p): &(PathBuf, PathBuf)| {
+        extract_from_path(p, source, output_dir, output_kind, aggregate.as_ref()).map(|c| {
+            if keep_components {
+                c
+            } else {
+                Vec::new()
+            }
+        })
+    };
+
+    let components = if args.parallel {
         paths
             .par_iter()
-            .for_each(|p| match extract_from_path(p, output_dir, output_kind) {
-                Ok(_) => {}
+            .flat_map(|x| match extract(x) {
+                Ok(c) => c,
                 Err(err) => {
                     println!("Error: {}", err);
+                    Vec::new()
                 }
-            });
+            })
+            .collect::<Vec<_>>()
     } else {
-        for p in &paths {
-            match extract_from_path(p, output_dir, output_kind) {
-                Ok(_) => {}
+        let mut components = Vec::new();
+        for x in &paths {
+            match extract(x) {
+                Ok(c) => components.extend(c),
                 Err(err) => {
                     println!("Error: {}", err);
                     continue;
                 }
             }
         }
+        components
+    };
+    if let Some(aggregate) = aggregate {
+        if let Err(err) = aggregate.into_inner().unwrap().finish() {
+            println!("Error: {}", err);
+        }
+    }
+
+    let written = match output_kind {
+        OKind::JavaStubs => write_java_stubs(&components, output_dir),
+        OKind::Rust => {
+            let options = RustBindingOptions { jni: args.jni };
+            std::fs::write(
+                output_dir.join("bindings.rs"),
+                rust_bindings(&components, options),
+            )
+            .map_err(|e| e.into())
+        }
+        OKind::Dts => {
+            std::fs::write(output_dir.join("index.d.ts"), dts(&components)).map_err(|e| e.into())
+        }
+        OKind::ProtoSchema => {
+            let options = ProtoSchemaOptions {
+                numbering: match args.field_numbering {
+                    Numbering::Declaration => FieldNumbering::Declaration,
+                    Numbering::Alphabetical => FieldNumbering::Alphabetical,
+                },
+                first_number: args.first_field_number,
+            };
+            write_proto_schema(&components, options, output_dir)
+        }
+        OKind::Json | OKind::Ndjson | OKind::Sqlite | OKind::Proto(_) => Ok(()),
+    };
+    if let Err(err) = written {
+        println!("Error: {}", err);
+    }
+
+    if args.hierarchy {
+        let hierarchy = TypeHierarchy::new(&components);
+        if let Err(err) = write_hierarchy(&hierarchy, output_dir, output_kind) {
+            println!("Error: {}", err);
+        }
     }
 
     if let Some(start_time) = start_time {
@@ -148,68 +525,507 @@ fn main() {
     }
 }
 
+/// Extracts the components of an input file, `source` being the input path it was found under,
+/// and writes them to their own output file or to the aggregated one.
 fn extract_from_path(
     p: &PathBuf,
+    source: &Path,
+    output_dir: &Path,
+    output_kind: OKind,
+    aggregate: Option<&Mutex<Aggregate>>,
+) -> Result<Vec<Component>, Box<dyn Error>> {
+    let ext = p.extension().unwrap_or_default();
+    let file_name = p.file_name().unwrap().to_str().unwrap();
+    let output_path = |output_ext: &str| output_dir.join(format!("{file_name}.{output_ext}"));
+    let is_archive = ext == "jar" || ext == "jmod";
+
+    let entries = if is_archive {
+        let jar = extract_entries_from_jar(p)?;
+        jar.errors.iter().for_each(|x| eprintln!("{x}"));
+        jar.entries
+    } else {
+        let class_file = std::fs::read(p).unwrap();
+        let (_, c) = parse_class_file(&class_file).unwrap();
+
+        let comp = try_extract_component(
+            &c,
+            &ExtractorContext {
+                target_access_modifiers: AccessModifier::empty(),
+            },
+        )?;
+        let entry_path = p.strip_prefix(source).unwrap_or(Path::new(""));
+        vec![(entry_path.to_string_lossy().replace('\\', "/"), comp)]
+    };
+
+    if let Some(aggregate) = aggregate {
+        let components = entries
+            .into_iter()
+            .map(|(entry_path, component)| SourcedComponent {
+                source: source.display().to_string(),
+                entry_path,
+                component,
+            })
+            .collect::<Vec<_>>();
+        aggregate.lock().unwrap().write(&components)?;
+        return Ok(components.into_iter().map(|x| x.component).collect());
+    }
+
+    let components = entries.into_iter().map(|(_, x)| x).collect::<Vec<_>>();
+    match output_kind {
+        OKind::Json => {
+            let writer = File::create(output_path("json")).unwrap();
+            if is_archive {
+                serde_json::to_writer(writer, &components).unwrap();
+            } else {
+                serde_json::to_writer(writer, &components[0]).unwrap();
+            }
+        }
+        OKind::Proto(schema_version) => {
+            let snapshot =
+                encode_snapshot(&components, schema_version, p, &AccessModifier::empty())?;
+            std::fs::write(output_path("pb"), snapshot)?;
+        }
+        // Written to the aggregated output as each input is extracted
+        OKind::Ndjson | OKind::Sqlite => {}
+        // Written once for all inputs, so that member classes can be nested
+        OKind::JavaStubs | OKind::Rust | OKind::Dts | OKind::ProtoSchema => {}
+    }
+
+    Ok(components)
+}
+
+/// The single output file of `--single-file`, `--ndjson` and `--sqlite`, written as the inputs
+/// are extracted.
+enum Aggregate {
+    Json {
+        writer: BufWriter<File>,
+        empty: bool,
+    },
+    Ndjson(BufWriter<File>),
+    Sqlite(SqliteWriter),
+    Proto(SnapshotWriter<BufWriter<File>>),
+}
+
+impl Aggregate {
+    fn create(output_dir: &Path, output_kind: OKind) -> Result<Self, Box<dyn Error>> {
+        match output_kind {
+            OKind::Proto(schema_version) => {
+                let writer = BufWriter::new(File::create(output_dir.join("components.pb"))?);
+                Ok(Aggregate::Proto(SnapshotWriter::new(
+                    writer,
+                    schema_version,
+                    &AccessModifier::empty(),
+                )?))
+            }
+            OKind::Ndjson => {
+                let writer = BufWriter::new(File::create(output_dir.join("components.ndjson"))?);
+                Ok(Aggregate::Ndjson(writer))
+            }
+            _ => {
+                let mut writer = BufWriter::new(File::create(output_dir.join("components.json"))?);
+                writer.write_all(b"[")?;
+                Ok(Aggregate::Json {
+                    writer,
+                    empty: true,
+                })
+            }
+        }
+    }
+
+    fn write(&mut self, components: &[SourcedComponent]) -> Result<(), Box<dyn Error>> {
+        for component in components {
+            match self {
+                Aggregate::Json { writer, empty } => {
+                    if !*empty {
+                        writer.write_all(b",")?;
+                    }
+                    *empty = false;
+                    serde_json::to_writer(&mut *writer, component)?;
+                }
+                Aggregate::Ndjson(writer) => {
+                    serde_json::to_writer(&mut *writer, component)?;
+                    writer.write_all(b"\n")?;
+                }
+                Aggregate::Sqlite(writer) => writer.write(component)?,
+                Aggregate::Proto(writer) => writer.write(component)?,
+            }
+        }
+        // Lines are complete once an input is written, so readers can follow the file
+        if let Aggregate::Ndjson(writer) = self {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<(), Box<dyn Error>> {
+        let mut writer = match self {
+            Aggregate::Json { mut writer, .. } => {
+                writer.write_all(b"]")?;
+                writer
+            }
+            Aggregate::Ndjson(writer) => writer,
+            Aggregate::Sqlite(writer) => {
+                writer.finish()?;
+                return Ok(());
+            }
+            Aggregate::Proto(writer) => writer.into_inner(),
+        };
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+fn write_java_stubs(components: &[Component], output_dir: &Path) -> Result<(), Box<dyn Error>> {
+    for stub in java_stubs(components) {
+        let path = output_dir.join(&stub.path);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, stub.source)?;
+    }
+
+    Ok(())
+}
+
+fn write_proto_schema(
+    components: &[Component],
+    options: ProtoSchemaOptions,
+    output_dir: &Path,
+) -> Result<(), Box<dyn Error>> {
+    for file in proto_schema(components, options) {
+        let path = output_dir.join(&file.path);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, file.source)?;
+    }
+
+    Ok(())
+}
+
+fn write_hierarchy(
+    hierarchy: &TypeHierarchy,
     output_dir: &Path,
     output_kind: OKind,
 ) -> Result<(), Box<dyn Error>> {
-    let output_ext = match output_kind {
-        OKind::Json => "json",
-        OKind::Proto => "pb",
+    match output_kind {
+        OKind::Json
+        | OKind::Ndjson
+        | OKind::Sqlite
+        | OKind::JavaStubs
+        | OKind::Rust
+        | OKind::Dts
+        | OKind::ProtoSchema => {
+            let writer = File::create(output_dir.join("hierarchy.json"))?;
+            serde_json::to_writer(writer, hierarchy)?;
+        }
+        OKind::Proto(_) => {
+            let hierarchy: classreader::proto::component::TypeHierarchy = hierarchy.into();
+            let mut writer = File::create(output_dir.join("hierarchy.pb"))?;
+            writer.write_all(&hierarchy.encode_to_vec())?;
+        }
+    }
+
+    Ok(())
+}
+
+fn run_compat(args: CompatArgs) -> i32 {
+    let (old, new) = match (
+        extract_members_from_path(&args.old_path),
+        extract_members_from_path(&args.new_path),
+    ) {
+        (Ok(old), Ok(new)) => (old, new),
+        (Err(err), _) | (_, Err(err)) => {
+            println!("Error: {}", err);
+            return 2;
+        }
     };
 
-    let ext = p.extension().unwrap_or_default();
-    let file_name = p.file_name().unwrap().to_str().unwrap();
-    let output_path = output_dir.join(format!("{file_name}.{output_ext}",));
+    let incompatibilities = check_compatibility(&old, &new);
+    for incompatibility in &incompatibilities {
+        println!("{incompatibility}");
+    }
+
+    let binary_count = incompatibilities
+        .iter()
+        .filter(|x| x.severity == Severity::Binary)
+        .count();
+    let source_count = incompatibilities.len() - binary_count;
+    println!(
+        "{binary_count} binary-incompatible and {source_count} source-incompatible changes found"
+    );
+
+    if let Some(json_path) = &args.json {
+        let written = File::create(json_path)
+            .map_err(|e| e.to_string())
+            .and_then(|writer| {
+                serde_json::to_writer_pretty(writer, &incompatibilities).map_err(|e| e.to_string())
+            });
+        if let Err(err) = written {
+            println!("Error: {}", err);
+            return 2;
+        }
+    }
+
+    if binary_count > 0 || (args.fail_on_source && source_count > 0) {
+        1
+    } else {
+        0
+    }
+}
 
-    if ext == "jar" || ext == "jmod" {
-        let components = match extract_members_from_jar(p) {
-            Ok(c) => c,
+fn run_diff(args: DiffArgs) -> i32 {
+    let (old, new) = match (
+        extract_members_from_path(&args.old_path),
+        extract_members_from_path(&args.new_path),
+    ) {
+        (Ok(old), Ok(new)) => (old, new),
+        (Err(err), _) | (_, Err(err)) => {
+            println!("Error: {}", err);
+            return 2;
+        }
+    };
+
+    let diff = diff_components(&old, &new);
+    let report = match args.format {
+        DiffFormat::Markdown => diff.to_markdown(),
+        DiffFormat::Json => match serde_json::to_string_pretty(&diff) {
+            Ok(json) => json,
             Err(err) => {
-                return Err(err);
+                println!("Error: {}", err);
+                return 2;
             }
-        };
+        },
+    };
 
-        let mut writer = File::create(output_path).unwrap();
+    match &args.output_path {
+        Some(output_path) => {
+            if let Err(err) = std::fs::write(output_path, report) {
+                println!("Error: {}", err);
+                return 2;
+            }
+        }
+        None => print!("{report}"),
+    }
 
-        match output_kind {
-            OKind::Json => {
-                serde_json::to_writer(writer, &components).unwrap();
+    0
+}
+
+fn run_deps(args: DepsArgs) -> i32 {
+    let mut graph = DependencyGraph::default();
+    for path in &args.input_paths {
+        match read_class_files_from_path(path) {
+            Ok(read) => {
+                read.errors.iter().for_each(|x| eprintln!("{x}"));
+                read.class_files.iter().for_each(|x| graph.insert(x));
+            }
+            Err(err) => {
+                println!("Error: {}", err);
+                return 2;
             }
-            OKind::Proto => {
-                let component: crate::proto::component::ComponentList = (&components).into();
+        }
+    }
 
-                let mut encoded_buf = Vec::new();
-                component.encode(&mut encoded_buf).unwrap();
-                writer.write_all(&encoded_buf).unwrap();
+    let granularity = if args.classes {
+        Granularity::Class
+    } else {
+        Granularity::Package
+    };
+    let report = match args.format {
+        DepsFormat::Table => graph.to_table(granularity),
+        DepsFormat::Dot => graph.to_dot(granularity),
+        DepsFormat::Json => match serde_json::to_string_pretty(&graph) {
+            Ok(json) => json,
+            Err(err) => {
+                println!("Error: {}", err);
+                return 2;
+            }
+        },
+    };
+
+    match &args.output_path {
+        Some(output_path) => {
+            if let Err(err) = std::fs::write(output_path, report) {
+                println!("Error: {}", err);
+                return 2;
+            }
+        }
+        None => print!("{report}"),
+    }
+
+    0
+}
+
+fn run_usage(args: UsageArgs) -> i32 {
+    let mut class_files = Vec::new();
+    let mut service_providers = Vec::new();
+    for path in &args.input_paths {
+        let read = read_class_files_from_path(path).and_then(|x| {
+            x.errors.iter().for_each(|x| eprintln!("{x}"));
+            class_files.extend(x.class_files);
+            read_service_providers_from_path(path)
+        });
+        match read {
+            Ok(providers) => service_providers.extend(providers),
+            Err(err) => {
+                println!("Error: {}", err);
+                return 2;
+            }
+        }
+    }
+
+    let entry_points = EntryPoints {
+        main_classes: args.main_classes,
+        service_providers,
+        keep_annotations: args.keep_annotations,
+        keep: args.keep,
+    };
+    let report = match find_unused(&class_files, &entry_points) {
+        Ok(report) => report,
+        Err(err) => {
+            println!("Error: {}", err);
+            return 2;
+        }
+    };
+
+    let report = match args.format {
+        UsageFormat::Text => report.to_text(),
+        UsageFormat::Json => match serde_json::to_string_pretty(&report) {
+            Ok(json) => json,
+            Err(err) => {
+                println!("Error: {}", err);
+                return 2;
+            }
+        },
+    };
+
+    match &args.output_path {
+        Some(output_path) => {
+            if let Err(err) = std::fs::write(output_path, report) {
+                println!("Error: {}", err);
+                return 2;
+            }
+        }
+        None => print!("{report}"),
+    }
+
+    0
+}
+
+fn run_conflicts(args: ConflictsArgs) -> i32 {
+    let mut entries = Vec::new();
+    for path in &args.input_paths {
+        match read_classpath_element(path) {
+            Ok(element) => {
+                element.errors.iter().for_each(|x| eprintln!("{x}"));
+                entries.extend(element.entries);
             }
+            Err(err) => {
+                println!("Error: {}", err);
+                return 2;
+            }
+        }
+    }
+
+    let report = find_conflicts(&entries);
+    print!("{}", report.to_table());
+
+    if let Some(json_path) = &args.json {
+        let written = File::create(json_path)
+            .map_err(|e| e.to_string())
+            .and_then(|writer| {
+                serde_json::to_writer_pretty(writer, &report).map_err(|e| e.to_string())
+            });
+        if let Err(err) = written {
+            println!("Error: {}", err);
+            return 2;
         }
+    }
+
+    if report.is_empty() {
+        0
     } else {
-        let class_file = std::fs::read(p).unwrap();
-        let (_, c) = parse_class_file(&class_file).unwrap();
+        1
+    }
+}
 
-        let comp = extract_component(
-            &c,
-            &ExtractorContext {
-                target_access_modifiers: AccessModifier::empty(),
-            },
-        );
+fn run_versions(args: VersionsArgs) -> i32 {
+    let mut entries = Vec::new();
+    for path in &args.input_paths {
+        match read_classpath_element(path) {
+            Ok(element) => {
+                element.errors.iter().for_each(|x| eprintln!("{x}"));
+                entries.extend(element.entries);
+            }
+            Err(err) => {
+                println!("Error: {}", err);
+                return 2;
+            }
+        }
+    }
 
-        let mut writer = File::create(output_path).unwrap();
+    let summaries = summarize_releases(&entries, args.max_release);
+    print!("{}", releases_to_table(&summaries, args.max_release));
 
-        match output_kind {
-            OKind::Json => {
-                serde_json::to_writer(writer, &comp).unwrap();
+    if let Some(json_path) = &args.json {
+        let written = File::create(json_path)
+            .map_err(|e| e.to_string())
+            .and_then(|writer| {
+                serde_json::to_writer_pretty(writer, &summaries).map_err(|e| e.to_string())
+            });
+        if let Err(err) = written {
+            println!("Error: {}", err);
+            return 2;
+        }
+    }
+
+    if summaries.iter().all(|x| x.too_new.is_empty()) {
+        0
+    } else {
+        1
+    }
+}
+
+fn run_query(args: QueryArgs) -> i32 {
+    let query = match parse_query(&args.expression) {
+        Ok(query) => query,
+        Err(err) => {
+            println!("Error: {}", err);
+            return 2;
+        }
+    };
+    let mut components = Vec::new();
+    for path in &args.input_paths {
+        match extract_members_from_path(path) {
+            Ok(c) => components.extend(c),
+            Err(err) => {
+                println!("Error: {}", err);
+                return 2;
+            }
+        }
+    }
+
+    let result = query.run(&components);
+    let report = match args.format {
+        QueryFormat::Table => result.to_table(),
+        QueryFormat::Json => match serde_json::to_string_pretty(&result.to_json()) {
+            Ok(json) => json,
+            Err(err) => {
+                println!("Error: {}", err);
+                return 2;
             }
-            OKind::Proto => {
-                let component: crate::proto::component::Component = (&comp).into();
+        },
+    };
 
-                let mut encoded_buf = Vec::new();
-                component.encode(&mut encoded_buf).unwrap();
-                writer.write_all(&encoded_buf).unwrap();
+    match &args.output_path {
+        Some(output_path) => {
+            if let Err(err) = std::fs::write(output_path, report) {
+                println!("Error: {}", err);
+                return 2;
             }
         }
+        None => print!("{report}"),
     }
 
-    Ok(())
+    0
 }
@@ -0,0 +1,665 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::{
+    class_file::{FieldAccessFlags, MethodAccessFlags},
+    component::{Component, Field, Method, Ty},
+    hierarchy::TypeHierarchy,
+    signature::{
+        ArrayTypeSignature, ClassTypeSignature, FieldSignature, MethodSignature,
+        ReferenceTypeSignature, Result, SimpleClassTypeSignature, ThrowsSignature, TypeArgument,
+        TypeParameter, TypeSignature,
+    },
+};
+
+/// A set of components searchable by qualified name, used to resolve inherited members.
+pub struct ClassPath<'a> {
+    components: HashMap<&'a str, &'a Component>,
+    hierarchy: TypeHierarchy,
+}
+
+/// Every member of a type, including the inherited ones.
+#[derive(Debug, Clone, Serialize)]
+pub struct MemberSet {
+    pub qualified_name: String,
+    pub methods: Vec<InheritedMethod>,
+    pub fields: Vec<InheritedField>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InheritedMethod {
+    pub declaring_type: String,
+    /// The method with the type variables of the declaring type substituted
+    pub method: Method,
+    /// The declaring types of the methods this one overrides or hides
+    pub overridden: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InheritedField {
+    pub declaring_type: String,
+    /// The field with the type variables of the declaring type substituted
+    pub field: Field,
+    /// The declaring types of the fields this one hides
+    pub hidden: Vec<String>,
+}
+
+type Substitution = HashMap<String, TypeArgument>;
+
+impl<'a> ClassPath<'a> {
+    pub fn new(components: &'a [Component]) -> Self {
+        Self {
+            components: components
+                .iter()
+                .filter_map(|x| x.qualified_name().map(|name| (name, x)))
+                .collect(),
+            hierarchy: TypeHierarchy::new(components),
+        }
+    }
+
+    pub fn get(&self, qualified_name: &str) -> Option<&'a Component> {
+        self.components.get(qualified_name).copied()
+    }
+
+    pub fn hierarchy(&self) -> &TypeHierarchy {
+        &self.hierarchy
+    }
+
+    /// Returns the members of the raw type. Type variables of generic supertypes are replaced by
+    /// their bounds.
+    pub fn members_of(&self, qualified_name: &str) -> Option<MemberSet> {
+        self.resolve(qualified_name, None)
+    }
+
+    /// Returns the members of a parameterized type such as `java.util.ArrayList<String>`,
+    /// with the type arguments substituted through every generic supertype.
+    pub fn members(&self, ty: &ClassTypeSignature) -> Option<MemberSet> {
+        self.resolve(&ty.qualified_name(), ty.type_arguments())
+    }
+
+    fn resolve(
+        &self,
+        qualified_name: &str,
+        type_arguments: Option<&Vec<TypeArgument>>,
+    ) -> Option<MemberSet> {
+        let component = self.get(qualified_name)?;
+
+        let mut substitutions = HashMap::new();
+        let substitution = bind(component, type_arguments);
+        substitutions.insert(qualified_name.to_string(), substitution.clone());
+        self.collect_substitutions(component, &substitution, &mut substitutions);
+
+        let package = package_of(qualified_name);
+        let mut methods: Vec<InheritedMethod> = Vec::new();
+        let mut method_keys: HashMap<String, usize> = HashMap::new();
+        let mut fields: Vec<InheritedField> = Vec::new();
+        let mut field_keys: HashMap<String, usize> = HashMap::new();
+
+        for ty in self.hierarchy.method_resolution_order(qualified_name) {
+            let Some(declaring) = self.get(ty) else {
+                continue;
+            };
+            let is_self = ty == qualified_name;
+            let same_package = package_of(ty) == package;
+            let empty = Substitution::new();
+            let substitution = substitutions.get(ty).unwrap_or(&empty);
+
+            for method in declaring.methods() {
+                let flags = method.access_flags;
+                if flags.intersects(MethodAccessFlags::BRIDGE | MethodAccessFlags::SYNTHETIC) {
+                    continue;
+                }
+                if !is_self
+                    && (method.name == "<init>"
+                        || method.name == "<clinit>"
+                        || flags.contains(MethodAccessFlags::PRIVATE)
+                        || (declaring.is_interface() && flags.contains(MethodAccessFlags::STATIC))
+                        || (!same_package && is_package_private_method(flags)))
+                {
+                    continue;
+                }
+
+                let key = override_key(method);
+                let method = substitute_method(method, substitution);
+                if let Some(idx) = method_keys.get(&key) {
+                    methods[*idx].overridden.push(ty.to_string());
+                } else {
+                    method_keys.insert(key, methods.len());
+                    methods.push(InheritedMethod {
+                        declaring_type: ty.to_string(),
+                        method,
+                        overridden: Vec::new(),
+                    });
+                }
+            }
+
+            // a bridge carries the erasure of the supertype method its target overrides, e.g.
+            // `compareTo(Object)` for `compareTo(String)` in a `Comparable<String>`
+            for bridge in declaring.methods() {
+                if !bridge.access_flags.contains(MethodAccessFlags::BRIDGE) {
+                    continue;
+                }
+                let mut targets = methods.iter().enumerate().filter(|(_, x)| {
+                    x.declaring_type == ty
+                        && x.method.name == bridge.name
+                        && x.method.param_tys.len() == bridge.param_tys.len()
+                });
+                if let (Some((idx, _)), None) = (targets.next(), targets.next()) {
+                    method_keys.entry(override_key(bridge)).or_insert(idx);
+                }
+            }
+
+            for field in declaring.fields() {
+                let flags = field.access_flags;
+                if !is_self
+                    && (flags.contains(FieldAccessFlags::PRIVATE)
+                        || (!same_package && is_package_private_field(flags)))
+                {
+                    continue;
+                }
+
+                if let Some(idx) = field_keys.get(&field.name) {
+                    fields[*idx].hidden.push(ty.to_string());
+                } else {
+                    field_keys.insert(field.name.clone(), fields.len());
+                    fields.push(InheritedField {
+                        declaring_type: ty.to_string(),
+                        field: substitute_field(field, substitution),
+                        hidden: Vec::new(),
+                    });
+                }
+            }
+        }
+
+        Some(MemberSet {
+            qualified_name: qualified_name.to_string(),
+            methods,
+            fields,
+        })
+    }
+
+    fn collect_substitutions(
+        &self,
+        component: &Component,
+        substitution: &Substitution,
+        substitutions: &mut HashMap<String, Substitution>,
+    ) {
+        let super_types: Vec<(String, Option<Vec<TypeArgument>>)> =
+            if let Some(sig) = component.signature() {
+                std::iter::once(&sig.superclass_signature)
+                    .chain(sig.superinterface_signatures.iter())
+                    .map(|x| {
+                        let x = substitute_class(x, substitution);
+                        (x.qualified_name(), x.type_arguments().cloned())
+                    })
+                    .collect()
+            } else {
+                self.hierarchy
+                    .direct_supertypes(component.qualified_name().unwrap_or_default())
+                    .into_iter()
+                    .map(|x| (x.to_string(), None))
+                    .collect()
+            };
+
+        for (name, type_arguments) in super_types {
+            if substitutions.contains_key(&name) {
+                continue;
+            }
+            let Some(super_component) = self.get(&name) else {
+                continue;
+            };
+            // marked before recursing so a cyclic (invalid) hierarchy terminates
+            let super_substitution = bind(super_component, type_arguments.as_ref());
+            substitutions.insert(name, super_substitution.clone());
+            self.collect_substitutions(super_component, &super_substitution, substitutions);
+        }
+    }
+}
+
+fn package_of(qualified_name: &str) -> &str {
+    qualified_name
+        .rsplit_once('.')
+        .map(|(package, _)| package)
+        .unwrap_or_default()
+}
+
+fn is_package_private_method(flags: MethodAccessFlags) -> bool {
+    !flags.intersects(
+        MethodAccessFlags::PUBLIC | MethodAccessFlags::PROTECTED | MethodAccessFlags::PRIVATE,
+    )
+}
+
+fn is_package_private_field(flags: FieldAccessFlags) -> bool {
+    !flags.intersects(
+        FieldAccessFlags::PUBLIC | FieldAccessFlags::PROTECTED | FieldAccessFlags::PRIVATE,
+    )
+}
+
+fn object_type() -> ClassTypeSignature {
+    ClassTypeSignature {
+        package_specifier: Some("java.lang".to_string()),
+        simple_class_type_signature: SimpleClassTypeSignature {
+            identifier: "Object".to_string(),
+            type_arguments: None,
+        },
+        class_type_signature_suffixes: Vec::new(),
+    }
+}
+
+fn erased_bound(type_parameter: &TypeParameter) -> ClassTypeSignature {
+    match type_parameter
+        .class_bound
+        .as_ref()
+        .or(type_parameter.interface_bounds.first())
+    {
+        Some(ReferenceTypeSignature::Class(c)) => ClassTypeSignature {
+            package_specifier: c.package_specifier.clone(),
+            simple_class_type_signature: SimpleClassTypeSignature {
+                identifier: c.simple_class_type_signature.identifier.clone(),
+                type_arguments: None,
+            },
+            class_type_signature_suffixes: c
+                .class_type_signature_suffixes
+                .iter()
+                .map(|x| SimpleClassTypeSignature {
+                    identifier: x.identifier.clone(),
+                    type_arguments: None,
+                })
+                .collect(),
+        },
+        _ => object_type(),
+    }
+}
+
+/// Binds the type parameters of a component to the given arguments. A raw reference binds
+/// every type parameter to its erased bound.
+pub(crate) fn bind(
+    component: &Component,
+    type_arguments: Option<&Vec<TypeArgument>>,
+) -> Substitution {
+    let Some(type_parameters) = component
+        .signature()
+        .and_then(|x| x.type_parameters.as_ref())
+    else {
+        return Substitution::new();
+    };
+
+    match type_arguments {
+        Some(args) if args.len() == type_parameters.len() => type_parameters
+            .iter()
+            .zip(args.iter())
+            .map(|(param, arg)| (param.identifier.clone(), arg.clone()))
+            .collect(),
+        _ => type_parameters
+            .iter()
+            .map(|param| {
+                (
+                    param.identifier.clone(),
+                    TypeArgument::ReferenceType(
+                        None,
+                        ReferenceTypeSignature::Class(erased_bound(param)),
+                    ),
+                )
+            })
+            .collect(),
+    }
+}
+
+fn substitute_reference(
+    ty: &ReferenceTypeSignature,
+    substitution: &Substitution,
+) -> ReferenceTypeSignature {
+    match ty {
+        ReferenceTypeSignature::TypeVariable(v) => match substitution.get(&v.identifier) {
+            Some(TypeArgument::ReferenceType(_, r)) => r.clone(),
+            Some(TypeArgument::Any) => ReferenceTypeSignature::Class(object_type()),
+            None => ty.clone(),
+        },
+        ReferenceTypeSignature::Class(c) => {
+            ReferenceTypeSignature::Class(substitute_class(c, substitution))
+        }
+        ReferenceTypeSignature::Array(a) => ReferenceTypeSignature::Array(ArrayTypeSignature {
+            java_type_signature: Box::new(substitute_type(&a.java_type_signature, substitution)),
+        }),
+    }
+}
+
+fn substitute_class(ty: &ClassTypeSignature, substitution: &Substitution) -> ClassTypeSignature {
+    let substitute_simple = |x: &SimpleClassTypeSignature| SimpleClassTypeSignature {
+        identifier: x.identifier.clone(),
+        type_arguments: x.type_arguments.as_ref().map(|args| {
+            args.iter()
+                .map(|arg| substitute_argument(arg, substitution))
+                .collect()
+        }),
+    };
+
+    ClassTypeSignature {
+        package_specifier: ty.package_specifier.clone(),
+        simple_class_type_signature: substitute_simple(&ty.simple_class_type_signature),
+        class_type_signature_suffixes: ty
+            .class_type_signature_suffixes
+            .iter()
+            .map(substitute_simple)
+            .collect(),
+    }
+}
+
+fn substitute_argument(arg: &TypeArgument, substitution: &Substitution) -> TypeArgument {
+    match arg {
+        TypeArgument::Any => TypeArgument::Any,
+        TypeArgument::ReferenceType(wildcard, ReferenceTypeSignature::TypeVariable(v)) => {
+            match (wildcard, substitution.get(&v.identifier)) {
+                (None, Some(bound)) => bound.clone(),
+                (Some(_), Some(TypeArgument::ReferenceType(_, r))) => {
+                    TypeArgument::ReferenceType(wildcard.clone(), r.clone())
+                }
+                (Some(_), Some(TypeArgument::Any)) => TypeArgument::ReferenceType(
+                    wildcard.clone(),
+                    ReferenceTypeSignature::Class(object_type()),
+                ),
+                (_, None) => arg.clone(),
+            }
+        }
+        TypeArgument::ReferenceType(wildcard, r) => {
+            TypeArgument::ReferenceType(wildcard.clone(), substitute_reference(r, substitution))
+        }
+    }
+}
+
+fn substitute_type(ty: &TypeSignature, substitution: &Substitution) -> TypeSignature {
+    match ty {
+        TypeSignature::Base(_) => ty.clone(),
+        TypeSignature::Reference(r) => {
+            TypeSignature::Reference(substitute_reference(r, substitution))
+        }
+    }
+}
+
+pub(crate) fn substitute_method(method: &Method, substitution: &Substitution) -> Method {
+    let Some(sig) = &method.signature else {
+        return method.clone();
+    };
+    if substitution.is_empty() {
+        return method.clone();
+    }
+
+    // type parameters of the method shadow those of the declaring type
+    let mut substitution = substitution.clone();
+    for type_parameter in sig.type_parameters.iter().flatten() {
+        substitution.remove(&type_parameter.identifier);
+    }
+
+    let sig = MethodSignature {
+        type_parameters: sig.type_parameters.as_ref().map(|params| {
+            params
+                .iter()
+                .map(|param| TypeParameter {
+                    identifier: param.identifier.clone(),
+                    class_bound: param
+                        .class_bound
+                        .as_ref()
+                        .map(|x| substitute_reference(x, &substitution)),
+                    interface_bounds: param
+                        .interface_bounds
+                        .iter()
+                        .map(|x| substitute_reference(x, &substitution))
+                        .collect(),
+                })
+                .collect()
+        }),
+        parameters: sig
+            .parameters
+            .iter()
+            .map(|x| substitute_type(x, &substitution))
+            .collect(),
+        result: match &sig.result {
+            Result::JavaTypeSignature(ty) => {
+                Result::JavaTypeSignature(substitute_type(ty, &substitution))
+            }
+            Result::VoidDescriptor => Result::VoidDescriptor,
+        },
+        throws_signatures: sig
+            .throws_signatures
+            .iter()
+            .map(|x| match x {
+                ThrowsSignature::ClassTypeSignature(c) => {
+                    ThrowsSignature::ClassTypeSignature(substitute_class(c, &substitution))
+                }
+                ThrowsSignature::TypeVariableSignature(v) => {
+                    match substitution.get(&v.identifier) {
+                        Some(TypeArgument::ReferenceType(_, ReferenceTypeSignature::Class(c))) => {
+                            ThrowsSignature::ClassTypeSignature(c.clone())
+                        }
+                        _ => x.clone(),
+                    }
+                }
+            })
+            .collect(),
+    };
+
+    Method {
+        param_tys: sig.parameters.iter().map(|x| x.into()).collect(),
+        ret_ty: match &sig.result {
+            Result::JavaTypeSignature(ty) => ty.into(),
+            Result::VoidDescriptor => Ty::Void,
+        },
+        signature: Some(sig),
+        ..method.clone()
+    }
+}
+
+fn substitute_field(field: &Field, substitution: &Substitution) -> Field {
+    let Some(sig) = &field.signature else {
+        return field.clone();
+    };
+
+    let sig = FieldSignature {
+        reference_type_signature: substitute_reference(&sig.reference_type_signature, substitution),
+    };
+    Field {
+        ty: (&sig).into(),
+        signature: Some(sig),
+        ..field.clone()
+    }
+}
+
+/// Returns the name and erased parameter types of the declared method. Overloads which only
+/// coincide after substitution, such as `m(T)` and `m(String)` on `Foo<String>`, stay apart.
+fn override_key(method: &Method) -> String {
+    let params = method
+        .descriptor
+        .split_once(')')
+        .map(|(params, _)| params)
+        .unwrap_or_default();
+    format!("{}{params})", method.name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        component::{Class, ComponentKind, Interface},
+        signature::{parse_class_signature, parse_type_signature},
+        test_util::{self, component, method},
+    };
+
+    fn components() -> Vec<Component> {
+        vec![
+            component(ComponentKind::Interface(Interface {
+                signature: Some(
+                    parse_class_signature("<E:Ljava/lang/Object;>Ljava/lang/Object;")
+                        .unwrap()
+                        .1,
+                ),
+                methods: vec![
+                    method("get", "(I)Ljava/lang/Object;", Some("(I)TE;"), 0x0401),
+                    method("add", "(Ljava/lang/Object;)Z", Some("(TE;)Z"), 0x0401),
+                    method("isEmpty", "()Z", None, 0x0001),
+                    method("of", "()La/List;", None, 0x0009),
+                ],
+                ..test_util::interface("a.List")
+            })),
+            component(ComponentKind::Class(Class {
+                interfaces: vec!["a.List".to_string()],
+                signature: Some(
+                    parse_class_signature("<T:Ljava/lang/Object;>Ljava/lang/Object;La/List<TT;>;")
+                        .unwrap()
+                        .1,
+                ),
+                methods: vec![
+                    method("<init>", "()V", None, 0x0001),
+                    method("add", "(Ljava/lang/Object;)Z", Some("(TT;)Z"), 0x0001),
+                    method("helper", "()V", None, 0x0002),
+                ],
+                is_abstract: true,
+                ..test_util::class("a.AbstractList")
+            })),
+            component(ComponentKind::Class(Class {
+                super_class: Some("a.AbstractList".to_string()),
+                signature: Some(
+                    parse_class_signature("<U:Ljava/lang/Object;>La/AbstractList<TU;>;")
+                        .unwrap()
+                        .1,
+                ),
+                methods: vec![
+                    method("<init>", "()V", None, 0x0001),
+                    method("get", "(I)Ljava/lang/Object;", Some("(I)TU;"), 0x0001),
+                    method("get", "(I)Ljava/lang/Object;", None, 0x1041),
+                ],
+                ..test_util::class("a.ArrayList")
+            })),
+        ]
+    }
+
+    #[test]
+    fn test_members_with_substitution() {
+        let components = components();
+        let class_path = ClassPath::new(&components);
+        let TypeSignature::Reference(ReferenceTypeSignature::Class(ty)) =
+            parse_type_signature("La/ArrayList<Ljava/lang/String;>;")
+                .unwrap()
+                .1
+        else {
+            unreachable!()
+        };
+
+        let members = class_path.members(&ty).unwrap();
+        let names = members
+            .methods
+            .iter()
+            .map(|x| (x.declaring_type.as_str(), x.method.name.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                ("a.ArrayList", "<init>"),
+                ("a.ArrayList", "get"),
+                ("a.AbstractList", "add"),
+                ("a.List", "isEmpty"),
+            ]
+        );
+
+        let get = &members.methods[1];
+        assert_eq!(get.overridden, vec!["a.List"]);
+        let Ty::Reference(ret) = &get.method.ret_ty else {
+            panic!("unexpected return type: {:?}", get.method.ret_ty)
+        };
+        assert_eq!(ret.name, "String");
+
+        let add = &members.methods[2];
+        assert_eq!(add.overridden, vec!["a.List"]);
+        let Ty::Reference(param) = &add.method.param_tys[0] else {
+            panic!("unexpected parameter type: {:?}", add.method.param_tys[0])
+        };
+        assert_eq!(param.name, "String");
+    }
+
+    #[test]
+    fn test_members_of_raw_type() {
+        let components = components();
+        let class_path = ClassPath::new(&components);
+        let members = class_path.members_of("a.ArrayList").unwrap();
+        let Ty::Reference(ret) = &members.methods[1].method.ret_ty else {
+            panic!("unexpected return type")
+        };
+        assert_eq!(ret.name, "Object");
+    }
+
+    #[test]
+    fn test_override_keys_use_declared_descriptors() {
+        let class = |name: &str, interfaces: &[&str], signature: &str, methods: Vec<Method>| {
+            component(ComponentKind::Class(Class {
+                interfaces: interfaces.iter().map(|x| x.to_string()).collect(),
+                signature: Some(parse_class_signature(signature).unwrap().1),
+                methods,
+                ..test_util::class(name)
+            }))
+        };
+        let mut components = vec![
+            class(
+                "a.Foo",
+                &[],
+                "<T:Ljava/lang/Object;>Ljava/lang/Object;",
+                vec![
+                    method("m", "(Ljava/lang/Object;)V", Some("(TT;)V"), 0x0001),
+                    method("m", "(Ljava/lang/String;)V", None, 0x0001),
+                ],
+            ),
+            class(
+                "a.Name",
+                &["a.Cmp"],
+                "Ljava/lang/Object;La/Cmp<La/Name;>;",
+                vec![
+                    method("compareTo", "(La/Name;)I", None, 0x0001),
+                    method("compareTo", "(Ljava/lang/Object;)I", None, 0x1041),
+                ],
+            ),
+        ];
+        components.push(component(ComponentKind::Interface(Interface {
+            signature: Some(
+                parse_class_signature("<T:Ljava/lang/Object;>Ljava/lang/Object;")
+                    .unwrap()
+                    .1,
+            ),
+            methods: vec![method(
+                "compareTo",
+                "(Ljava/lang/Object;)I",
+                Some("(TT;)I"),
+                0x0401,
+            )],
+            ..test_util::interface("a.Cmp")
+        })));
+        let class_path = ClassPath::new(&components);
+
+        let TypeSignature::Reference(ReferenceTypeSignature::Class(ty)) =
+            parse_type_signature("La/Foo<Ljava/lang/String;>;")
+                .unwrap()
+                .1
+        else {
+            unreachable!()
+        };
+        let members = class_path.members(&ty).unwrap();
+        assert_eq!(members.methods.len(), 2);
+
+        let members = class_path.members_of("a.Name").unwrap();
+        assert_eq!(members.methods.len(), 1);
+        assert_eq!(members.methods[0].overridden, vec!["a.Cmp"]);
+    }
+
+    #[test]
+    fn test_cyclic_supertypes() {
+        let class = |name: &str, super_class: &str| {
+            component(ComponentKind::Class(Class {
+                super_class: Some(super_class.to_string()),
+                methods: vec![method("m", "()V", None, 0x0001)],
+                ..test_util::class(name)
+            }))
+        };
+        let components = vec![class("q.A", "q.B"), class("q.B", "q.A")];
+        let class_path = ClassPath::new(&components);
+        let members = class_path.members_of("q.A").unwrap();
+        assert_eq!(members.methods[0].overridden, vec!["q.B"]);
+    }
+}
@@ -0,0 +1,640 @@
+use std::fmt::{self, Display, Formatter};
+
+use crate::{
+    class_file::MethodAccessFlags,
+    component::{
+        class_modifiers, Annotation, AnnotationKind, AnnotationValue, Component, ComponentKind,
+        Field, Method, Ty,
+    },
+    java_stubs::{
+        base_name, char_literal, constant, double_literal, float_literal, prim_name, string_literal,
+    },
+    signature::{
+        ClassSignature, ClassTypeSignature, FieldSignature, MethodSignature,
+        ReferenceTypeSignature, Result as SignatureResult, SimpleClassTypeSignature,
+        ThrowsSignature, TypeArgument, TypeParameter, TypeSignature, WildcardIndicator,
+    },
+};
+
+const JAVA_LANG_OBJECT: &str = "java.lang.Object";
+
+/// How [`PrettyPrint`] renders Java source text. `Display` uses the default options.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PrettyOptions {
+    /// Whether classes are named without their package, e.g. `Map.Entry` instead of
+    /// `java.util.Map.Entry`
+    pub simple_names: bool,
+    /// Whether declarations start with their annotations
+    pub annotations: bool,
+}
+
+/// Renders types, signatures and declarations as in Java source, e.g.
+/// `public <R, S extends List & AA> int add(int arg0, int arg1) throws IOException`.
+/// Parameters are named by position, as class files seldom keep their names.
+pub trait PrettyPrint {
+    fn fmt_pretty(&self, options: &PrettyOptions, f: &mut Formatter<'_>) -> fmt::Result;
+
+    fn pretty(&self, options: PrettyOptions) -> Pretty<'_, Self> {
+        Pretty {
+            value: self,
+            options,
+        }
+    }
+}
+
+/// A value displayed with [`PrettyPrint::fmt_pretty`].
+pub struct Pretty<'a, T: ?Sized> {
+    value: &'a T,
+    options: PrettyOptions,
+}
+
+impl<T: PrettyPrint + ?Sized> Display for Pretty<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.value.fmt_pretty(&self.options, f)
+    }
+}
+
+macro_rules! display_pretty {
+    ($($ty:ty),*) => {
+        $(
+            impl Display for $ty {
+                fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                    self.fmt_pretty(&PrettyOptions::default(), f)
+                }
+            }
+        )*
+    };
+}
+
+display_pretty!(
+    Ty,
+    TypeSignature,
+    ReferenceTypeSignature,
+    ClassTypeSignature,
+    TypeArgument,
+    TypeParameter,
+    ClassSignature,
+    MethodSignature,
+    FieldSignature,
+    Annotation,
+    AnnotationValue,
+    Field,
+    Method,
+    Component
+);
+
+impl PrettyPrint for Ty {
+    fn fmt_pretty(&self, options: &PrettyOptions, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Ty::Prim(prim) => f.write_str(prim_name(prim)),
+            Ty::Reference(name) => match &name.package_name {
+                Some(package) if !options.simple_names => {
+                    write!(f, "{package}.{}", source_name(&name.name))
+                }
+                _ => f.write_str(&source_name(&name.name)),
+            },
+            Ty::TyVar(name) => f.write_str(name),
+            Ty::Array(ty, dimensions) => {
+                write!(f, "{}{}", ty.pretty(*options), "[]".repeat(*dimensions))
+            }
+            Ty::Void => f.write_str("void"),
+        }
+    }
+}
+
+impl PrettyPrint for TypeSignature {
+    fn fmt_pretty(&self, options: &PrettyOptions, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeSignature::Base(base) => f.write_str(base_name(base)),
+            TypeSignature::Reference(x) => x.fmt_pretty(options, f),
+        }
+    }
+}
+
+impl PrettyPrint for ReferenceTypeSignature {
+    fn fmt_pretty(&self, options: &PrettyOptions, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ReferenceTypeSignature::TypeVariable(x) => f.write_str(&x.identifier),
+            ReferenceTypeSignature::Class(x) => x.fmt_pretty(options, f),
+            ReferenceTypeSignature::Array(x) => {
+                write!(f, "{}[]", x.java_type_signature.pretty(*options))
+            }
+        }
+    }
+}
+
+impl PrettyPrint for ClassTypeSignature {
+    fn fmt_pretty(&self, options: &PrettyOptions, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(package) = self.package_specifier.as_ref() {
+            if !options.simple_names {
+                write!(f, "{package}.")?;
+            }
+        }
+        let segment = |x: &SimpleClassTypeSignature, f: &mut Formatter<'_>| {
+            f.write_str(&source_name(&x.identifier))?;
+            if let Some(type_arguments) = &x.type_arguments {
+                f.write_str("<")?;
+                comma_separated(type_arguments, options, f)?;
+                f.write_str(">")?;
+            }
+            Ok(())
+        };
+        segment(&self.simple_class_type_signature, f)?;
+        for x in &self.class_type_signature_suffixes {
+            f.write_str(".")?;
+            segment(x, f)?;
+        }
+        Ok(())
+    }
+}
+
+impl PrettyPrint for TypeArgument {
+    fn fmt_pretty(&self, options: &PrettyOptions, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeArgument::Any => f.write_str("?"),
+            TypeArgument::ReferenceType(None, x) => x.fmt_pretty(options, f),
+            TypeArgument::ReferenceType(Some(WildcardIndicator::Plus), x) => {
+                write!(f, "? extends {}", x.pretty(*options))
+            }
+            TypeArgument::ReferenceType(Some(WildcardIndicator::Minus), x) => {
+                write!(f, "? super {}", x.pretty(*options))
+            }
+        }
+    }
+}
+
+impl PrettyPrint for TypeParameter {
+    fn fmt_pretty(&self, options: &PrettyOptions, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.identifier)?;
+        let bounds = self
+            .class_bound
+            .iter()
+            .chain(&self.interface_bounds)
+            .filter(|x| !is_object(x))
+            .collect::<Vec<_>>();
+        for (i, bound) in bounds.iter().enumerate() {
+            f.write_str(if i == 0 { " extends " } else { " & " })?;
+            bound.fmt_pretty(options, f)?;
+        }
+        Ok(())
+    }
+}
+
+/// `<T> extends Superclass implements Interface`, leaving out `extends java.lang.Object`.
+impl PrettyPrint for ClassSignature {
+    fn fmt_pretty(&self, options: &PrettyOptions, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut clauses = Vec::new();
+        if let Some(type_parameters) = self.type_parameters.as_ref().filter(|x| !x.is_empty()) {
+            clauses.push(type_parameters_string(type_parameters, options));
+        }
+        let superclass = &self.superclass_signature;
+        if !is_object_class(superclass) {
+            clauses.push(format!("extends {}", superclass.pretty(*options)));
+        }
+        if !self.superinterface_signatures.is_empty() {
+            clauses.push(format!(
+                "implements {}",
+                comma_separated_string(&self.superinterface_signatures, options)
+            ));
+        }
+        f.write_str(&clauses.join(" "))
+    }
+}
+
+/// `<T> T(java.lang.Class<T>, int) throws java.io.IOException`, the type of a method.
+impl PrettyPrint for MethodSignature {
+    fn fmt_pretty(&self, options: &PrettyOptions, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(type_parameters) = self.type_parameters.as_ref().filter(|x| !x.is_empty()) {
+            write!(f, "{} ", type_parameters_string(type_parameters, options))?;
+        }
+        match &self.result {
+            SignatureResult::JavaTypeSignature(x) => x.fmt_pretty(options, f)?,
+            SignatureResult::VoidDescriptor => f.write_str("void")?,
+        }
+        f.write_str("(")?;
+        comma_separated(&self.parameters, options, f)?;
+        f.write_str(")")?;
+        let throws = throws_strings(&self.throws_signatures, options);
+        if !throws.is_empty() {
+            write!(f, " throws {}", throws.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl PrettyPrint for FieldSignature {
+    fn fmt_pretty(&self, options: &PrettyOptions, f: &mut Formatter<'_>) -> fmt::Result {
+        self.reference_type_signature.fmt_pretty(options, f)
+    }
+}
+
+impl PrettyPrint for Annotation {
+    fn fmt_pretty(&self, options: &PrettyOptions, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "@{}", self.ty.pretty(*options))?;
+        match self.elements.as_slice() {
+            [] => Ok(()),
+            [x] if x.name == "value" => write!(f, "({})", x.value.pretty(*options)),
+            elements => {
+                let elements = elements
+                    .iter()
+                    .map(|x| format!("{} = {}", x.name, x.value.pretty(*options)))
+                    .collect::<Vec<_>>();
+                write!(f, "({})", elements.join(", "))
+            }
+        }
+    }
+}
+
+impl PrettyPrint for AnnotationValue {
+    fn fmt_pretty(&self, options: &PrettyOptions, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            AnnotationValue::Byte(x) => write!(f, "(byte) {x}"),
+            AnnotationValue::Char(x) => f.write_str(&char_literal(*x)),
+            AnnotationValue::Short(x) => write!(f, "(short) {x}"),
+            AnnotationValue::Int(x) => write!(f, "{x}"),
+            AnnotationValue::Long(x) => write!(f, "{x}L"),
+            AnnotationValue::Float(x) => f.write_str(&float_literal(*x)),
+            AnnotationValue::Double(x) => f.write_str(&double_literal(*x)),
+            AnnotationValue::Boolean(x) => write!(f, "{x}"),
+            AnnotationValue::String(x) => f.write_str(&string_literal(x)),
+            AnnotationValue::Enum { ty, name } => write!(f, "{}.{name}", ty.pretty(*options)),
+            AnnotationValue::Class(ty) => write!(f, "{}.class", ty.pretty(*options)),
+            AnnotationValue::Annotation(x) => x.fmt_pretty(options, f),
+            AnnotationValue::Array(values) => {
+                f.write_str("{")?;
+                comma_separated(values, options, f)?;
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+/// `private static final long serialVersionUID = 1L`, with the constant value if any.
+impl PrettyPrint for Field {
+    fn fmt_pretty(&self, options: &PrettyOptions, f: &mut Formatter<'_>) -> fmt::Result {
+        declaration_annotations(&self.annotations, options, " ", f)?;
+        if !self.modifiers.is_empty() {
+            write!(f, "{} ", self.modifiers)?;
+        }
+        match &self.signature {
+            Some(signature) => signature.fmt_pretty(options, f)?,
+            None => self.ty.fmt_pretty(options, f)?,
+        }
+        write!(f, " {}", self.name)?;
+        if let Some(value) = &self.constant_value {
+            write!(f, " = {}", constant(value, &self.ty))?;
+        }
+        Ok(())
+    }
+}
+
+/// A method declaration without body. Constructors keep their `<init>` name, as methods do not
+/// know their class, except when rendered with their [`Component`].
+impl PrettyPrint for Method {
+    fn fmt_pretty(&self, options: &PrettyOptions, f: &mut Formatter<'_>) -> fmt::Result {
+        method(self, None, options, f)
+    }
+}
+
+/// A class declaration with its fields and methods, each on its own line, as `javap` lists
+/// them.
+impl PrettyPrint for Component {
+    fn fmt_pretty(&self, options: &PrettyOptions, f: &mut Formatter<'_>) -> fmt::Result {
+        let (name, is_enum, is_annotation) = match &self.kind {
+            ComponentKind::Module(module) => return write!(f, "module {}", module.name),
+            ComponentKind::Class(x) => (&x.qualified_name, x.is_enum, false),
+            ComponentKind::Interface(x) => (&x.qualified_name, false, x.is_annotation),
+        };
+        let kind = match &self.kind {
+            _ if is_enum => "enum",
+            _ if is_annotation => "@interface",
+            ComponentKind::Interface(_) => "interface",
+            _ => "class",
+        };
+
+        declaration_annotations(self.annotations(), options, "\n", f)?;
+        // Enums are implicitly final, or abstract when their constants have bodies
+        let modifiers = class_modifiers(self.access_flags());
+        let modifiers = modifiers
+            .split(' ')
+            .filter(|x| !x.is_empty() && !(is_enum && (*x == "final" || *x == "abstract")));
+        for modifier in modifiers {
+            write!(f, "{modifier} ")?;
+        }
+        let source_name = match name.rsplit_once('.') {
+            Some((package, name)) if !options.simple_names => {
+                format!("{package}.{}", source_name(name))
+            }
+            Some((_, name)) => source_name(name),
+            None => source_name(name),
+        };
+        write!(f, "{kind} {source_name}")?;
+
+        match self.signature() {
+            Some(signature) => {
+                if let Some(type_parameters) =
+                    signature.type_parameters.as_ref().filter(|x| !x.is_empty())
+                {
+                    f.write_str(&type_parameters_string(type_parameters, options))?;
+                }
+                let superclass = &signature.superclass_signature;
+                if !self.is_interface() && !is_enum && !is_object_class(superclass) {
+                    write!(f, " extends {}", superclass.pretty(*options))?;
+                }
+                let interfaces = &signature.superinterface_signatures;
+                if !interfaces.is_empty() {
+                    let interfaces = comma_separated_string(interfaces, options);
+                    write!(f, " {} {interfaces}", implements_keyword(self))?;
+                }
+            }
+            None => {
+                if let Some(superclass) = self
+                    .super_class()
+                    .filter(|x| !is_enum && *x != JAVA_LANG_OBJECT && !self.is_interface())
+                {
+                    write!(f, " extends {}", class_name(superclass, options))?;
+                }
+                let interfaces = self
+                    .interfaces()
+                    .iter()
+                    .filter(|x| !(is_annotation && *x == "java.lang.annotation.Annotation"))
+                    .map(|x| class_name(x, options))
+                    .collect::<Vec<_>>();
+                if !interfaces.is_empty() {
+                    write!(f, " {} {}", implements_keyword(self), interfaces.join(", "))?;
+                }
+            }
+        }
+
+        f.write_str(" {\n")?;
+        let simple_name = source_name.rsplit('.').next().unwrap_or_default();
+        for field in self.fields() {
+            writeln!(f, "    {};", field.pretty(*options))?;
+        }
+        for x in self.methods() {
+            f.write_str("    ")?;
+            method(x, Some(simple_name), options, f)?;
+            f.write_str(";\n")?;
+        }
+        f.write_str("}")
+    }
+}
+
+fn method(
+    method: &Method,
+    constructor_name: Option<&str>,
+    options: &PrettyOptions,
+    f: &mut Formatter<'_>,
+) -> fmt::Result {
+    if method.name == "<clinit>" {
+        return f.write_str("static {}");
+    }
+    declaration_annotations(&method.annotations, options, " ", f)?;
+    if !method.modifiers.is_empty() {
+        write!(f, "{} ", method.modifiers)?;
+    }
+
+    let signature = method.signature.as_ref();
+    if let Some(type_parameters) = signature
+        .and_then(|x| x.type_parameters.as_ref())
+        .filter(|x| !x.is_empty())
+    {
+        write!(f, "{} ", type_parameters_string(type_parameters, options))?;
+    }
+    match constructor_name.filter(|_| method.name == "<init>") {
+        Some(name) => f.write_str(name)?,
+        None => {
+            match signature.map(|x| &x.result) {
+                Some(SignatureResult::JavaTypeSignature(x)) => x.fmt_pretty(options, f)?,
+                Some(SignatureResult::VoidDescriptor) => f.write_str("void")?,
+                None => method.ret_ty.fmt_pretty(options, f)?,
+            }
+            write!(f, " {}", method.name)?;
+        }
+    }
+
+    let mut params = match signature {
+        Some(signature) => signature
+            .parameters
+            .iter()
+            .map(|x| x.pretty(*options).to_string())
+            .collect::<Vec<_>>(),
+        None => method
+            .param_tys
+            .iter()
+            .map(|x| x.pretty(*options).to_string())
+            .collect(),
+    };
+    if method.access_flags.contains(MethodAccessFlags::VARARGS) {
+        if let Some(last) = params.last_mut().filter(|x| x.ends_with("[]")) {
+            last.truncate(last.len() - 2);
+            last.push_str("...");
+        }
+    }
+    let params = params
+        .iter()
+        .enumerate()
+        .map(|(i, x)| format!("{x} arg{i}"))
+        .collect::<Vec<_>>();
+    write!(f, "({})", params.join(", "))?;
+
+    if let Some(value) = &method.annotation_default {
+        write!(f, " default {}", value.pretty(*options))?;
+    }
+    let throws = match signature {
+        Some(signature) if !signature.throws_signatures.is_empty() => {
+            throws_strings(&signature.throws_signatures, options)
+        }
+        _ => method
+            .exceptions
+            .iter()
+            .map(|x| class_name(x, options))
+            .collect(),
+    };
+    if !throws.is_empty() {
+        write!(f, " throws {}", throws.join(", "))?;
+    }
+    Ok(())
+}
+
+/// The declaration annotations, each followed by the separator, when they are included.
+fn declaration_annotations(
+    annotations: &[Annotation],
+    options: &PrettyOptions,
+    separator: &str,
+    f: &mut Formatter<'_>,
+) -> fmt::Result {
+    if !options.annotations {
+        return Ok(());
+    }
+    for annotation in annotations {
+        if matches!(
+            annotation.kind,
+            AnnotationKind::RuntimeVisible | AnnotationKind::RuntimeInvisible
+        ) {
+            write!(f, "{}{separator}", annotation.pretty(*options))?;
+        }
+    }
+    Ok(())
+}
+
+fn implements_keyword(component: &Component) -> &'static str {
+    if component.is_interface() {
+        "extends"
+    } else {
+        "implements"
+    }
+}
+
+fn type_parameters_string(type_parameters: &[TypeParameter], options: &PrettyOptions) -> String {
+    format!("<{}>", comma_separated_string(type_parameters, options))
+}
+
+fn throws_strings(throws: &[ThrowsSignature], options: &PrettyOptions) -> Vec<String> {
+    throws
+        .iter()
+        .map(|x| match x {
+            ThrowsSignature::ClassTypeSignature(x) => x.pretty(*options).to_string(),
+            ThrowsSignature::TypeVariableSignature(x) => x.identifier.clone(),
+        })
+        .collect()
+}
+
+fn comma_separated<T: PrettyPrint>(
+    values: &[T],
+    options: &PrettyOptions,
+    f: &mut Formatter<'_>,
+) -> fmt::Result {
+    for (i, x) in values.iter().enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        x.fmt_pretty(options, f)?;
+    }
+    Ok(())
+}
+
+fn comma_separated_string<T: PrettyPrint>(values: &[T], options: &PrettyOptions) -> String {
+    values
+        .iter()
+        .map(|x| x.pretty(*options).to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The name of a class in source from its binary name.
+fn class_name(binary_name: &str, options: &PrettyOptions) -> String {
+    match binary_name.rsplit_once('.') {
+        Some((package, name)) if !options.simple_names => {
+            format!("{package}.{}", source_name(name))
+        }
+        Some((_, name)) => source_name(name),
+        None => source_name(binary_name),
+    }
+}
+
+/// The name in source of a class in its package, assuming that `$` separates member classes
+/// unless it starts a local or anonymous class name, e.g. `Map.Entry` for `Map$Entry`.
+pub(crate) fn source_name(binary_name: &str) -> String {
+    let mut name = String::with_capacity(binary_name.len());
+    let mut rest = binary_name;
+    while let Some(i) = rest.find('$') {
+        name.push_str(&rest[..i]);
+        rest = &rest[i + 1..];
+        let nested = i > 0 && rest.starts_with(|c: char| c.is_alphabetic() || c == '_');
+        name.push(if nested { '.' } else { '$' });
+    }
+    name.push_str(rest);
+    name
+}
+
+fn is_object(signature: &ReferenceTypeSignature) -> bool {
+    matches!(signature, ReferenceTypeSignature::Class(x) if is_object_class(x))
+}
+
+fn is_object_class(signature: &ClassTypeSignature) -> bool {
+    signature.package_specifier.as_deref() == Some("java.lang")
+        && signature.simple_class_type_signature.identifier == "Object"
+        && signature.class_type_signature_suffixes.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        class_file::AccessFlags,
+        component::{AnnotationElement, Class, ConstantValue},
+        signature::{parse_class_signature, parse_field_signature},
+        test_util::{self, component, field, method, reference},
+    };
+
+    #[test]
+    fn renders_java_source_text() {
+        let simple = PrettyOptions {
+            simple_names: true,
+            annotations: true,
+        };
+        let add = Method {
+            annotations: vec![Annotation {
+                kind: AnnotationKind::RuntimeVisible,
+                ty: reference("java.lang", "Deprecated"),
+                elements: vec![AnnotationElement {
+                    name: "since".to_string(),
+                    value: AnnotationValue::String("9".to_string()),
+                }],
+            }],
+            ..method(
+                "add",
+                "(II)I",
+                Some("<R:Ljava/lang/Object;S::Ljava/util/List;:LAA;>(II)I^Ljava/io/IOException;"),
+                0x0001,
+            )
+        };
+        assert_eq!(
+            add.pretty(simple).to_string(),
+            "@Deprecated(since = \"9\") public <R, S extends List & AA> int add(int arg0, int arg1) \
+             throws IOException"
+        );
+        assert_eq!(
+            add.to_string(),
+            "public <R, S extends java.util.List & AA> int add(int arg0, int arg1) \
+             throws java.io.IOException"
+        );
+
+        let map = parse_field_signature("Ljava/util/Map<TE;-TT;>;").unwrap().1;
+        assert_eq!(map.pretty(simple).to_string(), "Map<E, ? super T>");
+        let entry = parse_field_signature("Ljava/util/Map$Entry<*+Ljava/lang/Number;>;")
+            .unwrap()
+            .1;
+        assert_eq!(
+            entry.to_string(),
+            "java.util.Map.Entry<?, ? extends java.lang.Number>"
+        );
+
+        let component = component(ComponentKind::Class(Class {
+            super_class: Some("java.lang.Object".to_string()),
+            interfaces: vec!["java.io.Serializable".to_string()],
+            signature: Some(
+                parse_class_signature(
+                    "<T::Ljava/lang/Comparable<TT;>;>Ljava/lang/Object;Ljava/io/Serializable;",
+                )
+                .unwrap()
+                .1,
+            ),
+            methods: vec![method("<init>", "([Ljava/lang/String;)V", None, 0x0081)],
+            fields: vec![Field {
+                constant_value: Some(ConstantValue::Long(1)),
+                ..field("serialVersionUID", "J", None, 0x000a)
+            }],
+            access_flags: AccessFlags::PUBLIC | AccessFlags::FINAL,
+            ..test_util::class("a.Outer$Box")
+        }));
+        assert_eq!(
+            component.pretty(simple).to_string(),
+            "public final class Outer.Box<T extends Comparable<T>> implements Serializable {
+    private static long serialVersionUID = 1L;
+    public Box(String... arg0);
+}"
+        );
+    }
+}
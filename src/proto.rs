@@ -1,14 +1,18 @@
 use crate::{
+    class_file::{AccessFlags, FieldAccessFlags, MethodAccessFlags},
     component::{
-        Annotation, AnnotationKind, Class, Component, ComponentKind, Field, Interface, Method,
-        Module, PrimTy, Ty,
+        Annotation, AnnotationKind, Class, Component, ComponentKind, ConstantValue, Field,
+        Interface, Method, Module, PrimTy, Ty,
     },
+    hierarchy::TypeHierarchy,
     signature::{
-        self, ArrayTypeSignature, BaseType, ClassTypeSignature, FieldSignature,
-        ReferenceTypeSignature, TypeArgument, TypeParameter, TypeSignature, WildcardIndicator,
+        self, ArrayTypeSignature, BaseType, ClassSignature, ClassTypeSignature, FieldSignature,
+        MethodSignature, ReferenceTypeSignature, SimpleClassTypeSignature, TypeArgument,
+        TypeParameter, TypeSignature, TypeVariableSignature, WildcardIndicator,
     },
 };
 
+#[allow(clippy::module_inception)]
 pub mod component {
     include!(concat!(
         env!("OUT_DIR"),
@@ -118,6 +122,8 @@ impl From<&Class> for component::Class {
             methods: value.methods.iter().map(|x| x.into()).collect::<Vec<_>>(),
             is_enum: value.is_enum,
             is_abstract: value.is_abstract,
+            access_flags: value.access_flags.bits() as i32,
+            permitted_subclasses: value.permitted_subclasses.clone(),
             annotations: value
                 .annotations
                 .iter()
@@ -175,6 +181,8 @@ impl From<&Interface> for component::Interface {
             fields: value.fields.iter().map(|x| x.into()).collect::<Vec<_>>(),
             methods: value.methods.iter().map(|x| x.into()).collect::<Vec<_>>(),
             is_annotation: value.is_annotation,
+            access_flags: value.access_flags.bits() as i32,
+            permitted_subclasses: value.permitted_subclasses.clone(),
             annotations: value
                 .annotations
                 .iter()
@@ -236,6 +244,8 @@ impl From<&Method> for component::Method {
                 method_kind,
                 type_parameters,
                 is_static: value.is_static,
+                descriptor: value.descriptor.clone(),
+                access_flags: value.access_flags.bits() as i32,
                 annotations: value
                     .annotations
                     .iter()
@@ -251,6 +261,8 @@ impl From<&Method> for component::Method {
                 method_kind,
                 type_parameters: Vec::new(),
                 is_static: value.is_static,
+                descriptor: value.descriptor.clone(),
+                access_flags: value.access_flags.bits() as i32,
                 annotations: value
                     .annotations
                     .iter()
@@ -268,6 +280,9 @@ impl From<&Field> for component::Field {
             r#type: Some(convert_field_ty_to_proto_ty(&value.ty, &value.signature)),
             modifiers: value.modifiers.clone(),
             is_static: value.is_static,
+            descriptor: value.descriptor.clone(),
+            access_flags: value.access_flags.bits() as i32,
+            constant_value: value.constant_value.as_ref().map(|x| x.into()),
             annotations: value
                 .annotations
                 .iter()
@@ -277,6 +292,22 @@ impl From<&Field> for component::Field {
     }
 }
 
+impl From<&ConstantValue> for component::ConstantValue {
+    fn from(value: &ConstantValue) -> Self {
+        Self {
+            value: Some(match value {
+                ConstantValue::Int(v) => component::constant_value::Value::IntValue(*v),
+                ConstantValue::Long(v) => component::constant_value::Value::LongValue(*v),
+                ConstantValue::Float(v) => component::constant_value::Value::FloatValue(*v),
+                ConstantValue::Double(v) => component::constant_value::Value::DoubleValue(*v),
+                ConstantValue::String(v) => {
+                    component::constant_value::Value::StringValue(v.clone())
+                }
+            }),
+        }
+    }
+}
+
 impl From<&PrimTy> for component::PrimitiveType {
     fn from(value: &PrimTy) -> Self {
         let val = match value {
@@ -404,11 +435,25 @@ impl From<&ClassTypeSignature> for component::ClassType {
             .map(|x| x.into())
             .collect::<Vec<_>>();
 
+        let class_type_signature_suffixes = value
+            .class_type_signature_suffixes
+            .iter()
+            .map(|x| component::SimpleClassTypeSignature {
+                identifier: x.identifier.clone(),
+                type_arguments: x
+                    .type_arguments
+                    .iter()
+                    .flatten()
+                    .map(|x| x.into())
+                    .collect(),
+            })
+            .collect();
+
         Self {
             package_specifier,
             identifier,
             type_arguments,
-            class_type_signature_suffixes: Vec::new(),
+            class_type_signature_suffixes,
         }
     }
 }
@@ -522,6 +567,7 @@ impl From<&TypeParameter> for component::TypeParameter {
         Self {
             identifier: value.identifier.clone(),
             type_bounds,
+            interface_bounds_only: value.class_bound.is_none(),
         }
     }
 }
@@ -600,3 +646,615 @@ impl From<Component> for component::Component {
         (&value).into()
     }
 }
+
+impl From<&TypeHierarchy> for component::TypeHierarchy {
+    fn from(value: &TypeHierarchy) -> Self {
+        Self {
+            types: value
+                .types
+                .values()
+                .map(|x| component::TypeHierarchyNode {
+                    qualified_name: x.qualified_name.clone(),
+                    is_interface: x.is_interface,
+                    super_class: x.super_class.clone().unwrap_or_default(),
+                    interfaces: x.interfaces.clone(),
+                    direct_subtypes: value
+                        .direct_subtypes(&x.qualified_name)
+                        .into_iter()
+                        .map(|x| x.to_string())
+                        .collect(),
+                })
+                .collect::<Vec<_>>(),
+        }
+    }
+}
+
+impl TryFrom<component::ComponentList> for Vec<Component> {
+    type Error = String;
+
+    fn try_from(value: component::ComponentList) -> Result<Self, Self::Error> {
+        value.components.into_iter().map(|x| x.try_into()).collect()
+    }
+}
+
+/// Restores a component from its protobuf message. The message does not carry the class file
+/// version, the `InnerClasses` entry, thrown exceptions, annotation elements and defaults, or
+/// constructor calls, which are left empty, so generic `throws` clauses are lost too. Signatures
+/// are restored when a type is generic or its array dimensions differ from the descriptor's.
+impl TryFrom<component::Component> for Component {
+    type Error = String;
+
+    fn try_from(value: component::Component) -> Result<Self, Self::Error> {
+        let kind = match value.component_kind {
+            Some(component::component::ComponentKind::Class(c)) => {
+                ComponentKind::Class(c.try_into()?)
+            }
+            Some(component::component::ComponentKind::Interface(i)) => {
+                ComponentKind::Interface(i.try_into()?)
+            }
+            Some(component::component::ComponentKind::Module(m)) => ComponentKind::Module(m.into()),
+            None => return Err(format!("Component {} has no kind", value.class_file_name)),
+        };
+
+        Ok(Self {
+            minor_version: 0,
+            major_version: 0,
+            kind,
+            class_file_name: value.class_file_name,
+        })
+    }
+}
+
+impl TryFrom<component::Class> for Class {
+    type Error = String;
+
+    fn try_from(value: component::Class) -> Result<Self, Self::Error> {
+        let superclass_signature = value.superclass.map(|x| x.try_into()).transpose()?;
+        let superinterface_signatures = value
+            .interface_types
+            .into_iter()
+            .map(|x| x.try_into())
+            .collect::<Result<Vec<ClassTypeSignature>, _>>()?;
+        let type_parameters = value
+            .type_parameters
+            .into_iter()
+            .map(|x| x.try_into())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let super_class = superclass_signature
+            .as_ref()
+            .map(|x: &ClassTypeSignature| x.qualified_name());
+        let interfaces = superinterface_signatures
+            .iter()
+            .map(|x| x.qualified_name())
+            .collect();
+        let signature = class_signature(
+            type_parameters,
+            superclass_signature,
+            superinterface_signatures,
+        );
+
+        Ok(Self {
+            qualified_name: value.qualified_name,
+            super_class,
+            interfaces,
+            signature,
+            methods: try_into_vec(value.methods)?,
+            fields: try_into_vec(value.fields)?,
+            annotations: try_into_vec(value.annotations)?,
+            is_enum: value.is_enum,
+            is_abstract: value.is_abstract,
+            access_flags: AccessFlags::from_bits_retain(value.access_flags as u16),
+            permitted_subclasses: value.permitted_subclasses,
+            inner_class: None,
+        })
+    }
+}
+
+impl TryFrom<component::Interface> for Interface {
+    type Error = String;
+
+    fn try_from(value: component::Interface) -> Result<Self, Self::Error> {
+        let superinterface_signatures = value
+            .interface_types
+            .into_iter()
+            .map(|x| x.try_into())
+            .collect::<Result<Vec<ClassTypeSignature>, _>>()?;
+        let type_parameters = value
+            .type_parameters
+            .into_iter()
+            .map(|x| x.try_into())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let interfaces = superinterface_signatures
+            .iter()
+            .map(|x| x.qualified_name())
+            .collect();
+        let signature = class_signature(type_parameters, None, superinterface_signatures);
+
+        Ok(Self {
+            is_annotation: value.is_annotation,
+            qualified_name: value.qualified_name,
+            interfaces,
+            signature,
+            methods: try_into_vec(value.methods)?,
+            fields: try_into_vec(value.fields)?,
+            annotations: try_into_vec(value.annotations)?,
+            access_flags: AccessFlags::from_bits_retain(value.access_flags as u16),
+            permitted_subclasses: value.permitted_subclasses,
+            inner_class: None,
+        })
+    }
+}
+
+impl From<component::Module> for Module {
+    fn from(value: component::Module) -> Self {
+        Self {
+            name: value.name,
+            version: value.version,
+        }
+    }
+}
+
+impl TryFrom<component::Method> for Method {
+    type Error = String;
+
+    fn try_from(value: component::Method) -> Result<Self, Self::Error> {
+        let type_parameters = value
+            .type_parameters
+            .into_iter()
+            .map(|x| x.try_into())
+            .collect::<Result<Vec<TypeParameter>, _>>()?;
+        let types = value
+            .parameter_types
+            .iter()
+            .chain(&value.return_type)
+            .collect::<Vec<_>>();
+        let from_signature =
+            !type_parameters.is_empty() || is_from_signature(&types, &value.descriptor);
+
+        let parameters = value
+            .parameter_types
+            .iter()
+            .map(|x| {
+                type_signature(x, from_signature)?
+                    .ok_or_else(|| format!("Method {} has a void parameter", value.name))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let result = match &value.return_type {
+            Some(x) => type_signature(x, from_signature)?,
+            None => None,
+        };
+
+        let signature = from_signature.then(|| MethodSignature {
+            type_parameters: Some(type_parameters.clone()).filter(|x| !x.is_empty()),
+            parameters: parameters.clone(),
+            result: match &result {
+                Some(x) => signature::Result::JavaTypeSignature(x.clone()),
+                None => signature::Result::VoidDescriptor,
+            },
+            throws_signatures: Vec::new(),
+        });
+
+        Ok(Self {
+            name: value.name,
+            descriptor: value.descriptor,
+            access_flags: MethodAccessFlags::from_bits_retain(value.access_flags as u16),
+            signature,
+            modifiers: value.modifiers,
+            param_tys: parameters.iter().map(|x| ty(x, from_signature)).collect(),
+            ret_ty: result.as_ref().map_or(Ty::Void, |x| ty(x, from_signature)),
+            type_params: type_parameters.into_iter().map(|x| x.identifier).collect(),
+            annotations: try_into_vec(value.annotations)?,
+            is_static: value.is_static,
+            exceptions: Vec::new(),
+            annotation_default: None,
+            constructor_call: None,
+        })
+    }
+}
+
+impl TryFrom<component::Field> for Field {
+    type Error = String;
+
+    fn try_from(value: component::Field) -> Result<Self, Self::Error> {
+        let ty = value
+            .r#type
+            .as_ref()
+            .ok_or_else(|| format!("Field {} has no type", value.name))?;
+        let from_signature = is_from_signature(&[ty], &value.descriptor);
+        let ty = type_signature(ty, from_signature)?
+            .ok_or_else(|| format!("Field {} has type void", value.name))?;
+        let signature = match &ty {
+            TypeSignature::Reference(x) if from_signature => Some(FieldSignature {
+                reference_type_signature: x.clone(),
+            }),
+            _ => None,
+        };
+
+        Ok(Self {
+            ty: self::ty(&ty, from_signature),
+            signature,
+            access_flags: FieldAccessFlags::from_bits_retain(value.access_flags as u16),
+            constant_value: value.constant_value.map(|x| x.try_into()).transpose()?,
+            annotations: try_into_vec(value.annotations)?,
+            name: value.name,
+            descriptor: value.descriptor,
+            modifiers: value.modifiers,
+            is_static: value.is_static,
+        })
+    }
+}
+
+impl TryFrom<component::ConstantValue> for ConstantValue {
+    type Error = String;
+
+    fn try_from(value: component::ConstantValue) -> Result<Self, Self::Error> {
+        Ok(match value.value.ok_or("ConstantValue has no value")? {
+            component::constant_value::Value::IntValue(v) => ConstantValue::Int(v),
+            component::constant_value::Value::LongValue(v) => ConstantValue::Long(v),
+            component::constant_value::Value::FloatValue(v) => ConstantValue::Float(v),
+            component::constant_value::Value::DoubleValue(v) => ConstantValue::Double(v),
+            component::constant_value::Value::StringValue(v) => ConstantValue::String(v),
+        })
+    }
+}
+
+impl TryFrom<component::Annotation> for Annotation {
+    type Error = String;
+
+    fn try_from(value: component::Annotation) -> Result<Self, Self::Error> {
+        let ty = value
+            .annotation_type
+            .as_ref()
+            .ok_or("Annotation has no type")?;
+        let ty = type_signature(ty, false)?.ok_or("Annotation has type void")?;
+
+        Ok(Self {
+            kind: match value.annotation_kind {
+                0 => AnnotationKind::RuntimeInvisible,
+                1 => AnnotationKind::RuntimeVisible,
+                2 => AnnotationKind::RuntimeInvisibleParameter,
+                3 => AnnotationKind::RuntimeVisibleParameter,
+                4 => AnnotationKind::RuntimeInvisibleType,
+                5 => AnnotationKind::RuntimeVisibleType,
+                kind => return Err(format!("Invalid annotation kind: {kind}")),
+            },
+            ty: (&ty).into(),
+            elements: Vec::new(),
+        })
+    }
+}
+
+impl TryFrom<component::ClassType> for ClassTypeSignature {
+    type Error = String;
+
+    fn try_from(value: component::ClassType) -> Result<Self, Self::Error> {
+        Ok(Self {
+            package_specifier: Some(value.package_specifier).filter(|x| !x.is_empty()),
+            simple_class_type_signature: SimpleClassTypeSignature {
+                identifier: value.identifier,
+                type_arguments: type_arguments(value.type_arguments)?,
+            },
+            class_type_signature_suffixes: value
+                .class_type_signature_suffixes
+                .into_iter()
+                .map(|x| {
+                    Ok(SimpleClassTypeSignature {
+                        identifier: x.identifier,
+                        type_arguments: type_arguments(x.type_arguments)?,
+                    })
+                })
+                .collect::<Result<_, String>>()?,
+        })
+    }
+}
+
+impl TryFrom<component::TypeArgument> for TypeArgument {
+    type Error = String;
+
+    fn try_from(value: component::TypeArgument) -> Result<Self, Self::Error> {
+        match value.type_argument_kind {
+            Some(component::type_argument::TypeArgumentKind::Any(_)) => Ok(TypeArgument::Any),
+            Some(component::type_argument::TypeArgumentKind::ReferenceType(x)) => {
+                let wildcard = match x.wildcard_type {
+                    0 => None,
+                    1 => Some(WildcardIndicator::Plus),
+                    2 => Some(WildcardIndicator::Minus),
+                    wildcard => return Err(format!("Invalid wildcard type: {wildcard}")),
+                };
+                let ty = x.reference_type.ok_or("Type argument has no type")?;
+                Ok(TypeArgument::ReferenceType(
+                    wildcard,
+                    reference_type_signature(&ty, true)?,
+                ))
+            }
+            None => Err("Type argument has no kind".to_string()),
+        }
+    }
+}
+
+impl TryFrom<component::TypeParameter> for TypeParameter {
+    type Error = String;
+
+    fn try_from(value: component::TypeParameter) -> Result<Self, Self::Error> {
+        let mut bounds = value.type_bounds.into_iter().map(|x| {
+            let ty = match x.type_bound_kind.ok_or("Type bound has no kind")? {
+                component::type_bound::TypeBoundKind::ClassType(c) => {
+                    component::reference_type::ReferenceTypeKind::ClassType(c)
+                }
+                component::type_bound::TypeBoundKind::TypeVariable(v) => {
+                    component::reference_type::ReferenceTypeKind::TypeVariable(v)
+                }
+                component::type_bound::TypeBoundKind::ArrayType(a) => {
+                    component::reference_type::ReferenceTypeKind::ArrayType(Box::new(a))
+                }
+            };
+            let ty = component::ReferenceType {
+                reference_type_kind: Some(ty),
+            };
+            reference_type_signature(&ty, true)
+        });
+
+        let class_bound = if value.interface_bounds_only {
+            None
+        } else {
+            bounds.next().transpose()?
+        };
+        Ok(Self {
+            identifier: value.identifier,
+            class_bound,
+            interface_bounds: bounds.collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+fn try_into_vec<T, U: TryFrom<T, Error = String>>(values: Vec<T>) -> Result<Vec<U>, String> {
+    values.into_iter().map(|x| x.try_into()).collect()
+}
+
+fn type_arguments(
+    values: Vec<component::TypeArgument>,
+) -> Result<Option<Vec<TypeArgument>>, String> {
+    let arguments = try_into_vec(values)?;
+    Ok(Some(arguments).filter(|x: &Vec<_>| !x.is_empty()))
+}
+
+/// A class signature, or `None` when neither the class nor its supertypes are generic.
+fn class_signature(
+    type_parameters: Vec<TypeParameter>,
+    superclass_signature: Option<ClassTypeSignature>,
+    superinterface_signatures: Vec<ClassTypeSignature>,
+) -> Option<ClassSignature> {
+    let is_generic = |x: &ClassTypeSignature| {
+        std::iter::once(&x.simple_class_type_signature)
+            .chain(&x.class_type_signature_suffixes)
+            .any(|x| x.type_arguments.is_some())
+    };
+    if type_parameters.is_empty()
+        && !superclass_signature.iter().any(is_generic)
+        && !superinterface_signatures.iter().any(is_generic)
+    {
+        return None;
+    }
+
+    Some(ClassSignature {
+        type_parameters: Some(type_parameters).filter(|x| !x.is_empty()),
+        superclass_signature: superclass_signature.unwrap_or_else(|| ClassTypeSignature {
+            package_specifier: Some("java.lang".to_string()),
+            simple_class_type_signature: SimpleClassTypeSignature {
+                identifier: "Object".to_string(),
+                type_arguments: None,
+            },
+            class_type_signature_suffixes: Vec::new(),
+        }),
+        superinterface_signatures,
+    })
+}
+
+/// The type of a member as extraction builds it, where descriptor arrays nest one dimension each.
+fn ty(value: &TypeSignature, from_signature: bool) -> Ty {
+    fn nest(ty: Ty) -> Ty {
+        match ty {
+            Ty::Array(element, dimension) => {
+                (0..dimension).fold(nest(*element), |x, _| Ty::Array(Box::new(x), 1))
+            }
+            ty => ty,
+        }
+    }
+
+    let ty = value.into();
+    if from_signature {
+        ty
+    } else {
+        nest(ty)
+    }
+}
+
+/// Whether types were converted from a signature rather than from their descriptor: they are
+/// generic, or their array dimensions don't count from one as the descriptor's do.
+fn is_from_signature(types: &[&component::Type], descriptor: &str) -> bool {
+    if types.iter().any(|x| is_generic(x)) {
+        return true;
+    }
+    let dimensions = descriptor_dimensions(descriptor);
+    types.len() != dimensions.len()
+        || types
+            .iter()
+            .zip(dimensions)
+            .any(|(ty, dimension)| array_dimension(ty).is_some_and(|x| x != dimension))
+}
+
+/// The array dimensions of each type in a field or method descriptor, the return type last.
+fn descriptor_dimensions(descriptor: &str) -> Vec<i32> {
+    let mut dimensions = Vec::new();
+    let mut chars = descriptor.chars().filter(|x| *x != '(' && *x != ')');
+    let mut dimension = 0;
+    while let Some(c) = chars.next() {
+        match c {
+            '[' => dimension += 1,
+            'L' => {
+                chars.by_ref().find(|x| *x == ';');
+                dimensions.push(std::mem::take(&mut dimension));
+            }
+            _ => dimensions.push(std::mem::take(&mut dimension)),
+        }
+    }
+    dimensions
+}
+
+/// The total dimension of an array type as stored, `None` for other types.
+fn array_dimension(value: &component::Type) -> Option<i32> {
+    let Some(component::r#type::TypeKind::ReferenceType(ty)) = &value.type_kind else {
+        return None;
+    };
+    match &ty.reference_type_kind {
+        Some(component::reference_type::ReferenceTypeKind::ArrayType(a)) => Some(
+            a.dimension
+                + a.inner_type
+                    .as_deref()
+                    .and_then(array_dimension)
+                    .unwrap_or_default(),
+        ),
+        _ => None,
+    }
+}
+
+/// Whether a type has type arguments or type variables, and so was converted from a signature.
+fn is_generic(value: &component::Type) -> bool {
+    use component::reference_type::ReferenceTypeKind;
+
+    let Some(component::r#type::TypeKind::ReferenceType(ty)) = &value.type_kind else {
+        return false;
+    };
+    match &ty.reference_type_kind {
+        Some(ReferenceTypeKind::ClassType(c)) => {
+            !c.type_arguments.is_empty()
+                || c.class_type_signature_suffixes
+                    .iter()
+                    .any(|x| !x.type_arguments.is_empty())
+        }
+        Some(ReferenceTypeKind::TypeVariable(_)) => true,
+        Some(ReferenceTypeKind::ArrayType(a)) => a.inner_type.as_deref().is_some_and(is_generic),
+        None => false,
+    }
+}
+
+/// Converts a type to a signature, `None` for `void`. Array types converted from signatures
+/// count their dimensions from zero, and those converted from descriptors from one.
+fn type_signature(
+    value: &component::Type,
+    from_signature: bool,
+) -> Result<Option<TypeSignature>, String> {
+    match value.type_kind.as_ref().ok_or("Type has no kind")? {
+        component::r#type::TypeKind::PrimitiveType(p) => {
+            let base = match p.primitive_type_kind {
+                0 => BaseType::Byte,
+                1 => BaseType::Short,
+                2 => BaseType::Int,
+                3 => BaseType::Long,
+                4 => BaseType::Float,
+                5 => BaseType::Double,
+                6 => BaseType::Boolean,
+                7 => BaseType::Char,
+                8 => return Ok(None),
+                kind => return Err(format!("Invalid primitive type: {kind}")),
+            };
+            Ok(Some(TypeSignature::Base(base)))
+        }
+        component::r#type::TypeKind::ReferenceType(r) => Ok(Some(TypeSignature::Reference(
+            reference_type_signature(r, from_signature)?,
+        ))),
+    }
+}
+
+fn reference_type_signature(
+    value: &component::ReferenceType,
+    from_signature: bool,
+) -> Result<ReferenceTypeSignature, String> {
+    use component::reference_type::ReferenceTypeKind;
+
+    match value
+        .reference_type_kind
+        .as_ref()
+        .ok_or("Reference type has no kind")?
+    {
+        ReferenceTypeKind::ClassType(c) => Ok(ReferenceTypeSignature::Class(c.clone().try_into()?)),
+        ReferenceTypeKind::TypeVariable(v) => Ok(ReferenceTypeSignature::TypeVariable(
+            TypeVariableSignature {
+                identifier: v.clone(),
+            },
+        )),
+        ReferenceTypeKind::ArrayType(a) => {
+            let dimension = a.dimension + i32::from(from_signature);
+            if dimension < 1 {
+                return Err(format!("Invalid array dimension: {}", a.dimension));
+            }
+            let inner = a
+                .inner_type
+                .as_deref()
+                .ok_or("Array type has no element type")?;
+            let mut ty =
+                type_signature(inner, from_signature)?.ok_or("Array type has element type void")?;
+            for _ in 0..dimension {
+                ty = TypeSignature::Reference(ReferenceTypeSignature::Array(ArrayTypeSignature {
+                    java_type_signature: Box::new(ty),
+                }));
+            }
+            match ty {
+                TypeSignature::Reference(x) => Ok(x),
+                TypeSignature::Base(_) => unreachable!(),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{signature::parse_method_signature, test_util};
+
+    fn method(descriptor: &str, signature: Option<&str>) -> Method {
+        test_util::method("m", descriptor, signature, 0x0001)
+    }
+
+    fn round_trip(method: &Method) -> Method {
+        component::Method::from(method).try_into().unwrap()
+    }
+
+    #[test]
+    fn restores_methods_and_constants() {
+        let plain = round_trip(&method("([I)[[J", None));
+        assert_eq!(plain.signature, None);
+        assert_eq!(
+            serde_json::to_value(&plain).unwrap(),
+            serde_json::to_value(method("([I)[[J", None)).unwrap()
+        );
+
+        let signature = "<T::Ljava/lang/Comparable<-TT;>;>(Ljava/util/Map$Entry<TT;[I>;)[TT;";
+        let generic = round_trip(&method(
+            "(Ljava/util/Map$Entry;)[Ljava/lang/Comparable;",
+            Some(signature),
+        ));
+        assert_eq!(
+            generic.signature,
+            Some(parse_method_signature(signature).unwrap().1)
+        );
+        assert_eq!(generic.type_params, ["T"]);
+
+        // Only the signature's throws clause is generic, and the proto has no exceptions
+        let throws = round_trip(&method("([I)V", Some("([I)V^TE;")));
+        assert_eq!(
+            throws.signature,
+            Some(parse_method_signature("([I)V").unwrap().1)
+        );
+
+        for value in [f64::NAN, f64::NEG_INFINITY, 4.149515568880993e180] {
+            let json = serde_json::to_string(&ConstantValue::Double(value)).unwrap();
+            match serde_json::from_str(&json).unwrap() {
+                ConstantValue::Double(x) => assert_eq!(x.to_bits(), value.to_bits()),
+                x => panic!("unexpected {x:?}"),
+            }
+        }
+    }
+}
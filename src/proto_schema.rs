@@ -0,0 +1,860 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use crate::{
+    class_file::{AccessFlags, FieldAccessFlags},
+    component::{Component, ComponentKind, Field, InnerClassInfo},
+    rust_bindings::snake_case,
+    signature::{
+        parse_type_signature, BaseType, ReferenceTypeSignature, TypeArgument, TypeSignature,
+        WildcardIndicator,
+    },
+};
+
+const INDENT: &str = "    ";
+
+/// Classes mapped to repeated fields of their element type.
+const COLLECTIONS: &[&str] = &[
+    "java.lang.Iterable",
+    "java.util.ArrayDeque",
+    "java.util.ArrayList",
+    "java.util.Collection",
+    "java.util.Deque",
+    "java.util.HashSet",
+    "java.util.LinkedHashSet",
+    "java.util.LinkedList",
+    "java.util.List",
+    "java.util.NavigableSet",
+    "java.util.Queue",
+    "java.util.Set",
+    "java.util.SortedSet",
+    "java.util.TreeSet",
+];
+
+/// Classes mapped to map fields.
+const MAPS: &[&str] = &[
+    "java.util.HashMap",
+    "java.util.LinkedHashMap",
+    "java.util.Map",
+    "java.util.NavigableMap",
+    "java.util.SortedMap",
+    "java.util.TreeMap",
+    "java.util.concurrent.ConcurrentHashMap",
+    "java.util.concurrent.ConcurrentMap",
+];
+
+/// Classes mapped to `string`, by their text representation.
+const STRINGS: &[&str] = &[
+    "java.lang.CharSequence",
+    "java.lang.String",
+    "java.math.BigDecimal",
+    "java.math.BigInteger",
+    "java.net.URI",
+    "java.net.URL",
+    "java.time.LocalDate",
+    "java.time.LocalDateTime",
+    "java.time.LocalTime",
+    "java.time.OffsetDateTime",
+    "java.time.ZonedDateTime",
+    "java.util.UUID",
+];
+
+/// Field numbers reserved for the protobuf implementation.
+const RESERVED_NUMBERS: std::ops::RangeInclusive<u32> = 19000..=19999;
+
+/// How the fields of a message are numbered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FieldNumbering {
+    /// In declaration order, inherited fields first
+    #[default]
+    Declaration,
+    /// In alphabetical order of the field names
+    Alphabetical,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ProtoSchemaOptions {
+    pub numbering: FieldNumbering,
+    /// The number of the first field of each message, at least 1
+    pub first_number: u32,
+}
+
+impl Default for ProtoSchemaOptions {
+    fn default() -> Self {
+        Self {
+            numbering: FieldNumbering::default(),
+            first_number: 1,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ProtoFile {
+    /// Relative path following the package, e.g. `com/example/dto.proto`
+    pub path: String,
+    pub source: String,
+}
+
+/// Renders the classes and records of the components as proto3 messages of their instance
+/// fields, and their enums as protobuf enums, in one file per package. Member classes are nested
+/// in the message of their declaring class. Primitives and their boxes map to scalars, the boxes
+/// and `Optional` to `optional` fields, collections and arrays to `repeated` fields, maps to
+/// `map` fields, and classes outside the components to `google.protobuf.Any`. Fields without a
+/// protobuf equivalent, such as nested collections, are left as comments.
+///
+/// Packages referencing each other share a file, since imports cannot be cyclic. It declares
+/// their common package, and nests the others in messages named after their remaining segments,
+/// which keeps the full names of the definitions.
+pub fn proto_schema(components: &[Component], options: ProtoSchemaOptions) -> Vec<ProtoFile> {
+    let schema = Schema::new(components, options);
+
+    let mut packages: BTreeMap<&str, Vec<&Component>> = BTreeMap::new();
+    for (name, component) in &schema.types {
+        if schema.outer_message(component).is_none() {
+            packages.entry(package(name)).or_default().push(component);
+        }
+    }
+
+    let mut dependencies = HashMap::new();
+    for (package, types) in &packages {
+        let mut imports = Imports::default();
+        schema.definitions(types, 0, &mut imports, &mut String::new());
+        dependencies.insert(*package, imports.packages);
+    }
+    let mut files: Vec<Vec<&str>> = packages.keys().map(|x| vec![*x]).collect();
+    loop {
+        let count = files.len();
+        files = merge_cycles(files, &dependencies);
+        // A package cannot share its name with a message nesting packages of another file
+        while let Some((i, j)) = (0..files.len())
+            .flat_map(|i| (0..files.len()).map(move |j| (i, j)))
+            .find(|(i, j)| i != j && nests(&files[*i], &files[*j]))
+        {
+            let other = files.remove(j);
+            let i = if j < i { i - 1 } else { i };
+            files[i].extend(other);
+            files[i].sort();
+        }
+        if files.len() == count {
+            break;
+        }
+    }
+
+    let paths = files
+        .iter()
+        .flat_map(|file| file.iter().map(|x| (*x, file_path(file[0]))))
+        .collect::<HashMap<_, _>>();
+    files
+        .iter()
+        .map(|file| {
+            let package = common_package(file);
+            let mut imports = Imports::default();
+            let mut body = String::new();
+            schema.namespace(package, file, &packages, 0, &mut imports, &mut body);
+
+            let mut source = "syntax = \"proto3\";\n".to_string();
+            if !package.is_empty() {
+                source.push_str(&format!("\npackage {package};\n"));
+            }
+            let path = file_path(file[0]);
+            let imports = imports
+                .packages
+                .iter()
+                .map(|x| paths[x.as_str()].clone())
+                .chain(imports.files)
+                .filter(|x| *x != path)
+                .collect::<BTreeSet<_>>();
+            if !imports.is_empty() {
+                source.push('\n');
+                for import in imports {
+                    source.push_str(&format!("import \"{import}\";\n"));
+                }
+            }
+            source.push('\n');
+            source.push_str(&body);
+            ProtoFile { path, source }
+        })
+        .collect()
+}
+
+/// The files and packages a file imports.
+#[derive(Default)]
+struct Imports {
+    /// Files of well-known types
+    files: BTreeSet<String>,
+    /// Packages of the referenced messages and enums
+    packages: BTreeSet<String>,
+}
+
+struct Schema<'a> {
+    /// Classes and enums with a protobuf definition, by binary name
+    types: BTreeMap<&'a str, &'a Component>,
+    /// Classes by binary name, for inherited fields
+    classes: HashMap<&'a str, &'a Component>,
+    /// Member classes nested in the message of their declaring class, by its binary name
+    members: HashMap<&'a str, Vec<&'a Component>>,
+    options: ProtoSchemaOptions,
+}
+
+impl<'a> Schema<'a> {
+    fn new(components: &'a [Component], options: ProtoSchemaOptions) -> Self {
+        let mut classes = HashMap::new();
+        let mut types = BTreeMap::new();
+        for component in components {
+            let ComponentKind::Class(class) = &component.kind else {
+                continue;
+            };
+            let name = class.qualified_name.as_str();
+            classes.entry(name).or_insert(component);
+
+            let is_local = inner_class(component).is_some_and(|x| x.outer_class.is_none());
+            if !is_local && !class.access_flags.contains(AccessFlags::SYNTHETIC) {
+                types.entry(name).or_insert(component);
+            }
+        }
+
+        let mut schema = Self {
+            types,
+            classes,
+            members: HashMap::new(),
+            options,
+        };
+        for component in schema.types.values() {
+            if let Some(outer) = schema.outer_message(component) {
+                let outer = outer.qualified_name().unwrap();
+                schema.members.entry(outer).or_default().push(component);
+            }
+        }
+        schema
+    }
+
+    /// Renders the definitions of a package, followed by messages named after the next segment
+    /// of the subpackages in the file.
+    fn namespace(
+        &self,
+        package: &str,
+        file: &[&str],
+        packages: &BTreeMap<&str, Vec<&Component>>,
+        depth: usize,
+        imports: &mut Imports,
+        out: &mut String,
+    ) {
+        let mut first = true;
+        if file.contains(&package) {
+            self.definitions(&packages[package], depth, imports, out);
+            first = false;
+        }
+
+        let segments = file
+            .iter()
+            .filter_map(|x| match package {
+                "" => Some(*x).filter(|x| !x.is_empty()),
+                _ => x.strip_prefix(package).and_then(|x| x.strip_prefix('.')),
+            })
+            .map(|x| x.split('.').next().unwrap())
+            .collect::<BTreeSet<_>>();
+        let indent = INDENT.repeat(depth);
+        for segment in segments {
+            if !first {
+                out.push('\n');
+            }
+            first = false;
+            out.push_str(&format!("{indent}message {segment} {{\n"));
+            let subpackage = match package {
+                "" => segment.to_string(),
+                _ => format!("{package}.{segment}"),
+            };
+            self.namespace(&subpackage, file, packages, depth + 1, imports, out);
+            out.push_str(&format!("{indent}}}\n"));
+        }
+    }
+
+    fn definitions(
+        &self,
+        types: &[&Component],
+        depth: usize,
+        imports: &mut Imports,
+        out: &mut String,
+    ) {
+        for (i, component) in types.iter().enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+            self.definition(component, depth, imports, out);
+        }
+    }
+
+    fn definition(
+        &self,
+        component: &Component,
+        depth: usize,
+        imports: &mut Imports,
+        out: &mut String,
+    ) {
+        if is_enum(component) {
+            self.enumeration(component, depth, out);
+        } else {
+            self.message(component, depth, imports, out);
+        }
+    }
+
+    fn enumeration(&self, component: &Component, depth: usize, out: &mut String) {
+        let indent = INDENT.repeat(depth);
+        let name = self.simple_name(component);
+        let prefix = constant_name(&name);
+        let unspecified = format!("{prefix}_UNSPECIFIED");
+        let values = component
+            .fields()
+            .iter()
+            .filter(|x| x.access_flags.contains(FieldAccessFlags::ENUM))
+            .map(|x| format!("{prefix}_{}", constant_name(&x.name)))
+            .collect::<Vec<_>>();
+
+        out.push_str(&format!("{indent}enum {name} {{\n"));
+        // proto3 enums start at zero, the default value
+        let defaults = if values.contains(&unspecified) {
+            Vec::new()
+        } else {
+            vec![unspecified]
+        };
+        for (number, value) in defaults.iter().chain(&values).enumerate() {
+            out.push_str(&format!("{indent}{INDENT}{value} = {number};\n"));
+        }
+        out.push_str(&format!("{indent}}}\n"));
+    }
+
+    fn message(
+        &self,
+        component: &Component,
+        depth: usize,
+        imports: &mut Imports,
+        out: &mut String,
+    ) {
+        let indent = INDENT.repeat(depth);
+        let mut fields = self
+            .fields(component)
+            .into_iter()
+            .map(|x| {
+                let name = snake_case(&x.name.replace('$', "_"));
+                (name, self.field_type(x, component, imports))
+            })
+            .collect::<Vec<_>>();
+        if self.options.numbering == FieldNumbering::Alphabetical {
+            fields.sort_by(|a, b| a.0.cmp(&b.0));
+        }
+
+        let mut body = String::new();
+        let mut number = self.options.first_number;
+        for (name, ty) in fields {
+            match ty {
+                Ok(ty) => {
+                    if RESERVED_NUMBERS.contains(&number) {
+                        number = RESERVED_NUMBERS.end() + 1;
+                    }
+                    body.push_str(&format!("{indent}{INDENT}{ty} {name} = {number};\n"));
+                    number += 1;
+                }
+                Err(reason) => {
+                    body.push_str(&format!("{indent}{INDENT}// Skipped {name}: {reason}\n"));
+                }
+            }
+        }
+
+        let members = self.members.get(component.qualified_name().unwrap());
+        for member in members.into_iter().flatten() {
+            if !body.is_empty() {
+                body.push('\n');
+            }
+            self.definition(member, depth + 1, imports, &mut body);
+        }
+
+        let name = self.simple_name(component);
+        if body.is_empty() {
+            out.push_str(&format!("{indent}message {name} {{}}\n"));
+        } else {
+            out.push_str(&format!("{indent}message {name} {{\n{body}{indent}}}\n"));
+        }
+    }
+
+    /// The instance fields of a class and of its superclasses among the components, superclass
+    /// fields first. Transient and synthetic fields are skipped, and so are hidden fields.
+    fn fields(&self, component: &'a Component) -> Vec<&'a Field> {
+        let mut chain = vec![component];
+        while let Some(super_class) = chain
+            .last()
+            .and_then(|x| x.super_class())
+            .and_then(|x| self.classes.get(x))
+        {
+            if chain.iter().any(|x| std::ptr::eq(*x, *super_class)) {
+                break;
+            }
+            chain.push(super_class);
+        }
+
+        let mut names = HashSet::new();
+        chain
+            .iter()
+            .rev()
+            .flat_map(|x| x.fields())
+            .filter(|x| {
+                !x.access_flags.intersects(
+                    FieldAccessFlags::STATIC
+                        | FieldAccessFlags::TRANSIENT
+                        | FieldAccessFlags::SYNTHETIC,
+                )
+            })
+            .filter(|x| names.insert(x.name.as_str()))
+            .collect()
+    }
+
+    fn field_type(
+        &self,
+        field: &Field,
+        scope: &Component,
+        imports: &mut Imports,
+    ) -> Result<String, &'static str> {
+        let ty = match &field.signature {
+            Some(signature) => TypeSignature::Reference(signature.reference_type_signature.clone()),
+            None => match parse_type_signature(&field.descriptor) {
+                Ok((_, ty)) => ty,
+                Err(_) => return Err("invalid descriptor"),
+            },
+        };
+        let class = match &ty {
+            TypeSignature::Reference(ReferenceTypeSignature::Array(array))
+                if *array.java_type_signature != TypeSignature::Base(BaseType::Byte) =>
+            {
+                let element = self.element(&array.java_type_signature, scope, imports)?;
+                return Ok(format!("repeated {element}"));
+            }
+            TypeSignature::Reference(ReferenceTypeSignature::Class(class)) => class,
+            _ => return self.element(&ty, scope, imports),
+        };
+
+        let name = class.qualified_name();
+        let arguments = class
+            .type_arguments()
+            .map(|x| x.iter().map(type_argument).collect::<Vec<_>>())
+            .unwrap_or_default();
+        let argument = |i: usize, imports: &mut Imports| match arguments.get(i) {
+            Some(Some(x)) => self.element(x, scope, imports),
+            _ => Ok(any(imports)),
+        };
+
+        if COLLECTIONS.contains(&name.as_str()) {
+            Ok(format!("repeated {}", argument(0, imports)?))
+        } else if MAPS.contains(&name.as_str()) {
+            let key = argument(0, imports)?;
+            if !["bool", "int32", "int64", "string"].contains(&key.as_str()) {
+                return Err("protobuf map keys must be integers, booleans or strings");
+            }
+            Ok(format!("map<{key}, {}>", argument(1, imports)?))
+        } else if name == "java.util.Optional" {
+            Ok(format!("optional {}", argument(0, imports)?))
+        } else if let Some(scalar) = optional_scalar(&name).or_else(|| boxed_scalar(&name)) {
+            Ok(format!("optional {scalar}"))
+        } else {
+            self.element(&ty, scope, imports)
+        }
+    }
+
+    /// The type of a singular field or of the elements of a repeated field.
+    fn element(
+        &self,
+        ty: &TypeSignature,
+        scope: &Component,
+        imports: &mut Imports,
+    ) -> Result<String, &'static str> {
+        let class = match ty {
+            TypeSignature::Base(base) => return Ok(scalar(base).to_string()),
+            TypeSignature::Reference(ReferenceTypeSignature::Array(array)) => {
+                return if *array.java_type_signature == TypeSignature::Base(BaseType::Byte) {
+                    Ok("bytes".to_string())
+                } else {
+                    Err("protobuf has no nested repeated fields")
+                };
+            }
+            TypeSignature::Reference(ReferenceTypeSignature::TypeVariable(_)) => {
+                return Ok(any(imports))
+            }
+            TypeSignature::Reference(ReferenceTypeSignature::Class(class)) => class,
+        };
+
+        let name = class.qualified_name();
+        if COLLECTIONS.contains(&name.as_str())
+            || MAPS.contains(&name.as_str())
+            || name == "java.util.Optional"
+            || optional_scalar(&name).is_some()
+        {
+            return Err("protobuf has no nested repeated, map or optional fields");
+        }
+        if let Some(scalar) = boxed_scalar(&name) {
+            return Ok(scalar.to_string());
+        }
+        if STRINGS.contains(&name.as_str()) {
+            return Ok("string".to_string());
+        }
+        let well_known = match name.as_str() {
+            "java.time.Instant" | "java.util.Date" | "java.sql.Timestamp" => Some("timestamp"),
+            "java.time.Duration" => Some("duration"),
+            _ => None,
+        };
+        if let Some(well_known) = well_known {
+            imports
+                .files
+                .insert(format!("google/protobuf/{well_known}.proto"));
+            let mut chars = well_known.chars();
+            let first = chars.next().unwrap().to_ascii_uppercase();
+            return Ok(format!("google.protobuf.{first}{}", chars.as_str()));
+        }
+
+        match self.types.get(name.as_str()) {
+            Some(target) => Ok(self.reference(target, scope, imports)),
+            None => Ok(any(imports)),
+        }
+    }
+
+    /// The name of a message or enum in a field of `scope`, relative to its package when the
+    /// name cannot resolve to a nested definition.
+    fn reference(&self, target: &Component, scope: &Component, imports: &mut Imports) -> String {
+        let package = package(target.qualified_name().unwrap());
+        let name = self.proto_name(target);
+        let absolute = if package.is_empty() {
+            format!(".{name}")
+        } else {
+            format!(".{package}.{name}")
+        };
+        if package != self::package(scope.qualified_name().unwrap()) {
+            imports.packages.insert(package.to_string());
+            return absolute;
+        }
+
+        // Names resolve from the innermost scope outwards
+        let first = name.split('.').next().unwrap();
+        let mut enclosing = Some(scope);
+        while let Some(message) = enclosing {
+            let members = self.members.get(message.qualified_name().unwrap());
+            if members
+                .into_iter()
+                .flatten()
+                .any(|x| self.simple_name(x) == first)
+            {
+                return absolute;
+            }
+            enclosing = self.outer_message(message);
+        }
+        name
+    }
+
+    /// The message a member class is nested in, if its declaring class is a message.
+    fn outer_message(&self, component: &Component) -> Option<&'a Component> {
+        let outer_class = inner_class(component)?.outer_class.as_deref()?;
+        self.types.get(outer_class).filter(|x| !is_enum(x)).copied()
+    }
+
+    /// The name of a message or enum within its package, `Outer.Inner` for a member class.
+    /// Member classes of enums and of classes outside the components are not nested, and are
+    /// named `OuterInner`.
+    fn proto_name(&self, component: &Component) -> String {
+        match self.outer_message(component) {
+            Some(outer) => format!("{}.{}", self.proto_name(outer), self.simple_name(component)),
+            None => self.simple_name(component),
+        }
+    }
+
+    fn simple_name(&self, component: &Component) -> String {
+        match (self.outer_message(component), inner_class(component)) {
+            (
+                Some(_),
+                Some(InnerClassInfo {
+                    simple_name: Some(simple_name),
+                    ..
+                }),
+            ) => simple_name.clone(),
+            _ => {
+                let name = component.qualified_name().unwrap();
+                name.rsplit('.').next().unwrap().replace('$', "")
+            }
+        }
+    }
+}
+
+fn inner_class(component: &Component) -> Option<&InnerClassInfo> {
+    match &component.kind {
+        ComponentKind::Class(x) => x.inner_class.as_ref(),
+        ComponentKind::Interface(x) => x.inner_class.as_ref(),
+        ComponentKind::Module(_) => None,
+    }
+}
+
+fn is_enum(component: &Component) -> bool {
+    matches!(&component.kind, ComponentKind::Class(x) if x.is_enum)
+}
+
+fn package(binary_name: &str) -> &str {
+    binary_name.rsplit_once('.').map_or("", |x| x.0)
+}
+
+/// Merges the files that import each other, directly or through other files.
+fn merge_cycles<'a>(
+    files: Vec<Vec<&'a str>>,
+    dependencies: &HashMap<&str, BTreeSet<String>>,
+) -> Vec<Vec<&'a str>> {
+    let file_of = files
+        .iter()
+        .enumerate()
+        .flat_map(|(i, file)| file.iter().map(move |x| (*x, i)))
+        .collect::<HashMap<_, _>>();
+    let imports = files
+        .iter()
+        .map(|file| {
+            file.iter()
+                .flat_map(|x| dependencies.get(x).into_iter().flatten())
+                .filter_map(|x| file_of.get(x.as_str()).copied())
+                .collect::<BTreeSet<_>>()
+        })
+        .collect::<Vec<_>>();
+    let reachable = (0..files.len())
+        .map(|i| {
+            let mut visited = HashSet::new();
+            let mut pending = vec![i];
+            while let Some(file) = pending.pop() {
+                if visited.insert(file) {
+                    pending.extend(&imports[file]);
+                }
+            }
+            visited
+        })
+        .collect::<Vec<_>>();
+
+    let mut merged = vec![false; files.len()];
+    let mut cycles = Vec::new();
+    for i in 0..files.len() {
+        if merged[i] {
+            continue;
+        }
+        let mut cycle = Vec::new();
+        for j in i..files.len() {
+            if !merged[j] && reachable[i].contains(&j) && reachable[j].contains(&i) {
+                merged[j] = true;
+                cycle.extend(&files[j]);
+            }
+        }
+        cycle.sort();
+        cycles.push(cycle);
+    }
+    cycles
+}
+
+/// Whether a file nests a package of another file in its messages named after packages.
+fn nests(file: &[&str], other: &[&str]) -> bool {
+    let common = common_package(file);
+    file.iter().filter(|x| **x != common).any(|package| {
+        other.iter().any(|x| {
+            let nested = match common {
+                "" => Some(*x),
+                _ => x.strip_prefix(common).and_then(|x| x.strip_prefix('.')),
+            };
+            let segment = package[common.len()..].trim_start_matches('.');
+            let segment = segment.split('.').next().unwrap();
+            nested.is_some_and(|x| x == segment || x.starts_with(&format!("{segment}.")))
+        })
+    })
+}
+
+/// The longest package containing all the packages, possibly the unnamed package.
+fn common_package<'a>(packages: &[&'a str]) -> &'a str {
+    let mut common = packages[0];
+    for package in &packages[1..] {
+        while !(common.is_empty()
+            || *package == common
+            || package
+                .strip_prefix(common)
+                .is_some_and(|x| x.starts_with('.')))
+        {
+            common = common.rsplit_once('.').map_or("", |x| x.0);
+        }
+    }
+    common
+}
+
+fn file_path(package: &str) -> String {
+    if package.is_empty() {
+        "default.proto".to_string()
+    } else {
+        format!("{}.proto", package.replace('.', "/"))
+    }
+}
+
+/// Converts a Java name to an enum value name: `inProgress` becomes `IN_PROGRESS`.
+fn constant_name(name: &str) -> String {
+    snake_case(&name.replace('$', "_")).to_uppercase()
+}
+
+fn any(imports: &mut Imports) -> String {
+    imports
+        .files
+        .insert("google/protobuf/any.proto".to_string());
+    "google.protobuf.Any".to_string()
+}
+
+/// The element type of a type argument, `None` for `?` and `? super T`.
+fn type_argument(argument: &TypeArgument) -> Option<TypeSignature> {
+    match argument {
+        TypeArgument::ReferenceType(None | Some(WildcardIndicator::Plus), ty) => {
+            Some(TypeSignature::Reference(ty.clone()))
+        }
+        TypeArgument::ReferenceType(Some(WildcardIndicator::Minus), _) | TypeArgument::Any => None,
+    }
+}
+
+fn scalar(base: &BaseType) -> &'static str {
+    match base {
+        BaseType::Byte | BaseType::Short | BaseType::Int => "int32",
+        BaseType::Char => "string",
+        BaseType::Long => "int64",
+        BaseType::Float => "float",
+        BaseType::Double => "double",
+        BaseType::Boolean => "bool",
+    }
+}
+
+fn boxed_scalar(name: &str) -> Option<&'static str> {
+    let base = match name {
+        "java.lang.Byte" => BaseType::Byte,
+        "java.lang.Short" => BaseType::Short,
+        "java.lang.Integer" => BaseType::Int,
+        "java.lang.Character" => BaseType::Char,
+        "java.lang.Long" => BaseType::Long,
+        "java.lang.Float" => BaseType::Float,
+        "java.lang.Double" => BaseType::Double,
+        "java.lang.Boolean" => BaseType::Boolean,
+        _ => return None,
+    };
+    Some(scalar(&base))
+}
+
+fn optional_scalar(name: &str) -> Option<&'static str> {
+    match name {
+        "java.util.OptionalInt" => Some("int32"),
+        "java.util.OptionalLong" => Some("int64"),
+        "java.util.OptionalDouble" => Some("double"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        class_file::InnerClassAccessFlags,
+        component::Class,
+        test_util::{self, component, field},
+    };
+
+    fn class(name: &str, fields: Vec<Field>, is_enum: bool, outer: Option<&str>) -> Component {
+        component(ComponentKind::Class(Class {
+            fields,
+            is_enum,
+            inner_class: outer.map(|x| InnerClassInfo {
+                outer_class: Some(x.to_string()),
+                simple_name: name.rsplit('$').next().map(|x| x.to_string()),
+                access_flags: InnerClassAccessFlags::PUBLIC | InnerClassAccessFlags::STATIC,
+            }),
+            ..test_util::class(name)
+        }))
+    }
+
+    fn components() -> Vec<Component> {
+        let order = class(
+            "a.Order",
+            vec![
+                field("orderId", "J", None, 0x0002),
+                field(
+                    "tags",
+                    "Ljava/util/List;",
+                    Some("Ljava/util/List<Ljava/lang/String;>;"),
+                    0x0002,
+                ),
+                field(
+                    "counts",
+                    "Ljava/util/Map;",
+                    Some("Ljava/util/Map<Ljava/lang/String;Ljava/lang/Integer;>;"),
+                    0x0002,
+                ),
+                field(
+                    "matrix",
+                    "Ljava/util/List;",
+                    Some("Ljava/util/List<Ljava/util/List<Ljava/lang/String;>;>;"),
+                    0x0002,
+                ),
+                field("priority", "Ljava/lang/Integer;", None, 0x0002),
+                field("status", "La/Order$Status;", None, 0x0002),
+                field("link", "Lb/Link;", None, 0x0002),
+                field("cache", "Ljava/lang/Object;", None, 0x0082),
+                field("COUNT", "I", None, 0x0019),
+            ],
+            false,
+            None,
+        );
+        let status = class(
+            "a.Order$Status",
+            vec![
+                field("NEW", "La/Order$Status;", None, 0x4019),
+                field("inProgress", "La/Order$Status;", None, 0x4019),
+            ],
+            true,
+            Some("a.Order"),
+        );
+        let link = class(
+            "b.Link",
+            vec![field("href", "[B", None, 0x0012)],
+            false,
+            None,
+        );
+        vec![order, status, link]
+    }
+
+    #[test]
+    fn renders_messages_and_enums() {
+        let files = proto_schema(&components(), ProtoSchemaOptions::default());
+        assert_eq!(
+            files.iter().map(|x| x.path.as_str()).collect::<Vec<_>>(),
+            ["a.proto", "b.proto"]
+        );
+        assert_eq!(
+            files[0].source,
+            r#"syntax = "proto3";
+
+package a;
+
+import "b.proto";
+
+message Order {
+    int64 order_id = 1;
+    repeated string tags = 2;
+    map<string, int32> counts = 3;
+    // Skipped matrix: protobuf has no nested repeated, map or optional fields
+    optional int32 priority = 4;
+    Order.Status status = 5;
+    .b.Link link = 6;
+
+    enum Status {
+        STATUS_UNSPECIFIED = 0;
+        STATUS_NEW = 1;
+        STATUS_IN_PROGRESS = 2;
+    }
+}
+"#
+        );
+
+        let options = ProtoSchemaOptions {
+            numbering: FieldNumbering::Alphabetical,
+            first_number: 10,
+        };
+        let files = proto_schema(&components(), options);
+        assert!(files[0].source.contains("    .b.Link link = 11;\n"));
+        assert!(files[1]
+            .source
+            .ends_with("message Link {\n    bytes href = 10;\n}\n"));
+    }
+}
@@ -0,0 +1,952 @@
+use crate::{
+    class_file::{AccessFlags, FieldAccessFlags, InnerClassAccessFlags, MethodAccessFlags},
+    component::{
+        Annotation, AnnotationElement, AnnotationKind, AnnotationValue, Class, Component,
+        ComponentKind, ConstantValue, ConstructorCall, Field, InnerClassInfo, Interface, Method,
+        Module, PrimTy, Ty, TyName,
+    },
+    signature::{
+        parse_class_signature, parse_field_signature, parse_method_signature, BaseType,
+        ClassSignature, ClassTypeSignature, FieldSignature, MethodSignature,
+        ReferenceTypeSignature, Result as SignatureResult, SimpleClassTypeSignature,
+        ThrowsSignature, TypeArgument, TypeParameter, TypeSignature, WildcardIndicator,
+    },
+};
+
+#[allow(clippy::module_inception)]
+pub mod component {
+    include!(concat!(
+        env!("OUT_DIR"),
+        "/classreader_rs.proto.component.v2.rs"
+    ));
+}
+
+use component::java_type::TypeKind;
+
+impl From<&Vec<Component>> for component::ComponentList {
+    fn from(value: &Vec<Component>) -> Self {
+        Self {
+            components: value.iter().map(|x| x.into()).collect(),
+        }
+    }
+}
+
+impl From<&Component> for component::Component {
+    fn from(value: &Component) -> Self {
+        use component::component::ComponentKind as Kind;
+
+        Self {
+            class_file_name: value.class_file_name.clone(),
+            minor_version: value.minor_version.into(),
+            major_version: value.major_version.into(),
+            component_kind: Some(match &value.kind {
+                ComponentKind::Class(x) => Kind::Class(x.into()),
+                ComponentKind::Interface(x) => Kind::Interface(x.into()),
+                ComponentKind::Module(x) => Kind::Module(component::Module {
+                    name: x.name.clone(),
+                    version: x.version.clone(),
+                }),
+            }),
+        }
+    }
+}
+
+impl From<&Class> for component::Class {
+    fn from(value: &Class) -> Self {
+        let (superclass_type, interface_types) = match &value.signature {
+            Some(sig) => (
+                Some(class_type(&sig.superclass_signature)),
+                sig.superinterface_signatures
+                    .iter()
+                    .map(class_type)
+                    .collect(),
+            ),
+            None => (
+                value.super_class.as_deref().map(binary_name_type),
+                value
+                    .interfaces
+                    .iter()
+                    .map(|x| binary_name_type(x))
+                    .collect(),
+            ),
+        };
+
+        Self {
+            qualified_name: value.qualified_name.clone(),
+            super_class: value.super_class.clone(),
+            interfaces: value.interfaces.clone(),
+            signature: value
+                .signature
+                .as_ref()
+                .map(ClassSignature::to_signature_string),
+            type_parameters: value
+                .signature
+                .as_ref()
+                .map_or(Vec::new(), |x| type_parameters(&x.type_parameters)),
+            superclass_type,
+            interface_types,
+            fields: value.fields.iter().map(|x| x.into()).collect(),
+            methods: value.methods.iter().map(|x| x.into()).collect(),
+            annotations: value.annotations.iter().map(|x| x.into()).collect(),
+            is_enum: value.is_enum,
+            is_abstract: value.is_abstract,
+            access_flags: value.access_flags.bits().into(),
+            permitted_subclasses: value.permitted_subclasses.clone(),
+            inner_class: value.inner_class.as_ref().map(|x| x.into()),
+        }
+    }
+}
+
+impl From<&Interface> for component::Interface {
+    fn from(value: &Interface) -> Self {
+        let interface_types = match &value.signature {
+            Some(sig) => sig
+                .superinterface_signatures
+                .iter()
+                .map(class_type)
+                .collect(),
+            None => value
+                .interfaces
+                .iter()
+                .map(|x| binary_name_type(x))
+                .collect(),
+        };
+
+        Self {
+            qualified_name: value.qualified_name.clone(),
+            interfaces: value.interfaces.clone(),
+            signature: value
+                .signature
+                .as_ref()
+                .map(ClassSignature::to_signature_string),
+            type_parameters: value
+                .signature
+                .as_ref()
+                .map_or(Vec::new(), |x| type_parameters(&x.type_parameters)),
+            interface_types,
+            fields: value.fields.iter().map(|x| x.into()).collect(),
+            methods: value.methods.iter().map(|x| x.into()).collect(),
+            annotations: value.annotations.iter().map(|x| x.into()).collect(),
+            is_annotation: value.is_annotation,
+            access_flags: value.access_flags.bits().into(),
+            permitted_subclasses: value.permitted_subclasses.clone(),
+            inner_class: value.inner_class.as_ref().map(|x| x.into()),
+        }
+    }
+}
+
+impl From<&InnerClassInfo> for component::InnerClass {
+    fn from(value: &InnerClassInfo) -> Self {
+        Self {
+            outer_class: value.outer_class.clone(),
+            simple_name: value.simple_name.clone(),
+            access_flags: value.access_flags.bits().into(),
+        }
+    }
+}
+
+impl From<&Field> for component::Field {
+    fn from(value: &Field) -> Self {
+        Self {
+            name: value.name.clone(),
+            descriptor: value.descriptor.clone(),
+            signature: value
+                .signature
+                .as_ref()
+                .map(FieldSignature::to_signature_string),
+            r#type: Some(match &value.signature {
+                Some(sig) => reference_type(&sig.reference_type_signature),
+                None => ty_type(&value.ty),
+            }),
+            modifiers: value.modifiers.clone(),
+            is_static: value.is_static,
+            access_flags: value.access_flags.bits().into(),
+            constant_value: value.constant_value.as_ref().map(|x| x.into()),
+            annotations: value.annotations.iter().map(|x| x.into()).collect(),
+        }
+    }
+}
+
+impl From<&ConstantValue> for component::ConstantValue {
+    fn from(value: &ConstantValue) -> Self {
+        use component::constant_value::Value;
+
+        Self {
+            value: Some(match value {
+                ConstantValue::Int(x) => Value::IntValue(*x),
+                ConstantValue::Long(x) => Value::LongValue(*x),
+                ConstantValue::Float(x) => Value::FloatValue(*x),
+                ConstantValue::Double(x) => Value::DoubleValue(*x),
+                ConstantValue::String(x) => Value::StringValue(x.clone()),
+            }),
+        }
+    }
+}
+
+impl From<&Method> for component::Method {
+    fn from(value: &Method) -> Self {
+        let (parameter_types, return_type) = match &value.signature {
+            Some(sig) => (
+                sig.parameters.iter().map(java_type).collect(),
+                match &sig.result {
+                    SignatureResult::JavaTypeSignature(x) => java_type(x),
+                    SignatureResult::VoidDescriptor => base_type(component::BaseType::Void),
+                },
+            ),
+            None => (
+                value.param_tys.iter().map(ty_type).collect(),
+                ty_type(&value.ret_ty),
+            ),
+        };
+
+        Self {
+            name: value.name.clone(),
+            descriptor: value.descriptor.clone(),
+            signature: value
+                .signature
+                .as_ref()
+                .map(MethodSignature::to_signature_string),
+            type_parameters: value
+                .signature
+                .as_ref()
+                .map_or(Vec::new(), |x| type_parameters(&x.type_parameters)),
+            parameter_types,
+            return_type: Some(return_type),
+            throws_types: value.signature.as_ref().map_or(Vec::new(), |x| {
+                x.throws_signatures
+                    .iter()
+                    .map(|x| match x {
+                        ThrowsSignature::ClassTypeSignature(x) => class_type(x),
+                        ThrowsSignature::TypeVariableSignature(x) => component::JavaType {
+                            type_kind: Some(TypeKind::TypeVariable(x.identifier.clone())),
+                        },
+                    })
+                    .collect()
+            }),
+            exceptions: value.exceptions.clone(),
+            modifiers: value.modifiers.clone(),
+            is_static: value.is_static,
+            access_flags: value.access_flags.bits().into(),
+            annotations: value.annotations.iter().map(|x| x.into()).collect(),
+            annotation_default: value.annotation_default.as_ref().map(|x| x.into()),
+            constructor_call: value
+                .constructor_call
+                .as_ref()
+                .map(|x| component::ConstructorCall {
+                    class_name: x.class_name.clone(),
+                    descriptor: x.descriptor.clone(),
+                }),
+        }
+    }
+}
+
+impl From<&Annotation> for component::Annotation {
+    fn from(value: &Annotation) -> Self {
+        let kind = match value.kind {
+            AnnotationKind::RuntimeInvisible => component::AnnotationKind::RuntimeInvisible,
+            AnnotationKind::RuntimeVisible => component::AnnotationKind::RuntimeVisible,
+            AnnotationKind::RuntimeInvisibleParameter => {
+                component::AnnotationKind::RuntimeInvisibleParameter
+            }
+            AnnotationKind::RuntimeVisibleParameter => {
+                component::AnnotationKind::RuntimeVisibleParameter
+            }
+            AnnotationKind::RuntimeInvisibleType => component::AnnotationKind::RuntimeInvisibleType,
+            AnnotationKind::RuntimeVisibleType => component::AnnotationKind::RuntimeVisibleType,
+        };
+
+        Self {
+            kind: kind as i32,
+            r#type: Some(ty_type(&value.ty)),
+            elements: value
+                .elements
+                .iter()
+                .map(|x| component::AnnotationElement {
+                    name: x.name.clone(),
+                    value: Some((&x.value).into()),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<&AnnotationValue> for component::AnnotationValue {
+    fn from(value: &AnnotationValue) -> Self {
+        use component::annotation_value::Value;
+
+        Self {
+            value: Some(match value {
+                AnnotationValue::Byte(x) => Value::ByteValue((*x).into()),
+                AnnotationValue::Char(x) => Value::CharValue((*x).into()),
+                AnnotationValue::Short(x) => Value::ShortValue((*x).into()),
+                AnnotationValue::Int(x) => Value::IntValue(*x),
+                AnnotationValue::Long(x) => Value::LongValue(*x),
+                AnnotationValue::Float(x) => Value::FloatValue(*x),
+                AnnotationValue::Double(x) => Value::DoubleValue(*x),
+                AnnotationValue::Boolean(x) => Value::BooleanValue(*x),
+                AnnotationValue::String(x) => Value::StringValue(x.clone()),
+                AnnotationValue::Enum { ty, name } => Value::EnumValue(component::EnumConstant {
+                    r#type: Some(ty_type(ty)),
+                    name: name.clone(),
+                }),
+                AnnotationValue::Class(ty) => Value::ClassValue(ty_type(ty)),
+                AnnotationValue::Annotation(x) => Value::AnnotationValue(x.as_ref().into()),
+                AnnotationValue::Array(x) => Value::ArrayValue(component::AnnotationValues {
+                    values: x.iter().map(|x| x.into()).collect(),
+                }),
+            }),
+        }
+    }
+}
+
+fn base_type(value: component::BaseType) -> component::JavaType {
+    component::JavaType {
+        type_kind: Some(TypeKind::BaseType(value as i32)),
+    }
+}
+
+/// The type of a class named by its binary name, such as a superclass without a signature.
+fn binary_name_type(name: &str) -> component::JavaType {
+    let (package_name, identifier) = name.rsplit_once('.').unwrap_or(("", name));
+    component::JavaType {
+        type_kind: Some(TypeKind::ClassType(component::ClassType {
+            package_name: package_name.to_string(),
+            segments: vec![component::ClassTypeSegment {
+                identifier: identifier.to_string(),
+                type_arguments: Vec::new(),
+            }],
+        })),
+    }
+}
+
+/// Converts a type taken from a descriptor, whose arrays nest one dimension each.
+fn ty_type(value: &Ty) -> component::JavaType {
+    let kind = match value {
+        Ty::Prim(x) => {
+            return base_type(match x {
+                PrimTy::Byte => component::BaseType::Byte,
+                PrimTy::Short => component::BaseType::Short,
+                PrimTy::Int => component::BaseType::Int,
+                PrimTy::Long => component::BaseType::Long,
+                PrimTy::Float => component::BaseType::Float,
+                PrimTy::Double => component::BaseType::Double,
+                PrimTy::Boolean => component::BaseType::Boolean,
+                PrimTy::Char => component::BaseType::Char,
+                PrimTy::Void => component::BaseType::Void,
+            })
+        }
+        Ty::Void => return base_type(component::BaseType::Void),
+        Ty::Reference(x) => return binary_name_type(&x.qualified_name()),
+        Ty::TyVar(x) => TypeKind::TypeVariable(x.clone()),
+        Ty::Array(element, dimension) => {
+            let mut element = element.as_ref();
+            let mut dimensions = *dimension;
+            while let Ty::Array(inner, dimension) = element {
+                element = inner;
+                dimensions += dimension;
+            }
+            TypeKind::ArrayType(Box::new(component::ArrayType {
+                dimensions: dimensions as u32,
+                element_type: Some(Box::new(ty_type(element))),
+            }))
+        }
+    };
+    component::JavaType {
+        type_kind: Some(kind),
+    }
+}
+
+fn java_type(value: &TypeSignature) -> component::JavaType {
+    match value {
+        TypeSignature::Base(x) => base_type(match x {
+            BaseType::Byte => component::BaseType::Byte,
+            BaseType::Short => component::BaseType::Short,
+            BaseType::Int => component::BaseType::Int,
+            BaseType::Long => component::BaseType::Long,
+            BaseType::Float => component::BaseType::Float,
+            BaseType::Double => component::BaseType::Double,
+            BaseType::Boolean => component::BaseType::Boolean,
+            BaseType::Char => component::BaseType::Char,
+        }),
+        TypeSignature::Reference(x) => reference_type(x),
+    }
+}
+
+fn reference_type(value: &ReferenceTypeSignature) -> component::JavaType {
+    let kind = match value {
+        ReferenceTypeSignature::TypeVariable(x) => TypeKind::TypeVariable(x.identifier.clone()),
+        ReferenceTypeSignature::Class(x) => return class_type(x),
+        ReferenceTypeSignature::Array(x) => {
+            let mut element = x.java_type_signature.as_ref();
+            let mut dimensions = 1;
+            while let TypeSignature::Reference(ReferenceTypeSignature::Array(x)) = element {
+                element = &x.java_type_signature;
+                dimensions += 1;
+            }
+            TypeKind::ArrayType(Box::new(component::ArrayType {
+                dimensions,
+                element_type: Some(Box::new(java_type(element))),
+            }))
+        }
+    };
+    component::JavaType {
+        type_kind: Some(kind),
+    }
+}
+
+fn class_type(value: &ClassTypeSignature) -> component::JavaType {
+    let segment = |x: &SimpleClassTypeSignature| component::ClassTypeSegment {
+        identifier: x.identifier.clone(),
+        type_arguments: x
+            .type_arguments
+            .iter()
+            .flatten()
+            .map(|x| match x {
+                TypeArgument::Any => component::TypeArgument {
+                    wildcard: component::Wildcard::Any as i32,
+                    r#type: None,
+                },
+                TypeArgument::ReferenceType(wildcard, ty) => component::TypeArgument {
+                    wildcard: match wildcard {
+                        None => component::Wildcard::None,
+                        Some(WildcardIndicator::Plus) => component::Wildcard::Extends,
+                        Some(WildcardIndicator::Minus) => component::Wildcard::Super,
+                    } as i32,
+                    r#type: Some(reference_type(ty)),
+                },
+            })
+            .collect(),
+    };
+
+    component::JavaType {
+        type_kind: Some(TypeKind::ClassType(component::ClassType {
+            package_name: value.package_specifier.clone().unwrap_or_default(),
+            segments: std::iter::once(&value.simple_class_type_signature)
+                .chain(&value.class_type_signature_suffixes)
+                .map(segment)
+                .collect(),
+        })),
+    }
+}
+
+fn type_parameters(value: &Option<Vec<TypeParameter>>) -> Vec<component::TypeParameter> {
+    value
+        .iter()
+        .flatten()
+        .map(|x| component::TypeParameter {
+            identifier: x.identifier.clone(),
+            class_bound: x.class_bound.as_ref().map(reference_type),
+            interface_bounds: x.interface_bounds.iter().map(reference_type).collect(),
+        })
+        .collect()
+}
+
+impl TryFrom<component::ComponentList> for Vec<Component> {
+    type Error = String;
+
+    fn try_from(value: component::ComponentList) -> Result<Self, Self::Error> {
+        try_into_vec(value.components)
+    }
+}
+
+impl TryFrom<component::Component> for Component {
+    type Error = String;
+
+    fn try_from(value: component::Component) -> Result<Self, Self::Error> {
+        use component::component::ComponentKind as Kind;
+
+        let kind = match value.component_kind.ok_or("Component has no kind")? {
+            Kind::Class(x) => ComponentKind::Class(x.try_into()?),
+            Kind::Interface(x) => ComponentKind::Interface(x.try_into()?),
+            Kind::Module(x) => ComponentKind::Module(Module {
+                name: x.name,
+                version: x.version,
+            }),
+        };
+        Ok(Self {
+            minor_version: u16_from(value.minor_version, "minor version")?,
+            major_version: u16_from(value.major_version, "major version")?,
+            kind,
+            class_file_name: value.class_file_name,
+        })
+    }
+}
+
+impl TryFrom<component::Class> for Class {
+    type Error = String;
+
+    fn try_from(value: component::Class) -> Result<Self, Self::Error> {
+        Ok(Self {
+            qualified_name: value.qualified_name,
+            super_class: value.super_class,
+            interfaces: value.interfaces,
+            signature: value
+                .signature
+                .map(|x| parse_signature(&x, parse_class_signature))
+                .transpose()?,
+            methods: try_into_vec(value.methods)?,
+            fields: try_into_vec(value.fields)?,
+            annotations: try_into_vec(value.annotations)?,
+            is_enum: value.is_enum,
+            is_abstract: value.is_abstract,
+            access_flags: AccessFlags::from_bits_retain(u16_from(
+                value.access_flags,
+                "access flags",
+            )?),
+            permitted_subclasses: value.permitted_subclasses,
+            inner_class: value.inner_class.map(|x| x.try_into()).transpose()?,
+        })
+    }
+}
+
+impl TryFrom<component::Interface> for Interface {
+    type Error = String;
+
+    fn try_from(value: component::Interface) -> Result<Self, Self::Error> {
+        Ok(Self {
+            is_annotation: value.is_annotation,
+            qualified_name: value.qualified_name,
+            interfaces: value.interfaces,
+            signature: value
+                .signature
+                .map(|x| parse_signature(&x, parse_class_signature))
+                .transpose()?,
+            methods: try_into_vec(value.methods)?,
+            fields: try_into_vec(value.fields)?,
+            annotations: try_into_vec(value.annotations)?,
+            access_flags: AccessFlags::from_bits_retain(u16_from(
+                value.access_flags,
+                "access flags",
+            )?),
+            permitted_subclasses: value.permitted_subclasses,
+            inner_class: value.inner_class.map(|x| x.try_into()).transpose()?,
+        })
+    }
+}
+
+impl TryFrom<component::InnerClass> for InnerClassInfo {
+    type Error = String;
+
+    fn try_from(value: component::InnerClass) -> Result<Self, Self::Error> {
+        Ok(Self {
+            outer_class: value.outer_class,
+            simple_name: value.simple_name,
+            access_flags: InnerClassAccessFlags::from_bits_retain(u16_from(
+                value.access_flags,
+                "inner class access flags",
+            )?),
+        })
+    }
+}
+
+impl TryFrom<component::Field> for Field {
+    type Error = String;
+
+    fn try_from(value: component::Field) -> Result<Self, Self::Error> {
+        let signature = value
+            .signature
+            .map(|x| parse_signature(&x, parse_field_signature))
+            .transpose()?;
+        let ty = value
+            .r#type
+            .ok_or_else(|| format!("Field {} has no type", value.name))?;
+
+        Ok(Self {
+            ty: self::ty(&ty, signature.is_some())?,
+            signature,
+            name: value.name,
+            descriptor: value.descriptor,
+            access_flags: FieldAccessFlags::from_bits_retain(u16_from(
+                value.access_flags,
+                "access flags",
+            )?),
+            modifiers: value.modifiers,
+            annotations: try_into_vec(value.annotations)?,
+            is_static: value.is_static,
+            constant_value: value.constant_value.map(|x| x.try_into()).transpose()?,
+        })
+    }
+}
+
+impl TryFrom<component::ConstantValue> for ConstantValue {
+    type Error = String;
+
+    fn try_from(value: component::ConstantValue) -> Result<Self, Self::Error> {
+        use component::constant_value::Value;
+
+        Ok(match value.value.ok_or("Constant value has no value")? {
+            Value::IntValue(x) => ConstantValue::Int(x),
+            Value::LongValue(x) => ConstantValue::Long(x),
+            Value::FloatValue(x) => ConstantValue::Float(x),
+            Value::DoubleValue(x) => ConstantValue::Double(x),
+            Value::StringValue(x) => ConstantValue::String(x),
+        })
+    }
+}
+
+impl TryFrom<component::Method> for Method {
+    type Error = String;
+
+    fn try_from(value: component::Method) -> Result<Self, Self::Error> {
+        let signature = value
+            .signature
+            .map(|x| parse_signature(&x, parse_method_signature))
+            .transpose()?;
+        let from_signature = signature.is_some();
+        let return_type = value
+            .return_type
+            .ok_or_else(|| format!("Method {} has no return type", value.name))?;
+
+        Ok(Self {
+            param_tys: value
+                .parameter_types
+                .iter()
+                .map(|x| ty(x, from_signature))
+                .collect::<Result<_, _>>()?,
+            ret_ty: ty(&return_type, from_signature)?,
+            type_params: value
+                .type_parameters
+                .into_iter()
+                .map(|x| x.identifier)
+                .collect(),
+            signature,
+            name: value.name,
+            descriptor: value.descriptor,
+            access_flags: MethodAccessFlags::from_bits_retain(u16_from(
+                value.access_flags,
+                "access flags",
+            )?),
+            modifiers: value.modifiers,
+            annotations: try_into_vec(value.annotations)?,
+            is_static: value.is_static,
+            exceptions: value.exceptions,
+            annotation_default: value.annotation_default.map(|x| x.try_into()).transpose()?,
+            constructor_call: value.constructor_call.map(|x| ConstructorCall {
+                class_name: x.class_name,
+                descriptor: x.descriptor,
+            }),
+        })
+    }
+}
+
+impl TryFrom<component::Annotation> for Annotation {
+    type Error = String;
+
+    fn try_from(value: component::Annotation) -> Result<Self, Self::Error> {
+        let kind = match component::AnnotationKind::from_i32(value.kind) {
+            Some(component::AnnotationKind::RuntimeInvisible) => AnnotationKind::RuntimeInvisible,
+            Some(component::AnnotationKind::RuntimeVisible) => AnnotationKind::RuntimeVisible,
+            Some(component::AnnotationKind::RuntimeInvisibleParameter) => {
+                AnnotationKind::RuntimeInvisibleParameter
+            }
+            Some(component::AnnotationKind::RuntimeVisibleParameter) => {
+                AnnotationKind::RuntimeVisibleParameter
+            }
+            Some(component::AnnotationKind::RuntimeInvisibleType) => {
+                AnnotationKind::RuntimeInvisibleType
+            }
+            Some(component::AnnotationKind::RuntimeVisibleType) => {
+                AnnotationKind::RuntimeVisibleType
+            }
+            None => return Err(format!("Invalid annotation kind: {}", value.kind)),
+        };
+        let ty = value.r#type.ok_or("Annotation has no type")?;
+
+        Ok(Self {
+            kind,
+            ty: self::ty(&ty, false)?,
+            elements: value
+                .elements
+                .into_iter()
+                .map(|x| {
+                    Ok(AnnotationElement {
+                        value: x
+                            .value
+                            .ok_or_else(|| format!("Annotation element {} has no value", x.name))?
+                            .try_into()?,
+                        name: x.name,
+                    })
+                })
+                .collect::<Result<_, String>>()?,
+        })
+    }
+}
+
+impl TryFrom<component::AnnotationValue> for AnnotationValue {
+    type Error = String;
+
+    fn try_from(value: component::AnnotationValue) -> Result<Self, Self::Error> {
+        use component::annotation_value::Value;
+
+        let invalid = |x: &dyn std::fmt::Display| format!("Invalid annotation value: {x}");
+        Ok(match value.value.ok_or("Annotation value has no value")? {
+            Value::ByteValue(x) => AnnotationValue::Byte(x.try_into().map_err(|_| invalid(&x))?),
+            Value::CharValue(x) => AnnotationValue::Char(x.try_into().map_err(|_| invalid(&x))?),
+            Value::ShortValue(x) => AnnotationValue::Short(x.try_into().map_err(|_| invalid(&x))?),
+            Value::IntValue(x) => AnnotationValue::Int(x),
+            Value::LongValue(x) => AnnotationValue::Long(x),
+            Value::FloatValue(x) => AnnotationValue::Float(x),
+            Value::DoubleValue(x) => AnnotationValue::Double(x),
+            Value::BooleanValue(x) => AnnotationValue::Boolean(x),
+            Value::StringValue(x) => AnnotationValue::String(x),
+            Value::EnumValue(x) => AnnotationValue::Enum {
+                ty: ty(&x.r#type.ok_or("Enum constant has no type")?, false)?,
+                name: x.name,
+            },
+            Value::ClassValue(x) => AnnotationValue::Class(ty(&x, false)?),
+            Value::AnnotationValue(x) => AnnotationValue::Annotation(Box::new(x.try_into()?)),
+            Value::ArrayValue(x) => AnnotationValue::Array(try_into_vec(x.values)?),
+        })
+    }
+}
+
+fn try_into_vec<T, U: TryFrom<T, Error = String>>(values: Vec<T>) -> Result<Vec<U>, String> {
+    values.into_iter().map(|x| x.try_into()).collect()
+}
+
+fn u16_from(value: u32, name: &str) -> Result<u16, String> {
+    value
+        .try_into()
+        .map_err(|_| format!("Invalid {name}: {value}"))
+}
+
+fn parse_signature<T>(
+    value: &str,
+    parse: impl Fn(&str) -> nom::IResult<&str, T>,
+) -> Result<T, String> {
+    match parse(value) {
+        Ok(("", x)) => Ok(x),
+        _ => Err(format!("Invalid signature: {value}")),
+    }
+}
+
+/// The type of a member as extraction builds it: types taken from signatures name only their
+/// outermost class and flatten arrays, and those taken from descriptors nest arrays one
+/// dimension each.
+fn ty(value: &component::JavaType, from_signature: bool) -> Result<Ty, String> {
+    Ok(match value.type_kind.as_ref().ok_or("Type has no kind")? {
+        TypeKind::BaseType(x) => match component::BaseType::from_i32(*x) {
+            Some(component::BaseType::Byte) => Ty::Prim(PrimTy::Byte),
+            Some(component::BaseType::Short) => Ty::Prim(PrimTy::Short),
+            Some(component::BaseType::Int) => Ty::Prim(PrimTy::Int),
+            Some(component::BaseType::Long) => Ty::Prim(PrimTy::Long),
+            Some(component::BaseType::Float) => Ty::Prim(PrimTy::Float),
+            Some(component::BaseType::Double) => Ty::Prim(PrimTy::Double),
+            Some(component::BaseType::Boolean) => Ty::Prim(PrimTy::Boolean),
+            Some(component::BaseType::Char) => Ty::Prim(PrimTy::Char),
+            Some(component::BaseType::Void) => Ty::Void,
+            Some(component::BaseType::Unspecified) | None => {
+                return Err(format!("Invalid base type: {x}"))
+            }
+        },
+        TypeKind::ClassType(x) => Ty::Reference(TyName {
+            package_name: Some(x.package_name.clone()).filter(|x| !x.is_empty()),
+            name: x
+                .segments
+                .first()
+                .ok_or("Class type has no name")?
+                .identifier
+                .clone(),
+        }),
+        TypeKind::TypeVariable(x) => Ty::TyVar(x.clone()),
+        TypeKind::ArrayType(x) => {
+            let element = x
+                .element_type
+                .as_deref()
+                .ok_or("Array type has no element")?;
+            let element = ty(element, from_signature)?;
+            let dimensions = x.dimensions as usize;
+            if dimensions == 0 {
+                return Err("Array type has no dimensions".to_string());
+            }
+            if from_signature {
+                Ty::Array(Box::new(element), dimensions)
+            } else {
+                (0..dimensions).fold(element, |x, _| Ty::Array(Box::new(x), 1))
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use prost::Message;
+
+    use super::*;
+    use crate::{descriptor::parse_field_descriptor, test_util};
+
+    fn method(name: &str, descriptor: &str, signature: Option<&str>) -> Method {
+        test_util::method(name, descriptor, signature, 0x0001)
+    }
+
+    fn field(name: &str, descriptor: &str, signature: Option<&str>) -> Field {
+        test_util::field(name, descriptor, signature, 0x0001)
+    }
+
+    fn annotation(descriptor: &str, elements: Vec<(&str, AnnotationValue)>) -> Annotation {
+        Annotation {
+            kind: AnnotationKind::RuntimeVisible,
+            ty: (&parse_field_descriptor(descriptor)).into(),
+            elements: elements
+                .into_iter()
+                .map(|(name, value)| AnnotationElement {
+                    name: name.to_string(),
+                    value,
+                })
+                .collect(),
+        }
+    }
+
+    fn component() -> Component {
+        let mut get = method(
+            "get",
+            "(ILjava/util/Map$Entry;)[[Ljava/lang/Object;",
+            Some("<X:Ljava/lang/Exception;>(ILjava/util/Map<TK;*>.Entry<+TK;-[I>;)[[TV;^TX;"),
+        );
+        get.exceptions = vec!["java.io.IOException".to_string()];
+        get.annotations = vec![annotation(
+            "Lcom/example/Tag;",
+            vec![
+                ("b", AnnotationValue::Byte(-1)),
+                ("c", AnnotationValue::Char(0xD800)),
+                ("s", AnnotationValue::Short(-300)),
+                ("f", AnnotationValue::Float(f32::NAN)),
+                ("d", AnnotationValue::Double(f64::NEG_INFINITY)),
+                ("z", AnnotationValue::Boolean(true)),
+                (
+                    "e",
+                    AnnotationValue::Enum {
+                        ty: (&parse_field_descriptor("Ljava/lang/annotation/ElementType;")).into(),
+                        name: "FIELD".to_string(),
+                    },
+                ),
+                ("v", AnnotationValue::Class(Ty::Void)),
+                (
+                    "a",
+                    AnnotationValue::Array(vec![
+                        AnnotationValue::Class((&parse_field_descriptor("[[I")).into()),
+                        AnnotationValue::Annotation(Box::new(annotation(
+                            "LDeprecated;",
+                            Vec::new(),
+                        ))),
+                    ]),
+                ),
+            ],
+        )];
+
+        let mut init = method("<init>", "(Lcom/example/Outer;[J)V", Some("([J)V"));
+        init.constructor_call = Some(ConstructorCall {
+            class_name: "java.lang.Object".to_string(),
+            descriptor: "()V".to_string(),
+        });
+        let mut plain = method("plain", "([[I)[Ljava/lang/String;", None);
+        plain.annotation_default = Some(AnnotationValue::String("x".to_string()));
+
+        let mut constant = field("MAX", "D", None);
+        constant.constant_value = Some(ConstantValue::Double(f64::NAN));
+
+        Component {
+            minor_version: 0xFFFF,
+            major_version: 61,
+            class_file_name: "com/example/Outer$Inner.class".to_string(),
+            kind: ComponentKind::Class(Class {
+                qualified_name: "com.example.Outer$Inner".to_string(),
+                super_class: Some("java.util.AbstractMap".to_string()),
+                interfaces: vec!["java.io.Serializable".to_string()],
+                signature: Some(
+                    parse_class_signature(
+                        "<K::Ljava/lang/Comparable<TK;>;V:Ljava/lang/Object;>\
+                         Ljava/util/AbstractMap<TK;TV;>;Ljava/io/Serializable;",
+                    )
+                    .unwrap()
+                    .1,
+                ),
+                methods: vec![get, init, plain],
+                fields: vec![
+                    constant,
+                    field("grid", "[[I", None),
+                    field("map", "Ljava/util/Map;", Some("Ljava/util/Map<TK;[[TV;>;")),
+                ],
+                annotations: Vec::new(),
+                is_enum: false,
+                is_abstract: true,
+                access_flags: AccessFlags::PUBLIC | AccessFlags::SUPER | AccessFlags::ABSTRACT,
+                permitted_subclasses: Vec::new(),
+                inner_class: Some(InnerClassInfo {
+                    outer_class: Some("com.example.Outer".to_string()),
+                    simple_name: Some("Inner".to_string()),
+                    access_flags: InnerClassAccessFlags::PUBLIC,
+                }),
+            }),
+        }
+    }
+
+    #[test]
+    fn carries_the_same_information_as_json() {
+        let original = component();
+        let expected = serde_json::to_value(&original).unwrap();
+
+        let json: Component =
+            serde_json::from_str(&serde_json::to_string(&original).unwrap()).unwrap();
+        assert_eq!(serde_json::to_value(json).unwrap(), expected);
+
+        let encoded = component::Component::from(&original).encode_to_vec();
+        let decoded: Component = component::Component::decode(encoded.as_slice())
+            .unwrap()
+            .try_into()
+            .unwrap();
+        assert_eq!(serde_json::to_value(decoded).unwrap(), expected);
+    }
+
+    #[test]
+    fn keeps_raw_strings_and_one_type_representation() {
+        let proto = component::Component::from(&component());
+        let Some(component::component::ComponentKind::Class(class)) = proto.component_kind else {
+            panic!("not a class");
+        };
+
+        let get = &class.methods[0];
+        assert_eq!(
+            get.signature.as_deref(),
+            Some("<X:Ljava/lang/Exception;>(ILjava/util/Map<TK;*>.Entry<+TK;-[I>;)[[TV;^TX;")
+        );
+        assert_eq!(
+            get.descriptor,
+            "(ILjava/util/Map$Entry;)[[Ljava/lang/Object;"
+        );
+        assert_eq!(class.methods[1].signature.as_deref(), Some("([J)V"));
+        assert_eq!(
+            class.methods[1].return_type,
+            Some(base_type(component::BaseType::Void))
+        );
+        assert_eq!(class.methods[2].signature, None);
+
+        // `int[][]` from a descriptor and from a signature
+        let grid = class.fields[1].r#type.clone();
+        let signature = parse_field_signature("[[I").unwrap().1;
+        assert_eq!(
+            grid,
+            Some(reference_type(&signature.reference_type_signature))
+        );
+        assert_eq!(class.methods[2].parameter_types[0], grid.unwrap());
+
+        let Some(TypeKind::ClassType(entry)) = &get.parameter_types[1].type_kind else {
+            panic!("not a class type");
+        };
+        assert_eq!(entry.package_name, "java.util");
+        assert_eq!(
+            entry
+                .segments
+                .iter()
+                .map(|x| (x.identifier.as_str(), x.type_arguments.len()))
+                .collect::<Vec<_>>(),
+            [("Map", 2), ("Entry", 2)]
+        );
+        assert_eq!(
+            class.interface_types,
+            [binary_name_type("java.io.Serializable")]
+        );
+        assert_eq!(class.type_parameters[0].class_bound, None);
+        assert_eq!(class.type_parameters[0].interface_bounds.len(), 1);
+    }
+}
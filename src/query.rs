@@ -0,0 +1,706 @@
+/*
+Query:
+    Target [where Condition] [select Attribute {, Attribute}]
+
+Target:
+    classes
+    methods
+    fields
+
+Condition:
+    Conjunction {or Conjunction}
+
+Conjunction:
+    Unary {and Unary}
+
+Unary:
+    not Unary
+    ( Condition )
+    is Modifier
+    has field Value
+    has method Value
+    Attribute Operator Value
+
+Operator:
+    =   equal to
+    !=  not equal to
+    ~   matching a glob, with * and ?
+    =~  matching a regular expression
+
+Value:
+    Word
+    "Text without quotes"
+*/
+
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_while, take_while1},
+    character::complete::{char, multispace0, multispace1},
+    combinator::{all_consuming, map, map_opt, not, opt, peek},
+    multi::separated_list1,
+    sequence::{delimited, preceded, terminated},
+    IResult,
+};
+use regex::Regex;
+use serde_json::{json, Map, Value};
+
+use crate::{
+    component::{class_modifiers, Annotation, Component, ComponentKind, Field, Method},
+    dependency::package_name,
+    hierarchy::TypeHierarchy,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Classes,
+    Methods,
+    Fields,
+}
+
+/// What is compared by a condition or output by a column. Types are named as by [`Ty::name`],
+/// e.g. `int[]` or `java.util.Map$Entry`.
+///
+/// [`Ty::name`]: crate::component::Ty::name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Attribute {
+    /// `class`, `enum`, `interface` or `annotation` for classes, `method`, `constructor` or
+    /// `initializer` for methods, and `field` for fields
+    Kind,
+    /// The binary name of a class, or the name of a member
+    Name,
+    /// The binary name of the class, or of the class declaring the member
+    Class,
+    Package,
+    Modifiers,
+    /// The type of each annotation
+    Annotation,
+    Returns,
+    /// The type of each parameter
+    Param,
+    /// The type of a field
+    Type,
+    Descriptor,
+    /// The superclass of a class, or each superinterface of an interface
+    Extends,
+    /// Each interface of a class
+    Implements,
+    /// Each direct or inherited supertype of a class known from the inputs
+    Supertype,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Equal,
+    NotEqual,
+    Glob,
+    Regex,
+}
+
+#[derive(Debug, Clone)]
+pub enum Condition {
+    Not(Box<Condition>),
+    And(Vec<Condition>),
+    Or(Vec<Condition>),
+    /// The item has the modifier, e.g. `static`
+    Is(String),
+    /// The class declares a field, or a method, with a name matching the glob
+    HasField(Pattern),
+    HasMethod(Pattern),
+    /// Some value of the attribute matches, or none does for [`Operator::NotEqual`]
+    Compare(Attribute, Operator, Pattern),
+}
+
+/// A value compared exactly, or a compiled glob or regular expression.
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    Exact(String),
+    Regex(Regex),
+}
+
+impl Pattern {
+    fn matches(&self, value: &str) -> bool {
+        match self {
+            Pattern::Exact(x) => x == value,
+            Pattern::Regex(x) => x.is_match(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Query {
+    pub target: Target,
+    pub condition: Option<Condition>,
+    pub columns: Vec<Attribute>,
+}
+
+/// The columns and rows selected by a query, where each cell holds the values of a column's
+/// attribute.
+#[derive(Debug)]
+pub struct QueryResult {
+    pub columns: Vec<Attribute>,
+    pub rows: Vec<Vec<Vec<String>>>,
+}
+
+/// A class, method or field being filtered.
+enum Item<'a> {
+    Class(&'a Component),
+    Method(&'a Component, &'a Method),
+    Field(&'a Component, &'a Field),
+}
+
+impl Attribute {
+    fn name(self) -> &'static str {
+        match self {
+            Attribute::Kind => "kind",
+            Attribute::Name => "name",
+            Attribute::Class => "class",
+            Attribute::Package => "package",
+            Attribute::Modifiers => "modifiers",
+            Attribute::Annotation => "annotation",
+            Attribute::Returns => "returns",
+            Attribute::Param => "param",
+            Attribute::Type => "type",
+            Attribute::Descriptor => "descriptor",
+            Attribute::Extends => "extends",
+            Attribute::Implements => "implements",
+            Attribute::Supertype => "supertype",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        [
+            Attribute::Kind,
+            Attribute::Name,
+            Attribute::Class,
+            Attribute::Package,
+            Attribute::Modifiers,
+            Attribute::Annotation,
+            Attribute::Returns,
+            Attribute::Param,
+            Attribute::Type,
+            Attribute::Descriptor,
+            Attribute::Extends,
+            Attribute::Implements,
+            Attribute::Supertype,
+        ]
+        .into_iter()
+        .find(|x| x.name() == name)
+    }
+
+    fn applies_to(self, target: Target) -> bool {
+        match self {
+            Attribute::Kind
+            | Attribute::Name
+            | Attribute::Class
+            | Attribute::Package
+            | Attribute::Modifiers
+            | Attribute::Annotation => true,
+            Attribute::Returns | Attribute::Param => target == Target::Methods,
+            Attribute::Type => target == Target::Fields,
+            Attribute::Descriptor => target != Target::Classes,
+            Attribute::Extends | Attribute::Implements | Attribute::Supertype => {
+                target == Target::Classes
+            }
+        }
+    }
+
+    /// Whether an item may have several values, which are output as a JSON array.
+    fn is_list(self) -> bool {
+        matches!(
+            self,
+            Attribute::Annotation
+                | Attribute::Param
+                | Attribute::Extends
+                | Attribute::Implements
+                | Attribute::Supertype
+        )
+    }
+}
+
+impl Query {
+    /// Evaluates the query over components, reading supertypes from all of them.
+    pub fn run(&self, components: &[Component]) -> QueryResult {
+        let hierarchy = TypeHierarchy::new(components);
+        let classes = components.iter().filter(|x| x.qualified_name().is_some());
+        let items: Vec<Item> = match self.target {
+            Target::Classes => classes.map(Item::Class).collect(),
+            Target::Methods => classes
+                .flat_map(|c| c.methods().iter().map(move |m| Item::Method(c, m)))
+                .collect(),
+            Target::Fields => classes
+                .flat_map(|c| c.fields().iter().map(move |f| Item::Field(c, f)))
+                .collect(),
+        };
+
+        let rows = items
+            .iter()
+            .filter(|item| match &self.condition {
+                Some(condition) => item.satisfies(condition, &hierarchy),
+                None => true,
+            })
+            .map(|item| {
+                self.columns
+                    .iter()
+                    .map(|x| item.values(*x, &hierarchy))
+                    .collect()
+            })
+            .collect();
+        QueryResult {
+            columns: self.columns.clone(),
+            rows,
+        }
+    }
+}
+
+impl Item<'_> {
+    fn component(&self) -> &Component {
+        match self {
+            Item::Class(c) | Item::Method(c, _) | Item::Field(c, _) => c,
+        }
+    }
+
+    fn class_name(&self) -> &str {
+        self.component().qualified_name().unwrap_or_default()
+    }
+
+    fn modifiers(&self) -> String {
+        match self {
+            Item::Class(c) => class_modifiers(c.access_flags()),
+            Item::Method(_, m) => m.modifiers.clone(),
+            Item::Field(_, f) => f.modifiers.clone(),
+        }
+    }
+
+    fn annotations(&self) -> &[Annotation] {
+        match self {
+            Item::Class(c) => c.annotations(),
+            Item::Method(_, m) => &m.annotations,
+            Item::Field(_, f) => &f.annotations,
+        }
+    }
+
+    fn values(&self, attribute: Attribute, hierarchy: &TypeHierarchy) -> Vec<String> {
+        let class_name = self.class_name();
+        match (attribute, self) {
+            (Attribute::Kind, Item::Class(c)) => vec![match &c.kind {
+                ComponentKind::Class(x) if x.is_enum => "enum",
+                ComponentKind::Interface(x) if x.is_annotation => "annotation",
+                ComponentKind::Interface(_) => "interface",
+                _ => "class",
+            }
+            .to_string()],
+            (Attribute::Kind, Item::Method(_, m)) => vec![match m.name.as_str() {
+                "<init>" => "constructor",
+                "<clinit>" => "initializer",
+                _ => "method",
+            }
+            .to_string()],
+            (Attribute::Kind, Item::Field(..)) => vec!["field".to_string()],
+            (Attribute::Name, Item::Class(_)) | (Attribute::Class, _) => {
+                vec![class_name.to_string()]
+            }
+            (Attribute::Name, Item::Method(_, m)) => vec![m.name.clone()],
+            (Attribute::Name, Item::Field(_, f)) => vec![f.name.clone()],
+            (Attribute::Package, _) => vec![package_name(class_name).to_string()],
+            (Attribute::Modifiers, _) => vec![self.modifiers()],
+            (Attribute::Annotation, _) => self.annotations().iter().map(|x| x.ty.name()).collect(),
+            (Attribute::Returns, Item::Method(_, m)) => vec![m.ret_ty.name()],
+            (Attribute::Param, Item::Method(_, m)) => {
+                m.param_tys.iter().map(|x| x.name()).collect()
+            }
+            (Attribute::Type, Item::Field(_, f)) => vec![f.ty.name()],
+            (Attribute::Descriptor, Item::Method(_, m)) => vec![m.descriptor.clone()],
+            (Attribute::Descriptor, Item::Field(_, f)) => vec![f.descriptor.clone()],
+            (Attribute::Extends, Item::Class(c)) if c.is_interface() => c.interfaces().to_vec(),
+            (Attribute::Extends, Item::Class(c)) => {
+                c.super_class().map(str::to_string).into_iter().collect()
+            }
+            (Attribute::Implements, Item::Class(c)) if !c.is_interface() => c.interfaces().to_vec(),
+            (Attribute::Supertype, Item::Class(_)) => hierarchy
+                .supertypes(class_name)
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn satisfies(&self, condition: &Condition, hierarchy: &TypeHierarchy) -> bool {
+        match condition {
+            Condition::Not(x) => !self.satisfies(x, hierarchy),
+            Condition::And(x) => x.iter().all(|x| self.satisfies(x, hierarchy)),
+            Condition::Or(x) => x.iter().any(|x| self.satisfies(x, hierarchy)),
+            Condition::Is(modifier) => self.modifiers().split(' ').any(|x| x == modifier),
+            Condition::HasField(pattern) => {
+                let fields = self.component().fields();
+                fields.iter().any(|x| pattern.matches(&x.name))
+            }
+            Condition::HasMethod(pattern) => {
+                let methods = self.component().methods();
+                methods.iter().any(|x| pattern.matches(&x.name))
+            }
+            Condition::Compare(attribute, operator, pattern) => {
+                let values = self.values(*attribute, hierarchy);
+                let any = values.iter().any(|x| pattern.matches(x));
+                match operator {
+                    Operator::NotEqual => !any,
+                    _ => any,
+                }
+            }
+        }
+    }
+}
+
+impl QueryResult {
+    /// Renders the rows as a table with a header, joining the values of list attributes.
+    pub fn to_table(&self) -> String {
+        let cells = self
+            .rows
+            .iter()
+            .map(|row| row.iter().map(|x| x.join(", ")).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let header = self
+            .columns
+            .iter()
+            .map(|x| x.name().to_uppercase())
+            .collect::<Vec<_>>();
+        let widths = (0..self.columns.len())
+            .map(|i| {
+                cells
+                    .iter()
+                    .map(|x| x[i].len())
+                    .chain([header[i].len()])
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>();
+
+        let mut out = String::new();
+        for row in std::iter::once(&header).chain(&cells) {
+            let line = row
+                .iter()
+                .zip(&widths)
+                .map(|(x, width)| format!("{x:width$}"))
+                .collect::<Vec<_>>()
+                .join("  ");
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Renders the rows as objects keyed by attribute, with arrays for list attributes.
+    pub fn to_json(&self) -> Value {
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| {
+                let object = self
+                    .columns
+                    .iter()
+                    .zip(row)
+                    .map(|(column, values)| {
+                        let value = if column.is_list() {
+                            json!(values)
+                        } else {
+                            json!(values.first())
+                        };
+                        (column.name().to_string(), value)
+                    })
+                    .collect::<Map<_, _>>();
+                Value::Object(object)
+            })
+            .collect();
+        Value::Array(rows)
+    }
+}
+
+/// Parses a query, e.g. `methods where is static and returns = java.util.Optional select
+/// class, name`.
+pub fn parse_query(input: &str) -> Result<Query, String> {
+    let (_, (target, condition, columns)) =
+        all_consuming(delimited(multispace0, query, multispace0))(input).map_err(|e| match e {
+            nom::Err::Error(e) | nom::Err::Failure(e) if !e.input.is_empty() => {
+                format!("Invalid query at `{}`", e.input)
+            }
+            _ => "Incomplete query".to_string(),
+        })?;
+
+    let columns = columns.unwrap_or_else(|| match target {
+        Target::Classes => vec![Attribute::Kind, Attribute::Name],
+        Target::Methods => vec![
+            Attribute::Class,
+            Attribute::Modifiers,
+            Attribute::Returns,
+            Attribute::Name,
+            Attribute::Param,
+        ],
+        Target::Fields => vec![
+            Attribute::Class,
+            Attribute::Modifiers,
+            Attribute::Type,
+            Attribute::Name,
+        ],
+    });
+    let mut attributes = columns.clone();
+    if let Some(condition) = &condition {
+        check_condition(condition, target, &mut attributes)?;
+    }
+    if let Some(x) = attributes.iter().find(|x| !x.applies_to(target)) {
+        return Err(format!(
+            "`{}` does not apply to {}",
+            x.name(),
+            target_name(target)
+        ));
+    }
+
+    Ok(Query {
+        target,
+        condition,
+        columns,
+    })
+}
+
+fn target_name(target: Target) -> &'static str {
+    match target {
+        Target::Classes => "classes",
+        Target::Methods => "methods",
+        Target::Fields => "fields",
+    }
+}
+
+/// Collects the attributes of a condition and rejects `has` outside class queries.
+fn check_condition(
+    condition: &Condition,
+    target: Target,
+    attributes: &mut Vec<Attribute>,
+) -> Result<(), String> {
+    match condition {
+        Condition::Not(x) => check_condition(x, target, attributes),
+        Condition::And(x) | Condition::Or(x) => x
+            .iter()
+            .try_for_each(|x| check_condition(x, target, attributes)),
+        Condition::Is(_) => Ok(()),
+        Condition::HasField(_) | Condition::HasMethod(_) if target != Target::Classes => {
+            Err(format!("`has` does not apply to {}", target_name(target)))
+        }
+        Condition::HasField(_) | Condition::HasMethod(_) => Ok(()),
+        Condition::Compare(attribute, _, _) => {
+            attributes.push(*attribute);
+            Ok(())
+        }
+    }
+}
+
+type Parsed = (Target, Option<Condition>, Option<Vec<Attribute>>);
+
+fn query(input: &str) -> IResult<&str, Parsed> {
+    let (input, target) = alt((
+        map(keyword("classes"), |_| Target::Classes),
+        map(keyword("methods"), |_| Target::Methods),
+        map(keyword("fields"), |_| Target::Fields),
+    ))(input)?;
+    let (input, condition) = opt(preceded(
+        delimited(multispace1, keyword("where"), multispace1),
+        condition,
+    ))(input)?;
+    let (input, columns) = opt(preceded(
+        delimited(multispace1, keyword("select"), multispace1),
+        separated_list1(
+            delimited(multispace0, char(','), multispace0),
+            map_opt(word, Attribute::from_name),
+        ),
+    ))(input)?;
+    Ok((input, (target, condition, columns)))
+}
+
+fn condition(input: &str) -> IResult<&str, Condition> {
+    let (input, mut x) = separated_list1(
+        delimited(multispace1, keyword("or"), multispace1),
+        conjunction,
+    )(input)?;
+    let condition = if x.len() == 1 {
+        x.remove(0)
+    } else {
+        Condition::Or(x)
+    };
+    Ok((input, condition))
+}
+
+fn conjunction(input: &str) -> IResult<&str, Condition> {
+    let (input, mut x) =
+        separated_list1(delimited(multispace1, keyword("and"), multispace1), unary)(input)?;
+    let condition = if x.len() == 1 {
+        x.remove(0)
+    } else {
+        Condition::And(x)
+    };
+    Ok((input, condition))
+}
+
+fn unary(input: &str) -> IResult<&str, Condition> {
+    alt((
+        map(
+            preceded(terminated(keyword("not"), multispace1), unary),
+            |x| Condition::Not(Box::new(x)),
+        ),
+        delimited(
+            terminated(char('('), multispace0),
+            condition,
+            preceded(multispace0, char(')')),
+        ),
+        map(
+            preceded(terminated(keyword("is"), multispace1), word),
+            |x| Condition::Is(x.to_string()),
+        ),
+        map(
+            preceded(
+                delimited(keyword("has"), multispace1, keyword("field")),
+                preceded(multispace1, value),
+            ),
+            |x| Condition::HasField(glob(x)),
+        ),
+        map(
+            preceded(
+                delimited(keyword("has"), multispace1, keyword("method")),
+                preceded(multispace1, value),
+            ),
+            |x| Condition::HasMethod(glob(x)),
+        ),
+        comparison,
+    ))(input)
+}
+
+fn comparison(input: &str) -> IResult<&str, Condition> {
+    let (input, attribute) = map_opt(word, Attribute::from_name)(input)?;
+    let (input, operator) = delimited(
+        multispace0,
+        alt((
+            map(tag("!="), |_| Operator::NotEqual),
+            map(tag("=~"), |_| Operator::Regex),
+            map(tag("="), |_| Operator::Equal),
+            map(tag("~"), |_| Operator::Glob),
+        )),
+        multispace0,
+    )(input)?;
+    let (rest, x) = value(input)?;
+    let pattern = match operator {
+        Operator::Equal | Operator::NotEqual => Pattern::Exact(x.to_string()),
+        Operator::Glob => glob(x),
+        Operator::Regex => match Regex::new(x) {
+            Ok(x) => Pattern::Regex(x),
+            Err(_) => {
+                return Err(nom::Err::Failure(nom::error::Error::new(
+                    input,
+                    nom::error::ErrorKind::Verify,
+                )))
+            }
+        },
+    };
+    Ok((rest, Condition::Compare(attribute, operator, pattern)))
+}
+
+fn glob(pattern: &str) -> Pattern {
+    let regex = regex::escape(pattern)
+        .replace("\\*", ".*")
+        .replace("\\?", ".");
+    Pattern::Regex(Regex::new(&format!("^{regex}$")).unwrap())
+}
+
+fn value(input: &str) -> IResult<&str, &str> {
+    alt((
+        delimited(char('"'), take_while(|x| x != '"'), char('"')),
+        word,
+    ))(input)
+}
+
+fn word(input: &str) -> IResult<&str, &str> {
+    take_while1(is_word_char)(input)
+}
+
+fn is_word_char(x: char) -> bool {
+    x.is_alphanumeric() || "_$.*?[]<>/".contains(x)
+}
+
+fn keyword<'a>(keyword: &'static str) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str> {
+    terminated(tag(keyword), not(peek(take_while1(is_word_char))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        component::{Class, Interface},
+        test_util::{self, component, field, method},
+    };
+
+    fn class(name: &str, super_class: &str, interfaces: &[&str], fields: &[&str]) -> Component {
+        component(ComponentKind::Class(Class {
+            super_class: Some(super_class.to_string()),
+            interfaces: interfaces.iter().map(|x| x.to_string()).collect(),
+            methods: vec![
+                method("find", "(I)Ljava/util/Optional;", None, 0x0009),
+                method("get", "(I)Ljava/util/Optional;", None, 0x0001),
+            ],
+            fields: fields.iter().map(|x| field(x, "J", None, 0x000a)).collect(),
+            ..test_util::class(name)
+        }))
+    }
+
+    #[test]
+    fn filters_and_projects_components() {
+        let components = vec![
+            component(ComponentKind::Interface(Interface {
+                interfaces: vec!["java.io.Serializable".to_string()],
+                ..test_util::interface("a.Entity")
+            })),
+            class(
+                "a.User",
+                "java.lang.Object",
+                &["a.Entity"],
+                &["serialVersionUID"],
+            ),
+            class("a.Order", "java.lang.Object", &["a.Entity"], &[]),
+            class("b.Service", "java.lang.Object", &[], &[]),
+        ];
+        let run = |query: &str| parse_query(query).unwrap().run(&components);
+
+        let result = run(
+            "methods where is public and is static and returns = java.util.Optional \
+             and package ~ \"a*\" select class, name",
+        );
+        assert_eq!(
+            result.to_table(),
+            "CLASS    NAME\na.User   find\na.Order  find\n"
+        );
+        let result = run(
+            "classes where kind = class and supertype = java.io.Serializable \
+             and not has field serialVersionUID",
+        );
+        assert_eq!(
+            result.to_json(),
+            json!([{"kind": "class", "name": "a.Order"}])
+        );
+        let result = run(
+            "classes where name =~ \"^a\\.[EU]\" or implements != a.Entity select name, extends",
+        );
+        assert_eq!(
+            result.to_json(),
+            json!([
+                {"name": "a.Entity", "extends": ["java.io.Serializable"]},
+                {"name": "a.User", "extends": ["java.lang.Object"]},
+                {"name": "b.Service", "extends": ["java.lang.Object"]},
+            ])
+        );
+
+        assert_eq!(
+            parse_query("classes where returns = int").unwrap_err(),
+            "`returns` does not apply to classes"
+        );
+        assert_eq!(
+            parse_query("methods where name = get and").unwrap_err(),
+            "Invalid query at `and`"
+        );
+        assert!(parse_query("fields where has method x").is_err());
+    }
+}
@@ -0,0 +1,194 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Serialize, Serializer};
+
+use crate::conflicts::ClasspathEntry;
+
+/// The minor version of class files that use preview features of their release.
+pub const PREVIEW_MINOR_VERSION: u16 = 0xFFFF;
+
+/// A Java SE release, numbered by feature version: 1.1 to 1.4 are 1 to 4, Java 5 is 5.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct JavaRelease(pub u16);
+
+impl JavaRelease {
+    /// Returns the release introducing a class file major version, which is 45 for 1.1 (and
+    /// 1.0.2), 52 for Java 8 and 61 for Java 17.
+    pub fn from_major_version(major_version: u16) -> Option<Self> {
+        major_version.checked_sub(44).filter(|x| *x > 0).map(Self)
+    }
+
+    /// The highest class file major version the release can run.
+    pub fn major_version(self) -> u16 {
+        self.0 + 44
+    }
+}
+
+impl fmt::Display for JavaRelease {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0 < 5 {
+            write!(f, "1.{}", self.0)
+        } else {
+            write!(f, "{}", self.0)
+        }
+    }
+}
+
+impl FromStr for JavaRelease {
+    type Err = String;
+
+    /// Parses `11`, `1.4` or `1.8`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let feature = s.strip_prefix("1.").unwrap_or(s);
+        match feature.parse::<u16>() {
+            Ok(x) if x > 0 && x < u16::MAX - 44 => Ok(Self(x)),
+            _ => Err(format!("Invalid Java release: {s}")),
+        }
+    }
+}
+
+impl Serialize for JavaRelease {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Whether a class file uses preview features, and so only runs on exactly its release with
+/// `--enable-preview`.
+pub fn is_preview(minor_version: u16) -> bool {
+    minor_version == PREVIEW_MINOR_VERSION
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClassRelease {
+    pub qualified_name: String,
+    pub release: JavaRelease,
+}
+
+/// The range of releases targeted by the classes of one JAR file or class directory.
+#[derive(Debug, Serialize)]
+pub struct ReleaseSummary {
+    pub source: String,
+    pub classes: usize,
+    pub min_release: Option<JavaRelease>,
+    pub max_release: Option<JavaRelease>,
+    pub preview_classes: Vec<ClassRelease>,
+    /// Classes targeting a newer release than allowed
+    pub too_new: Vec<ClassRelease>,
+}
+
+/// Summarizes the targeted releases per classpath element, in input order, and lists the
+/// classes newer than `allowed`.
+pub fn summarize_releases(
+    entries: &[ClasspathEntry],
+    allowed: Option<JavaRelease>,
+) -> Vec<ReleaseSummary> {
+    let mut summaries: Vec<ReleaseSummary> = Vec::new();
+    for entry in entries {
+        let index = match summaries.iter().position(|x| x.source == entry.source) {
+            Some(index) => index,
+            None => {
+                summaries.push(ReleaseSummary {
+                    source: entry.source.clone(),
+                    classes: 0,
+                    min_release: None,
+                    max_release: None,
+                    preview_classes: Vec::new(),
+                    too_new: Vec::new(),
+                });
+                summaries.len() - 1
+            }
+        };
+        let summary = &mut summaries[index];
+        summary.classes += 1;
+
+        let Some(release) = entry.component.release() else {
+            continue;
+        };
+        summary.min_release = Some(summary.min_release.map_or(release, |x| x.min(release)));
+        summary.max_release = Some(summary.max_release.map_or(release, |x| x.max(release)));
+
+        let class = || ClassRelease {
+            qualified_name: entry
+                .component
+                .qualified_name()
+                .unwrap_or_default()
+                .to_string(),
+            release,
+        };
+        if entry.component.is_preview() {
+            summary.preview_classes.push(class());
+        }
+        if allowed.is_some_and(|x| release > x) {
+            summary.too_new.push(class());
+        }
+    }
+    summaries
+}
+
+pub fn releases_to_table(summaries: &[ReleaseSummary], allowed: Option<JavaRelease>) -> String {
+    let width = summaries
+        .iter()
+        .map(|x| x.source.len())
+        .chain(std::iter::once("SOURCE".len()))
+        .max()
+        .unwrap_or_default();
+    let release = |x: Option<JavaRelease>| x.map_or("-".to_string(), |x| x.to_string());
+
+    let mut out = format!(
+        "{:width$}  {:>7}  {:>4}  {:>4}  {:>7}\n",
+        "SOURCE", "CLASSES", "MIN", "MAX", "PREVIEW"
+    );
+    for summary in summaries {
+        out.push_str(&format!(
+            "{:width$}  {:>7}  {:>4}  {:>4}  {:>7}\n",
+            summary.source,
+            summary.classes,
+            release(summary.min_release),
+            release(summary.max_release),
+            summary.preview_classes.len()
+        ));
+    }
+
+    for summary in summaries {
+        for class in &summary.preview_classes {
+            out.push_str(&format!(
+                "{}: {} uses preview features of Java {}\n",
+                summary.source, class.qualified_name, class.release
+            ));
+        }
+    }
+    if let Some(allowed) = allowed {
+        for summary in summaries {
+            for class in &summary.too_new {
+                out.push_str(&format!(
+                    "{}: {} requires Java {}, newer than Java {allowed}\n",
+                    summary.source, class.qualified_name, class.release
+                ));
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_major_versions_to_releases() {
+        let release = |major| JavaRelease::from_major_version(major).map(|x| x.to_string());
+        assert_eq!(release(44), None);
+        assert_eq!(release(45).as_deref(), Some("1.1"));
+        assert_eq!(release(48).as_deref(), Some("1.4"));
+        assert_eq!(release(49).as_deref(), Some("5"));
+        assert_eq!(release(52).as_deref(), Some("8"));
+        assert_eq!(release(67).as_deref(), Some("23"));
+
+        assert_eq!("1.8".parse(), Ok(JavaRelease(8)));
+        assert_eq!("1.4".parse::<JavaRelease>().unwrap().major_version(), 48);
+        assert_eq!("11".parse(), Ok(JavaRelease(11)));
+        assert!("eleven".parse::<JavaRelease>().is_err());
+        assert!(is_preview(0xFFFF));
+    }
+}
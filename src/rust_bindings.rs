@@ -0,0 +1,1168 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::{
+    class_file::{AccessFlags, FieldAccessFlags, MethodAccessFlags},
+    component::{Component, ComponentKind, ConstantValue, Field, Method, PrimTy, Ty},
+    signature::{
+        BaseType, ClassTypeSignature, ReferenceTypeSignature, Result, TypeArgument, TypeSignature,
+        WildcardIndicator,
+    },
+};
+
+const INDENT: &str = "    ";
+
+/// Names taken by the `JavaObject` trait of the `jni` bindings.
+const RESERVED: [&str; 2] = ["from_object", "as_object"];
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RustBindingOptions {
+    /// Whether to emit glue calling the Java methods by name and descriptor through the `jni`
+    /// crate (0.19), instead of `unimplemented!()` bodies
+    pub jni: bool,
+}
+
+/// Renders the public classes and interfaces of the components as one Rust source file, with a
+/// module per package. Classes become structs and interfaces become traits, with generics kept
+/// as Rust generics. Member classes are named `Outer_Inner`, methods and fields are converted to
+/// snake case, and overloads after the first get a numeric suffix. Only public members are
+/// bound, and annotation interfaces are skipped.
+///
+/// Without `jni`, the public instance fields of a class are fields of its struct. With `jni`,
+/// structs wrap a global reference to the Java object, fields are read through getters, and
+/// every struct implements the traits of the interfaces its class implements.
+pub fn rust_bindings(components: &[Component], options: RustBindingOptions) -> String {
+    let bindings = Bindings::new(components, options);
+
+    let mut tree = ModuleTree::default();
+    for component in components {
+        let Some(name) = component.qualified_name() else {
+            continue;
+        };
+        if !bindings
+            .classes
+            .get(name)
+            .is_some_and(|x| std::ptr::eq(*x, component))
+            || !bindings.paths.contains_key(name)
+        {
+            continue;
+        }
+        let mut module = &mut tree;
+        if let Some((package, _)) = name.rsplit_once('.') {
+            for segment in package.split('.') {
+                module = module.children.entry(identifier(segment)).or_default();
+            }
+        }
+        module.classes.push(component);
+    }
+
+    let mut out = String::new();
+    out.push_str(
+        "//! Rust bindings for Java classes, generated by classreader.\n\
+         #![allow(dead_code, non_camel_case_types, non_snake_case, unused_imports)]\n\
+         #![allow(unused_variables, clippy::all)]\n",
+    );
+    if options.jni {
+        out.push_str(
+            r#"
+/// A Java object wrapped by a binding type.
+pub trait JavaObject {
+    fn from_object(object: ::jni::objects::GlobalRef) -> Self
+    where
+        Self: Sized;
+
+    fn as_object(&self) -> ::jni::objects::JObject<'_>;
+}
+
+/// Any Java object whose class is not among the bindings, and Java arrays.
+pub struct Object {
+    object: ::jni::objects::GlobalRef,
+}
+
+impl JavaObject for Object {
+    fn from_object(object: ::jni::objects::GlobalRef) -> Self {
+        Self { object }
+    }
+
+    fn as_object(&self) -> ::jni::objects::JObject<'_> {
+        self.object.as_obj()
+    }
+}
+"#,
+        );
+    } else {
+        out.push_str(
+            r#"
+/// Any Java object whose class is not among the bindings.
+pub struct Object;
+"#,
+        );
+    }
+    out.push('\n');
+    bindings.module(&tree, 0, &mut out);
+    out
+}
+
+#[derive(Default)]
+struct ModuleTree<'a> {
+    children: BTreeMap<String, ModuleTree<'a>>,
+    classes: Vec<&'a Component>,
+}
+
+/// A Java type as it appears in the bindings.
+#[derive(Debug, Clone)]
+enum RustTy {
+    /// A primitive with its Rust name, `JValue` variant and `JValue` accessor
+    Prim(&'static str, &'static str, &'static str),
+    Unit,
+    String,
+    /// Any object outside the bindings, and arrays with `jni`
+    Object,
+    Vec(Box<RustTy>),
+    Generic(String),
+    /// A struct by its path from the root module, with type arguments
+    Struct(String, Vec<RustTy>),
+    /// A trait by its path from the root module, with type arguments
+    Trait(String, Vec<RustTy>),
+}
+
+/// The Rust types of type variables in scope, by their Java name.
+type Scope = HashMap<String, RustTy>;
+
+struct Bindings<'a> {
+    options: RustBindingOptions,
+    /// Classes and interfaces by binary name
+    classes: HashMap<&'a str, &'a Component>,
+    /// Paths from the root module of the bound classes and interfaces, by binary name
+    paths: HashMap<&'a str, String>,
+}
+
+impl<'a> Bindings<'a> {
+    fn new(components: &'a [Component], options: RustBindingOptions) -> Self {
+        let mut classes = HashMap::new();
+        for component in components {
+            if let Some(name) = component.qualified_name() {
+                classes.entry(name).or_insert(component);
+            }
+        }
+
+        let mut paths = HashMap::new();
+        for (name, component) in &classes {
+            let is_member_or_top_level = match &component.kind {
+                ComponentKind::Class(x) => x
+                    .inner_class
+                    .as_ref()
+                    .is_none_or(|x| x.outer_class.is_some() && x.simple_name.is_some()),
+                ComponentKind::Interface(x) => {
+                    !x.is_annotation
+                        && x.inner_class
+                            .as_ref()
+                            .is_none_or(|x| x.outer_class.is_some() && x.simple_name.is_some())
+                }
+                ComponentKind::Module(_) => false,
+            };
+            let flags = component.access_flags();
+            if !is_member_or_top_level
+                || !flags.contains(AccessFlags::PUBLIC)
+                || flags.contains(AccessFlags::SYNTHETIC)
+                || name.ends_with("package-info")
+            {
+                continue;
+            }
+
+            let (package, simple_name) = match name.rsplit_once('.') {
+                Some((package, simple_name)) => (Some(package), simple_name),
+                None => (None, *name),
+            };
+            let mut path = package
+                .into_iter()
+                .flat_map(|x| x.split('.'))
+                .map(identifier)
+                .collect::<Vec<_>>();
+            path.push(identifier(simple_name));
+            paths.insert(*name, path.join("::"));
+        }
+
+        Bindings {
+            options,
+            classes,
+            paths,
+        }
+    }
+
+    /// Renders the items of a module, separated by blank lines.
+    fn module(&self, tree: &ModuleTree, depth: usize, out: &mut String) {
+        let indent = INDENT.repeat(depth);
+        let mut items = Vec::new();
+        if self.options.jni && depth > 0 && !tree.classes.is_empty() {
+            items.push(format!("{indent}use {}JavaObject as _;\n", root(depth)));
+        }
+
+        let mut classes = tree.classes.clone();
+        classes.sort_by_key(|x| x.qualified_name());
+        for component in classes {
+            let mut item = String::new();
+            if component.is_interface() {
+                self.interface(component, depth, &mut item);
+            } else {
+                self.class(component, depth, &mut item);
+            }
+            items.push(item);
+        }
+
+        for (name, child) in &tree.children {
+            let mut item = format!("{indent}pub mod {name} {{\n");
+            self.module(child, depth + 1, &mut item);
+            item.push_str(&format!("{indent}}}\n"));
+            items.push(item);
+        }
+        out.push_str(&items.join("\n"));
+    }
+
+    fn class(&self, component: &Component, depth: usize, out: &mut String) {
+        let jni = self.options.jni;
+        let root = root(depth);
+        let indent = INDENT.repeat(depth);
+        let member_indent = INDENT.repeat(depth + 1);
+        let name = component.qualified_name().unwrap();
+        let rust_name = self.paths[name].rsplit("::").next().unwrap();
+        let (type_params, scope) = self.type_parameters(component);
+        let arguments = arguments(&type_params);
+
+        out.push_str(&format!("{indent}/// The Java class `{name}`.\n"));
+        let mut struct_fields = Vec::new();
+        if jni {
+            struct_fields.push("object: ::jni::objects::GlobalRef".to_string());
+        } else {
+            let mut used = HashSet::new();
+            for field in public_fields(component) {
+                if !field.access_flags.contains(FieldAccessFlags::STATIC) {
+                    let ty = self.field_type(field, &scope);
+                    let field_name = unique(&mut used, identifier(&snake_case(&field.name)));
+                    let ty = match ty {
+                        // Boxed, since classes can refer to themselves through their fields
+                        RustTy::Struct(..) => {
+                            format!("::std::boxed::Box<{}>", self.render(&ty, depth))
+                        }
+                        _ => self.render(&ty, depth),
+                    };
+                    struct_fields.push(format!("pub {field_name}: {ty}"));
+                }
+            }
+        }
+        if !type_params.is_empty() {
+            let marker = if jni { "marker" } else { "_marker" };
+            struct_fields.push(format!(
+                "{marker}: ::std::marker::PhantomData<{}>",
+                if type_params.len() == 1 {
+                    type_params[0].clone()
+                } else {
+                    format!("({})", type_params.join(", "))
+                }
+            ));
+        }
+        if struct_fields.is_empty() {
+            out.push_str(&format!("{indent}pub struct {rust_name};\n"));
+        } else {
+            out.push_str(&format!("{indent}pub struct {rust_name}{arguments} {{\n"));
+            for field in struct_fields {
+                out.push_str(&format!("{member_indent}{field},\n"));
+            }
+            out.push_str(&format!("{indent}}}\n"));
+        }
+
+        if jni {
+            let generics = generics(&type_params, "");
+            let marker = if type_params.is_empty() {
+                ""
+            } else {
+                ", marker: ::std::marker::PhantomData"
+            };
+            out.push_str(&format!(
+                r#"
+{indent}impl{generics} {root}JavaObject for {rust_name}{arguments} {{
+{member_indent}fn from_object(object: ::jni::objects::GlobalRef) -> Self {{
+{member_indent}{INDENT}Self {{ object{marker} }}
+{member_indent}}}
+
+{member_indent}fn as_object(&self) -> ::jni::objects::JObject<'_> {{
+{member_indent}{INDENT}self.object.as_obj()
+{member_indent}}}
+{indent}}}
+"#
+            ));
+        }
+
+        let is_abstract = component.access_flags().contains(AccessFlags::ABSTRACT);
+        let mut used = initial_names(jni);
+        let mut members = Vec::new();
+        self.fields(component, &scope, &mut used, depth + 1, &mut members);
+        for method in public_methods(component) {
+            if method.name == "<init>" && is_abstract {
+                continue;
+            }
+            let method_name = unique(&mut used, method_name(method));
+            members.push(self.method(component, &scope, method, &method_name, false, depth + 1));
+        }
+        if !members.is_empty() {
+            let generics = generics(&type_params, if jni { &root } else { "" });
+            out.push_str(&format!(
+                "\n{indent}impl{generics} {rust_name}{arguments} {{\n{}{indent}}}\n",
+                members.join("\n")
+            ));
+        }
+
+        if jni {
+            let generics = generics(&type_params, &root);
+            for ty in self.implemented_traits(component, &scope) {
+                out.push_str(&format!(
+                    "\n{indent}impl{generics} {} for {rust_name}{arguments} {{}}\n",
+                    self.trait_path(&ty, depth)
+                ));
+            }
+        }
+    }
+
+    fn interface(&self, component: &Component, depth: usize, out: &mut String) {
+        let jni = self.options.jni;
+        let root = root(depth);
+        let indent = INDENT.repeat(depth);
+        let name = component.qualified_name().unwrap();
+        let rust_name = self.paths[name].rsplit("::").next().unwrap();
+        let (type_params, scope) = self.type_parameters(component);
+        let arguments = arguments(&type_params);
+        let generics = generics(&type_params, if jni { &root } else { "" });
+
+        let mut supertraits = Vec::new();
+        if jni {
+            supertraits.push(format!("{root}JavaObject"));
+        }
+        for ty in self.implemented_traits(component, &scope) {
+            let RustTy::Trait(path, args) = ty else {
+                continue;
+            };
+            // Trait objects in the arguments of supertraits can refer back to the trait
+            let args = args.into_iter().map(erase_traits).collect();
+            supertraits.push(self.trait_path(&RustTy::Trait(path, args), depth));
+        }
+        let supertraits = if supertraits.is_empty() {
+            String::new()
+        } else {
+            format!(": {}", supertraits.join(" + "))
+        };
+
+        let mut used = initial_names(jni);
+        let mut instance_methods = Vec::new();
+        let mut static_members = Vec::new();
+        self.fields(component, &scope, &mut used, depth + 1, &mut static_members);
+        for method in public_methods(component) {
+            let method_name = unique(&mut used, method_name(method));
+            if method.access_flags.contains(MethodAccessFlags::STATIC) {
+                static_members.push(self.method(
+                    component,
+                    &scope,
+                    method,
+                    &method_name,
+                    false,
+                    depth + 1,
+                ));
+            } else {
+                instance_methods.push(self.method(
+                    component,
+                    &scope,
+                    method,
+                    &method_name,
+                    true,
+                    depth + 1,
+                ));
+            }
+        }
+
+        out.push_str(&format!("{indent}/// The Java interface `{name}`.\n"));
+        if instance_methods.is_empty() {
+            out.push_str(&format!(
+                "{indent}pub trait {rust_name}{generics}{supertraits} {{}}\n"
+            ));
+        } else {
+            out.push_str(&format!(
+                "{indent}pub trait {rust_name}{generics}{supertraits} {{\n{}{indent}}}\n",
+                instance_methods.join("\n")
+            ));
+        }
+        // Constants and static members are not allowed in a trait usable as `dyn`
+        if !static_members.is_empty() {
+            out.push_str(&format!(
+                "\n{indent}impl{generics} dyn {rust_name}{arguments} {{\n{}{indent}}}\n",
+                static_members.join("\n")
+            ));
+        }
+        // Objects of unknown classes returned as the interface
+        if jni {
+            out.push_str(&format!(
+                "\n{indent}impl{generics} {rust_name}{arguments} for {root}Object {{}}\n"
+            ));
+        }
+    }
+
+    /// Renders the public fields that are not struct fields: constants, and with `jni` getters
+    /// for the other fields.
+    fn fields(
+        &self,
+        component: &Component,
+        scope: &Scope,
+        used: &mut HashSet<String>,
+        depth: usize,
+        members: &mut Vec<String>,
+    ) {
+        let indent = INDENT.repeat(depth);
+        let body_indent = INDENT.repeat(depth + 1);
+        let class_name = component.qualified_name().unwrap().replace('.', "/");
+        for field in public_fields(component) {
+            let is_static = field.access_flags.contains(FieldAccessFlags::STATIC);
+            let ty = self.field_type(field, scope);
+            if let (true, Some(value)) = (
+                is_static && field.access_flags.contains(FieldAccessFlags::FINAL),
+                &field.constant_value,
+            ) {
+                let const_name = unique(used, identifier(&snake_case(&field.name).to_uppercase()));
+                let (ty, value) = match (&ty, value) {
+                    (RustTy::Prim("bool", ..), ConstantValue::Int(x)) => {
+                        ("bool", (*x != 0).to_string())
+                    }
+                    (RustTy::Prim(ty, ..), ConstantValue::Int(x)) => (*ty, x.to_string()),
+                    (_, ConstantValue::Long(x)) => ("i64", x.to_string()),
+                    (_, ConstantValue::Float(x)) => ("f32", float_literal(*x as f64, "f32")),
+                    (_, ConstantValue::Double(x)) => ("f64", float_literal(*x, "f64")),
+                    (_, ConstantValue::String(x)) => ("&str", format!("{x:?}")),
+                    _ => continue,
+                };
+                members.push(format!(
+                    "{indent}/// `{}`\n{indent}pub const {const_name}: {ty} = {value};\n",
+                    field.name
+                ));
+                continue;
+            }
+            if !self.options.jni {
+                continue;
+            }
+
+            let getter = unique(
+                used,
+                identifier(&format!("get_{}", snake_case(&field.name))),
+            );
+            let (receiver, call) = if is_static {
+                (
+                    "",
+                    format!(
+                        "env.get_static_field(\"{class_name}\", \"{}\", \"{}\")",
+                        field.name, field.descriptor
+                    ),
+                )
+            } else {
+                (
+                    "&self, ",
+                    format!(
+                        "env.get_field(self.as_object(), \"{}\", \"{}\")",
+                        field.name, field.descriptor
+                    ),
+                )
+            };
+            members.push(format!(
+                "{indent}/// `{}`\n\
+                 {indent}pub fn {getter}({receiver}env: &::jni::JNIEnv) -> ::jni::errors::Result<{}> {{\n\
+                 {body_indent}let result = {call}?;\n\
+                 {body_indent}{}\n\
+                 {indent}}}\n",
+                field.name,
+                self.render(&ty, depth - 1),
+                self.convert_result(&ty, depth - 1)
+            ));
+        }
+    }
+
+    /// Renders a method or constructor, as a trait method if `in_trait`.
+    fn method(
+        &self,
+        component: &Component,
+        scope: &Scope,
+        method: &Method,
+        name: &str,
+        in_trait: bool,
+        depth: usize,
+    ) -> String {
+        let jni = self.options.jni;
+        let module_depth = depth - 1;
+        let root = root(module_depth);
+        let indent = INDENT.repeat(depth);
+        let body_indent = INDENT.repeat(depth + 1);
+        let is_static = method.access_flags.contains(MethodAccessFlags::STATIC);
+        let is_constructor = method.name == "<init>";
+
+        let mut scope = scope.clone();
+        let mut type_params = Vec::new();
+        let signature_type_params = method
+            .signature
+            .iter()
+            .flat_map(|x| x.type_parameters.iter().flatten());
+        for param in signature_type_params {
+            let base = identifier(&param.identifier);
+            let mut rust_name = base.clone();
+            let mut i = 2;
+            while type_params.contains(&rust_name)
+                || scope
+                    .values()
+                    .any(|x| matches!(x, RustTy::Generic(x) if *x == rust_name))
+            {
+                rust_name = format!("{base}{i}");
+                i += 1;
+            }
+            type_params.push(rust_name.clone());
+            scope.insert(param.identifier.clone(), RustTy::Generic(rust_name));
+        }
+
+        let params = match &method.signature {
+            Some(signature) if signature.parameters.len() == method.param_tys.len() => signature
+                .parameters
+                .iter()
+                .map(|x| self.type_signature(x, &scope, false))
+                .collect::<Vec<_>>(),
+            _ => method.param_tys.iter().map(|x| self.ty(x)).collect(),
+        };
+        let ret = match method.signature.as_ref().map(|x| &x.result) {
+            Some(Result::JavaTypeSignature(x)) => self.type_signature(x, &scope, false),
+            Some(Result::VoidDescriptor) => RustTy::Unit,
+            None => self.ty(&method.ret_ty),
+        };
+
+        let mut inputs = Vec::new();
+        if !is_static && !is_constructor {
+            inputs.push("&self".to_string());
+        }
+        if jni {
+            inputs.push("env: &::jni::JNIEnv".to_string());
+        }
+        for (i, param) in params.iter().enumerate() {
+            let ty = if jni {
+                self.render_parameter(param, module_depth)
+            } else {
+                self.render(param, module_depth)
+            };
+            inputs.push(format!("arg{i}: {ty}"));
+        }
+        let output = match (jni, is_constructor, &ret) {
+            (true, true, _) => " -> ::jni::errors::Result<Self>".to_string(),
+            (true, false, _) => format!(
+                " -> ::jni::errors::Result<{}>",
+                self.render(&ret, module_depth)
+            ),
+            (false, true, _) => " -> Self".to_string(),
+            (false, false, RustTy::Unit) => String::new(),
+            (false, false, _) => format!(" -> {}", self.render(&ret, module_depth)),
+        };
+        // Keeps the trait usable as `dyn`
+        let where_clause = if in_trait && !type_params.is_empty() {
+            " where Self: Sized"
+        } else {
+            ""
+        };
+
+        let mut out = format!(
+            "{indent}/// `{}{}`\n{indent}",
+            method.name, method.descriptor
+        );
+        if !in_trait {
+            out.push_str("pub ");
+        }
+        out.push_str(&format!(
+            "fn {name}{}({}){output}{where_clause}",
+            generics(&type_params, if jni { &root } else { "" }),
+            inputs.join(", ")
+        ));
+
+        if !jni {
+            if in_trait {
+                out.push_str(";\n");
+            } else {
+                out.push_str(&format!(" {{\n{body_indent}unimplemented!()\n{indent}}}\n"));
+            }
+            return out;
+        }
+
+        let class_name = component.qualified_name().unwrap().replace('.', "/");
+        let args = params
+            .iter()
+            .enumerate()
+            .map(|(i, x)| jvalue(x, &format!("arg{i}")))
+            .collect::<Vec<_>>()
+            .join(", ");
+        out.push_str(" {\n");
+        if is_constructor {
+            out.push_str(&format!(
+                "{body_indent}let object = env.new_object(\"{class_name}\", \"{}\", &[{args}])?;\n\
+                 {body_indent}Ok(Self::from_object(env.new_global_ref(object)?))\n",
+                method.descriptor
+            ));
+        } else {
+            let call = if is_static {
+                format!(
+                    "env.call_static_method(\"{class_name}\", \"{}\", \"{}\", &[{args}])",
+                    method.name, method.descriptor
+                )
+            } else {
+                format!(
+                    "env.call_method(self.as_object(), \"{}\", \"{}\", &[{args}])",
+                    method.name, method.descriptor
+                )
+            };
+            out.push_str(&format!(
+                "{body_indent}let result = {call}?;\n{body_indent}{}\n",
+                self.convert_result(&ret, module_depth)
+            ));
+        }
+        out.push_str(&format!("{indent}}}\n"));
+        out
+    }
+
+    /// The expression converting the `JValue` named `result` to the Rust type.
+    fn convert_result(&self, ty: &RustTy, depth: usize) -> String {
+        let root = root(depth);
+        match ty {
+            RustTy::Prim(_, _, accessor) => format!("result.{accessor}()"),
+            RustTy::Unit => "result.v()".to_string(),
+            RustTy::String => "Ok(env.get_string(result.l()?.into())?.into())".to_string(),
+            RustTy::Trait(..) => {
+                format!("Ok(::std::boxed::Box::new({root}Object::from_object(env.new_global_ref(result.l()?)?)))")
+            }
+            RustTy::Object | RustTy::Vec(_) | RustTy::Generic(_) | RustTy::Struct(..) => {
+                format!("Ok({root}JavaObject::from_object(env.new_global_ref(result.l()?)?))")
+            }
+        }
+    }
+
+    /// The Rust type parameters of a class or interface, and the scope binding them.
+    fn type_parameters(&self, component: &Component) -> (Vec<String>, Scope) {
+        let mut names = Vec::new();
+        let mut scope = Scope::new();
+        let type_params = component
+            .signature()
+            .and_then(|x| x.type_parameters.as_deref())
+            .unwrap_or_default();
+        for param in type_params {
+            let name = identifier(&param.identifier);
+            names.push(name.clone());
+            scope.insert(param.identifier.clone(), RustTy::Generic(name));
+        }
+        (names, scope)
+    }
+
+    /// The traits of the bound interfaces a class or interface implements, directly or through
+    /// its supertypes.
+    fn implemented_traits(&self, component: &Component, scope: &Scope) -> Vec<RustTy> {
+        let mut visited = HashSet::new();
+        let mut traits = Vec::new();
+        self.collect_traits(component, scope, &mut visited, &mut traits);
+        traits
+    }
+
+    fn collect_traits(
+        &self,
+        component: &Component,
+        scope: &Scope,
+        visited: &mut HashSet<String>,
+        traits: &mut Vec<RustTy>,
+    ) {
+        let supertypes = match component.signature() {
+            Some(signature) => {
+                let super_class = (!component.is_interface())
+                    .then_some(&signature.superclass_signature)
+                    .into_iter();
+                super_class
+                    .chain(&signature.superinterface_signatures)
+                    .map(|x| {
+                        let args = x
+                            .class_type_signature_suffixes
+                            .last()
+                            .unwrap_or(&x.simple_class_type_signature)
+                            .type_arguments
+                            .as_ref()
+                            .map(|args| {
+                                args.iter()
+                                    .map(|x| self.type_argument(x, scope))
+                                    .collect::<Vec<_>>()
+                            });
+                        (x.qualified_name(), args)
+                    })
+                    .collect::<Vec<_>>()
+            }
+            None => component
+                .super_class()
+                .into_iter()
+                .chain(component.interfaces().iter().map(|x| x.as_str()))
+                .map(|x| (x.to_string(), None))
+                .collect(),
+        };
+
+        for (name, args) in supertypes {
+            if !visited.insert(name.clone()) {
+                continue;
+            }
+            let Some(super_component) = self.classes.get(name.as_str()) else {
+                continue;
+            };
+            let type_params = self.type_parameters(super_component).0;
+            let args = match args {
+                Some(args) if args.len() == type_params.len() => args,
+                _ => vec![RustTy::Object; type_params.len()],
+            };
+            let mut super_scope = Scope::new();
+            let java_type_params = super_component
+                .signature()
+                .and_then(|x| x.type_parameters.as_deref())
+                .unwrap_or_default();
+            for (param, arg) in java_type_params.iter().zip(&args) {
+                super_scope.insert(param.identifier.clone(), arg.clone());
+            }
+
+            if super_component.is_interface() {
+                if let Some(path) = self.paths.get(name.as_str()) {
+                    traits.push(RustTy::Trait(path.clone(), args));
+                }
+            }
+            self.collect_traits(super_component, &super_scope, visited, traits);
+        }
+    }
+
+    fn field_type(&self, field: &Field, scope: &Scope) -> RustTy {
+        match &field.signature {
+            Some(signature) => self.reference(&signature.reference_type_signature, scope, false),
+            None => self.ty(&field.ty),
+        }
+    }
+
+    fn ty(&self, ty: &Ty) -> RustTy {
+        match ty {
+            Ty::Prim(prim) => prim_ty(prim),
+            Ty::Void => RustTy::Unit,
+            Ty::TyVar(_) => RustTy::Object,
+            Ty::Reference(name) => self.named(&name.qualified_name(), None, false),
+            Ty::Array(element, dimensions) => {
+                if self.options.jni {
+                    return RustTy::Object;
+                }
+                let mut ty = self.ty(element);
+                for _ in 0..*dimensions {
+                    ty = RustTy::Vec(Box::new(ty));
+                }
+                ty
+            }
+        }
+    }
+
+    /// Maps a Java type to Rust, where `argument` marks type argument positions, which
+    /// require a `JavaObject` with `jni`.
+    fn type_signature(&self, signature: &TypeSignature, scope: &Scope, argument: bool) -> RustTy {
+        match signature {
+            TypeSignature::Base(base) => base_ty(base),
+            TypeSignature::Reference(reference) => self.reference(reference, scope, argument),
+        }
+    }
+
+    fn reference(
+        &self,
+        signature: &ReferenceTypeSignature,
+        scope: &Scope,
+        argument: bool,
+    ) -> RustTy {
+        match signature {
+            ReferenceTypeSignature::TypeVariable(x) => {
+                scope.get(&x.identifier).cloned().unwrap_or(RustTy::Object)
+            }
+            ReferenceTypeSignature::Class(x) => self.class_type(x, scope, argument),
+            ReferenceTypeSignature::Array(_) if self.options.jni => RustTy::Object,
+            ReferenceTypeSignature::Array(x) => RustTy::Vec(Box::new(self.type_signature(
+                &x.java_type_signature,
+                scope,
+                argument,
+            ))),
+        }
+    }
+
+    fn class_type(&self, signature: &ClassTypeSignature, scope: &Scope, argument: bool) -> RustTy {
+        let args = signature
+            .class_type_signature_suffixes
+            .last()
+            .unwrap_or(&signature.simple_class_type_signature)
+            .type_arguments
+            .as_ref()
+            .map(|args| {
+                args.iter()
+                    .map(|x| self.type_argument(x, scope))
+                    .collect::<Vec<_>>()
+            });
+        self.named(&signature.qualified_name(), args, argument)
+    }
+
+    /// Wildcards have no Rust equivalent: `? extends T` becomes `T`, other wildcards `Object`.
+    fn type_argument(&self, arg: &TypeArgument, scope: &Scope) -> RustTy {
+        match arg {
+            TypeArgument::Any | TypeArgument::ReferenceType(Some(WildcardIndicator::Minus), _) => {
+                RustTy::Object
+            }
+            TypeArgument::ReferenceType(_, x) => self.reference(x, scope, true),
+        }
+    }
+
+    /// The Rust type of a class by binary name. Raw types get `Object` type arguments.
+    fn named(&self, name: &str, args: Option<Vec<RustTy>>, argument: bool) -> RustTy {
+        let jni_argument = self.options.jni && argument;
+        if name == "java.lang.String" {
+            return if jni_argument {
+                RustTy::Object
+            } else {
+                RustTy::String
+            };
+        }
+        let (Some(path), Some(component)) = (self.paths.get(name), self.classes.get(name)) else {
+            return RustTy::Object;
+        };
+        let count = self.type_parameters(component).0.len();
+        let args = match args {
+            Some(args) if args.len() == count => args,
+            _ => vec![RustTy::Object; count],
+        };
+        if !component.is_interface() {
+            RustTy::Struct(path.clone(), args)
+        } else if jni_argument {
+            RustTy::Object
+        } else {
+            RustTy::Trait(path.clone(), args)
+        }
+    }
+
+    /// Renders an owned type, in a module at the given depth.
+    fn render(&self, ty: &RustTy, depth: usize) -> String {
+        let root = root(depth);
+        match ty {
+            RustTy::Prim(name, ..) => name.to_string(),
+            RustTy::Unit => "()".to_string(),
+            RustTy::String => "::std::string::String".to_string(),
+            RustTy::Object => format!("{root}Object"),
+            RustTy::Vec(x) => format!("::std::vec::Vec<{}>", self.render(x, depth)),
+            RustTy::Generic(x) => x.clone(),
+            RustTy::Struct(..) => self.trait_path(ty, depth),
+            RustTy::Trait(..) => format!("::std::boxed::Box<dyn {}>", self.trait_path(ty, depth)),
+        }
+    }
+
+    /// Renders the type of a `jni` parameter, which borrows objects.
+    fn render_parameter(&self, ty: &RustTy, depth: usize) -> String {
+        match ty {
+            RustTy::Prim(..) | RustTy::Unit => self.render(ty, depth),
+            RustTy::String => "&str".to_string(),
+            RustTy::Trait(..) => format!("&dyn {}", self.trait_path(ty, depth)),
+            _ => format!("&{}", self.render(ty, depth)),
+        }
+    }
+
+    /// Renders the path with type arguments of a struct or trait.
+    fn trait_path(&self, ty: &RustTy, depth: usize) -> String {
+        let (RustTy::Struct(path, args) | RustTy::Trait(path, args)) = ty else {
+            return self.render(ty, depth);
+        };
+        if args.is_empty() {
+            format!("{}{path}", root(depth))
+        } else {
+            let args = args
+                .iter()
+                .map(|x| self.render(x, depth))
+                .collect::<Vec<_>>();
+            format!("{}{path}<{}>", root(depth), args.join(", "))
+        }
+    }
+}
+
+fn public_fields(component: &Component) -> impl Iterator<Item = &Field> {
+    component.fields().iter().filter(|x| {
+        x.access_flags.contains(FieldAccessFlags::PUBLIC)
+            && !x.access_flags.contains(FieldAccessFlags::SYNTHETIC)
+    })
+}
+
+fn public_methods(component: &Component) -> impl Iterator<Item = &Method> {
+    component.methods().iter().filter(|x| {
+        x.access_flags.contains(MethodAccessFlags::PUBLIC)
+            && !x
+                .access_flags
+                .intersects(MethodAccessFlags::SYNTHETIC | MethodAccessFlags::BRIDGE)
+            && x.name != "<clinit>"
+    })
+}
+
+fn method_name(method: &Method) -> String {
+    if method.name == "<init>" {
+        "new".to_string()
+    } else {
+        identifier(&snake_case(&method.name))
+    }
+}
+
+fn initial_names(jni: bool) -> HashSet<String> {
+    if jni {
+        RESERVED.iter().map(|x| x.to_string()).collect()
+    } else {
+        HashSet::new()
+    }
+}
+
+/// Returns the name, or the name with the first free numeric suffix if it is taken.
+fn unique(used: &mut HashSet<String>, name: String) -> String {
+    if used.insert(name.clone()) {
+        return name;
+    }
+    (1..)
+        .map(|i| format!("{name}_{i}"))
+        .find(|x| used.insert(x.clone()))
+        .unwrap()
+}
+
+/// The `super::` prefix leading from a module at the given depth to the root module.
+fn root(depth: usize) -> String {
+    "super::".repeat(depth)
+}
+
+fn arguments(type_params: &[String]) -> String {
+    if type_params.is_empty() {
+        String::new()
+    } else {
+        format!("<{}>", type_params.join(", "))
+    }
+}
+
+/// Declares type parameters, bounded by `JavaObject` if the root prefix is given.
+fn generics(type_params: &[String], jni_root: &str) -> String {
+    if type_params.is_empty() {
+        return String::new();
+    }
+    let params = type_params
+        .iter()
+        .map(|x| {
+            if jni_root.is_empty() {
+                x.clone()
+            } else {
+                format!("{x}: {jni_root}JavaObject")
+            }
+        })
+        .collect::<Vec<_>>();
+    format!("<{}>", params.join(", "))
+}
+
+/// Replaces trait objects by `Object`.
+fn erase_traits(ty: RustTy) -> RustTy {
+    match ty {
+        RustTy::Trait(..) => RustTy::Object,
+        RustTy::Vec(x) => RustTy::Vec(Box::new(erase_traits(*x))),
+        RustTy::Struct(path, args) => {
+            RustTy::Struct(path, args.into_iter().map(erase_traits).collect())
+        }
+        _ => ty,
+    }
+}
+
+/// The `JValue` passing a parameter to Java.
+fn jvalue(ty: &RustTy, name: &str) -> String {
+    match ty {
+        RustTy::Prim("bool", ..) => format!("::jni::objects::JValue::Bool({name} as u8)"),
+        RustTy::Prim(_, variant, _) => format!("::jni::objects::JValue::{variant}({name})"),
+        RustTy::String => format!("::jni::objects::JValue::Object(env.new_string({name})?.into())"),
+        _ => format!("::jni::objects::JValue::Object({name}.as_object())"),
+    }
+}
+
+fn prim_ty(prim: &PrimTy) -> RustTy {
+    match prim {
+        PrimTy::Byte => RustTy::Prim("i8", "Byte", "b"),
+        PrimTy::Char => RustTy::Prim("u16", "Char", "c"),
+        PrimTy::Double => RustTy::Prim("f64", "Double", "d"),
+        PrimTy::Float => RustTy::Prim("f32", "Float", "f"),
+        PrimTy::Int => RustTy::Prim("i32", "Int", "i"),
+        PrimTy::Long => RustTy::Prim("i64", "Long", "j"),
+        PrimTy::Short => RustTy::Prim("i16", "Short", "s"),
+        PrimTy::Boolean => RustTy::Prim("bool", "Bool", "z"),
+        PrimTy::Void => RustTy::Unit,
+    }
+}
+
+fn base_ty(base: &BaseType) -> RustTy {
+    prim_ty(&match base {
+        BaseType::Byte => PrimTy::Byte,
+        BaseType::Char => PrimTy::Char,
+        BaseType::Double => PrimTy::Double,
+        BaseType::Float => PrimTy::Float,
+        BaseType::Int => PrimTy::Int,
+        BaseType::Long => PrimTy::Long,
+        BaseType::Short => PrimTy::Short,
+        BaseType::Boolean => PrimTy::Boolean,
+    })
+}
+
+fn float_literal(x: f64, ty: &str) -> String {
+    if x.is_nan() {
+        format!("{ty}::NAN")
+    } else if x == f64::INFINITY {
+        format!("{ty}::INFINITY")
+    } else if x == f64::NEG_INFINITY {
+        format!("{ty}::NEG_INFINITY")
+    } else if ty == "f32" {
+        format!("{:?}", x as f32)
+    } else {
+        format!("{x:?}")
+    }
+}
+
+/// Converts a Java name to snake case: `toURI` becomes `to_uri`, `HTTPServer` `http_server`.
+pub(crate) fn snake_case(name: &str) -> String {
+    let chars = name.chars().collect::<Vec<_>>();
+    let mut out = String::new();
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            let previous = i.checked_sub(1).map(|i| chars[i]);
+            let next = chars.get(i + 1);
+            let starts_word = previous.is_some_and(|x| x.is_lowercase() || x.is_ascii_digit())
+                || (previous.is_some_and(|x| x.is_uppercase())
+                    && next.is_some_and(|x| x.is_lowercase()));
+            if starts_word {
+                out.push('_');
+            }
+            out.extend(c.to_lowercase());
+        } else {
+            out.push(*c);
+        }
+    }
+    out
+}
+
+/// Makes a Java identifier a Rust one: `$` becomes `_`, and keywords are escaped.
+fn identifier(name: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do",
+        "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in",
+        "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+        "return", "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe",
+        "unsized", "use", "virtual", "where", "while", "yield",
+    ];
+    let name = name.replace('$', "_");
+    if matches!(name.as_str(), "self" | "Self" | "super" | "crate" | "_") {
+        format!("{name}_")
+    } else if KEYWORDS.contains(&name.as_str()) {
+        format!("r#{name}")
+    } else {
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        component::{Class, Interface},
+        signature::parse_class_signature,
+        test_util::{self, component, field, method},
+    };
+
+    fn components() -> Vec<Component> {
+        let named = Interface {
+            signature: Some(
+                parse_class_signature("<T:Ljava/lang/Object;>Ljava/lang/Object;")
+                    .unwrap()
+                    .1,
+            ),
+            methods: vec![method(
+                "nameOf",
+                "(I)Ljava/lang/Object;",
+                Some("(I)TT;"),
+                0x0401,
+            )],
+            ..test_util::interface("a.Named")
+        };
+        let boxed = Class {
+            super_class: Some("java.lang.Object".to_string()),
+            interfaces: vec!["a.Named".to_string()],
+            signature: Some(
+                parse_class_signature("<T:Ljava/lang/Object;>Ljava/lang/Object;La/Named<TT;>;")
+                    .unwrap()
+                    .1,
+            ),
+            methods: vec![
+                method("<init>", "(Ljava/lang/String;)V", None, 0x0001),
+                method("isEmpty", "()Z", None, 0x0001),
+                method("map", "()V", Some("<T:Ljava/lang/Object;>()V"), 0x0009),
+                method("hidden", "()V", None, 0x0002),
+            ],
+            fields: vec![
+                Field {
+                    constant_value: Some(ConstantValue::Int(3)),
+                    ..field("MAX", "I", None, 0x0019)
+                },
+                field("itemCount", "J", None, 0x0001),
+            ],
+            ..test_util::class("a.Box")
+        };
+        vec![
+            component(ComponentKind::Interface(named)),
+            component(ComponentKind::Class(boxed)),
+        ]
+    }
+
+    #[test]
+    fn renders_structs_and_traits() {
+        let source = rust_bindings(&components(), RustBindingOptions::default());
+        assert_eq!(
+            source.split_once("pub mod a {\n").unwrap().1,
+            r#"    /// The Java class `a.Box`.
+    pub struct Box<T> {
+        pub item_count: i64,
+        _marker: ::std::marker::PhantomData<T>,
+    }
+
+    impl<T> Box<T> {
+        /// `MAX`
+        pub const MAX: i32 = 3;
+
+        /// `<init>(Ljava/lang/String;)V`
+        pub fn new(arg0: ::std::string::String) -> Self {
+            unimplemented!()
+        }
+
+        /// `isEmpty()Z`
+        pub fn is_empty(&self) -> bool {
+            unimplemented!()
+        }
+
+        /// `map()V`
+        pub fn map<T2>() {
+            unimplemented!()
+        }
+    }
+
+    /// The Java interface `a.Named`.
+    pub trait Named<T> {
+        /// `nameOf(I)Ljava/lang/Object;`
+        fn name_of(&self, arg0: i32) -> T;
+    }
+}
+"#
+        );
+
+        let source = rust_bindings(&components(), RustBindingOptions { jni: true });
+        for line in [
+            "    impl<T: super::JavaObject> super::a::Named<T> for Box<T> {}",
+            "        pub fn get_item_count(&self, env: &::jni::JNIEnv) -> ::jni::errors::Result<i64> {",
+            "            let object = env.new_object(\"a/Box\", \"(Ljava/lang/String;)V\", \
+             &[::jni::objects::JValue::Object(env.new_string(arg0)?.into())])?;",
+            "            let result = env.call_method(self.as_object(), \"nameOf\", \
+             \"(I)Ljava/lang/Object;\", &[::jni::objects::JValue::Int(arg0)])?;",
+            "    impl<T: super::JavaObject> Named<T> for super::Object {}",
+        ] {
+            assert!(source.contains(line), "{line}\n{source}");
+        }
+    }
+}
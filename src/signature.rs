@@ -70,16 +70,16 @@ use nom::{
     sequence::pair,
     IResult,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 // JavaTypeSignature
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TypeSignature {
     Reference(ReferenceTypeSignature),
     Base(BaseType),
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BaseType {
     Byte,
     Char,
@@ -91,57 +91,57 @@ pub enum BaseType {
     Boolean,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ReferenceTypeSignature {
     TypeVariable(TypeVariableSignature),
     Class(ClassTypeSignature),
     Array(ArrayTypeSignature),
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ClassTypeSignature {
     pub package_specifier: Option<String>,
     pub simple_class_type_signature: SimpleClassTypeSignature,
     pub class_type_signature_suffixes: Vec<SimpleClassTypeSignature>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SimpleClassTypeSignature {
     pub identifier: String,
     pub type_arguments: Option<Vec<TypeArgument>>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TypeArgument {
     ReferenceType(Option<WildcardIndicator>, ReferenceTypeSignature),
     Any,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum WildcardIndicator {
     Plus,
     Minus,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TypeVariableSignature {
     pub identifier: String,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ArrayTypeSignature {
     pub java_type_signature: Box<TypeSignature>,
 }
 
 // ClassSignature
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ClassSignature {
     pub type_parameters: Option<Vec<TypeParameter>>,
     pub superclass_signature: ClassTypeSignature,
     pub superinterface_signatures: Vec<ClassTypeSignature>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TypeParameter {
     pub identifier: String,
     pub class_bound: Option<ReferenceTypeSignature>,
@@ -149,7 +149,7 @@ pub struct TypeParameter {
 }
 
 // MethodSignature
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct MethodSignature {
     pub type_parameters: Option<Vec<TypeParameter>>,
     pub parameters: Vec<TypeSignature>,
@@ -157,24 +157,187 @@ pub struct MethodSignature {
     pub throws_signatures: Vec<ThrowsSignature>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Result {
     JavaTypeSignature(TypeSignature),
     VoidDescriptor,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ThrowsSignature {
     ClassTypeSignature(ClassTypeSignature),
     TypeVariableSignature(TypeVariableSignature),
 }
 
 // FieldSignature
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct FieldSignature {
     pub reference_type_signature: ReferenceTypeSignature,
 }
 
+impl ClassTypeSignature {
+    /// Returns the binary name of the class with '.' as package separator and '$' before
+    /// inner class names, e.g. `java.util.Map$Entry`.
+    pub fn qualified_name(&self) -> String {
+        let mut name = match &self.package_specifier {
+            Some(package) => format!("{package}.{}", self.simple_class_type_signature.identifier),
+            None => self.simple_class_type_signature.identifier.clone(),
+        };
+        for suffix in &self.class_type_signature_suffixes {
+            name.push('$');
+            name.push_str(&suffix.identifier);
+        }
+        name
+    }
+
+    /// Returns the type arguments applied to the innermost class.
+    pub fn type_arguments(&self) -> Option<&Vec<TypeArgument>> {
+        self.class_type_signature_suffixes
+            .last()
+            .unwrap_or(&self.simple_class_type_signature)
+            .type_arguments
+            .as_ref()
+    }
+
+    /// Renders the signature back to its class file form, e.g. `Ljava/util/Map$Entry<TK;TV;>;`.
+    pub fn to_signature_string(&self) -> String {
+        let mut out = "L".to_string();
+        if let Some(package) = &self.package_specifier {
+            out.push_str(&package.replace('.', "/"));
+            out.push('/');
+        }
+        push_simple_class_type_signature(&self.simple_class_type_signature, &mut out);
+        for x in &self.class_type_signature_suffixes {
+            out.push('.');
+            push_simple_class_type_signature(x, &mut out);
+        }
+        out.push(';');
+        out
+    }
+}
+
+impl TypeSignature {
+    /// Renders the signature back to its class file form, e.g. `[Ljava/lang/String;`.
+    pub fn to_signature_string(&self) -> String {
+        match self {
+            TypeSignature::Base(x) => match x {
+                BaseType::Byte => "B",
+                BaseType::Char => "C",
+                BaseType::Double => "D",
+                BaseType::Float => "F",
+                BaseType::Int => "I",
+                BaseType::Long => "J",
+                BaseType::Short => "S",
+                BaseType::Boolean => "Z",
+            }
+            .to_string(),
+            TypeSignature::Reference(x) => x.to_signature_string(),
+        }
+    }
+}
+
+impl ReferenceTypeSignature {
+    /// Renders the signature back to its class file form, e.g. `TT;`.
+    pub fn to_signature_string(&self) -> String {
+        match self {
+            ReferenceTypeSignature::TypeVariable(x) => format!("T{};", x.identifier),
+            ReferenceTypeSignature::Class(x) => x.to_signature_string(),
+            ReferenceTypeSignature::Array(x) => {
+                format!("[{}", x.java_type_signature.to_signature_string())
+            }
+        }
+    }
+}
+
+impl ClassSignature {
+    /// Renders the signature back to its class file form, e.g.
+    /// `<T:Ljava/lang/Object;>Ljava/lang/Object;Ljava/lang/Iterable<TT;>;`.
+    pub fn to_signature_string(&self) -> String {
+        let mut out = String::new();
+        push_type_parameters(&self.type_parameters, &mut out);
+        out.push_str(&self.superclass_signature.to_signature_string());
+        for x in &self.superinterface_signatures {
+            out.push_str(&x.to_signature_string());
+        }
+        out
+    }
+}
+
+impl MethodSignature {
+    /// Renders the signature back to its class file form, e.g. `<X:Ljava/lang/Throwable;>()V^TX;`.
+    pub fn to_signature_string(&self) -> String {
+        let mut out = String::new();
+        push_type_parameters(&self.type_parameters, &mut out);
+        out.push('(');
+        for x in &self.parameters {
+            out.push_str(&x.to_signature_string());
+        }
+        out.push(')');
+        match &self.result {
+            Result::JavaTypeSignature(x) => out.push_str(&x.to_signature_string()),
+            Result::VoidDescriptor => out.push('V'),
+        }
+        for x in &self.throws_signatures {
+            out.push('^');
+            match x {
+                ThrowsSignature::ClassTypeSignature(x) => out.push_str(&x.to_signature_string()),
+                ThrowsSignature::TypeVariableSignature(x) => {
+                    out.push_str(&format!("T{};", x.identifier))
+                }
+            }
+        }
+        out
+    }
+}
+
+impl FieldSignature {
+    /// Renders the signature back to its class file form, e.g. `Ljava/util/List<*>;`.
+    pub fn to_signature_string(&self) -> String {
+        self.reference_type_signature.to_signature_string()
+    }
+}
+
+fn push_simple_class_type_signature(value: &SimpleClassTypeSignature, out: &mut String) {
+    out.push_str(&value.identifier);
+    let Some(type_arguments) = &value.type_arguments else {
+        return;
+    };
+    out.push('<');
+    for x in type_arguments {
+        match x {
+            TypeArgument::Any => out.push('*'),
+            TypeArgument::ReferenceType(wildcard, ty) => {
+                match wildcard {
+                    Some(WildcardIndicator::Plus) => out.push('+'),
+                    Some(WildcardIndicator::Minus) => out.push('-'),
+                    None => {}
+                }
+                out.push_str(&ty.to_signature_string());
+            }
+        }
+    }
+    out.push('>');
+}
+
+fn push_type_parameters(value: &Option<Vec<TypeParameter>>, out: &mut String) {
+    let Some(type_parameters) = value else {
+        return;
+    };
+    out.push('<');
+    for x in type_parameters {
+        out.push_str(&x.identifier);
+        out.push(':');
+        if let Some(bound) = &x.class_bound {
+            out.push_str(&bound.to_signature_string());
+        }
+        for bound in &x.interface_bounds {
+            out.push(':');
+            out.push_str(&bound.to_signature_string());
+        }
+    }
+    out.push('>');
+}
+
 pub fn parse_type_signature(input: &str) -> IResult<&str, TypeSignature> {
     alt((parse_base_type, parse_reference_type_signature))(input)
 }
@@ -453,7 +616,7 @@ pub fn parse_method_signature(input: &str) -> IResult<&str, MethodSignature> {
 }
 
 fn parse_result(input: &str) -> IResult<&str, Result> {
-    if tag::<_, _, nom::error::Error<_>>("V")(input).is_ok() {
+    if let Ok((input, _)) = tag::<_, _, nom::error::Error<_>>("V")(input) {
         Ok((input, Result::VoidDescriptor))
     } else {
         parse_type_signature(input).map(|(i, ts)| (i, Result::JavaTypeSignature(ts)))
@@ -506,6 +669,7 @@ pub fn parse_field_signature(input: &str) -> IResult<&str, FieldSignature> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use quickcheck::{Arbitrary, Gen, QuickCheck};
 
     #[test]
     fn test_java_type_signature() {
@@ -649,6 +813,19 @@ mod tests {
                     throws_signatures: vec![],
                 },
             ),
+            (
+                "()V^TX;",
+                MethodSignature {
+                    type_parameters: None,
+                    parameters: vec![],
+                    result: Result::VoidDescriptor,
+                    throws_signatures: vec![ThrowsSignature::TypeVariableSignature(
+                        TypeVariableSignature {
+                            identifier: "X".to_string(),
+                        },
+                    )],
+                },
+            ),
         ];
 
         for (input, expect) in test_cases.iter() {
@@ -656,4 +833,218 @@ mod tests {
             assert_eq!(actual, *expect);
         }
     }
+
+    #[test]
+    fn test_to_signature_string() {
+        let class_signatures = [
+            "<T:Ljava/lang/Object;>Ljava/lang/Object;",
+            "<K:Ljava/lang/Object;V:Ljava/lang/Object;>Ljava/util/AbstractMap<TK;TV;>;Ljava/util/Map<TK;TV;>;Ljava/lang/Cloneable;Ljava/io/Serializable;",
+            "<E:Ljava/lang/Enum<TE;>;>Ljava/lang/Object;Ljava/lang/Comparable<TE;>;",
+            "<T::Ljava/lang/Comparable<-TT;>;>Ljava/lang/Object;",
+        ];
+        for input in class_signatures {
+            let (_, actual) = parse_class_signature(input).unwrap();
+            assert_eq!(actual.to_signature_string(), input);
+        }
+
+        let method_signatures = [
+            "<T:Ljava/lang/Object;>(Ljava/lang/Object;)TT;",
+            "()Ljava/util/Set<Ljava/util/Map$Entry<TK;TV;>;>;",
+            "(Ljava/util/Collection<+TE;>;[[I)Z",
+            "<X:Ljava/lang/Throwable;>(Ljava/util/function/Supplier<+TX;>;)TT;^TX;",
+            "()V^Ljava/io/IOException;",
+            "(Lp/Outer<TT;>.Inner<*>;)V",
+        ];
+        for input in method_signatures {
+            let (_, actual) = parse_method_signature(input).unwrap();
+            assert_eq!(actual.to_signature_string(), input);
+        }
+
+        let field_signatures = ["Ljava/util/List<*>;", "[TT;", "TT;"];
+        for input in field_signatures {
+            let (_, actual) = parse_field_signature(input).unwrap();
+            assert_eq!(actual.to_signature_string(), input);
+        }
+    }
+
+    #[test]
+    fn test_signature_round_trip() {
+        fn type_signature(value: TypeSignature) -> bool {
+            parse_type_signature(&value.to_signature_string()) == Ok(("", value))
+        }
+        fn class_signature(value: ClassSignature) -> bool {
+            parse_class_signature(&value.to_signature_string()) == Ok(("", value))
+        }
+        fn method_signature(value: MethodSignature) -> bool {
+            parse_method_signature(&value.to_signature_string()) == Ok(("", value))
+        }
+        fn field_signature(value: FieldSignature) -> bool {
+            parse_field_signature(&value.to_signature_string()) == Ok(("", value))
+        }
+
+        let mut qc = QuickCheck::new().tests(500);
+        qc.quickcheck(type_signature as fn(TypeSignature) -> bool);
+        qc.quickcheck(class_signature as fn(ClassSignature) -> bool);
+        qc.quickcheck(method_signature as fn(MethodSignature) -> bool);
+        qc.quickcheck(field_signature as fn(FieldSignature) -> bool);
+    }
+
+    // Generators only build ASTs the parser can produce: identifiers are non-empty, type
+    // arguments and type parameters are either absent or non-empty, and nesting is bounded.
+    const DEPTH: usize = 3;
+
+    fn below(g: &mut Gen, n: usize) -> usize {
+        usize::arbitrary(g) % n
+    }
+
+    fn identifier(g: &mut Gen) -> String {
+        let first = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz_$".as_bytes();
+        let rest = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz_$0123456789".as_bytes();
+        let mut out = (*g.choose(first).unwrap() as char).to_string();
+        for _ in 0..below(g, 6) {
+            out.push(*g.choose(rest).unwrap() as char);
+        }
+        out
+    }
+
+    fn simple_class_type(g: &mut Gen, depth: usize) -> SimpleClassTypeSignature {
+        SimpleClassTypeSignature {
+            identifier: identifier(g),
+            type_arguments: (depth > 0 && bool::arbitrary(g)).then(|| {
+                (0..1 + below(g, 3))
+                    .map(|_| match below(g, 4) {
+                        0 => TypeArgument::Any,
+                        n => TypeArgument::ReferenceType(
+                            [
+                                None,
+                                Some(WildcardIndicator::Plus),
+                                Some(WildcardIndicator::Minus),
+                            ][n - 1]
+                                .clone(),
+                            reference_type(g, depth - 1),
+                        ),
+                    })
+                    .collect()
+            }),
+        }
+    }
+
+    fn class_type(g: &mut Gen, depth: usize) -> ClassTypeSignature {
+        ClassTypeSignature {
+            package_specifier: bool::arbitrary(g).then(|| {
+                (0..1 + below(g, 3))
+                    .map(|_| identifier(g))
+                    .collect::<Vec<_>>()
+                    .join(".")
+            }),
+            simple_class_type_signature: simple_class_type(g, depth),
+            class_type_signature_suffixes: (0..below(g, 3))
+                .map(|_| simple_class_type(g, depth))
+                .collect(),
+        }
+    }
+
+    fn type_variable(g: &mut Gen) -> TypeVariableSignature {
+        TypeVariableSignature {
+            identifier: identifier(g),
+        }
+    }
+
+    fn reference_type(g: &mut Gen, depth: usize) -> ReferenceTypeSignature {
+        match below(g, if depth > 0 { 3 } else { 2 }) {
+            0 => ReferenceTypeSignature::TypeVariable(type_variable(g)),
+            1 => ReferenceTypeSignature::Class(class_type(g, depth)),
+            _ => ReferenceTypeSignature::Array(ArrayTypeSignature {
+                java_type_signature: Box::new(type_signature(g, depth - 1)),
+            }),
+        }
+    }
+
+    fn type_signature(g: &mut Gen, depth: usize) -> TypeSignature {
+        if bool::arbitrary(g) {
+            TypeSignature::Reference(reference_type(g, depth))
+        } else {
+            TypeSignature::Base(
+                g.choose(&[
+                    BaseType::Byte,
+                    BaseType::Char,
+                    BaseType::Double,
+                    BaseType::Float,
+                    BaseType::Int,
+                    BaseType::Long,
+                    BaseType::Short,
+                    BaseType::Boolean,
+                ])
+                .unwrap()
+                .clone(),
+            )
+        }
+    }
+
+    fn type_parameters(g: &mut Gen, depth: usize) -> Option<Vec<TypeParameter>> {
+        bool::arbitrary(g).then(|| {
+            (0..1 + below(g, 3))
+                .map(|_| {
+                    let class_bound = bool::arbitrary(g).then(|| reference_type(g, depth));
+                    // A parameter without a class bound needs an interface bound, otherwise
+                    // `T:` would swallow the next parameter as its bound.
+                    let min = usize::from(class_bound.is_none());
+                    TypeParameter {
+                        identifier: identifier(g),
+                        class_bound,
+                        interface_bounds: (0..min + below(g, 2))
+                            .map(|_| reference_type(g, depth))
+                            .collect(),
+                    }
+                })
+                .collect()
+        })
+    }
+
+    impl Arbitrary for TypeSignature {
+        fn arbitrary(g: &mut Gen) -> Self {
+            type_signature(g, DEPTH)
+        }
+    }
+
+    impl Arbitrary for ClassSignature {
+        fn arbitrary(g: &mut Gen) -> Self {
+            ClassSignature {
+                type_parameters: type_parameters(g, DEPTH),
+                superclass_signature: class_type(g, DEPTH),
+                superinterface_signatures: (0..below(g, 3)).map(|_| class_type(g, DEPTH)).collect(),
+            }
+        }
+    }
+
+    impl Arbitrary for MethodSignature {
+        fn arbitrary(g: &mut Gen) -> Self {
+            MethodSignature {
+                type_parameters: type_parameters(g, DEPTH),
+                parameters: (0..below(g, 4)).map(|_| type_signature(g, DEPTH)).collect(),
+                result: if bool::arbitrary(g) {
+                    Result::JavaTypeSignature(type_signature(g, DEPTH))
+                } else {
+                    Result::VoidDescriptor
+                },
+                throws_signatures: (0..below(g, 3))
+                    .map(|_| {
+                        if bool::arbitrary(g) {
+                            ThrowsSignature::ClassTypeSignature(class_type(g, DEPTH))
+                        } else {
+                            ThrowsSignature::TypeVariableSignature(type_variable(g))
+                        }
+                    })
+                    .collect(),
+            }
+        }
+    }
+
+    impl Arbitrary for FieldSignature {
+        fn arbitrary(g: &mut Gen) -> Self {
+            FieldSignature {
+                reference_type_signature: reference_type(g, DEPTH),
+            }
+        }
+    }
 }
@@ -0,0 +1,314 @@
+use std::{
+    error::Error,
+    io::Write,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use prost::Message;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    component::{AccessModifier, Component, SourcedComponent},
+    proto, proto_v2,
+};
+
+#[allow(clippy::module_inception)]
+pub mod snapshot {
+    include!(concat!(
+        env!("OUT_DIR"),
+        "/classreader_rs.proto.snapshot.rs"
+    ));
+}
+
+/// The `magic` of every snapshot header, which tells snapshots from bare component messages.
+pub const MAGIC: &str = "classreader";
+
+/// The newest component schema version that can be read and written.
+pub const LATEST_SCHEMA_VERSION: u32 = 2;
+
+/// The components of a `.pb` file, along with what produced them. Components of files written
+/// for one input have its path as source and no entry path.
+#[derive(Debug)]
+pub struct Snapshot {
+    pub header: snapshot::Header,
+    pub components: Vec<SourcedComponent>,
+}
+
+/// Encodes components with the given schema version, under a header recording the input, the
+/// extraction options and this version of classreader.
+pub fn encode_snapshot(
+    components: &Vec<Component>,
+    schema_version: u32,
+    input_path: &Path,
+    access_modifiers: &AccessModifier,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let header = header(schema_version, access_modifiers)?;
+    let encoded = match schema_version {
+        1 => proto::component::ComponentList::from(components).encode_to_vec(),
+        _ => proto_v2::component::ComponentList::from(components).encode_to_vec(),
+    };
+    let header = snapshot::Header {
+        input_path: input_path.display().to_string(),
+        input_sha256: input_sha256(input_path)?,
+        ..header
+    };
+
+    Ok(snapshot::Snapshot {
+        header: Some(header),
+        components: encoded,
+        entries: Vec::new(),
+    }
+    .encode_to_vec())
+}
+
+/// Writes a snapshot aggregating many inputs, with each component tagged by its source and
+/// appended as soon as it is written, so that the snapshot is never held in memory.
+pub struct SnapshotWriter<W: Write> {
+    writer: W,
+    schema_version: u32,
+}
+
+impl<W: Write> SnapshotWriter<W> {
+    /// Writes the header, with no input path or hash as the entries name their sources.
+    pub fn new(
+        mut writer: W,
+        schema_version: u32,
+        access_modifiers: &AccessModifier,
+    ) -> Result<Self, Box<dyn Error>> {
+        let snapshot = snapshot::Snapshot {
+            header: Some(header(schema_version, access_modifiers)?),
+            ..Default::default()
+        };
+        writer.write_all(&snapshot.encode_to_vec())?;
+        Ok(Self {
+            writer,
+            schema_version,
+        })
+    }
+
+    /// Appends a component as an entry of the snapshot, as concatenated messages are merged.
+    pub fn write(&mut self, component: &SourcedComponent) -> Result<(), Box<dyn Error>> {
+        let encoded = match self.schema_version {
+            1 => proto::component::Component::from(&component.component).encode_to_vec(),
+            _ => proto_v2::component::Component::from(&component.component).encode_to_vec(),
+        };
+        let snapshot = snapshot::Snapshot {
+            entries: vec![snapshot::Entry {
+                source: component.source.clone(),
+                entry_path: component.entry_path.clone(),
+                component: encoded,
+            }],
+            ..Default::default()
+        };
+        self.writer.write_all(&snapshot.encode_to_vec())?;
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+fn header(
+    schema_version: u32,
+    access_modifiers: &AccessModifier,
+) -> Result<snapshot::Header, Box<dyn Error>> {
+    if schema_version == 0 || schema_version > LATEST_SCHEMA_VERSION {
+        return Err(format!("Unsupported schema version: {schema_version}").into());
+    }
+
+    Ok(snapshot::Header {
+        magic: MAGIC.to_string(),
+        schema_version,
+        classreader_version: env!("CARGO_PKG_VERSION").to_string(),
+        input_path: String::new(),
+        input_sha256: String::new(),
+        options: Some(snapshot::ExtractionOptions {
+            access_modifiers: access_modifiers
+                .iter_names()
+                .map(|(name, _)| name.to_string())
+                .collect(),
+        }),
+        created_at: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64,
+    })
+}
+
+/// Decodes a snapshot, upgrading the components of older schema versions, and rejects newer
+/// ones. Returns `None` when there is no snapshot header, as in files written before headers,
+/// which hold bare schema version 1 messages.
+pub fn decode_snapshot(buf: &[u8]) -> Result<Option<Snapshot>, String> {
+    let Ok(snapshot::Snapshot {
+        header: Some(header),
+        components,
+        entries,
+    }) = snapshot::Snapshot::decode(buf)
+    else {
+        return Ok(None);
+    };
+    if header.magic != MAGIC {
+        return Ok(None);
+    }
+
+    let schema_version = header.schema_version;
+    if schema_version == 0 || schema_version > LATEST_SCHEMA_VERSION {
+        return Err(format!(
+            "Unsupported schema version {schema_version} written by classreader {}, the latest \
+             supported is {LATEST_SCHEMA_VERSION}",
+            header.classreader_version
+        ));
+    }
+
+    let mut sourced = decode_list(schema_version, &components)?
+        .into_iter()
+        .map(|component| SourcedComponent {
+            source: header.input_path.clone(),
+            entry_path: String::new(),
+            component,
+        })
+        .collect::<Vec<_>>();
+    for entry in entries {
+        sourced.push(SourcedComponent {
+            component: decode_component(schema_version, &entry.component)?,
+            source: entry.source,
+            entry_path: entry.entry_path,
+        });
+    }
+    Ok(Some(Snapshot {
+        header,
+        components: sourced,
+    }))
+}
+
+fn decode_list(schema_version: u32, buf: &[u8]) -> Result<Vec<Component>, String> {
+    match schema_version {
+        1 => proto::component::ComponentList::decode(buf)
+            .map_err(|e| e.to_string())?
+            .try_into(),
+        _ => proto_v2::component::ComponentList::decode(buf)
+            .map_err(|e| e.to_string())?
+            .try_into(),
+    }
+}
+
+fn decode_component(schema_version: u32, buf: &[u8]) -> Result<Component, String> {
+    match schema_version {
+        1 => proto::component::Component::decode(buf)
+            .map_err(|e| e.to_string())?
+            .try_into(),
+        _ => proto_v2::component::Component::decode(buf)
+            .map_err(|e| e.to_string())?
+            .try_into(),
+    }
+}
+
+/// The hex SHA-256 of a file, or of the relative paths and contents of the files below a
+/// directory in path order.
+fn input_sha256(path: &Path) -> Result<String, Box<dyn Error>> {
+    let mut hasher = Sha256::new();
+    if path.is_dir() {
+        for entry in walkdir::WalkDir::new(path).sort_by_file_name() {
+            let entry = entry?;
+            if entry.file_type().is_file() {
+                let relative = entry.path().strip_prefix(path)?;
+                hasher.update(relative.to_string_lossy().as_bytes());
+                hasher.update([0]);
+                hasher.update(std::fs::read(entry.path())?);
+            }
+        }
+    } else {
+        hasher.update(std::fs::read(path)?);
+    }
+
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|x| format!("{x:02x}"))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::component::{ComponentKind, Module};
+
+    #[test]
+    fn stamps_and_checks_schema_versions() {
+        let path = std::env::temp_dir().join("classreader-snapshot-test.class");
+        std::fs::write(&path, b"abc").unwrap();
+        let module = || Component {
+            minor_version: 0,
+            major_version: 61,
+            kind: ComponentKind::Module(Module {
+                name: "java.sql".to_string(),
+                version: "17".to_string(),
+            }),
+            class_file_name: "module-info.class".to_string(),
+        };
+        let components = vec![module()];
+
+        for version in [1, 2] {
+            let buf =
+                encode_snapshot(&components, version, &path, &AccessModifier::PUBLIC).unwrap();
+            let snapshot = decode_snapshot(&buf).unwrap().unwrap();
+            assert_eq!(snapshot.header.schema_version, version);
+            assert_eq!(
+                snapshot.header.input_sha256,
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+            );
+            assert_eq!(
+                snapshot.header.options.unwrap().access_modifiers,
+                ["PUBLIC"]
+            );
+            assert_eq!(
+                snapshot.components[0].component.class_file_name,
+                "module-info.class"
+            );
+            assert_eq!(
+                crate::decode_component_list(buf.as_slice()),
+                Ok(proto::component::ComponentList::from(&components))
+            );
+        }
+        assert!(encode_snapshot(&components, 3, &path, &AccessModifier::empty()).is_err());
+
+        let mut writer = SnapshotWriter::new(Vec::new(), 1, &AccessModifier::empty()).unwrap();
+        for source in ["a.jar", "b.jar"] {
+            let component = SourcedComponent {
+                source: source.to_string(),
+                entry_path: "module-info.class".to_string(),
+                component: module(),
+            };
+            writer.write(&component).unwrap();
+        }
+        let aggregated = decode_snapshot(&writer.into_inner()).unwrap().unwrap();
+        assert_eq!(aggregated.header.input_path, "");
+        assert_eq!(
+            aggregated
+                .components
+                .iter()
+                .map(|x| format!("{}!/{}", x.source, x.entry_path))
+                .collect::<Vec<_>>(),
+            ["a.jar!/module-info.class", "b.jar!/module-info.class"]
+        );
+
+        let newer = snapshot::Snapshot {
+            header: Some(snapshot::Header {
+                magic: MAGIC.to_string(),
+                schema_version: LATEST_SCHEMA_VERSION + 1,
+                ..Default::default()
+            }),
+            components: Vec::new(),
+            entries: Vec::new(),
+        };
+        assert!(decode_snapshot(&newer.encode_to_vec()).is_err());
+        assert!(crate::decode_component_list(newer.encode_to_vec().as_slice()).is_err());
+
+        let bare = proto::component::ComponentList::from(&components);
+        assert!(decode_snapshot(&bare.encode_to_vec()).unwrap().is_none());
+        assert_eq!(
+            crate::decode_component_list(bare.encode_to_vec().as_slice()),
+            Ok(bare)
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,783 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    error::Error,
+    path::Path,
+};
+
+use rusqlite::{params, types::Value, Connection};
+
+use crate::{
+    component::{
+        class_modifiers, Annotation, AnnotationKind, AnnotationValue, ComponentKind, ConstantValue,
+        Field, Method, SourcedComponent, Ty,
+    },
+    signature::{
+        ClassSignature, ClassTypeSignature, FieldSignature, MethodSignature,
+        ReferenceTypeSignature, Result as SignatureResult, SimpleClassTypeSignature,
+        ThrowsSignature, TypeArgument, TypeParameter, TypeSignature,
+    },
+};
+
+/// The tables of the database. Types are named as in source with binary names, e.g. `int[]`,
+/// `T` or `java.util.Map$Entry`, erasing the type arguments kept by the raw signatures.
+pub const SCHEMA: &str = "
+CREATE TABLE sources (
+    id INTEGER PRIMARY KEY,
+    -- the input path the components were found under
+    path TEXT NOT NULL UNIQUE
+);
+
+CREATE TABLE modules (
+    id INTEGER PRIMARY KEY,
+    source_id INTEGER NOT NULL REFERENCES sources(id),
+    entry_path TEXT NOT NULL,
+    name TEXT NOT NULL,
+    version TEXT NOT NULL
+);
+
+CREATE TABLE classes (
+    id INTEGER PRIMARY KEY,
+    source_id INTEGER NOT NULL REFERENCES sources(id),
+    entry_path TEXT NOT NULL,
+    -- binary name, e.g. java.util.Map$Entry
+    name TEXT NOT NULL,
+    -- class, enum, interface or annotation
+    kind TEXT NOT NULL,
+    major_version INTEGER NOT NULL,
+    minor_version INTEGER NOT NULL,
+    access_flags INTEGER NOT NULL,
+    modifiers TEXT NOT NULL,
+    -- raw Signature attribute
+    signature TEXT,
+    -- binary name of the enclosing class of member classes
+    outer_class TEXT
+);
+
+CREATE TABLE supertypes (
+    class_id INTEGER NOT NULL REFERENCES classes(id),
+    -- 0 for the superclass, then the interfaces in declaration order
+    position INTEGER NOT NULL,
+    -- superclass or interface
+    kind TEXT NOT NULL,
+    name TEXT NOT NULL,
+    PRIMARY KEY (class_id, position)
+);
+
+CREATE TABLE fields (
+    id INTEGER PRIMARY KEY,
+    class_id INTEGER NOT NULL REFERENCES classes(id),
+    name TEXT NOT NULL,
+    descriptor TEXT NOT NULL,
+    signature TEXT,
+    type TEXT NOT NULL,
+    access_flags INTEGER NOT NULL,
+    modifiers TEXT NOT NULL,
+    is_static INTEGER NOT NULL,
+    -- an integer, real or text constant
+    constant_value
+);
+
+CREATE TABLE methods (
+    id INTEGER PRIMARY KEY,
+    class_id INTEGER NOT NULL REFERENCES classes(id),
+    name TEXT NOT NULL,
+    descriptor TEXT NOT NULL,
+    signature TEXT,
+    return_type TEXT NOT NULL,
+    access_flags INTEGER NOT NULL,
+    modifiers TEXT NOT NULL,
+    is_static INTEGER NOT NULL
+);
+
+CREATE TABLE parameters (
+    method_id INTEGER NOT NULL REFERENCES methods(id),
+    position INTEGER NOT NULL,
+    type TEXT NOT NULL,
+    PRIMARY KEY (method_id, position)
+);
+
+-- Parameter annotations belong to their method, with a parameter kind.
+CREATE TABLE annotations (
+    id INTEGER PRIMARY KEY,
+    class_id INTEGER REFERENCES classes(id),
+    method_id INTEGER REFERENCES methods(id),
+    field_id INTEGER REFERENCES fields(id),
+    -- for annotations nested in an annotation value
+    value_id INTEGER REFERENCES annotation_values(id),
+    type TEXT NOT NULL,
+    -- runtime_visible, runtime_invisible, runtime_visible_parameter, ...
+    kind TEXT NOT NULL,
+    CHECK ((class_id IS NOT NULL) + (method_id IS NOT NULL) + (field_id IS NOT NULL)
+        + (value_id IS NOT NULL) = 1)
+);
+
+CREATE TABLE annotation_values (
+    id INTEGER PRIMARY KEY,
+    -- for the elements of an annotation, named by name
+    annotation_id INTEGER REFERENCES annotations(id),
+    -- for the elements of an array value
+    array_id INTEGER REFERENCES annotation_values(id),
+    -- for the default value of an annotation interface element
+    method_id INTEGER REFERENCES methods(id),
+    name TEXT,
+    position INTEGER NOT NULL,
+    -- byte, char, short, int, long, float, double, boolean, string, enum, class, annotation or
+    -- array
+    kind TEXT NOT NULL,
+    -- the constant, the enum constant name or the class literal type, null for annotations and
+    -- arrays
+    value,
+    -- the type of enum constants
+    type TEXT,
+    CHECK ((annotation_id IS NOT NULL) + (array_id IS NOT NULL) + (method_id IS NOT NULL) = 1)
+);
+
+-- The classes named by the declaration of a class, or of one of its members when method_id or
+-- field_id is set: in its types, signatures, throws clauses and annotations.
+CREATE TABLE type_references (
+    class_id INTEGER NOT NULL REFERENCES classes(id),
+    method_id INTEGER REFERENCES methods(id),
+    field_id INTEGER REFERENCES fields(id),
+    name TEXT NOT NULL
+);
+";
+
+const INDICES: &str = "
+CREATE INDEX classes_name ON classes(name);
+CREATE INDEX classes_source_id ON classes(source_id);
+CREATE INDEX modules_source_id ON modules(source_id);
+CREATE INDEX supertypes_name ON supertypes(name);
+CREATE INDEX fields_class_id ON fields(class_id);
+CREATE INDEX fields_name ON fields(name);
+CREATE INDEX fields_type ON fields(type);
+CREATE INDEX methods_class_id ON methods(class_id);
+CREATE INDEX methods_name ON methods(name);
+CREATE INDEX methods_return_type ON methods(return_type);
+CREATE INDEX parameters_type ON parameters(type);
+CREATE INDEX annotations_class_id ON annotations(class_id);
+CREATE INDEX annotations_method_id ON annotations(method_id);
+CREATE INDEX annotations_field_id ON annotations(field_id);
+CREATE INDEX annotations_value_id ON annotations(value_id);
+CREATE INDEX annotations_type ON annotations(type);
+CREATE INDEX annotation_values_annotation_id ON annotation_values(annotation_id);
+CREATE INDEX annotation_values_array_id ON annotation_values(array_id);
+CREATE INDEX annotation_values_method_id ON annotation_values(method_id);
+CREATE INDEX type_references_class_id ON type_references(class_id);
+CREATE INDEX type_references_method_id ON type_references(method_id);
+CREATE INDEX type_references_field_id ON type_references(field_id);
+CREATE INDEX type_references_name ON type_references(name);
+";
+
+/// Writes components to a SQLite database with [`SCHEMA`], in a single transaction committed
+/// by [`SqliteWriter::finish`].
+pub struct SqliteWriter {
+    connection: Connection,
+    sources: HashMap<String, i64>,
+}
+
+/// The owner of an annotation.
+#[derive(Clone, Copy)]
+enum Target {
+    Class(i64),
+    Method(i64),
+    Field(i64),
+    Value(i64),
+}
+
+/// The owner of an annotation value.
+#[derive(Clone, Copy)]
+enum ValueOwner<'a> {
+    Annotation(i64, &'a str),
+    Array(i64),
+    Default(i64),
+}
+
+impl SqliteWriter {
+    /// Creates the database, replacing any file at the path.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let path = path.as_ref();
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+        Self::new(Connection::open(path)?)
+    }
+
+    /// Creates the tables in a connection, e.g. to an in-memory database.
+    pub fn new(connection: Connection) -> Result<Self, Box<dyn Error>> {
+        connection.execute_batch("PRAGMA foreign_keys = ON;")?;
+        connection.execute_batch(SCHEMA)?;
+        connection.execute_batch("BEGIN;")?;
+        Ok(Self {
+            connection,
+            sources: HashMap::new(),
+        })
+    }
+
+    pub fn write(&mut self, component: &SourcedComponent) -> Result<(), Box<dyn Error>> {
+        let source_id = self.source_id(&component.source)?;
+        let entry_path = &component.entry_path;
+        let c = &component.component;
+        let (name, kind, access_flags, signature, inner_class) = match &c.kind {
+            ComponentKind::Module(module) => {
+                self.connection
+                    .prepare_cached(
+                        "INSERT INTO modules (source_id, entry_path, name, version)
+                         VALUES (?1, ?2, ?3, ?4)",
+                    )?
+                    .execute(params![source_id, entry_path, module.name, module.version])?;
+                return Ok(());
+            }
+            ComponentKind::Class(x) => (
+                &x.qualified_name,
+                if x.is_enum { "enum" } else { "class" },
+                x.access_flags,
+                &x.signature,
+                &x.inner_class,
+            ),
+            ComponentKind::Interface(x) => (
+                &x.qualified_name,
+                if x.is_annotation {
+                    "annotation"
+                } else {
+                    "interface"
+                },
+                x.access_flags,
+                &x.signature,
+                &x.inner_class,
+            ),
+        };
+        self.connection
+            .prepare_cached(
+                "INSERT INTO classes (source_id, entry_path, name, kind, major_version,
+                     minor_version, access_flags, modifiers, signature, outer_class)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            )?
+            .execute(params![
+                source_id,
+                entry_path,
+                name,
+                kind,
+                c.major_version,
+                c.minor_version,
+                access_flags.bits(),
+                class_modifiers(access_flags),
+                signature.as_ref().map(ClassSignature::to_signature_string),
+                inner_class.as_ref().and_then(|x| x.outer_class.as_ref()),
+            ])?;
+        let class_id = self.connection.last_insert_rowid();
+
+        let (super_class, interfaces, annotations, fields, methods) = match &c.kind {
+            ComponentKind::Class(x) => (
+                x.super_class.as_deref(),
+                &x.interfaces,
+                &x.annotations,
+                &x.fields,
+                &x.methods,
+            ),
+            ComponentKind::Interface(x) => {
+                (None, &x.interfaces, &x.annotations, &x.fields, &x.methods)
+            }
+            ComponentKind::Module(_) => unreachable!(),
+        };
+        let supertypes = super_class.map(|x| (0, "superclass", x)).into_iter().chain(
+            interfaces
+                .iter()
+                .enumerate()
+                .map(|(i, x)| (i + 1, "interface", x.as_str())),
+        );
+        for (position, kind, name) in supertypes {
+            self.connection
+                .prepare_cached(
+                    "INSERT INTO supertypes (class_id, position, kind, name)
+                     VALUES (?1, ?2, ?3, ?4)",
+                )?
+                .execute(params![class_id, position, kind, name])?;
+        }
+        self.annotations(annotations, Target::Class(class_id))?;
+
+        let mut references = BTreeSet::new();
+        references.extend(super_class.map(str::to_string));
+        references.extend(interfaces.iter().cloned());
+        if let Some(signature) = signature {
+            class_signature_references(signature, &mut references);
+        }
+        annotation_references(annotations, &mut references);
+        references.remove(name);
+        self.references(class_id, None, None, &references)?;
+
+        for field in fields {
+            self.field(class_id, name, field)?;
+        }
+        for method in methods {
+            self.method(class_id, name, method)?;
+        }
+        Ok(())
+    }
+
+    /// Commits the components, then creates the indices.
+    pub fn finish(self) -> Result<Connection, Box<dyn Error>> {
+        self.connection.execute_batch("COMMIT;")?;
+        self.connection.execute_batch(INDICES)?;
+        Ok(self.connection)
+    }
+
+    fn source_id(&mut self, source: &str) -> Result<i64, Box<dyn Error>> {
+        if let Some(id) = self.sources.get(source) {
+            return Ok(*id);
+        }
+        self.connection
+            .prepare_cached("INSERT INTO sources (path) VALUES (?1)")?
+            .execute(params![source])?;
+        let id = self.connection.last_insert_rowid();
+        self.sources.insert(source.to_string(), id);
+        Ok(id)
+    }
+
+    fn field(
+        &mut self,
+        class_id: i64,
+        class_name: &str,
+        field: &Field,
+    ) -> Result<(), Box<dyn Error>> {
+        let constant_value = field.constant_value.as_ref().map(|x| match x {
+            ConstantValue::Int(x) => Value::Integer(*x as i64),
+            ConstantValue::Long(x) => Value::Integer(*x),
+            ConstantValue::Float(x) => Value::Real(*x as f64),
+            ConstantValue::Double(x) => Value::Real(*x),
+            ConstantValue::String(x) => Value::Text(x.clone()),
+        });
+        self.connection
+            .prepare_cached(
+                "INSERT INTO fields (class_id, name, descriptor, signature, type, access_flags,
+                     modifiers, is_static, constant_value)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            )?
+            .execute(params![
+                class_id,
+                field.name,
+                field.descriptor,
+                field
+                    .signature
+                    .as_ref()
+                    .map(FieldSignature::to_signature_string),
+                field.ty.name(),
+                field.access_flags.bits(),
+                field.modifiers,
+                field.is_static,
+                constant_value,
+            ])?;
+        let field_id = self.connection.last_insert_rowid();
+        self.annotations(&field.annotations, Target::Field(field_id))?;
+
+        let mut references = BTreeSet::new();
+        ty_references(&field.ty, &mut references);
+        if let Some(signature) = &field.signature {
+            field_signature_references(signature, &mut references);
+        }
+        annotation_references(&field.annotations, &mut references);
+        references.remove(class_name);
+        self.references(class_id, None, Some(field_id), &references)
+    }
+
+    fn method(
+        &mut self,
+        class_id: i64,
+        class_name: &str,
+        method: &Method,
+    ) -> Result<(), Box<dyn Error>> {
+        self.connection
+            .prepare_cached(
+                "INSERT INTO methods (class_id, name, descriptor, signature, return_type,
+                     access_flags, modifiers, is_static)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            )?
+            .execute(params![
+                class_id,
+                method.name,
+                method.descriptor,
+                method
+                    .signature
+                    .as_ref()
+                    .map(MethodSignature::to_signature_string),
+                method.ret_ty.name(),
+                method.access_flags.bits(),
+                method.modifiers,
+                method.is_static,
+            ])?;
+        let method_id = self.connection.last_insert_rowid();
+        for (position, ty) in method.param_tys.iter().enumerate() {
+            self.connection
+                .prepare_cached(
+                    "INSERT INTO parameters (method_id, position, type) VALUES (?1, ?2, ?3)",
+                )?
+                .execute(params![method_id, position, ty.name()])?;
+        }
+        self.annotations(&method.annotations, Target::Method(method_id))?;
+        if let Some(value) = &method.annotation_default {
+            self.annotation_value(value, ValueOwner::Default(method_id), 0)?;
+        }
+
+        let mut references = BTreeSet::new();
+        for ty in method.param_tys.iter().chain([&method.ret_ty]) {
+            ty_references(ty, &mut references);
+        }
+        references.extend(method.exceptions.iter().cloned());
+        if let Some(signature) = &method.signature {
+            method_signature_references(signature, &mut references);
+        }
+        annotation_references(&method.annotations, &mut references);
+        if let Some(value) = &method.annotation_default {
+            annotation_value_references(value, &mut references);
+        }
+        references.remove(class_name);
+        self.references(class_id, Some(method_id), None, &references)
+    }
+
+    fn annotations(
+        &mut self,
+        annotations: &[Annotation],
+        target: Target,
+    ) -> Result<(), Box<dyn Error>> {
+        for annotation in annotations {
+            self.annotation(annotation, target)?;
+        }
+        Ok(())
+    }
+
+    fn annotation(
+        &mut self,
+        annotation: &Annotation,
+        target: Target,
+    ) -> Result<(), Box<dyn Error>> {
+        let (class_id, method_id, field_id, value_id) = match target {
+            Target::Class(id) => (Some(id), None, None, None),
+            Target::Method(id) => (None, Some(id), None, None),
+            Target::Field(id) => (None, None, Some(id), None),
+            Target::Value(id) => (None, None, None, Some(id)),
+        };
+        let kind = match annotation.kind {
+            AnnotationKind::RuntimeInvisible => "runtime_invisible",
+            AnnotationKind::RuntimeVisible => "runtime_visible",
+            AnnotationKind::RuntimeInvisibleParameter => "runtime_invisible_parameter",
+            AnnotationKind::RuntimeVisibleParameter => "runtime_visible_parameter",
+            AnnotationKind::RuntimeInvisibleType => "runtime_invisible_type",
+            AnnotationKind::RuntimeVisibleType => "runtime_visible_type",
+        };
+        self.connection
+            .prepare_cached(
+                "INSERT INTO annotations (class_id, method_id, field_id, value_id, type, kind)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?
+            .execute(params![
+                class_id,
+                method_id,
+                field_id,
+                value_id,
+                annotation.ty.name(),
+                kind
+            ])?;
+        let annotation_id = self.connection.last_insert_rowid();
+        for (position, element) in annotation.elements.iter().enumerate() {
+            self.annotation_value(
+                &element.value,
+                ValueOwner::Annotation(annotation_id, &element.name),
+                position,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn annotation_value(
+        &mut self,
+        value: &AnnotationValue,
+        owner: ValueOwner,
+        position: usize,
+    ) -> Result<(), Box<dyn Error>> {
+        let (annotation_id, array_id, method_id, name) = match owner {
+            ValueOwner::Annotation(id, name) => (Some(id), None, None, Some(name)),
+            ValueOwner::Array(id) => (None, Some(id), None, None),
+            ValueOwner::Default(id) => (None, None, Some(id), None),
+        };
+        let (kind, constant, ty) = match value {
+            AnnotationValue::Byte(x) => ("byte", Value::Integer(*x as i64), None),
+            AnnotationValue::Char(x) => {
+                ("char", Value::Text(String::from_utf16_lossy(&[*x])), None)
+            }
+            AnnotationValue::Short(x) => ("short", Value::Integer(*x as i64), None),
+            AnnotationValue::Int(x) => ("int", Value::Integer(*x as i64), None),
+            AnnotationValue::Long(x) => ("long", Value::Integer(*x), None),
+            AnnotationValue::Float(x) => ("float", Value::Real(*x as f64), None),
+            AnnotationValue::Double(x) => ("double", Value::Real(*x), None),
+            AnnotationValue::Boolean(x) => ("boolean", Value::Integer(*x as i64), None),
+            AnnotationValue::String(x) => ("string", Value::Text(x.clone()), None),
+            AnnotationValue::Enum { ty, name } => {
+                ("enum", Value::Text(name.clone()), Some(ty.name()))
+            }
+            AnnotationValue::Class(ty) => ("class", Value::Text(ty.name()), None),
+            AnnotationValue::Annotation(_) => ("annotation", Value::Null, None),
+            AnnotationValue::Array(_) => ("array", Value::Null, None),
+        };
+        self.connection
+            .prepare_cached(
+                "INSERT INTO annotation_values (annotation_id, array_id, method_id, name,
+                     position, kind, value, type)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            )?
+            .execute(params![
+                annotation_id,
+                array_id,
+                method_id,
+                name,
+                position,
+                kind,
+                constant,
+                ty
+            ])?;
+        let value_id = self.connection.last_insert_rowid();
+        match value {
+            AnnotationValue::Annotation(annotation) => {
+                self.annotation(annotation, Target::Value(value_id))?
+            }
+            AnnotationValue::Array(values) => {
+                for (position, value) in values.iter().enumerate() {
+                    self.annotation_value(value, ValueOwner::Array(value_id), position)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn references(
+        &mut self,
+        class_id: i64,
+        method_id: Option<i64>,
+        field_id: Option<i64>,
+        names: &BTreeSet<String>,
+    ) -> Result<(), Box<dyn Error>> {
+        for name in names {
+            self.connection
+                .prepare_cached(
+                    "INSERT INTO type_references (class_id, method_id, field_id, name)
+                     VALUES (?1, ?2, ?3, ?4)",
+                )?
+                .execute(params![class_id, method_id, field_id, name])?;
+        }
+        Ok(())
+    }
+}
+
+fn ty_references(ty: &Ty, out: &mut BTreeSet<String>) {
+    match ty {
+        Ty::Reference(name) => {
+            out.insert(name.qualified_name());
+        }
+        Ty::Array(ty, _) => ty_references(ty, out),
+        Ty::Prim(_) | Ty::TyVar(_) | Ty::Void => {}
+    }
+}
+
+fn annotation_references(annotations: &[Annotation], out: &mut BTreeSet<String>) {
+    for annotation in annotations {
+        ty_references(&annotation.ty, out);
+        for element in &annotation.elements {
+            annotation_value_references(&element.value, out);
+        }
+    }
+}
+
+fn annotation_value_references(value: &AnnotationValue, out: &mut BTreeSet<String>) {
+    match value {
+        AnnotationValue::Enum { ty, .. } | AnnotationValue::Class(ty) => ty_references(ty, out),
+        AnnotationValue::Annotation(annotation) => {
+            annotation_references(std::slice::from_ref(annotation.as_ref()), out)
+        }
+        AnnotationValue::Array(values) => {
+            for value in values {
+                annotation_value_references(value, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn class_signature_references(signature: &ClassSignature, out: &mut BTreeSet<String>) {
+    type_parameter_references(&signature.type_parameters, out);
+    class_type_references(&signature.superclass_signature, out);
+    for x in &signature.superinterface_signatures {
+        class_type_references(x, out);
+    }
+}
+
+fn method_signature_references(signature: &MethodSignature, out: &mut BTreeSet<String>) {
+    type_parameter_references(&signature.type_parameters, out);
+    for x in &signature.parameters {
+        type_signature_references(x, out);
+    }
+    if let SignatureResult::JavaTypeSignature(x) = &signature.result {
+        type_signature_references(x, out);
+    }
+    for x in &signature.throws_signatures {
+        if let ThrowsSignature::ClassTypeSignature(x) = x {
+            class_type_references(x, out);
+        }
+    }
+}
+
+fn field_signature_references(signature: &FieldSignature, out: &mut BTreeSet<String>) {
+    reference_type_references(&signature.reference_type_signature, out);
+}
+
+fn type_parameter_references(
+    type_parameters: &Option<Vec<TypeParameter>>,
+    out: &mut BTreeSet<String>,
+) {
+    for x in type_parameters.iter().flatten() {
+        for bound in x.class_bound.iter().chain(&x.interface_bounds) {
+            reference_type_references(bound, out);
+        }
+    }
+}
+
+fn type_signature_references(signature: &TypeSignature, out: &mut BTreeSet<String>) {
+    if let TypeSignature::Reference(x) = signature {
+        reference_type_references(x, out);
+    }
+}
+
+fn reference_type_references(signature: &ReferenceTypeSignature, out: &mut BTreeSet<String>) {
+    match signature {
+        ReferenceTypeSignature::Class(x) => class_type_references(x, out),
+        ReferenceTypeSignature::Array(x) => type_signature_references(&x.java_type_signature, out),
+        ReferenceTypeSignature::TypeVariable(_) => {}
+    }
+}
+
+fn class_type_references(signature: &ClassTypeSignature, out: &mut BTreeSet<String>) {
+    let mut name = match &signature.package_specifier {
+        Some(package) => format!("{package}."),
+        None => String::new(),
+    };
+    let segments = std::iter::once(&signature.simple_class_type_signature)
+        .chain(&signature.class_type_signature_suffixes);
+    for (i, segment) in segments.enumerate() {
+        if i > 0 {
+            name.push('$');
+        }
+        name.push_str(&segment.identifier);
+        type_argument_references(segment, out);
+    }
+    out.insert(name);
+}
+
+fn type_argument_references(signature: &SimpleClassTypeSignature, out: &mut BTreeSet<String>) {
+    for x in signature.type_arguments.iter().flatten() {
+        if let TypeArgument::ReferenceType(_, x) = x {
+            reference_type_references(x, out);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        component::{AnnotationElement, Class, Component},
+        test_util::{self, component, reference},
+    };
+
+    fn method(name: &str, descriptor: &str, signature: Option<&str>, deprecated: bool) -> Method {
+        Method {
+            annotations: deprecated
+                .then(|| Annotation {
+                    kind: AnnotationKind::RuntimeVisible,
+                    ty: reference("java.lang", "Deprecated"),
+                    elements: vec![AnnotationElement {
+                        name: "since".to_string(),
+                        value: AnnotationValue::String("2.0".to_string()),
+                    }],
+                })
+                .into_iter()
+                .collect(),
+            ..test_util::method(name, descriptor, signature, 0x0001)
+        }
+    }
+
+    #[test]
+    fn answers_api_questions_in_sql() {
+        let methods = vec![
+            method(
+                "find",
+                "(I)Ljava/util/Optional;",
+                Some("(I)Ljava/util/Optional<Ljava/math/BigDecimal;>;"),
+                true,
+            ),
+            method("findAll", "(I)Ljava/util/List;", None, true),
+            method("lookup", "(I)Ljava/util/Optional;", None, false),
+        ];
+        let component = SourcedComponent {
+            source: "lib.jar".to_string(),
+            entry_path: "a/Repository.class".to_string(),
+            component: Component {
+                class_file_name: "Repository.class".to_string(),
+                ..component(ComponentKind::Class(Class {
+                    super_class: Some("java.lang.Object".to_string()),
+                    interfaces: vec!["java.io.Closeable".to_string()],
+                    methods,
+                    ..test_util::class("a.Repository")
+                }))
+            },
+        };
+        let mut writer = SqliteWriter::new(Connection::open_in_memory().unwrap()).unwrap();
+        writer.write(&component).unwrap();
+        let connection = writer.finish().unwrap();
+        let query = |sql: &str| {
+            let mut statement = connection.prepare(sql).unwrap();
+            let rows = statement
+                .query_map([], |row| row.get::<_, String>(0))
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            rows
+        };
+
+        assert_eq!(
+            query(
+                "SELECT c.name || '.' || m.name || ' ' || s.path FROM methods m
+                 JOIN classes c ON c.id = m.class_id
+                 JOIN sources s ON s.id = c.source_id
+                 JOIN annotations a ON a.method_id = m.id
+                 WHERE a.type = 'java.lang.Deprecated' AND m.return_type = 'java.util.Optional'"
+            ),
+            ["a.Repository.find lib.jar"]
+        );
+        assert_eq!(
+            query(
+                "SELECT v.value FROM annotation_values v
+                 JOIN annotations a ON a.id = v.annotation_id
+                 JOIN methods m ON m.id = a.method_id
+                 WHERE m.name = 'findAll' AND v.name = 'since'"
+            ),
+            ["2.0"]
+        );
+        assert_eq!(
+            query("SELECT name FROM supertypes ORDER BY position"),
+            ["java.lang.Object", "java.io.Closeable"]
+        );
+        assert_eq!(
+            query(
+                "SELECT r.name FROM type_references r JOIN methods m ON m.id = r.method_id
+                 WHERE m.name = 'find' ORDER BY 1"
+            ),
+            [
+                "java.lang.Deprecated",
+                "java.math.BigDecimal",
+                "java.util.Optional"
+            ]
+        );
+        assert_eq!(
+            query("SELECT signature FROM methods WHERE name = 'find'"),
+            ["(I)Ljava/util/Optional<Ljava/math/BigDecimal;>;"]
+        );
+    }
+}
@@ -0,0 +1,340 @@
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::{
+    class_file::{
+        AttributeKind, ClassFile, MethodAccessFlags, MethodInfo, StackMapFrame, StackMapFrameKind,
+        VerificationTypeInfo,
+    },
+    constant_pool::ConstantPool,
+    descriptor::{try_parse_method_descriptor, BaseTy, FieldDescriptor, FieldTy},
+};
+
+/// A verification type with its class name resolved.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum VerificationType {
+    Top,
+    Integer,
+    Float,
+    Long,
+    Double,
+    Null,
+    UninitializedThis,
+    /// A class in binary form (`java.lang.String`), or an array descriptor (`[Ljava.lang.String;`)
+    Object(String),
+    /// An object created by the `new` instruction at this offset, before its constructor runs
+    Uninitialized(u32),
+}
+
+impl VerificationType {
+    /// Whether the type takes two local variable slots.
+    pub fn is_wide(&self) -> bool {
+        matches!(self, VerificationType::Long | VerificationType::Double)
+    }
+}
+
+impl fmt::Display for VerificationType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerificationType::Top => f.write_str("top"),
+            VerificationType::Integer => f.write_str("int"),
+            VerificationType::Float => f.write_str("float"),
+            VerificationType::Long => f.write_str("long"),
+            VerificationType::Double => f.write_str("double"),
+            VerificationType::Null => f.write_str("null"),
+            VerificationType::UninitializedThis => f.write_str("uninitializedThis"),
+            VerificationType::Object(name) => f.write_str(name),
+            VerificationType::Uninitialized(offset) => write!(f, "uninitialized({offset})"),
+        }
+    }
+}
+
+/// The types of the local variables and the operand stack at a bytecode offset.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Frame {
+    pub offset: u32,
+    /// Indexed by local variable slot: `long` and `double` values are followed by `Top`
+    pub locals: Vec<VerificationType>,
+    /// From bottom to top, with one entry per value
+    pub stack: Vec<VerificationType>,
+}
+
+/// Returns the frame at the start of a method, derived from its descriptor.
+pub fn initial_frame(class_file: &ClassFile, method: &MethodInfo) -> Result<Frame, String> {
+    let pool = class_file.pool();
+    let class_name = pool
+        .class_name(class_file.this_class)
+        .map_err(|e| e.to_string())?;
+    let name = pool.utf8(method.name_index).map_err(|e| e.to_string())?;
+    let descriptor = pool
+        .utf8(method.descriptor_index)
+        .map_err(|e| e.to_string())?;
+
+    let mut locals = Vec::new();
+    if !method.access_flags.contains(MethodAccessFlags::STATIC) {
+        if name == "<init>" && class_name != "java/lang/Object" {
+            locals.push(VerificationType::UninitializedThis);
+        } else {
+            locals.push(VerificationType::Object(class_name.replace('/', ".")));
+        }
+    }
+    for param in try_parse_method_descriptor(descriptor)?.param_descs {
+        push_local(&mut locals, from_descriptor(&param));
+    }
+
+    Ok(Frame {
+        offset: 0,
+        locals,
+        stack: Vec::new(),
+    })
+}
+
+/// Expands the `StackMapTable` of a method into frames with absolute offsets and every local
+/// and stack entry spelled out. The implicit frame at the start of the method is not included,
+/// see [`initial_frame`]. Methods without code or without a table have no frames.
+pub fn method_frames(class_file: &ClassFile, method: &MethodInfo) -> Result<Vec<Frame>, String> {
+    let Some(entries) = method
+        .attributes
+        .iter()
+        .find_map(|x| match &x.kind {
+            AttributeKind::Code { attributes, .. } => Some(attributes),
+            _ => None,
+        })
+        .and_then(|attributes| {
+            attributes.iter().find_map(|x| match &x.kind {
+                AttributeKind::StackMapTable { entries, .. } => Some(entries),
+                _ => None,
+            })
+        })
+    else {
+        return Ok(Vec::new());
+    };
+
+    let pool = class_file.pool();
+    let mut previous = initial_frame(class_file, method)?;
+    let mut frames = Vec::with_capacity(entries.len());
+    for (i, entry) in entries.iter().enumerate() {
+        let offset = if i == 0 {
+            entry.offset_delta() as u32
+        } else {
+            previous.offset + entry.offset_delta() as u32 + 1
+        };
+        let frame = expand_frame(pool, &previous, entry, offset)
+            .map_err(|e| format!("Frame {i} at offset {offset}: {e}"))?;
+        frames.push(frame.clone());
+        previous = frame;
+    }
+    Ok(frames)
+}
+
+fn expand_frame(
+    pool: ConstantPool,
+    previous: &Frame,
+    entry: &StackMapFrame,
+    offset: u32,
+) -> Result<Frame, String> {
+    let mut locals = previous.locals.clone();
+    let stack = match &entry.kind {
+        StackMapFrameKind::Same | StackMapFrameKind::SameExtended { .. } => Vec::new(),
+        StackMapFrameKind::SameLocals1StackItem { stack }
+        | StackMapFrameKind::SameLocals1StackItemExtended { stack, .. } => {
+            vec![resolve(pool, stack)?]
+        }
+        StackMapFrameKind::Chop { .. } => {
+            for _ in 0..251 - entry.frame_type {
+                pop_local(&mut locals)?;
+            }
+            Vec::new()
+        }
+        StackMapFrameKind::Append {
+            locals: appended, ..
+        } => {
+            for local in appended {
+                push_local(&mut locals, resolve(pool, local)?);
+            }
+            Vec::new()
+        }
+        StackMapFrameKind::Full {
+            locals: full_locals,
+            stack,
+            ..
+        } => {
+            locals.clear();
+            for local in full_locals {
+                push_local(&mut locals, resolve(pool, local)?);
+            }
+            stack
+                .iter()
+                .map(|x| resolve(pool, x))
+                .collect::<Result<_, _>>()?
+        }
+    };
+
+    Ok(Frame {
+        offset,
+        locals,
+        stack,
+    })
+}
+
+fn push_local(locals: &mut Vec<VerificationType>, ty: VerificationType) {
+    let wide = ty.is_wide();
+    locals.push(ty);
+    if wide {
+        locals.push(VerificationType::Top);
+    }
+}
+
+/// Removes the last local, which takes two slots if it is a `long` or `double`.
+fn pop_local(locals: &mut Vec<VerificationType>) -> Result<(), String> {
+    let Some(last) = locals.pop() else {
+        return Err("chop frame removes more locals than there are".to_string());
+    };
+    if last == VerificationType::Top && locals.last().is_some_and(|x| x.is_wide()) {
+        locals.pop();
+    }
+    Ok(())
+}
+
+fn resolve(pool: ConstantPool, info: &VerificationTypeInfo) -> Result<VerificationType, String> {
+    Ok(match info {
+        VerificationTypeInfo::Top => VerificationType::Top,
+        VerificationTypeInfo::Integer => VerificationType::Integer,
+        VerificationTypeInfo::Float => VerificationType::Float,
+        VerificationTypeInfo::Long => VerificationType::Long,
+        VerificationTypeInfo::Double => VerificationType::Double,
+        VerificationTypeInfo::Null => VerificationType::Null,
+        VerificationTypeInfo::UninitializedThis => VerificationType::UninitializedThis,
+        VerificationTypeInfo::Object { cpool_index } => VerificationType::Object(
+            pool.class_name(*cpool_index)
+                .map_err(|e| e.to_string())?
+                .replace('/', "."),
+        ),
+        VerificationTypeInfo::Uninitialized { offset } => {
+            VerificationType::Uninitialized(*offset as u32)
+        }
+    })
+}
+
+pub(crate) fn from_descriptor(descriptor: &FieldDescriptor) -> VerificationType {
+    match &descriptor.ty {
+        FieldTy::Base(BaseTy::Float) => VerificationType::Float,
+        FieldTy::Base(BaseTy::Long) => VerificationType::Long,
+        FieldTy::Base(BaseTy::Double) => VerificationType::Double,
+        FieldTy::Base(_) => VerificationType::Integer,
+        FieldTy::Obj(obj) => VerificationType::Object(obj.class_name.replace('/', ".")),
+        FieldTy::Array(_) => VerificationType::Object(descriptor.descriptor.replace('/', ".")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::class_file::{AccessFlags, Attribute, ConstantPoolInfo};
+    use crate::test_util::utf8;
+
+    fn attribute(kind: AttributeKind) -> Attribute {
+        Attribute {
+            attribute_name_index: 0,
+            attribute_length: 0,
+            kind,
+        }
+    }
+
+    fn frame(frame_type: u8, kind: StackMapFrameKind) -> StackMapFrame {
+        StackMapFrame { frame_type, kind }
+    }
+
+    #[test]
+    fn expands_compressed_frames() {
+        let entries = vec![
+            frame(
+                252,
+                StackMapFrameKind::Append {
+                    offset_delta: 4,
+                    locals: vec![VerificationTypeInfo::Long],
+                },
+            ),
+            frame(
+                70,
+                StackMapFrameKind::SameLocals1StackItem {
+                    stack: VerificationTypeInfo::Object { cpool_index: 2 },
+                },
+            ),
+            frame(250, StackMapFrameKind::Chop { offset_delta: 2 }),
+            frame(3, StackMapFrameKind::Same),
+        ];
+        let method = MethodInfo {
+            access_flags: MethodAccessFlags::STATIC,
+            name_index: 3,
+            descriptor_index: 4,
+            attributes_count: 1,
+            attributes: vec![attribute(AttributeKind::Code {
+                max_stack: 2,
+                max_locals: 3,
+                code_length: 0,
+                code: Vec::new(),
+                exception_table_length: 0,
+                exception_table: Vec::new(),
+                attributes_count: 1,
+                attributes: vec![attribute(AttributeKind::StackMapTable {
+                    number_of_entries: entries.len() as u16,
+                    entries,
+                })],
+            })],
+        };
+        let class_file = ClassFile {
+            minor_version: 0,
+            major_version: 61,
+            constant_pool_count: 5,
+            constant_pool: vec![
+                ConstantPoolInfo::Empty,
+                utf8("a/b/Main"),
+                ConstantPoolInfo::Class { name_index: 1 },
+                utf8("run"),
+                utf8("([Ljava/lang/String;)V"),
+            ],
+            access_flags: AccessFlags::PUBLIC,
+            this_class: 2,
+            super_class: 0,
+            interfaces_count: 0,
+            interfaces: Vec::new(),
+            fields_count: 0,
+            fields: Vec::new(),
+            methods_count: 0,
+            methods: Vec::new(),
+            attributes_count: 0,
+            attributes: Vec::new(),
+        };
+
+        let args = VerificationType::Object("[Ljava.lang.String;".to_string());
+        let main = VerificationType::Object("a.b.Main".to_string());
+        let frames = method_frames(&class_file, &method).unwrap();
+        assert_eq!(
+            frames,
+            vec![
+                Frame {
+                    offset: 4,
+                    locals: vec![args.clone(), VerificationType::Long, VerificationType::Top],
+                    stack: Vec::new(),
+                },
+                Frame {
+                    offset: 11,
+                    locals: vec![args.clone(), VerificationType::Long, VerificationType::Top],
+                    stack: vec![main],
+                },
+                Frame {
+                    offset: 14,
+                    locals: vec![args.clone()],
+                    stack: Vec::new(),
+                },
+                Frame {
+                    offset: 18,
+                    locals: vec![args],
+                    stack: Vec::new(),
+                },
+            ]
+        );
+    }
+}
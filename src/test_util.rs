@@ -0,0 +1,293 @@
+use crate::{
+    class_file::{
+        AccessFlags, Attribute, AttributeKind, ClassFile, ConstantPoolInfo, FieldAccessFlags,
+        MethodAccessFlags, MethodInfo,
+    },
+    component::{
+        field_modifiers, method_modifiers, Class, Component, ComponentKind, Field, Interface,
+        Method, Ty, TyName,
+    },
+    descriptor::{parse_field_descriptor, parse_method_descriptor, ReturnDescriptor},
+    signature::{parse_field_signature, parse_method_signature, Result as SignatureResult},
+};
+
+/// A method as the extractor builds it, with its types taken from the signature if there is
+/// one and from the descriptor otherwise, e.g. `method("get", "(I)Ljava/lang/Object;",
+/// Some("(I)TT;"), 0x0001)`.
+pub(crate) fn method(name: &str, descriptor: &str, signature: Option<&str>, flags: u16) -> Method {
+    let access_flags = MethodAccessFlags::from_bits(flags).unwrap();
+    let signature = signature.map(|x| parse_method_signature(x).unwrap().1);
+    let parsed = parse_method_descriptor(descriptor);
+    let (param_tys, ret_ty, type_params) = match &signature {
+        Some(sig) => (
+            sig.parameters.iter().map(|x| x.into()).collect(),
+            match &sig.result {
+                SignatureResult::JavaTypeSignature(x) => x.into(),
+                SignatureResult::VoidDescriptor => Ty::Void,
+            },
+            sig.type_parameters
+                .iter()
+                .flatten()
+                .map(|x| x.identifier.to_string())
+                .collect(),
+        ),
+        None => (
+            parsed.param_descs.iter().map(|x| (&x.ty).into()).collect(),
+            match &parsed.ret_desc {
+                ReturnDescriptor::TyDesc(x) => (&x.ty).into(),
+                ReturnDescriptor::Void => Ty::Void,
+            },
+            Vec::new(),
+        ),
+    };
+    Method {
+        name: name.to_string(),
+        descriptor: descriptor.to_string(),
+        access_flags,
+        signature,
+        modifiers: method_modifiers(access_flags),
+        param_tys,
+        ret_ty,
+        type_params,
+        annotations: Vec::new(),
+        is_static: access_flags.contains(MethodAccessFlags::STATIC),
+        exceptions: Vec::new(),
+        annotation_default: None,
+        constructor_call: None,
+    }
+}
+
+/// A field as the extractor builds it, e.g. `field("count", "I", None, 0x0002)`.
+pub(crate) fn field(name: &str, descriptor: &str, signature: Option<&str>, flags: u16) -> Field {
+    let access_flags = FieldAccessFlags::from_bits(flags).unwrap();
+    let signature = signature.map(|x| parse_field_signature(x).unwrap().1);
+    Field {
+        name: name.to_string(),
+        descriptor: descriptor.to_string(),
+        access_flags,
+        ty: match &signature {
+            Some(sig) => sig.into(),
+            None => (&parse_field_descriptor(descriptor)).into(),
+        },
+        signature,
+        modifiers: field_modifiers(access_flags),
+        annotations: Vec::new(),
+        is_static: access_flags.contains(FieldAccessFlags::STATIC),
+        constant_value: None,
+    }
+}
+
+/// An empty public class without a superclass.
+pub(crate) fn class(name: &str) -> Class {
+    Class {
+        qualified_name: name.to_string(),
+        super_class: None,
+        interfaces: Vec::new(),
+        signature: None,
+        methods: Vec::new(),
+        fields: Vec::new(),
+        annotations: Vec::new(),
+        is_enum: false,
+        is_abstract: false,
+        access_flags: AccessFlags::PUBLIC,
+        permitted_subclasses: Vec::new(),
+        inner_class: None,
+    }
+}
+
+/// An empty public interface.
+pub(crate) fn interface(name: &str) -> Interface {
+    Interface {
+        is_annotation: false,
+        qualified_name: name.to_string(),
+        interfaces: Vec::new(),
+        signature: None,
+        methods: Vec::new(),
+        fields: Vec::new(),
+        annotations: Vec::new(),
+        access_flags: AccessFlags::PUBLIC | AccessFlags::INTERFACE | AccessFlags::ABSTRACT,
+        permitted_subclasses: Vec::new(),
+        inner_class: None,
+    }
+}
+
+/// A component of a version 61 class file.
+pub(crate) fn component(kind: ComponentKind) -> Component {
+    Component {
+        minor_version: 0,
+        major_version: 61,
+        kind,
+        class_file_name: String::new(),
+    }
+}
+
+pub(crate) fn reference(package_name: &str, name: &str) -> Ty {
+    Ty::Reference(TyName {
+        package_name: Some(package_name.to_string()),
+        name: name.to_string(),
+    })
+}
+
+pub(crate) fn utf8(s: &str) -> ConstantPoolInfo {
+    ConstantPoolInfo::Utf8 {
+        length: s.len() as u16,
+        bytes: s.as_bytes().to_vec(),
+        utf8_str: s.to_string(),
+    }
+}
+
+/// Builds the constant pool of a hand-made class file. Each method returns the index of the
+/// entry it adds, and names are in internal form (`java/lang/Object`).
+#[derive(Debug)]
+pub(crate) struct ConstantPoolBuilder {
+    pub(crate) entries: Vec<ConstantPoolInfo>,
+}
+
+impl ConstantPoolBuilder {
+    pub(crate) fn new() -> Self {
+        Self {
+            entries: vec![ConstantPoolInfo::Empty],
+        }
+    }
+
+    pub(crate) fn add(&mut self, entry: ConstantPoolInfo) -> u16 {
+        self.entries.push(entry);
+        (self.entries.len() - 1) as u16
+    }
+
+    pub(crate) fn utf8(&mut self, s: &str) -> u16 {
+        self.add(utf8(s))
+    }
+
+    pub(crate) fn class(&mut self, name: &str) -> u16 {
+        let name_index = self.utf8(name);
+        self.add(ConstantPoolInfo::Class { name_index })
+    }
+
+    pub(crate) fn method_ref(&mut self, class_name: &str, name: &str, descriptor: &str) -> u16 {
+        let class_index = self.class(class_name);
+        let name_and_type_index = self.name_and_type(name, descriptor);
+        self.add(ConstantPoolInfo::Methodref {
+            class_index,
+            name_and_type_index,
+        })
+    }
+
+    pub(crate) fn interface_method_ref(
+        &mut self,
+        class_name: &str,
+        name: &str,
+        descriptor: &str,
+    ) -> u16 {
+        let class_index = self.class(class_name);
+        let name_and_type_index = self.name_and_type(name, descriptor);
+        self.add(ConstantPoolInfo::InterfaceMethodref {
+            class_index,
+            name_and_type_index,
+        })
+    }
+
+    pub(crate) fn name_and_type(&mut self, name: &str, descriptor: &str) -> u16 {
+        let name_index = self.utf8(name);
+        let descriptor_index = self.utf8(descriptor);
+        self.add(ConstantPoolInfo::NameAndType {
+            name_index,
+            descriptor_index,
+        })
+    }
+
+    /// A `REF_invokeStatic` handle to a static method.
+    pub(crate) fn static_method_handle(
+        &mut self,
+        class_name: &str,
+        name: &str,
+        descriptor: &str,
+    ) -> u16 {
+        let reference_index = self.method_ref(class_name, name, descriptor);
+        self.add(ConstantPoolInfo::MethodHandle {
+            reference_kind: 6,
+            reference_index,
+        })
+    }
+
+    pub(crate) fn invoke_dynamic(
+        &mut self,
+        bootstrap_method: u16,
+        name: &str,
+        descriptor: &str,
+    ) -> u16 {
+        let name_and_type_index = self.name_and_type(name, descriptor);
+        self.add(ConstantPoolInfo::InvokeDynamic {
+            bootstrap_method_attr_index: bootstrap_method,
+            name_and_type_index,
+        })
+    }
+
+    /// A method with the given code, or without a `Code` attribute if it is abstract.
+    pub(crate) fn method(
+        &mut self,
+        name: &str,
+        descriptor: &str,
+        access_flags: MethodAccessFlags,
+        code: Vec<u8>,
+    ) -> MethodInfo {
+        let name_index = self.utf8(name);
+        let descriptor_index = self.utf8(descriptor);
+        let mut attributes = Vec::new();
+        if !access_flags.contains(MethodAccessFlags::ABSTRACT) {
+            let name_index = self.utf8("Code");
+            attributes.push(Attribute::new(
+                name_index,
+                AttributeKind::Code {
+                    max_stack: 4,
+                    max_locals: 4,
+                    code_length: code.len() as u32,
+                    code,
+                    exception_table_length: 0,
+                    exception_table: Vec::new(),
+                    attributes_count: 0,
+                    attributes: Vec::new(),
+                },
+            ));
+        }
+        MethodInfo {
+            access_flags,
+            name_index,
+            descriptor_index,
+            attributes_count: attributes.len() as u16,
+            attributes,
+        }
+    }
+
+    /// A version 61 class file with this pool.
+    pub(crate) fn class_file(
+        mut self,
+        access_flags: AccessFlags,
+        name: &str,
+        super_class: &str,
+        interfaces: &[&str],
+        methods: Vec<MethodInfo>,
+        attributes: Vec<Attribute>,
+    ) -> ClassFile {
+        let this_class = self.class(name);
+        let super_class = self.class(super_class);
+        let interfaces = interfaces.iter().map(|x| self.class(x)).collect::<Vec<_>>();
+        ClassFile {
+            minor_version: 0,
+            major_version: 61,
+            constant_pool_count: self.entries.len() as u16,
+            constant_pool: self.entries,
+            access_flags,
+            this_class,
+            super_class,
+            interfaces_count: interfaces.len() as u16,
+            interfaces,
+            fields_count: 0,
+            fields: Vec::new(),
+            methods_count: methods.len() as u16,
+            methods,
+            attributes_count: attributes.len() as u16,
+            attributes,
+        }
+    }
+}
@@ -0,0 +1,1380 @@
+use std::{collections::BTreeMap, fmt};
+
+use crate::{
+    bytecode::{opcodes, parse_instructions, Instruction, Operands},
+    class_file::{AttributeKind, ClassFile, ConstantPoolInfo, ExceptionTableEntry, MethodInfo},
+    constant_pool::ConstantPool,
+    descriptor::{try_parse_field_descriptor, try_parse_method_descriptor, ReturnDescriptor},
+    stack_map::{from_descriptor, initial_frame, method_frames, Frame, VerificationType},
+};
+
+/// Type checks the code of a method against its `StackMapTable`, as the JVM verifies class files
+/// of version 51 and later (JVMS 4.10.1). Each instruction must find values of the right types
+/// on the stack and in the locals, and the types reaching a branch target or exception handler
+/// must match its frame. Without the rest of the classpath, class types are not checked against
+/// each other, only against arrays and primitives. Returns the first error, as the JVM does.
+/// Methods without code pass.
+pub fn type_check(class_file: &ClassFile, method: &MethodInfo) -> Result<(), String> {
+    let Some((max_stack, max_locals, code, exception_table)) =
+        method.attributes.iter().find_map(|x| match &x.kind {
+            AttributeKind::Code {
+                max_stack,
+                max_locals,
+                code,
+                exception_table,
+                ..
+            } => Some((*max_stack, *max_locals, code, exception_table)),
+            _ => None,
+        })
+    else {
+        return Ok(());
+    };
+
+    let instructions = match parse_instructions(code) {
+        Ok(([], instructions)) => instructions,
+        _ => return Err("code cannot be decoded".to_string()),
+    };
+    let initial = initial_frame(class_file, method)?;
+    let frames: BTreeMap<u32, Frame> = method_frames(class_file, method)?
+        .into_iter()
+        .map(|x| (x.offset, x))
+        .collect();
+
+    let pool = class_file.pool();
+    let class_name = pool
+        .class_name(class_file.this_class)
+        .map_err(|e| e.to_string())?;
+    let name = pool.utf8(method.name_index).map_err(|e| e.to_string())?;
+    let descriptor = pool
+        .utf8(method.descriptor_index)
+        .map_err(|e| e.to_string())?;
+    let ret = match try_parse_method_descriptor(descriptor)?.ret_desc {
+        ReturnDescriptor::TyDesc(x) => Some(from_descriptor(&x)),
+        ReturnDescriptor::Void => None,
+    };
+
+    let mut checker = Checker {
+        pool,
+        class_name: class_name.replace('/', "."),
+        is_init: name == "<init>",
+        ret,
+        max_stack: max_stack as usize,
+        max_locals: max_locals as usize,
+        instructions: &instructions,
+        frames: &frames,
+        locals: initial.locals,
+        stack: initial.stack,
+    };
+    checker.run(exception_table)
+}
+
+/// The kinds of values loaded, stored and returned, in the order of their opcodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Int,
+    Long,
+    Float,
+    Double,
+    Reference,
+}
+
+const KINDS: [Kind; 5] = [
+    Kind::Int,
+    Kind::Long,
+    Kind::Float,
+    Kind::Double,
+    Kind::Reference,
+];
+
+impl Kind {
+    /// Whether a value of this kind can have the type, including uninitialized references.
+    fn matches(self, ty: &VerificationType) -> bool {
+        match self {
+            Kind::Int => *ty == VerificationType::Integer,
+            Kind::Long => *ty == VerificationType::Long,
+            Kind::Float => *ty == VerificationType::Float,
+            Kind::Double => *ty == VerificationType::Double,
+            Kind::Reference => matches!(
+                ty,
+                VerificationType::Null
+                    | VerificationType::UninitializedThis
+                    | VerificationType::Object(_)
+                    | VerificationType::Uninitialized(_)
+            ),
+        }
+    }
+
+    /// The type of a primitive value of this kind.
+    fn ty(self) -> VerificationType {
+        match self {
+            Kind::Int => VerificationType::Integer,
+            Kind::Long => VerificationType::Long,
+            Kind::Float => VerificationType::Float,
+            Kind::Double => VerificationType::Double,
+            Kind::Reference => VerificationType::Object("java.lang.Object".to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Kind::Int => "int",
+            Kind::Long => "long",
+            Kind::Float => "float",
+            Kind::Double => "double",
+            Kind::Reference => "a reference",
+        })
+    }
+}
+
+struct Checker<'a> {
+    pool: ConstantPool<'a>,
+    /// In binary form, `a.b.Main`
+    class_name: String,
+    is_init: bool,
+    /// `None` for `void` methods
+    ret: Option<VerificationType>,
+    max_stack: usize,
+    max_locals: usize,
+    instructions: &'a [Instruction],
+    frames: &'a BTreeMap<u32, Frame>,
+    locals: Vec<VerificationType>,
+    stack: Vec<VerificationType>,
+}
+
+impl Checker<'_> {
+    fn run(&mut self, exception_table: &[ExceptionTableEntry]) -> Result<(), String> {
+        // The instruction before the current one, if execution can fall through from it
+        let mut previous: Option<String> = Some("the start of the method".to_string());
+        for instruction in self.instructions {
+            let offset = instruction.offset;
+            let at = format!("{} at offset {offset}", instruction.mnemonic());
+            if let Some(frame) = self.frames.get(&offset) {
+                if let Some(previous) = &previous {
+                    self.check_frame(frame)
+                        .map_err(|e| format!("{previous} falls through to {offset}, but {e}"))?;
+                }
+                self.locals = frame.locals.clone();
+                self.stack = frame.stack.clone();
+            } else if previous.is_none() {
+                return Err(format!(
+                    "{at} follows an unconditional branch but has no stack map frame"
+                ));
+            }
+
+            for entry in exception_table {
+                if (entry.start_pc as u32..entry.end_pc as u32).contains(&offset) {
+                    self.check_handler(entry)
+                        .map_err(|e| format!("{at} is covered by the exception handler at {e}"))?;
+                }
+            }
+
+            let falls_through = self.execute(instruction).map_err(|e| format!("{at} {e}"))?;
+            previous = falls_through.then_some(at);
+        }
+
+        match previous {
+            Some(previous) => Err(format!("{previous} falls off the end of the code")),
+            None => Ok(()),
+        }
+    }
+
+    /// Checks that the current locals and stack are assignable to a frame.
+    fn check_frame(&self, frame: &Frame) -> Result<(), String> {
+        check_assignable(&self.locals, &self.stack, frame)
+    }
+
+    fn check_handler(&self, entry: &ExceptionTableEntry) -> Result<(), String> {
+        let handler = entry.handler_pc as u32;
+        let exception = if entry.catch_type == 0 {
+            "java.lang.Throwable".to_string()
+        } else {
+            self.pool
+                .class_name(entry.catch_type)
+                .map_err(|e| format!("{handler}, but {e}"))?
+                .replace('/', ".")
+        };
+        let frame = self
+            .frames
+            .get(&handler)
+            .ok_or_else(|| format!("{handler}, which has no stack map frame"))?;
+        check_assignable(&self.locals, &[VerificationType::Object(exception)], frame)
+            .map_err(|e| format!("{handler}, but {e}"))
+    }
+
+    fn check_branches(&self, instruction: &Instruction) -> Result<(), String> {
+        for target in instruction.branch_targets() {
+            let frame = self
+                .frames
+                .get(&target)
+                .ok_or_else(|| format!("branches to {target}, which has no stack map frame"))?;
+            self.check_frame(frame)
+                .map_err(|e| format!("branches to {target}, but {e}"))?;
+        }
+        Ok(())
+    }
+
+    /// Applies an instruction to the locals and stack. Returns whether execution continues with
+    /// the next instruction.
+    fn execute(&mut self, instruction: &Instruction) -> Result<bool, String> {
+        let opcode = instruction.opcode;
+        match opcode {
+            0x00 => {}
+            0x01 => self.push(VerificationType::Null)?,
+            0x02..=0x08 | 0x10 | 0x11 => self.push(VerificationType::Integer)?,
+            0x09 | 0x0a => self.push(VerificationType::Long)?,
+            0x0b..=0x0d => self.push(VerificationType::Float)?,
+            0x0e | 0x0f => self.push(VerificationType::Double)?,
+            opcodes::LDC | opcodes::LDC_W | opcodes::LDC2_W => {
+                let ty = self.constant_type(constant_index(instruction)?)?;
+                if ty.is_wide() != (opcode == opcodes::LDC2_W) {
+                    return Err(format!("loads a {ty} constant of the wrong size"));
+                }
+                self.push(ty)?;
+            }
+            0x15..=0x19 => self.load(local_index(instruction)?, KINDS[(opcode - 0x15) as usize])?,
+            0x1a..=0x2d => {
+                let n = opcode - 0x1a;
+                self.load((n % 4) as u16, KINDS[(n / 4) as usize])?;
+            }
+            0x2e..=0x35 => {
+                self.pop_expect(&VerificationType::Integer)?;
+                let (what, element): (&str, fn(&str) -> bool) = match opcode {
+                    0x2e => ("int", |x| x == "I"),
+                    0x2f => ("long", |x| x == "J"),
+                    0x30 => ("float", |x| x == "F"),
+                    0x31 => ("double", |x| x == "D"),
+                    0x32 => ("references", is_reference_element),
+                    0x33 => ("byte or boolean", |x| x == "B" || x == "Z"),
+                    0x34 => ("char", |x| x == "C"),
+                    _ => ("short", |x| x == "S"),
+                };
+                let array = self.pop_array(what, element)?;
+                self.push(match (opcode, array) {
+                    (0x32, VerificationType::Object(array)) => element_class(&array[1..])
+                        .map_or(VerificationType::Null, |x| {
+                            VerificationType::Object(x.to_string())
+                        }),
+                    (0x32, _) => VerificationType::Null,
+                    (0x2f, _) => VerificationType::Long,
+                    (0x30, _) => VerificationType::Float,
+                    (0x31, _) => VerificationType::Double,
+                    _ => VerificationType::Integer,
+                })?;
+            }
+            0x36..=0x3a => {
+                self.store(local_index(instruction)?, KINDS[(opcode - 0x36) as usize])?
+            }
+            0x3b..=0x4e => {
+                let n = opcode - 0x3b;
+                self.store((n % 4) as u16, KINDS[(n / 4) as usize])?;
+            }
+            0x4f..=0x56 => {
+                let (what, element): (&str, fn(&str) -> bool) = match opcode {
+                    0x4f => ("int", |x| x == "I"),
+                    0x50 => ("long", |x| x == "J"),
+                    0x51 => ("float", |x| x == "F"),
+                    0x52 => ("double", |x| x == "D"),
+                    0x53 => ("references", is_reference_element),
+                    0x54 => ("byte or boolean", |x| x == "B" || x == "Z"),
+                    0x55 => ("char", |x| x == "C"),
+                    _ => ("short", |x| x == "S"),
+                };
+                match opcode {
+                    0x50 => self.pop_expect(&VerificationType::Long)?,
+                    0x51 => self.pop_expect(&VerificationType::Float)?,
+                    0x52 => self.pop_expect(&VerificationType::Double)?,
+                    0x53 => self.pop_expect(&Kind::Reference.ty())?,
+                    _ => self.pop_expect(&VerificationType::Integer)?,
+                };
+                self.pop_expect(&VerificationType::Integer)?;
+                self.pop_array(what, element)?;
+            }
+            // pop and pop2
+            0x57 | 0x58 => {
+                self.pop_slots((opcode - 0x56) as usize)?;
+            }
+            // dup, dup_x1, dup_x2, dup2, dup2_x1 and dup2_x2
+            0x59..=0x5e => {
+                let n = opcode - 0x59;
+                let top = self.pop_slots((n / 3 + 1) as usize)?;
+                let below = self.pop_slots((n % 3) as usize)?;
+                for ty in top.iter().chain(&below).chain(&top) {
+                    self.push(ty.clone())?;
+                }
+            }
+            // swap
+            0x5f => {
+                let top = self.pop_slots(1)?;
+                let below = self.pop_slots(1)?;
+                for ty in top.into_iter().chain(below) {
+                    self.push(ty)?;
+                }
+            }
+            // add, sub, mul, div and rem
+            0x60..=0x73 => {
+                let ty = KINDS[((opcode - 0x60) % 4) as usize].ty();
+                self.pop_expect(&ty)?;
+                self.pop_expect(&ty)?;
+                self.push(ty)?;
+            }
+            // neg
+            0x74..=0x77 => {
+                let ty = KINDS[((opcode - 0x74) % 4) as usize].ty();
+                self.pop_expect(&ty)?;
+                self.push(ty)?;
+            }
+            // shl, shr and ushr
+            0x78..=0x7d => {
+                let ty = KINDS[((opcode - 0x78) % 2) as usize].ty();
+                self.pop_expect(&VerificationType::Integer)?;
+                self.pop_expect(&ty)?;
+                self.push(ty)?;
+            }
+            // and, or and xor
+            0x7e..=0x83 => {
+                let ty = KINDS[((opcode - 0x7e) % 2) as usize].ty();
+                self.pop_expect(&ty)?;
+                self.pop_expect(&ty)?;
+                self.push(ty)?;
+            }
+            opcodes::IINC => {
+                let Operands::Iinc { index, .. } = instruction.operands else {
+                    return Err("has no local operand".to_string());
+                };
+                let actual = self.local(index);
+                if actual != VerificationType::Integer {
+                    return Err(format!("expects int in local {index}, found {actual}"));
+                }
+            }
+            // i2l to i2s
+            0x85..=0x93 => {
+                use VerificationType::{Double, Float, Integer, Long};
+                let (from, to) = [
+                    (Integer, Long),
+                    (Integer, Float),
+                    (Integer, Double),
+                    (Long, Integer),
+                    (Long, Float),
+                    (Long, Double),
+                    (Float, Integer),
+                    (Float, Long),
+                    (Float, Double),
+                    (Double, Integer),
+                    (Double, Long),
+                    (Double, Float),
+                    (Integer, Integer),
+                    (Integer, Integer),
+                    (Integer, Integer),
+                ][(opcode - 0x85) as usize]
+                    .clone();
+                self.pop_expect(&from)?;
+                self.push(to)?;
+            }
+            // lcmp, fcmpl, fcmpg, dcmpl and dcmpg
+            0x94..=0x98 => {
+                let ty = match opcode {
+                    0x94 => VerificationType::Long,
+                    0x95 | 0x96 => VerificationType::Float,
+                    _ => VerificationType::Double,
+                };
+                self.pop_expect(&ty)?;
+                self.pop_expect(&ty)?;
+                self.push(VerificationType::Integer)?;
+            }
+            // ifeq to ifle
+            0x99..=0x9e => {
+                self.pop_expect(&VerificationType::Integer)?;
+                self.check_branches(instruction)?;
+            }
+            // if_icmpeq to if_icmple
+            0x9f..=0xa4 => {
+                self.pop_expect(&VerificationType::Integer)?;
+                self.pop_expect(&VerificationType::Integer)?;
+                self.check_branches(instruction)?;
+            }
+            // if_acmpeq, if_acmpne, ifnull and ifnonnull
+            0xa5 | 0xa6 | 0xc6 | 0xc7 => {
+                for _ in 0..if opcode < 0xc6 { 2 } else { 1 } {
+                    self.pop_kind(Kind::Reference)?;
+                }
+                self.check_branches(instruction)?;
+            }
+            opcodes::GOTO | opcodes::GOTO_W => {
+                self.check_branches(instruction)?;
+                return Ok(false);
+            }
+            opcodes::JSR | opcodes::JSR_W | opcodes::RET => {
+                return Err("is not allowed in class files of version 51 or later".to_string());
+            }
+            opcodes::TABLESWITCH | opcodes::LOOKUPSWITCH => {
+                self.pop_expect(&VerificationType::Integer)?;
+                self.check_branches(instruction)?;
+                return Ok(false);
+            }
+            // ireturn to areturn
+            0xac..=0xb0 => {
+                let kind = KINDS[(opcode - 0xac) as usize];
+                let Some(ret) = self.ret.clone().filter(|x| kind.matches(x)) else {
+                    return Err(match &self.ret {
+                        Some(ret) => format!("returns {kind} from a method returning {ret}"),
+                        None => format!("returns {kind} from a void method"),
+                    });
+                };
+                self.pop_expect(&ret)?;
+                return Ok(false);
+            }
+            opcodes::RETURN => {
+                if let Some(ret) = &self.ret {
+                    return Err(format!("returns nothing from a method returning {ret}"));
+                }
+                if self.is_init && self.locals.contains(&VerificationType::UninitializedThis) {
+                    return Err("returns before a superclass constructor is called".to_string());
+                }
+                return Ok(false);
+            }
+            opcodes::GETSTATIC..=opcodes::PUTFIELD => self.access_field(instruction)?,
+            opcodes::INVOKEVIRTUAL..=opcodes::INVOKEDYNAMIC => self.invoke(instruction)?,
+            opcodes::NEW => {
+                self.push(VerificationType::Uninitialized(instruction.offset))?;
+            }
+            // newarray
+            0xbc => {
+                let Operands::NewArray(code) = instruction.operands else {
+                    return Err("has no array type".to_string());
+                };
+                let element = match code {
+                    4 => "Z",
+                    5 => "C",
+                    6 => "F",
+                    7 => "D",
+                    8 => "B",
+                    9 => "S",
+                    10 => "I",
+                    11 => "J",
+                    _ => return Err(format!("has an invalid array type {code}")),
+                };
+                self.pop_expect(&VerificationType::Integer)?;
+                self.push(VerificationType::Object(format!("[{element}")))?;
+            }
+            opcodes::ANEWARRAY => {
+                let class_name = self.class_name(constant_index(instruction)?)?;
+                self.pop_expect(&VerificationType::Integer)?;
+                self.push(VerificationType::Object(if class_name.starts_with('[') {
+                    format!("[{class_name}")
+                } else {
+                    format!("[L{class_name};")
+                }))?;
+            }
+            // arraylength
+            0xbe => {
+                self.pop_array("any type", |_| true)?;
+                self.push(VerificationType::Integer)?;
+            }
+            opcodes::ATHROW => {
+                self.pop_expect(&VerificationType::Object("java.lang.Throwable".to_string()))?;
+                return Ok(false);
+            }
+            opcodes::CHECKCAST => {
+                let class_name = self.class_name(constant_index(instruction)?)?;
+                self.pop_expect(&Kind::Reference.ty())?;
+                self.push(VerificationType::Object(class_name))?;
+            }
+            opcodes::INSTANCEOF => {
+                self.pop_expect(&Kind::Reference.ty())?;
+                self.push(VerificationType::Integer)?;
+            }
+            // monitorenter and monitorexit
+            0xc2 | 0xc3 => {
+                self.pop_expect(&Kind::Reference.ty())?;
+            }
+            opcodes::MULTIANEWARRAY => {
+                let Operands::MultiANewArray { index, dimensions } = instruction.operands else {
+                    return Err("has no dimensions".to_string());
+                };
+                let class_name = self.class_name(index)?;
+                if dimensions == 0
+                    || class_name.bytes().take_while(|x| *x == b'[').count() < dimensions as usize
+                {
+                    return Err(format!("creates {dimensions} dimensions of {class_name}"));
+                }
+                for _ in 0..dimensions {
+                    self.pop_expect(&VerificationType::Integer)?;
+                }
+                self.push(VerificationType::Object(class_name))?;
+            }
+            _ => return Err("is not a valid instruction".to_string()),
+        }
+        Ok(true)
+    }
+
+    fn access_field(&mut self, instruction: &Instruction) -> Result<(), String> {
+        let field = self
+            .pool
+            .member_ref(constant_index(instruction)?)
+            .map_err(|e| e.to_string())?;
+        let ty = from_descriptor(&try_parse_field_descriptor(field.descriptor)?);
+        let owner = field.class_name.replace('/', ".");
+        match instruction.opcode {
+            opcodes::GETSTATIC => self.push(ty),
+            opcodes::PUTSTATIC => self.pop_expect(&ty).map(|_| ()),
+            opcodes::GETFIELD => {
+                self.pop_expect(&VerificationType::Object(owner))?;
+                self.push(ty)
+            }
+            _ => {
+                self.pop_expect(&ty)?;
+                // Constructors may set their own fields before calling the superclass constructor
+                if self.is_init
+                    && owner == self.class_name
+                    && self.stack.last() == Some(&VerificationType::UninitializedThis)
+                {
+                    self.stack.pop();
+                    Ok(())
+                } else {
+                    self.pop_expect(&VerificationType::Object(owner))
+                        .map(|_| ())
+                }
+            }
+        }
+    }
+
+    fn invoke(&mut self, instruction: &Instruction) -> Result<(), String> {
+        let index = constant_index(instruction)?;
+        let (owner, name, descriptor) = if instruction.opcode == opcodes::INVOKEDYNAMIC {
+            let ConstantPoolInfo::InvokeDynamic {
+                name_and_type_index,
+                ..
+            } = self.pool.get(index).map_err(|e| e.to_string())?
+            else {
+                return Err(format!(
+                    "refers to constant pool #{index}, which is not an InvokeDynamic"
+                ));
+            };
+            let (name, descriptor) = self
+                .pool
+                .name_and_type(*name_and_type_index)
+                .map_err(|e| e.to_string())?;
+            (None, name, descriptor)
+        } else {
+            let method = self.pool.member_ref(index).map_err(|e| e.to_string())?;
+            (
+                Some(method.class_name.replace('/', ".")),
+                method.name,
+                method.descriptor,
+            )
+        };
+
+        let descriptor = try_parse_method_descriptor(descriptor)?;
+        for param in descriptor.param_descs.iter().rev() {
+            self.pop_expect(&from_descriptor(param))?;
+        }
+        if let Some(owner) = owner.filter(|_| instruction.opcode != opcodes::INVOKESTATIC) {
+            if name == "<init>" {
+                self.initialize(owner)?;
+            } else {
+                self.pop_expect(&VerificationType::Object(owner))?;
+            }
+        }
+        match descriptor.ret_desc {
+            ReturnDescriptor::TyDesc(x) => self.push(from_descriptor(&x)),
+            ReturnDescriptor::Void => Ok(()),
+        }
+    }
+
+    /// Pops the object a constructor is called on, and marks every copy of it as initialized.
+    fn initialize(&mut self, owner: String) -> Result<(), String> {
+        let uninitialized = self.pop()?;
+        let initialized = match &uninitialized {
+            VerificationType::UninitializedThis if self.is_init => {
+                VerificationType::Object(self.class_name.clone())
+            }
+            VerificationType::Uninitialized(offset) => {
+                let new = self
+                    .instructions
+                    .iter()
+                    .find(|x| x.offset == *offset && x.opcode == opcodes::NEW)
+                    .ok_or_else(|| {
+                        format!("initializes an object not created by new at {offset}")
+                    })?;
+                let class_name = self.class_name(constant_index(new)?)?;
+                if class_name != owner {
+                    return Err(format!(
+                        "calls a constructor of {owner} on a new {class_name}"
+                    ));
+                }
+                VerificationType::Object(class_name)
+            }
+            other => {
+                return Err(format!(
+                    "calls a constructor on {other}, which is not uninitialized"
+                ))
+            }
+        };
+        for ty in self.locals.iter_mut().chain(self.stack.iter_mut()) {
+            if *ty == uninitialized {
+                *ty = initialized.clone();
+            }
+        }
+        Ok(())
+    }
+
+    fn class_name(&self, index: u16) -> Result<String, String> {
+        self.pool
+            .class_name(index)
+            .map(|x| x.replace('/', "."))
+            .map_err(|e| e.to_string())
+    }
+
+    /// The type of a loadable constant.
+    fn constant_type(&self, index: u16) -> Result<VerificationType, String> {
+        let object = |name: &str| VerificationType::Object(name.to_string());
+        Ok(match self.pool.get(index).map_err(|e| e.to_string())? {
+            ConstantPoolInfo::Integer { .. } => VerificationType::Integer,
+            ConstantPoolInfo::Float { .. } => VerificationType::Float,
+            ConstantPoolInfo::Long { .. } => VerificationType::Long,
+            ConstantPoolInfo::Double { .. } => VerificationType::Double,
+            ConstantPoolInfo::String { .. } => object("java.lang.String"),
+            ConstantPoolInfo::Class { .. } => object("java.lang.Class"),
+            ConstantPoolInfo::MethodHandle { .. } => object("java.lang.invoke.MethodHandle"),
+            ConstantPoolInfo::MethodType { .. } => object("java.lang.invoke.MethodType"),
+            ConstantPoolInfo::Dynamic {
+                name_and_type_index,
+                ..
+            } => {
+                let (_, descriptor) = self
+                    .pool
+                    .name_and_type(*name_and_type_index)
+                    .map_err(|e| e.to_string())?;
+                from_descriptor(&try_parse_field_descriptor(descriptor)?)
+            }
+            _ => {
+                return Err(format!(
+                    "refers to constant pool #{index}, which is not loadable"
+                ))
+            }
+        })
+    }
+
+    fn local(&self, index: u16) -> VerificationType {
+        self.locals
+            .get(index as usize)
+            .cloned()
+            .unwrap_or(VerificationType::Top)
+    }
+
+    fn load(&mut self, index: u16, kind: Kind) -> Result<(), String> {
+        let actual = self.local(index);
+        if !kind.matches(&actual) {
+            return Err(format!("expects {kind} in local {index}, found {actual}"));
+        }
+        self.push(actual)
+    }
+
+    fn store(&mut self, index: u16, kind: Kind) -> Result<(), String> {
+        let value = self.pop_kind(kind)?;
+        let index = index as usize;
+        let end = index + if value.is_wide() { 2 } else { 1 };
+        if end > self.max_locals {
+            return Err(format!(
+                "stores to local {index}, but max_locals is {}",
+                self.max_locals
+            ));
+        }
+        if self.locals.len() < end {
+            self.locals.resize(end, VerificationType::Top);
+        }
+        // Overwriting the second half of a long or double invalidates the first
+        if index > 0 && self.locals[index - 1].is_wide() {
+            self.locals[index - 1] = VerificationType::Top;
+        }
+        if value.is_wide() {
+            self.locals[index + 1] = VerificationType::Top;
+        }
+        self.locals[index] = value;
+        Ok(())
+    }
+
+    fn push(&mut self, ty: VerificationType) -> Result<(), String> {
+        self.stack.push(ty);
+        if stack_size(&self.stack) > self.max_stack {
+            return Err(format!(
+                "overflows the stack, as max_stack is {}",
+                self.max_stack
+            ));
+        }
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Result<VerificationType, String> {
+        self.stack
+            .pop()
+            .ok_or_else(|| "pops from an empty stack".to_string())
+    }
+
+    fn pop_kind(&mut self, kind: Kind) -> Result<VerificationType, String> {
+        let actual = self.pop()?;
+        if !kind.matches(&actual) {
+            return Err(format!("expects {kind} on the stack, found {actual}"));
+        }
+        Ok(actual)
+    }
+
+    fn pop_expect(&mut self, expected: &VerificationType) -> Result<VerificationType, String> {
+        let actual = self.pop()?;
+        if !is_assignable(&actual, expected) {
+            return Err(format!("expects {expected} on the stack, found {actual}"));
+        }
+        Ok(actual)
+    }
+
+    /// Pops an array, or `null`, whose element descriptor satisfies `element`.
+    fn pop_array(
+        &mut self,
+        what: &str,
+        element: fn(&str) -> bool,
+    ) -> Result<VerificationType, String> {
+        let actual = self.pop()?;
+        match &actual {
+            VerificationType::Null => Ok(actual),
+            VerificationType::Object(x) if x.strip_prefix('[').is_some_and(element) => Ok(actual),
+            _ => Err(format!(
+                "expects an array of {what} on the stack, found {actual}"
+            )),
+        }
+    }
+
+    /// Pops values taking exactly `slots` stack slots, returning them from bottom to top.
+    fn pop_slots(&mut self, slots: usize) -> Result<Vec<VerificationType>, String> {
+        let mut values = Vec::new();
+        let mut size = 0;
+        while size < slots {
+            let value = self.pop()?;
+            size += if value.is_wide() { 2 } else { 1 };
+            values.insert(0, value);
+        }
+        if size != slots {
+            return Err("splits a long or double on the stack".to_string());
+        }
+        Ok(values)
+    }
+}
+
+fn constant_index(instruction: &Instruction) -> Result<u16, String> {
+    instruction
+        .constant_pool_index()
+        .ok_or_else(|| "has no constant pool operand".to_string())
+}
+
+fn local_index(instruction: &Instruction) -> Result<u16, String> {
+    match instruction.operands {
+        Operands::Local(index) => Ok(index),
+        _ => Err("has no local operand".to_string()),
+    }
+}
+
+fn stack_size(stack: &[VerificationType]) -> usize {
+    stack.iter().map(|x| if x.is_wide() { 2 } else { 1 }).sum()
+}
+
+/// Checks that locals and a stack are assignable to a frame, where missing locals are `top`.
+fn check_assignable(
+    locals: &[VerificationType],
+    stack: &[VerificationType],
+    frame: &Frame,
+) -> Result<(), String> {
+    for (i, expected) in frame.locals.iter().enumerate() {
+        let actual = locals.get(i).unwrap_or(&VerificationType::Top);
+        if !is_assignable(actual, expected) {
+            return Err(format!(
+                "local {i} is {actual} where the frame has {expected}"
+            ));
+        }
+    }
+    if stack.len() != frame.stack.len() {
+        return Err(format!(
+            "the stack has depth {} where the frame has depth {}",
+            stack.len(),
+            frame.stack.len()
+        ));
+    }
+    for (i, (actual, expected)) in stack.iter().zip(&frame.stack).enumerate() {
+        if !is_assignable(actual, expected) {
+            return Err(format!(
+                "stack value {i} is {actual} where the frame has {expected}"
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn is_assignable(from: &VerificationType, to: &VerificationType) -> bool {
+    match (from, to) {
+        (_, VerificationType::Top) => true,
+        (VerificationType::Null, VerificationType::Object(_)) => true,
+        (VerificationType::Object(from), VerificationType::Object(to)) => {
+            is_class_assignable(from, to)
+        }
+        _ => from == to,
+    }
+}
+
+/// Compares class names in binary form, or array descriptors.
+fn is_class_assignable(from: &str, to: &str) -> bool {
+    if from == to || to == "java.lang.Object" {
+        return true;
+    }
+    match (from.strip_prefix('['), to.strip_prefix('[')) {
+        (Some(from), Some(to)) => match (element_class(from), element_class(to)) {
+            (Some(from), Some(to)) => is_class_assignable(from, to),
+            _ => false,
+        },
+        (Some(_), None) => matches!(to, "java.lang.Cloneable" | "java.io.Serializable"),
+        (None, Some(_)) => false,
+        // Without the classpath, one class cannot be told to be a subclass of another
+        (None, None) => true,
+    }
+}
+
+fn is_reference_element(element: &str) -> bool {
+    element_class(element).is_some()
+}
+
+/// The class of an array element descriptor, `java.lang.String` for `Ljava.lang.String;` and
+/// `[I` for `[I`, or `None` for primitives.
+fn element_class(element: &str) -> Option<&str> {
+    if element.starts_with('[') {
+        Some(element)
+    } else {
+        element.strip_prefix('L')?.strip_suffix(';')
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        class_file::{
+            AccessFlags, Attribute, MethodAccessFlags, StackMapFrame, StackMapFrameKind,
+            VerificationTypeInfo,
+        },
+        test_util::utf8,
+    };
+
+    // constant pool indices
+    const MAIN: u16 = 2;
+    const OBJECT_INIT: u16 = 10;
+    const MAIN_INIT: u16 = 11;
+    const EXCEPTION: u16 = 13;
+    const COUNT: u16 = 17;
+    const MAIN_RUN: u16 = 20;
+
+    /// A method of `a.Main`, with 8 locals.
+    struct TestMethod {
+        name: &'static str,
+        descriptor: &'static str,
+        access_flags: MethodAccessFlags,
+        max_stack: u16,
+        code: Vec<u8>,
+        exception_table: Vec<ExceptionTableEntry>,
+        frames: Vec<StackMapFrame>,
+    }
+
+    fn method(descriptor: &'static str, max_stack: u16, code: Vec<u8>) -> TestMethod {
+        TestMethod {
+            name: "run",
+            descriptor,
+            access_flags: MethodAccessFlags::PUBLIC,
+            max_stack,
+            code,
+            exception_table: Vec::new(),
+            frames: Vec::new(),
+        }
+    }
+
+    fn check(method: TestMethod) -> Result<(), String> {
+        let constant_pool = vec![
+            ConstantPoolInfo::Empty,
+            utf8("a/Main"),
+            ConstantPoolInfo::Class { name_index: 1 },
+            utf8("java/lang/Object"),
+            ConstantPoolInfo::Class { name_index: 3 },
+            utf8("Code"),
+            utf8("StackMapTable"),
+            utf8("<init>"),
+            utf8("()V"),
+            ConstantPoolInfo::NameAndType {
+                name_index: 7,
+                descriptor_index: 8,
+            },
+            ConstantPoolInfo::Methodref {
+                class_index: 4,
+                name_and_type_index: 9,
+            },
+            ConstantPoolInfo::Methodref {
+                class_index: 2,
+                name_and_type_index: 9,
+            },
+            utf8("java/lang/Exception"),
+            ConstantPoolInfo::Class { name_index: 12 },
+            utf8("count"),
+            utf8("I"),
+            ConstantPoolInfo::NameAndType {
+                name_index: 14,
+                descriptor_index: 15,
+            },
+            ConstantPoolInfo::Fieldref {
+                class_index: 2,
+                name_and_type_index: 16,
+            },
+            utf8("run"),
+            ConstantPoolInfo::NameAndType {
+                name_index: 18,
+                descriptor_index: 8,
+            },
+            ConstantPoolInfo::Methodref {
+                class_index: 2,
+                name_and_type_index: 19,
+            },
+            utf8(method.name),
+            utf8(method.descriptor),
+        ];
+
+        let mut attributes = Vec::new();
+        if !method.frames.is_empty() {
+            attributes.push(Attribute {
+                attribute_name_index: 6,
+                attribute_length: 0,
+                kind: AttributeKind::StackMapTable {
+                    number_of_entries: method.frames.len() as u16,
+                    entries: method.frames,
+                },
+            });
+        }
+        let method = MethodInfo {
+            access_flags: method.access_flags,
+            name_index: 21,
+            descriptor_index: 22,
+            attributes_count: 1,
+            attributes: vec![Attribute {
+                attribute_name_index: 5,
+                attribute_length: 0,
+                kind: AttributeKind::Code {
+                    max_stack: method.max_stack,
+                    max_locals: 8,
+                    code_length: method.code.len() as u32,
+                    code: method.code,
+                    exception_table_length: method.exception_table.len() as u16,
+                    exception_table: method.exception_table,
+                    attributes_count: attributes.len() as u16,
+                    attributes,
+                },
+            }],
+        };
+        let class_file = ClassFile {
+            minor_version: 0,
+            major_version: 61,
+            constant_pool_count: constant_pool.len() as u16,
+            constant_pool,
+            access_flags: AccessFlags::PUBLIC | AccessFlags::SUPER,
+            this_class: MAIN,
+            super_class: 4,
+            interfaces_count: 0,
+            interfaces: Vec::new(),
+            fields_count: 0,
+            fields: Vec::new(),
+            methods_count: 0,
+            methods: Vec::new(),
+            attributes_count: 0,
+            attributes: Vec::new(),
+        };
+        type_check(&class_file, &method)
+    }
+
+    fn same(offset_delta: u8) -> StackMapFrame {
+        StackMapFrame {
+            frame_type: offset_delta,
+            kind: StackMapFrameKind::Same,
+        }
+    }
+
+    fn same_locals_1_stack_item(offset_delta: u8, stack: VerificationTypeInfo) -> StackMapFrame {
+        StackMapFrame {
+            frame_type: 64 + offset_delta,
+            kind: StackMapFrameKind::SameLocals1StackItem { stack },
+        }
+    }
+
+    fn catch(start_pc: u16, end_pc: u16, handler_pc: u16) -> ExceptionTableEntry {
+        ExceptionTableEntry {
+            start_pc,
+            end_pc,
+            handler_pc,
+            catch_type: EXCEPTION,
+        }
+    }
+
+    /// Locals 1 to 7 are an int, a long, a float, a double and a `String`.
+    const PARAMETERS: &str = "(IJFDLjava/lang/String;)V";
+
+    #[test]
+    fn accepts_well_typed_code() {
+        // iload_1, i2l, lload_2, ladd, l2i, ifeq 11, aload 7, pop, return, with a frame at 11
+        let code = vec![
+            0x1b, 0x85, 0x20, 0x61, 0x88, 0x99, 0, 6, 0x19, 7, 0x57, 0xb1,
+        ];
+        let branch = TestMethod {
+            frames: vec![same(11)],
+            ..method(PARAMETERS, 4, code)
+        };
+        assert_eq!(check(branch), Ok(()));
+    }
+
+    #[test]
+    fn checks_stack_depth_against_max_stack() {
+        assert_eq!(
+            check(method("()V", 1, vec![0x57, 0xb1])),
+            Err("pop at offset 0 pops from an empty stack".to_string())
+        );
+        // iconst_0, iadd
+        assert_eq!(
+            check(method("()V", 1, vec![0x03, 0x60, 0xb1])),
+            Err("iadd at offset 1 pops from an empty stack".to_string())
+        );
+        assert_eq!(
+            check(method("()V", 1, vec![0x03, 0x03, 0x58, 0xb1])),
+            Err("iconst_0 at offset 1 overflows the stack, as max_stack is 1".to_string())
+        );
+        // a long takes two slots
+        assert_eq!(
+            check(method("()V", 1, vec![0x09, 0x58, 0xb1])),
+            Err("lconst_0 at offset 0 overflows the stack, as max_stack is 1".to_string())
+        );
+        assert_eq!(check(method("()V", 2, vec![0x09, 0x58, 0xb1])), Ok(()));
+        // pop on half of a long
+        assert_eq!(
+            check(method("()V", 2, vec![0x09, 0x57, 0xb1])),
+            Err("pop at offset 1 splits a long or double on the stack".to_string())
+        );
+    }
+
+    #[test]
+    fn checks_load_kinds() {
+        for (opcode, local, error) in [
+            (
+                0x15,
+                4,
+                "iload at offset 0 expects int in local 4, found float",
+            ),
+            (
+                0x16,
+                1,
+                "lload at offset 0 expects long in local 1, found int",
+            ),
+            (
+                0x17,
+                7,
+                "fload at offset 0 expects float in local 7, found java.lang.String",
+            ),
+            (
+                0x18,
+                2,
+                "dload at offset 0 expects double in local 2, found long",
+            ),
+            (
+                0x19,
+                5,
+                "aload at offset 0 expects a reference in local 5, found double",
+            ),
+            (
+                0x15,
+                3,
+                "iload at offset 0 expects int in local 3, found top",
+            ),
+        ] {
+            assert_eq!(
+                check(method(PARAMETERS, 2, vec![opcode, local, 0x57, 0xb1])),
+                Err(error.to_string())
+            );
+        }
+        // the short forms
+        assert_eq!(check(method(PARAMETERS, 2, vec![0x2a, 0x57, 0xb1])), Ok(()));
+        assert_eq!(
+            check(method(PARAMETERS, 2, vec![0x1c, 0x57, 0xb1])),
+            Err("iload_2 at offset 0 expects int in local 2, found long".to_string())
+        );
+    }
+
+    #[test]
+    fn checks_store_kinds() {
+        for (push, opcode, error) in [
+            (
+                0x0b,
+                0x36,
+                "istore at offset 1 expects int on the stack, found float",
+            ),
+            (
+                0x03,
+                0x37,
+                "lstore at offset 1 expects long on the stack, found int",
+            ),
+            (
+                0x0e,
+                0x38,
+                "fstore at offset 1 expects float on the stack, found double",
+            ),
+            (
+                0x09,
+                0x39,
+                "dstore at offset 1 expects double on the stack, found long",
+            ),
+            (
+                0x0b,
+                0x3a,
+                "astore at offset 1 expects a reference on the stack, found float",
+            ),
+        ] {
+            assert_eq!(
+                check(method("()V", 2, vec![push, opcode, 1, 0xb1])),
+                Err(error.to_string())
+            );
+        }
+        // aconst_null, astore_1
+        assert_eq!(check(method("()V", 1, vec![0x01, 0x4c, 0xb1])), Ok(()));
+        // dconst_0, dstore 7 needs locals 7 and 8
+        assert_eq!(
+            check(method("()V", 2, vec![0x0e, 0x39, 7, 0xb1])),
+            Err("dstore at offset 1 stores to local 7, but max_locals is 8".to_string())
+        );
+    }
+
+    #[test]
+    fn checks_return_kinds() {
+        for (descriptor, code, error) in [
+            (
+                "(IJFDLjava/lang/String;)I",
+                vec![0x17, 4, 0xae],
+                "freturn at offset 2 returns float from a method returning int",
+            ),
+            (
+                "(IJFDLjava/lang/String;)I",
+                vec![0xb1],
+                "return at offset 0 returns nothing from a method returning int",
+            ),
+            (
+                PARAMETERS,
+                vec![0x1b, 0xac],
+                "ireturn at offset 1 returns int from a void method",
+            ),
+            (
+                "(IJFDLjava/lang/String;)J",
+                vec![0x18, 5, 0xad],
+                "lreturn at offset 2 expects long on the stack, found double",
+            ),
+            (
+                "(IJFDLjava/lang/String;)Ljava/lang/String;",
+                vec![0x1b, 0xb0],
+                "areturn at offset 1 expects java.lang.String on the stack, found int",
+            ),
+        ] {
+            assert_eq!(check(method(descriptor, 2, code)), Err(error.to_string()));
+        }
+        assert_eq!(
+            check(method(
+                "(IJFDLjava/lang/String;)Ljava/lang/Object;",
+                1,
+                vec![0x19, 7, 0xb0]
+            )),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn requires_frames_after_unconditional_branches() {
+        // goto 4, nop, return
+        let goto = |frames| TestMethod {
+            frames,
+            ..method("()V", 0, vec![0xa7, 0, 4, 0x00, 0xb1])
+        };
+        assert_eq!(
+            check(goto(Vec::new())),
+            Err("goto at offset 0 branches to 4, which has no stack map frame".to_string())
+        );
+        assert_eq!(
+            check(goto(vec![same(4)])),
+            Err(
+                "nop at offset 3 follows an unconditional branch but has no stack map frame"
+                    .to_string()
+            )
+        );
+        assert_eq!(check(goto(vec![same(3), same(0)])), Ok(()));
+        assert_eq!(
+            check(method("()V", 0, vec![0xb1, 0xb1])),
+            Err(
+                "return at offset 1 follows an unconditional branch but has no stack map frame"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn checks_fall_through_against_frames() {
+        // iconst_0 falls through to a frame with an empty stack
+        let push = TestMethod {
+            frames: vec![same(1)],
+            ..method("(I)V", 1, vec![0x03, 0x57, 0xb1])
+        };
+        assert_eq!(
+            check(push),
+            Err(
+                "iconst_0 at offset 0 falls through to 1, but the stack has depth 1 where the frame has depth 0"
+                    .to_string()
+            )
+        );
+
+        // the frame at 2 has an int in local 1
+        let store = |push, store| TestMethod {
+            frames: vec![same(2)],
+            ..method("(I)V", 1, vec![push, store, 0xb1])
+        };
+        // fconst_0, fstore_1
+        assert_eq!(
+            check(store(0x0b, 0x44)),
+            Err("fstore_1 at offset 1 falls through to 2, but local 1 is float where the frame has int".to_string())
+        );
+        // iconst_0, istore_1
+        assert_eq!(check(store(0x03, 0x3c)), Ok(()));
+
+        // null is assignable to the exception on the stack of the frame at 1
+        let null = TestMethod {
+            frames: vec![same_locals_1_stack_item(
+                1,
+                VerificationTypeInfo::Object {
+                    cpool_index: EXCEPTION,
+                },
+            )],
+            ..method("()V", 1, vec![0x01, 0x57, 0xb1])
+        };
+        assert_eq!(check(null), Ok(()));
+    }
+
+    #[test]
+    fn checks_exception_handlers_against_frames() {
+        // a store to local 1, return, and a handler at 3 which rethrows
+        let handler = |push, store, frames| TestMethod {
+            exception_table: vec![catch(0, 3, 3)],
+            frames,
+            ..method("(I)V", 1, vec![push, store, 0xb1, 0xbf])
+        };
+        let exception = same_locals_1_stack_item(
+            3,
+            VerificationTypeInfo::Object {
+                cpool_index: EXCEPTION,
+            },
+        );
+        assert_eq!(check(handler(0x03, 0x3c, vec![exception.clone()])), Ok(()));
+        assert_eq!(
+            check(handler(0x0b, 0x44, vec![exception])),
+            Err("return at offset 2 is covered by the exception handler at 3, but local 1 is float where the frame has int".to_string())
+        );
+        assert_eq!(
+            check(handler(0x03, 0x3c, Vec::new())),
+            Err(
+                "iconst_0 at offset 0 is covered by the exception handler at 3, which has no stack map frame"
+                    .to_string()
+            )
+        );
+        // the handler's stack must hold just the exception
+        assert_eq!(
+            check(handler(0x03, 0x3c, vec![same(3)])),
+            Err("iconst_0 at offset 0 is covered by the exception handler at 3, but the stack has depth 1 where the frame has depth 0".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_falling_off_the_end_of_the_code() {
+        assert_eq!(
+            check(method("()V", 1, vec![0x03, 0x57])),
+            Err("pop at offset 1 falls off the end of the code".to_string())
+        );
+        assert_eq!(
+            check(method("()V", 0, vec![0x00])),
+            Err("nop at offset 0 falls off the end of the code".to_string())
+        );
+    }
+
+    #[test]
+    fn tracks_uninitialized_objects() {
+        let [main_init, object_init, count, run] =
+            [MAIN_INIT, OBJECT_INIT, COUNT, MAIN_RUN].map(u16::to_be_bytes);
+        let constructor = |code| TestMethod {
+            name: "<init>",
+            ..method("()V", 2, code)
+        };
+
+        // aload_0, iconst_0, putfield count, aload_0, invokespecial Object.<init>, return
+        assert_eq!(
+            check(constructor(vec![
+                0x2a,
+                0x03,
+                0xb5,
+                count[0],
+                count[1],
+                0x2a,
+                0xb7,
+                object_init[0],
+                object_init[1],
+                0xb1,
+            ])),
+            Ok(())
+        );
+        assert_eq!(
+            check(constructor(vec![0xb1])),
+            Err("return at offset 0 returns before a superclass constructor is called".to_string())
+        );
+        // aload_0, invokevirtual run
+        assert_eq!(
+            check(constructor(vec![0x2a, 0xb6, run[0], run[1], 0xb1])),
+            Err(
+                "invokevirtual at offset 1 expects a.Main on the stack, found uninitializedThis"
+                    .to_string()
+            )
+        );
+        // the receiver of other methods is initialized
+        assert_eq!(
+            check(method("()V", 1, vec![0x2a, 0xb7, object_init[0], object_init[1], 0xb1])),
+            Err("invokespecial at offset 1 calls a constructor on a.Main, which is not uninitialized".to_string())
+        );
+
+        // new a.Main, dup, invokespecial <init>, areturn
+        let new = |init: [u8; 2]| TestMethod {
+            access_flags: MethodAccessFlags::PUBLIC | MethodAccessFlags::STATIC,
+            ..method(
+                "()La/Main;",
+                2,
+                vec![0xbb, 0, MAIN as u8, 0x59, 0xb7, init[0], init[1], 0xb0],
+            )
+        };
+        assert_eq!(check(new(main_init)), Ok(()));
+        assert_eq!(
+            check(new(object_init)),
+            Err(
+                "invokespecial at offset 4 calls a constructor of java.lang.Object on a new a.Main"
+                    .to_string()
+            )
+        );
+        // new a.Main, areturn
+        let uninitialized = TestMethod {
+            access_flags: MethodAccessFlags::PUBLIC | MethodAccessFlags::STATIC,
+            ..method("()La/Main;", 1, vec![0xbb, 0, MAIN as u8, 0xb0])
+        };
+        assert_eq!(
+            check(uninitialized),
+            Err(
+                "areturn at offset 3 expects a.Main on the stack, found uninitialized(0)"
+                    .to_string()
+            )
+        );
+    }
+}
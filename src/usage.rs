@@ -0,0 +1,633 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    fmt,
+};
+
+use serde::Serialize;
+
+use crate::{
+    bytecode::{opcodes, parse_instructions},
+    callgraph::{class_name, CallEdge, CallGraph, CallKind, MethodRef},
+    class_file::{AccessFlags, AttributeKind, ClassFile, ConstantPoolInfo, MethodAccessFlags},
+    component::{
+        try_extract_component, AccessModifier, Annotation, Component, ExtractorContext, Ty,
+    },
+    constant_pool::ConstantPool,
+    dependency::descriptor_class_names,
+    hierarchy::TypeHierarchy,
+};
+
+const JAVA_LANG_OBJECT: &str = "java.lang.Object";
+
+/// Methods of `java.lang.Object` which library code calls on any object.
+const OBJECT_METHODS: &[(&str, &str)] = &[
+    ("toString", "()Ljava/lang/String;"),
+    ("equals", "(Ljava/lang/Object;)Z"),
+    ("hashCode", "()I"),
+    ("clone", "()Ljava/lang/Object;"),
+    ("finalize", "()V"),
+];
+
+/// The roots of a usage analysis.
+#[derive(Debug, Default)]
+pub struct EntryPoints {
+    /// Classes whose `public static void main(String[])` method is an entry point
+    pub main_classes: Vec<String>,
+    /// Provider classes loaded through `ServiceLoader`, e.g. from `META-INF/services`
+    pub service_providers: Vec<String>,
+    /// Annotations marking classes and members as used, such as dependency injection
+    /// annotations. Constructors of annotated classes are kept as well.
+    pub keep_annotations: Vec<String>,
+    /// Qualified name patterns of classes kept with all their members. `*` matches within one
+    /// package segment and `**` matches across segments, e.g. `com.example.api.**`.
+    pub keep: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct FieldRef {
+    pub class_name: String,
+    pub name: String,
+    pub descriptor: String,
+}
+
+impl FieldRef {
+    pub fn new(class_name: &str, name: &str, descriptor: &str) -> Self {
+        Self {
+            class_name: class_name.to_string(),
+            name: name.to_string(),
+            descriptor: descriptor.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for FieldRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}:{}", self.class_name, self.name, self.descriptor)
+    }
+}
+
+/// Classes and members which are not reachable from the entry points, similar to the
+/// "usage" report of ProGuard. Members are only listed for classes which are used.
+#[derive(Debug, Default, Serialize)]
+pub struct UsageReport {
+    pub unused_classes: Vec<String>,
+    pub unused_methods: Vec<MethodRef>,
+    pub unused_fields: Vec<FieldRef>,
+}
+
+impl UsageReport {
+    /// Renders the report as text, listing unused classes by name and unused members below
+    /// their class.
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        for class in &self.unused_classes {
+            out.push_str(&format!("{class}\n"));
+        }
+
+        let mut members: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+        for method in &self.unused_methods {
+            members
+                .entry(&method.class_name)
+                .or_default()
+                .push(format!("{}{}", method.name, method.descriptor));
+        }
+        for field in &self.unused_fields {
+            members
+                .entry(&field.class_name)
+                .or_default()
+                .push(format!("{}:{}", field.name, field.descriptor));
+        }
+        for (class, members) in members {
+            out.push_str(&format!("{class}:\n"));
+            for member in members {
+                out.push_str(&format!("    {member}\n"));
+            }
+        }
+
+        out.push_str(&format!(
+            "{} unused classes, {} unused methods, {} unused fields\n",
+            self.unused_classes.len(),
+            self.unused_methods.len(),
+            self.unused_fields.len()
+        ));
+        out
+    }
+}
+
+/// Finds the classes, methods and fields of the class files which are unreachable from the
+/// entry points.
+///
+/// Method calls are followed through the call graph, dispatching virtual calls only to classes
+/// which are instantiated by reachable code (rapid type analysis). Field accesses and class
+/// references (`new`, casts, class literals, descriptors) are followed through the constant
+/// pool. A reachable class keeps its static initializer and supertypes. An instantiated class
+/// also keeps the methods library code may call: overrides of `Object` methods, and every
+/// instance method of a class extending a type outside the analyzed class files.
+pub fn find_unused(
+    class_files: &[ClassFile],
+    entry_points: &EntryPoints,
+) -> Result<UsageReport, String> {
+    let context = ExtractorContext {
+        target_access_modifiers: AccessModifier::empty(),
+    };
+    let components = class_files
+        .iter()
+        .filter(|x| !x.access_flags.contains(AccessFlags::MODULE))
+        .map(|x| {
+            try_extract_component(x, &context).map_err(|e| {
+                let name = class_name(x.pool(), x.this_class).unwrap_or_default();
+                format!("{e} in class `{name}`")
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let hierarchy = TypeHierarchy::new(&components);
+    let call_graph = CallGraph::new(class_files)?;
+
+    let mut analysis = UsageAnalysis {
+        hierarchy: &hierarchy,
+        call_graph: &call_graph,
+        components: components
+            .iter()
+            .filter_map(|x| x.qualified_name().map(|name| (name, x)))
+            .collect(),
+        calls: HashMap::new(),
+        references: HashMap::new(),
+        fields: HashSet::new(),
+        classes: HashSet::new(),
+        instantiated: HashSet::new(),
+        virtual_calls: HashMap::new(),
+        methods: HashSet::new(),
+        reachable_fields: HashSet::new(),
+        queue: VecDeque::new(),
+    };
+    for edge in &call_graph.edges {
+        analysis.calls.entry(&edge.caller).or_default().push(edge);
+    }
+    for class_file in class_files {
+        analysis.collect_references(class_file)?;
+    }
+
+    analysis.add_entry_points(entry_points)?;
+    analysis.run();
+    Ok(analysis.report())
+}
+
+/// Field accesses and class references made by the code of a method.
+#[derive(Default)]
+struct MethodReferences {
+    fields: Vec<FieldRef>,
+    classes: Vec<String>,
+    instantiated: Vec<String>,
+}
+
+enum Item {
+    Class(String),
+    Instantiated(String),
+    Method(MethodRef),
+    Field(FieldRef),
+}
+
+struct UsageAnalysis<'a> {
+    hierarchy: &'a TypeHierarchy,
+    call_graph: &'a CallGraph,
+    components: HashMap<&'a str, &'a Component>,
+    calls: HashMap<&'a MethodRef, Vec<&'a CallEdge>>,
+    references: HashMap<MethodRef, MethodReferences>,
+    /// Fields declared by the analyzed class files
+    fields: HashSet<FieldRef>,
+    classes: HashSet<String>,
+    instantiated: HashSet<String>,
+    /// Names and descriptors of the reachable virtual calls keyed by receiver type
+    virtual_calls: HashMap<String, HashSet<(String, String)>>,
+    methods: HashSet<MethodRef>,
+    reachable_fields: HashSet<FieldRef>,
+    queue: VecDeque<Item>,
+}
+
+impl<'a> UsageAnalysis<'a> {
+    fn collect_references(&mut self, class_file: &ClassFile) -> Result<(), String> {
+        let pool = class_file.pool();
+        let Some(owner) = class_name(pool, class_file.this_class) else {
+            return Ok(());
+        };
+
+        for field in &class_file.fields {
+            self.fields.insert(FieldRef::new(
+                &owner,
+                field.get_name(pool.entries()),
+                field.get_descriptor(pool.entries()),
+            ));
+        }
+
+        for method in &class_file.methods {
+            let Some(code) = method.attributes.iter().find_map(|x| match &x.kind {
+                AttributeKind::Code { code, .. } => Some(code),
+                _ => None,
+            }) else {
+                continue;
+            };
+
+            let method_ref = MethodRef::new(
+                &owner,
+                method.get_name(pool.entries()),
+                method.get_descriptor(pool.entries()),
+            );
+            let (_, instructions) =
+                parse_instructions(code).map_err(|e| format!("{method_ref}: {e}"))?;
+
+            let mut references = MethodReferences::default();
+            for instruction in instructions {
+                let Some(index) = instruction.constant_pool_index() else {
+                    continue;
+                };
+                match instruction.opcode {
+                    opcodes::GETSTATIC
+                    | opcodes::PUTSTATIC
+                    | opcodes::GETFIELD
+                    | opcodes::PUTFIELD => {
+                        if let Some(field) = field_ref(pool, index) {
+                            references.fields.push(field);
+                        }
+                    }
+                    opcodes::NEW => references.instantiated.extend(class_name(pool, index)),
+                    opcodes::LDC
+                    | opcodes::LDC_W
+                    | opcodes::ANEWARRAY
+                    | opcodes::CHECKCAST
+                    | opcodes::INSTANCEOF
+                    | opcodes::MULTIANEWARRAY => {
+                        match class_name(pool, index) {
+                            // array classes are named by their descriptor
+                            Some(name) if name.starts_with('[') => {
+                                references.classes.extend(descriptor_class_names(&name))
+                            }
+                            Some(name) => references.classes.push(name),
+                            None => {}
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            self.references.insert(method_ref, references);
+        }
+
+        Ok(())
+    }
+
+    fn add_entry_points(&mut self, entry_points: &EntryPoints) -> Result<(), String> {
+        for main_class in &entry_points.main_classes {
+            let Some(component) = self.components.get(main_class.as_str()) else {
+                return Err(format!("Main class `{main_class}` not found"));
+            };
+            if !component.methods().iter().any(|x| {
+                x.name == "main" && x.descriptor == "([Ljava/lang/String;)V" && x.is_static
+            }) {
+                return Err(format!(
+                    "Main class `{main_class}` has no `static void main(String[])` method"
+                ));
+            }
+            self.queue.push_back(Item::Method(MethodRef::new(
+                main_class,
+                "main",
+                "([Ljava/lang/String;)V",
+            )));
+        }
+
+        for provider in &entry_points.service_providers {
+            self.queue.push_back(Item::Instantiated(provider.clone()));
+            self.queue
+                .push_back(Item::Method(MethodRef::new(provider, "<init>", "()V")));
+            if let Some(component) = self.components.get(provider.as_str()) {
+                for method in component.methods() {
+                    if method.name == "provider" && method.is_static {
+                        self.queue.push_back(Item::Method(MethodRef::new(
+                            provider,
+                            &method.name,
+                            &method.descriptor,
+                        )));
+                    }
+                }
+            }
+        }
+
+        let components = self.components.values().copied().collect::<Vec<_>>();
+        for component in components {
+            let Some(name) = component.qualified_name() else {
+                continue;
+            };
+
+            if entry_points.keep.iter().any(|x| matches_pattern(x, name)) {
+                self.keep_class(component, true);
+                continue;
+            }
+
+            let is_kept = |annotations: &[Annotation]| {
+                annotations.iter().any(|x| match &x.ty {
+                    Ty::Reference(ty) => {
+                        entry_points.keep_annotations.contains(&ty.qualified_name())
+                    }
+                    _ => false,
+                })
+            };
+            if is_kept(component.annotations()) {
+                self.keep_class(component, false);
+            }
+            for method in component.methods() {
+                if is_kept(&method.annotations) {
+                    self.queue.push_back(Item::Method(MethodRef::new(
+                        name,
+                        &method.name,
+                        &method.descriptor,
+                    )));
+                }
+            }
+            for field in component.fields() {
+                if is_kept(&field.annotations) {
+                    self.queue.push_back(Item::Field(FieldRef::new(
+                        name,
+                        &field.name,
+                        &field.descriptor,
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Marks a class as instantiated and its constructors, or all of its members, as reachable.
+    fn keep_class(&mut self, component: &Component, all_members: bool) {
+        let Some(name) = component.qualified_name() else {
+            return;
+        };
+        self.queue.push_back(Item::Instantiated(name.to_string()));
+        for method in component.methods() {
+            if all_members || method.name == "<init>" {
+                self.queue.push_back(Item::Method(MethodRef::new(
+                    name,
+                    &method.name,
+                    &method.descriptor,
+                )));
+            }
+        }
+        if all_members {
+            for field in component.fields() {
+                self.queue.push_back(Item::Field(FieldRef::new(
+                    name,
+                    &field.name,
+                    &field.descriptor,
+                )));
+            }
+        }
+    }
+
+    fn run(&mut self) {
+        while let Some(item) = self.queue.pop_front() {
+            match item {
+                Item::Class(class) => self.visit_class(class),
+                Item::Instantiated(class) => self.visit_instantiated_class(class),
+                Item::Method(method) => self.visit_method(method),
+                Item::Field(field) => self.visit_field(field),
+            }
+        }
+    }
+
+    fn visit_class(&mut self, class: String) {
+        let Some(component) = self.components.get(class.as_str()).copied() else {
+            return;
+        };
+        if !self.classes.insert(class.clone()) {
+            return;
+        }
+
+        for super_type in self.hierarchy.direct_supertypes(&class) {
+            self.queue.push_back(Item::Class(super_type.to_string()));
+        }
+        if component.methods().iter().any(|x| x.name == "<clinit>") {
+            self.queue
+                .push_back(Item::Method(MethodRef::new(&class, "<clinit>", "()V")));
+        }
+    }
+
+    fn visit_instantiated_class(&mut self, class: String) {
+        let Some(component) = self.components.get(class.as_str()).copied() else {
+            return;
+        };
+        if !self.instantiated.insert(class.clone()) {
+            return;
+        }
+        self.queue.push_back(Item::Class(class.clone()));
+
+        // virtual calls reached before the class was instantiated
+        let supertypes = std::iter::once(class.as_str())
+            .chain(self.hierarchy.supertypes(&class))
+            .collect::<Vec<_>>();
+        let mut targets = Vec::new();
+        for super_type in supertypes {
+            for (name, descriptor) in self.virtual_calls.get(super_type).into_iter().flatten() {
+                targets.extend(self.call_graph.select_method(
+                    self.hierarchy,
+                    &class,
+                    name,
+                    descriptor,
+                ));
+            }
+        }
+        self.queue.extend(targets.into_iter().map(Item::Method));
+
+        let extends_library = self
+            .hierarchy
+            .supertypes(&class)
+            .into_iter()
+            .any(|x| x != JAVA_LANG_OBJECT && !self.components.contains_key(x));
+        let is_enum = component.access_flags().contains(AccessFlags::ENUM);
+        for method in component.methods() {
+            let is_instance_method = !method.is_static
+                && !method.access_flags.contains(MethodAccessFlags::PRIVATE)
+                && method.name != "<init>";
+            let is_kept = OBJECT_METHODS
+                .contains(&(method.name.as_str(), method.descriptor.as_str()))
+                || (extends_library && is_instance_method)
+                || (is_enum && (method.name == "values" || method.name == "valueOf"));
+            if is_kept {
+                self.queue.push_back(Item::Method(MethodRef::new(
+                    &class,
+                    &method.name,
+                    &method.descriptor,
+                )));
+            }
+        }
+    }
+
+    /// Records a reachable virtual call and queues its targets in the classes instantiated so
+    /// far.
+    fn add_virtual_call(&mut self, callee: &MethodRef) {
+        let is_new = self
+            .virtual_calls
+            .entry(callee.class_name.clone())
+            .or_default()
+            .insert((callee.name.clone(), callee.descriptor.clone()));
+        if !is_new {
+            return;
+        }
+
+        let receiver = callee.class_name.as_str();
+        for ty in std::iter::once(receiver).chain(self.hierarchy.subtypes(receiver)) {
+            if self.instantiated.contains(ty) {
+                self.queue.extend(
+                    self.call_graph
+                        .select_method(self.hierarchy, ty, &callee.name, &callee.descriptor)
+                        .map(Item::Method),
+                );
+            }
+        }
+    }
+
+    fn visit_method(&mut self, method: MethodRef) {
+        let Some(method) = self.call_graph.resolve_method(self.hierarchy, &method) else {
+            return;
+        };
+        if !self.methods.insert(method.clone()) {
+            return;
+        }
+
+        self.queue.push_back(Item::Class(method.class_name.clone()));
+        for class in descriptor_class_names(&method.descriptor) {
+            self.queue.push_back(Item::Class(class));
+        }
+        let calls = self.calls.get(&method).cloned().unwrap_or_default();
+        for call in calls {
+            // the resolved declaration is kept even for virtual calls, as linking needs it
+            self.queue.push_back(Item::Method(call.callee.clone()));
+            if matches!(call.kind, CallKind::Virtual | CallKind::Interface) {
+                self.add_virtual_call(&call.callee);
+            }
+        }
+        if let Some(references) = self.references.get(&method) {
+            for field in &references.fields {
+                self.queue.push_back(Item::Field(field.clone()));
+            }
+            for class in &references.classes {
+                self.queue.push_back(Item::Class(class.clone()));
+            }
+            for class in &references.instantiated {
+                self.queue.push_back(Item::Instantiated(class.clone()));
+            }
+        }
+    }
+
+    fn visit_field(&mut self, field: FieldRef) {
+        // fields resolve through superinterfaces before the superclass (JVMS 5.4.3.2), which
+        // only differs from the method resolution order for ambiguous names javac rejects
+        let Some(field) = self
+            .hierarchy
+            .method_resolution_order(&field.class_name)
+            .into_iter()
+            .map(|ty| FieldRef::new(ty, &field.name, &field.descriptor))
+            .find(|x| self.fields.contains(x))
+        else {
+            return;
+        };
+        if !self.reachable_fields.insert(field.clone()) {
+            return;
+        }
+
+        self.queue.push_back(Item::Class(field.class_name.clone()));
+        for class in descriptor_class_names(&field.descriptor) {
+            self.queue.push_back(Item::Class(class));
+        }
+    }
+
+    fn report(&self) -> UsageReport {
+        let mut report = UsageReport::default();
+        for class in self.components.keys() {
+            if !self.classes.contains(*class) {
+                report.unused_classes.push(class.to_string());
+            }
+        }
+        report.unused_classes.sort();
+
+        report.unused_methods = self
+            .call_graph
+            .methods
+            .iter()
+            .filter(|x| self.classes.contains(&x.class_name) && !self.methods.contains(*x))
+            .cloned()
+            .collect();
+
+        let fields = self
+            .fields
+            .iter()
+            .filter(|x| self.classes.contains(&x.class_name) && !self.reachable_fields.contains(*x))
+            .cloned()
+            .collect::<BTreeSet<_>>();
+        report.unused_fields = fields.into_iter().collect();
+
+        report
+    }
+}
+
+fn field_ref(pool: ConstantPool, index: u16) -> Option<FieldRef> {
+    let ConstantPoolInfo::Fieldref { .. } = pool.get(index).ok()? else {
+        return None;
+    };
+    let member = pool.member_ref(index).ok()?;
+    Some(FieldRef::new(
+        &member.class_name.replace('/', "."),
+        member.name,
+        member.descriptor,
+    ))
+}
+
+/// Matches a qualified class name against a keep pattern, where `*` matches any characters
+/// except `.` and `**` matches any characters.
+pub fn matches_pattern(pattern: &str, name: &str) -> bool {
+    if let Some(rest) = pattern.strip_prefix("**") {
+        return (0..=name.len())
+            .filter(|i| name.is_char_boundary(*i))
+            .any(|i| matches_pattern(rest, &name[i..]));
+    }
+    if let Some(rest) = pattern.strip_prefix('*') {
+        let segment_end = name.find('.').unwrap_or(name.len());
+        return (0..=segment_end)
+            .filter(|i| name.is_char_boundary(*i))
+            .any(|i| matches_pattern(rest, &name[i..]));
+    }
+
+    let mut pattern_chars = pattern.chars();
+    match (pattern_chars.next(), name.chars().next()) {
+        (None, None) => true,
+        (Some(p), Some(n)) if p == n => {
+            matches_pattern(pattern_chars.as_str(), &name[n.len_utf8()..])
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_pattern() {
+        assert!(matches_pattern("com.example.Main", "com.example.Main"));
+        assert!(matches_pattern("com.example.*", "com.example.Main"));
+        assert!(!matches_pattern("com.example.*", "com.example.api.Client"));
+        assert!(matches_pattern("com.example.**", "com.example.api.Client"));
+        assert!(matches_pattern("com.*.Client", "com.example.Client"));
+        assert!(matches_pattern("**Test", "com.example.MainTest"));
+        assert!(!matches_pattern("com.example.Main", "com.example.Main2"));
+    }
+
+    #[test]
+    fn test_unresolved_main_class() {
+        let entry_points = EntryPoints {
+            main_classes: vec!["q.Main".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            find_unused(&[], &entry_points).unwrap_err(),
+            "Main class `q.Main` not found"
+        );
+    }
+}
@@ -0,0 +1,1330 @@
+use std::{
+    collections::{BTreeSet, HashSet},
+    fmt,
+};
+
+use serde::Serialize;
+
+use crate::{
+    bytecode::{opcodes, parse_instructions, Operands},
+    class_file::{
+        AccessFlags, Attribute, AttributeKind, ClassFile, ConstantPoolInfo, ExceptionTableEntry,
+        FieldAccessFlags, MethodAccessFlags, MethodInfo,
+    },
+    constant_pool::{ConstantPool, ConstantPoolError},
+    stack_map::{initial_frame, method_frames, VerificationType},
+    type_check::type_check,
+};
+
+/// A violation of a format constraint of JVMS chapter 4.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Violation {
+    /// Where the problem is, e.g. `constant pool #12` or ``method `run()V` ``
+    pub location: String,
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.message)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ValidateOptions {
+    /// Whether to also type check the code of each method against its `StackMapTable`, see
+    /// [`type_check`]. Only class files of version 51 or later are type checked.
+    pub type_check: bool,
+}
+
+/// Checks a class file against the static constraints of JVMS chapter 4: constant pool entry
+/// types, access flag combinations, names and descriptors, attribute placement and version
+/// requirements, and the structure of `Code` attributes. Code is checked against its
+/// `StackMapTable` (frames at every branch target, within `max_locals` and `max_stack`), but
+/// the types of individual instructions are not inferred, see [`validate_with_options`].
+pub fn validate(class_file: &ClassFile) -> Vec<Violation> {
+    validate_with_options(class_file, ValidateOptions::default())
+}
+
+/// Checks a class file as [`validate`] does, and optionally type checks its code.
+pub fn validate_with_options(class_file: &ClassFile, options: ValidateOptions) -> Vec<Violation> {
+    let mut validator = Validator {
+        class_file,
+        pool: class_file.pool(),
+        options,
+        violations: Vec::new(),
+    };
+    validator.check_constant_pool();
+    validator.check_class();
+    validator.check_fields();
+    validator.check_methods();
+    validator.check_attributes("class", Context::Class, &class_file.attributes, None);
+    validator.violations
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Context {
+    Class,
+    Field,
+    Method,
+    Code,
+}
+
+impl fmt::Display for Context {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Context::Class => "class",
+            Context::Field => "field",
+            Context::Method => "method",
+            Context::Code => "Code attribute",
+        })
+    }
+}
+
+struct Validator<'a> {
+    class_file: &'a ClassFile,
+    pool: ConstantPool<'a>,
+    options: ValidateOptions,
+    violations: Vec<Violation>,
+}
+
+impl<'a> Validator<'a> {
+    fn report(&mut self, location: &str, message: impl Into<String>) {
+        self.violations.push(Violation {
+            location: location.to_string(),
+            message: message.into(),
+        });
+    }
+
+    /// Reports a failed constant pool lookup.
+    fn resolve<T>(&mut self, location: &str, result: Result<T, ConstantPoolError>) -> Option<T> {
+        match result {
+            Ok(x) => Some(x),
+            Err(e) => {
+                self.report(location, e.to_string());
+                None
+            }
+        }
+    }
+
+    fn require_version(&mut self, location: &str, what: &str, major: u16) {
+        if self.class_file.major_version < major {
+            self.report(
+                location,
+                format!(
+                    "{what} requires class file version {major} or later, found {}",
+                    self.class_file.major_version
+                ),
+            );
+        }
+    }
+
+    fn bootstrap_method_count(&self) -> Option<usize> {
+        self.class_file
+            .attributes
+            .iter()
+            .find_map(|x| match &x.kind {
+                AttributeKind::BootstrapMethods {
+                    bootstrap_methods, ..
+                } => Some(bootstrap_methods.len()),
+                _ => None,
+            })
+    }
+
+    fn check_constant_pool(&mut self) {
+        let entries = self.pool.entries();
+        if entries.len() != self.class_file.constant_pool_count as usize {
+            self.report(
+                "constant pool",
+                format!(
+                    "has {} entries but constant_pool_count is {}",
+                    entries.len(),
+                    self.class_file.constant_pool_count
+                ),
+            );
+        }
+
+        let is_module = self.class_file.access_flags.contains(AccessFlags::MODULE);
+        let bootstrap_method_count = self.bootstrap_method_count();
+        for (i, entry) in entries.iter().enumerate().skip(1) {
+            let index = i as u16;
+            let location = format!("constant pool #{i}");
+            match entry {
+                ConstantPoolInfo::Empty => {
+                    if !matches!(
+                        entries[i - 1],
+                        ConstantPoolInfo::Long { .. } | ConstantPoolInfo::Double { .. }
+                    ) {
+                        self.report(
+                            &location,
+                            "is unusable but does not follow a Long or Double",
+                        );
+                    }
+                }
+                ConstantPoolInfo::Long { .. } | ConstantPoolInfo::Double { .. } => {
+                    if !matches!(entries.get(i + 1), None | Some(ConstantPoolInfo::Empty)) {
+                        self.report(&location, "Long and Double entries take two slots");
+                    }
+                }
+                ConstantPoolInfo::Class { .. } => {
+                    if let Some(name) = self.resolve(&location, self.pool.class_name(index)) {
+                        if !is_class_name(name) {
+                            self.report(&location, format!("invalid class name `{name}`"));
+                        }
+                    }
+                }
+                ConstantPoolInfo::String { .. } => {
+                    self.resolve(&location, self.pool.string(index));
+                }
+                ConstantPoolInfo::NameAndType { .. } => {
+                    self.resolve(&location, self.pool.name_and_type(index));
+                }
+                ConstantPoolInfo::Fieldref { .. } => {
+                    if let Some(member) = self.resolve(&location, self.pool.member_ref(index)) {
+                        if !is_unqualified_name(member.name) {
+                            self.report(&location, format!("invalid field name `{}`", member.name));
+                        }
+                        if !is_field_descriptor(member.descriptor) {
+                            self.report(
+                                &location,
+                                format!("invalid field descriptor `{}`", member.descriptor),
+                            );
+                        }
+                    }
+                }
+                ConstantPoolInfo::Methodref { .. }
+                | ConstantPoolInfo::InterfaceMethodref { .. } => {
+                    if let Some(member) = self.resolve(&location, self.pool.member_ref(index)) {
+                        if member.name == "<clinit>" || !is_method_name(member.name) {
+                            self.report(
+                                &location,
+                                format!("invalid method name `{}`", member.name),
+                            );
+                        }
+                        if method_descriptor_slots(member.descriptor).is_none() {
+                            self.report(
+                                &location,
+                                format!("invalid method descriptor `{}`", member.descriptor),
+                            );
+                        } else if member.name == "<init>" && !member.descriptor.ends_with(")V") {
+                            self.report(&location, "<init> must return void");
+                        }
+                    }
+                }
+                ConstantPoolInfo::MethodHandle {
+                    reference_kind,
+                    reference_index,
+                } => {
+                    self.require_version(&location, "MethodHandle", 51);
+                    self.check_method_handle(&location, *reference_kind, *reference_index);
+                }
+                ConstantPoolInfo::MethodType { descriptor_index } => {
+                    self.require_version(&location, "MethodType", 51);
+                    if let Some(descriptor) =
+                        self.resolve(&location, self.pool.utf8(*descriptor_index))
+                    {
+                        if method_descriptor_slots(descriptor).is_none() {
+                            self.report(
+                                &location,
+                                format!("invalid method descriptor `{descriptor}`"),
+                            );
+                        }
+                    }
+                }
+                ConstantPoolInfo::Dynamic {
+                    bootstrap_method_attr_index,
+                    name_and_type_index,
+                }
+                | ConstantPoolInfo::InvokeDynamic {
+                    bootstrap_method_attr_index,
+                    name_and_type_index,
+                } => {
+                    let is_invoke = matches!(entry, ConstantPoolInfo::InvokeDynamic { .. });
+                    if is_invoke {
+                        self.require_version(&location, "InvokeDynamic", 51);
+                    } else {
+                        self.require_version(&location, "Dynamic", 55);
+                    }
+                    if let Some((_, descriptor)) =
+                        self.resolve(&location, self.pool.name_and_type(*name_and_type_index))
+                    {
+                        let valid = if is_invoke {
+                            method_descriptor_slots(descriptor).is_some()
+                        } else {
+                            is_field_descriptor(descriptor)
+                        };
+                        if !valid {
+                            self.report(&location, format!("invalid descriptor `{descriptor}`"));
+                        }
+                    }
+                    if bootstrap_method_count.unwrap_or_default()
+                        <= *bootstrap_method_attr_index as usize
+                    {
+                        self.report(
+                            &location,
+                            format!(
+                                "refers to bootstrap method {bootstrap_method_attr_index}, which does not exist"
+                            ),
+                        );
+                    }
+                }
+                ConstantPoolInfo::Module { name_index }
+                | ConstantPoolInfo::Package { name_index } => {
+                    self.require_version(&location, "Module and Package entries", 53);
+                    if !is_module {
+                        self.report(
+                            &location,
+                            "Module and Package entries are only allowed in module-info",
+                        );
+                    }
+                    self.resolve(&location, self.pool.utf8(*name_index));
+                }
+                ConstantPoolInfo::Utf8 { .. }
+                | ConstantPoolInfo::Integer { .. }
+                | ConstantPoolInfo::Float { .. } => {}
+            }
+        }
+    }
+
+    fn check_method_handle(&mut self, location: &str, reference_kind: u8, reference_index: u16) {
+        let Ok(reference) = self.pool.get(reference_index) else {
+            self.report(
+                location,
+                format!("invalid constant pool index {reference_index}"),
+            );
+            return;
+        };
+        let valid = match reference_kind {
+            1..=4 => matches!(reference, ConstantPoolInfo::Fieldref { .. }),
+            5 | 8 => matches!(reference, ConstantPoolInfo::Methodref { .. }),
+            6 | 7 => {
+                matches!(reference, ConstantPoolInfo::Methodref { .. })
+                    || (self.class_file.major_version >= 52
+                        && matches!(reference, ConstantPoolInfo::InterfaceMethodref { .. }))
+            }
+            9 => matches!(reference, ConstantPoolInfo::InterfaceMethodref { .. }),
+            _ => {
+                self.report(location, format!("invalid reference kind {reference_kind}"));
+                return;
+            }
+        };
+        if !valid {
+            self.report(
+                location,
+                format!("reference kind {reference_kind} does not match entry #{reference_index}"),
+            );
+            return;
+        }
+
+        if let Ok(member) = self.pool.member_ref(reference_index) {
+            let is_init = member.name == "<init>";
+            if (reference_kind == 8) != is_init || member.name == "<clinit>" {
+                self.report(
+                    location,
+                    format!(
+                        "reference kind {reference_kind} cannot refer to `{}`",
+                        member.name
+                    ),
+                );
+            }
+        }
+    }
+
+    fn check_class(&mut self) {
+        let class_file = self.class_file;
+        let flags = class_file.access_flags;
+        let location = "class";
+
+        if flags.contains(AccessFlags::MODULE) {
+            self.require_version(location, "ACC_MODULE", 53);
+            if flags != AccessFlags::MODULE {
+                self.report(location, "ACC_MODULE must not be combined with other flags");
+            }
+            if class_file.super_class != 0
+                || !class_file.interfaces.is_empty()
+                || !class_file.fields.is_empty()
+                || !class_file.methods.is_empty()
+            {
+                self.report(
+                    location,
+                    "module-info must not have a superclass, interfaces, fields or methods",
+                );
+            }
+            return;
+        }
+
+        if flags.contains(AccessFlags::INTERFACE) {
+            if !flags.contains(AccessFlags::ABSTRACT) {
+                self.report(location, "an interface must be ACC_ABSTRACT");
+            }
+            if flags.intersects(AccessFlags::FINAL | AccessFlags::SUPER | AccessFlags::ENUM) {
+                self.report(
+                    location,
+                    "an interface must not be ACC_FINAL, ACC_SUPER or ACC_ENUM",
+                );
+            }
+        } else {
+            if flags.contains(AccessFlags::ANNOTATION) {
+                self.report(location, "ACC_ANNOTATION requires ACC_INTERFACE");
+            }
+            if flags.contains(AccessFlags::FINAL | AccessFlags::ABSTRACT) {
+                self.report(
+                    location,
+                    "a class cannot be both ACC_FINAL and ACC_ABSTRACT",
+                );
+            }
+        }
+
+        let this_class = self.resolve("this_class", self.pool.class_name(class_file.this_class));
+        if this_class.is_some_and(|x| x.starts_with('[')) {
+            self.report("this_class", "must not be an array class");
+        }
+
+        if class_file.super_class == 0 {
+            if this_class.is_some_and(|x| x != "java/lang/Object") {
+                self.report(
+                    "super_class",
+                    "only java/lang/Object may have no superclass",
+                );
+            }
+        } else if let Some(super_class) =
+            self.resolve("super_class", self.pool.class_name(class_file.super_class))
+        {
+            if flags.contains(AccessFlags::INTERFACE) && super_class != "java/lang/Object" {
+                self.report(
+                    "super_class",
+                    "the superclass of an interface must be java/lang/Object",
+                );
+            }
+        }
+
+        for interface in &class_file.interfaces {
+            self.resolve("interfaces", self.pool.class_name(*interface));
+        }
+    }
+
+    fn check_fields(&mut self) {
+        let is_interface = self
+            .class_file
+            .access_flags
+            .contains(AccessFlags::INTERFACE);
+        let mut seen = HashSet::new();
+        for (i, field) in self.class_file.fields.iter().enumerate() {
+            let fallback = format!("field #{i}");
+            let name = self.resolve(&fallback, self.pool.utf8(field.name_index));
+            let descriptor = self.resolve(&fallback, self.pool.utf8(field.descriptor_index));
+            let location = name.map_or(fallback, |x| format!("field `{x}`"));
+
+            if let Some(name) = name {
+                if !is_unqualified_name(name) {
+                    self.report(&location, "invalid field name");
+                }
+            }
+            if let Some(descriptor) = descriptor {
+                if !is_field_descriptor(descriptor) {
+                    self.report(&location, format!("invalid descriptor `{descriptor}`"));
+                }
+            }
+            if let (Some(name), Some(descriptor)) = (name, descriptor) {
+                if !seen.insert((name, descriptor)) {
+                    self.report(&location, "is declared more than once");
+                }
+            }
+
+            let flags = field.access_flags;
+            let access =
+                FieldAccessFlags::PUBLIC | FieldAccessFlags::PRIVATE | FieldAccessFlags::PROTECTED;
+            if (flags & access).bits().count_ones() > 1 {
+                self.report(
+                    &location,
+                    "at most one of ACC_PUBLIC, ACC_PRIVATE and ACC_PROTECTED may be set",
+                );
+            }
+            if flags.contains(FieldAccessFlags::FINAL | FieldAccessFlags::VOLATILE) {
+                self.report(
+                    &location,
+                    "a field cannot be both ACC_FINAL and ACC_VOLATILE",
+                );
+            }
+            let constant =
+                FieldAccessFlags::PUBLIC | FieldAccessFlags::STATIC | FieldAccessFlags::FINAL;
+            if is_interface
+                && (!flags.contains(constant)
+                    || !(constant | FieldAccessFlags::SYNTHETIC).contains(flags))
+            {
+                self.report(
+                    &location,
+                    "interface fields must be ACC_PUBLIC, ACC_STATIC and ACC_FINAL, and may only also be ACC_SYNTHETIC",
+                );
+            }
+
+            self.check_attributes(&location, Context::Field, &field.attributes, descriptor);
+        }
+    }
+
+    fn check_methods(&mut self) {
+        let mut seen = HashSet::new();
+        for (i, method) in self.class_file.methods.iter().enumerate() {
+            let fallback = format!("method #{i}");
+            let name = self.resolve(&fallback, self.pool.utf8(method.name_index));
+            let descriptor = self.resolve(&fallback, self.pool.utf8(method.descriptor_index));
+            let location = match (name, descriptor) {
+                (Some(name), Some(descriptor)) => format!("method `{name}{descriptor}`"),
+                _ => fallback,
+            };
+
+            if let Some(name) = name {
+                if !is_method_name(name) {
+                    self.report(&location, "invalid method name");
+                }
+            }
+            if let Some(descriptor) = descriptor {
+                match method_descriptor_slots(descriptor) {
+                    None => self.report(&location, format!("invalid descriptor `{descriptor}`")),
+                    Some(slots) => {
+                        let this = !method.access_flags.contains(MethodAccessFlags::STATIC);
+                        if slots + this as usize > 255 {
+                            self.report(&location, "parameters take more than 255 local slots");
+                        }
+                    }
+                }
+            }
+            if let (Some(name), Some(descriptor)) = (name, descriptor) {
+                if !seen.insert((name, descriptor)) {
+                    self.report(&location, "is declared more than once");
+                }
+            }
+
+            self.check_method_flags(&location, method, name.unwrap_or_default(), descriptor);
+
+            let has_code = method
+                .attributes
+                .iter()
+                .any(|x| matches!(x.kind, AttributeKind::Code { .. }));
+            let needs_code = !method
+                .access_flags
+                .intersects(MethodAccessFlags::ABSTRACT | MethodAccessFlags::NATIVE);
+            if has_code && !needs_code {
+                self.report(&location, "abstract and native methods must not have code");
+            } else if !has_code && needs_code {
+                self.report(&location, "has no Code attribute");
+            }
+
+            self.check_attributes(&location, Context::Method, &method.attributes, None);
+            for attribute in &method.attributes {
+                if let AttributeKind::Code {
+                    max_stack,
+                    max_locals,
+                    code_length,
+                    code,
+                    exception_table,
+                    ..
+                } = &attribute.kind
+                {
+                    let code = CodeAttribute {
+                        max_stack: *max_stack,
+                        max_locals: *max_locals,
+                        code_length: *code_length,
+                        code,
+                        exception_table,
+                    };
+                    self.check_code(&location, method, &code);
+                }
+            }
+        }
+    }
+
+    fn check_method_flags(
+        &mut self,
+        location: &str,
+        method: &MethodInfo,
+        name: &str,
+        descriptor: Option<&str>,
+    ) {
+        let flags = method.access_flags;
+        let is_interface = self
+            .class_file
+            .access_flags
+            .contains(AccessFlags::INTERFACE);
+        let access =
+            MethodAccessFlags::PUBLIC | MethodAccessFlags::PRIVATE | MethodAccessFlags::PROTECTED;
+
+        if name == "<clinit>" {
+            if descriptor.is_some_and(|x| x != "()V") {
+                self.report(location, "<clinit> must have the descriptor ()V");
+            }
+            if self.class_file.major_version >= 51 && !flags.contains(MethodAccessFlags::STATIC) {
+                self.report(location, "<clinit> must be ACC_STATIC");
+            }
+            return;
+        }
+
+        if (flags & access).bits().count_ones() > 1 {
+            self.report(
+                location,
+                "at most one of ACC_PUBLIC, ACC_PRIVATE and ACC_PROTECTED may be set",
+            );
+        }
+
+        if name == "<init>" {
+            if is_interface {
+                self.report(location, "interfaces cannot declare constructors");
+            }
+            if descriptor.is_some_and(|x| !x.ends_with(")V")) {
+                self.report(location, "<init> must return void");
+            }
+            let allowed = access
+                | MethodAccessFlags::VARARGS
+                | MethodAccessFlags::STRICT
+                | MethodAccessFlags::SYNTHETIC;
+            if !allowed.contains(flags) {
+                self.report(
+                    location,
+                    "<init> may only be ACC_VARARGS, ACC_STRICT, ACC_SYNTHETIC and an access flag",
+                );
+            }
+            return;
+        }
+
+        if is_interface {
+            if self.class_file.major_version < 52 {
+                let allowed = MethodAccessFlags::PUBLIC
+                    | MethodAccessFlags::ABSTRACT
+                    | MethodAccessFlags::VARARGS
+                    | MethodAccessFlags::BRIDGE
+                    | MethodAccessFlags::SYNTHETIC;
+                if !flags.contains(MethodAccessFlags::PUBLIC | MethodAccessFlags::ABSTRACT)
+                    || !allowed.contains(flags)
+                {
+                    self.report(
+                        location,
+                        "interface methods before version 52 must be ACC_PUBLIC and ACC_ABSTRACT",
+                    );
+                }
+            } else {
+                if flags.intersects(
+                    MethodAccessFlags::PROTECTED
+                        | MethodAccessFlags::FINAL
+                        | MethodAccessFlags::SYNCHRONIZED
+                        | MethodAccessFlags::NATIVE,
+                ) {
+                    self.report(
+                        location,
+                        "interface methods must not be ACC_PROTECTED, ACC_FINAL, ACC_SYNCHRONIZED or ACC_NATIVE",
+                    );
+                }
+                if (flags & (MethodAccessFlags::PUBLIC | MethodAccessFlags::PRIVATE))
+                    .bits()
+                    .count_ones()
+                    != 1
+                {
+                    self.report(
+                        location,
+                        "interface methods must be exactly one of ACC_PUBLIC and ACC_PRIVATE",
+                    );
+                }
+            }
+        }
+
+        if flags.contains(MethodAccessFlags::ABSTRACT) {
+            let mut forbidden = MethodAccessFlags::PRIVATE
+                | MethodAccessFlags::STATIC
+                | MethodAccessFlags::FINAL
+                | MethodAccessFlags::SYNCHRONIZED
+                | MethodAccessFlags::NATIVE;
+            if (46..=60).contains(&self.class_file.major_version) {
+                forbidden |= MethodAccessFlags::STRICT;
+            }
+            if flags.intersects(forbidden) {
+                self.report(
+                    location,
+                    "abstract methods must not be private, static, final, synchronized, native or strictfp",
+                );
+            }
+        }
+    }
+
+    fn check_attributes(
+        &mut self,
+        location: &str,
+        context: Context,
+        attributes: &[Attribute],
+        field_descriptor: Option<&str>,
+    ) {
+        let mut seen = BTreeSet::new();
+        for attribute in attributes {
+            let Some((name, contexts, since)) = attribute_rule(&attribute.kind) else {
+                continue;
+            };
+            self.resolve(location, self.pool.utf8(attribute.attribute_name_index));
+            if !contexts.contains(&context) {
+                self.report(
+                    location,
+                    format!("{name} attribute is not allowed on a {context}"),
+                );
+            }
+            self.require_version(location, &format!("{name} attribute"), since);
+            if !matches!(
+                attribute.kind,
+                AttributeKind::LineNumberTable { .. }
+                    | AttributeKind::LocalVariableTable { .. }
+                    | AttributeKind::LocalVariableTypeTable { .. }
+            ) && !seen.insert(name)
+            {
+                self.report(location, format!("has more than one {name} attribute"));
+            }
+
+            self.check_attribute_references(location, &attribute.kind, field_descriptor);
+            if let AttributeKind::Code { attributes, .. } = &attribute.kind {
+                self.check_attributes(location, Context::Code, attributes, None);
+            }
+        }
+    }
+
+    fn check_attribute_references(
+        &mut self,
+        location: &str,
+        kind: &AttributeKind,
+        field_descriptor: Option<&str>,
+    ) {
+        match kind {
+            AttributeKind::ConstantValue {
+                constant_value_index,
+            } => {
+                let index = *constant_value_index;
+                let valid = match field_descriptor {
+                    Some("I" | "S" | "C" | "B" | "Z") => self.pool.integer(index).map(|_| ()),
+                    Some("F") => self.pool.float(index).map(|_| ()),
+                    Some("J") => self.pool.long(index).map(|_| ()),
+                    Some("D") => self.pool.double(index).map(|_| ()),
+                    Some("Ljava/lang/String;") => self.pool.string(index).map(|_| ()),
+                    _ => {
+                        self.report(location, "fields of this type cannot have a ConstantValue");
+                        Ok(())
+                    }
+                };
+                self.resolve(location, valid);
+            }
+            AttributeKind::Signature { signature_index } => {
+                self.resolve(location, self.pool.utf8(*signature_index));
+            }
+            AttributeKind::SourceFile { sourcefile_index } => {
+                self.resolve(location, self.pool.utf8(*sourcefile_index));
+            }
+            AttributeKind::Exceptions {
+                exception_index_table: classes,
+                ..
+            }
+            | AttributeKind::NestMembers { classes, .. }
+            | AttributeKind::PermittedSubclasses { classes, .. } => {
+                for class in classes {
+                    self.resolve(location, self.pool.class_name(*class));
+                }
+            }
+            AttributeKind::NestHost { host_class_index } => {
+                self.resolve(location, self.pool.class_name(*host_class_index));
+            }
+            AttributeKind::ModuleMainClass { main_class_index } => {
+                self.resolve(location, self.pool.class_name(*main_class_index));
+            }
+            AttributeKind::EnclosingMethod {
+                class_index,
+                method_index,
+            } => {
+                self.resolve(location, self.pool.class_name(*class_index));
+                if *method_index != 0 {
+                    self.resolve(location, self.pool.name_and_type(*method_index));
+                }
+            }
+            AttributeKind::BootstrapMethods {
+                bootstrap_methods, ..
+            } => {
+                for bootstrap_method in bootstrap_methods {
+                    self.resolve(
+                        location,
+                        self.pool
+                            .method_handle(bootstrap_method.bootstrap_method_ref),
+                    );
+                    for argument in &bootstrap_method.bootstrap_arguments {
+                        match self.pool.get(*argument) {
+                            Ok(
+                                ConstantPoolInfo::Integer { .. }
+                                | ConstantPoolInfo::Float { .. }
+                                | ConstantPoolInfo::Long { .. }
+                                | ConstantPoolInfo::Double { .. }
+                                | ConstantPoolInfo::Class { .. }
+                                | ConstantPoolInfo::String { .. }
+                                | ConstantPoolInfo::MethodHandle { .. }
+                                | ConstantPoolInfo::MethodType { .. }
+                                | ConstantPoolInfo::Dynamic { .. },
+                            ) => {}
+                            _ => self.report(
+                                location,
+                                format!(
+                                    "bootstrap argument #{argument} is not a loadable constant"
+                                ),
+                            ),
+                        }
+                    }
+                }
+            }
+            AttributeKind::RuntimeVisibleAnnotations { annotations, .. }
+            | AttributeKind::RuntimeInvisibleAnnotations { annotations, .. } => {
+                for annotation in annotations {
+                    if let Some(ty) = self.resolve(location, self.pool.utf8(annotation.type_index))
+                    {
+                        if !is_field_descriptor(ty) {
+                            self.report(location, format!("invalid annotation type `{ty}`"));
+                        }
+                    }
+                }
+            }
+            AttributeKind::Module {
+                module_name_index, ..
+            } => {
+                self.resolve(location, self.pool.module_name(*module_name_index));
+            }
+            _ => {}
+        }
+    }
+
+    fn check_code(&mut self, location: &str, method: &MethodInfo, code: &CodeAttribute) {
+        let length = code.code.len();
+        if length == 0 || length > u16::MAX as usize {
+            self.report(location, "code length must be between 1 and 65535");
+            return;
+        }
+        if code.code_length as usize != length {
+            self.report(
+                location,
+                format!(
+                    "code_length is {} but the code has {length} bytes",
+                    code.code_length
+                ),
+            );
+        }
+
+        let instructions = match parse_instructions(code.code) {
+            Ok(([], instructions)) => instructions,
+            _ => {
+                self.report(location, "code cannot be decoded");
+                return;
+            }
+        };
+        let boundaries: BTreeSet<u32> = instructions.iter().map(|x| x.offset).collect();
+
+        let mut targets = BTreeSet::new();
+        for instruction in &instructions {
+            let at = format!(
+                "{} at offset {}",
+                instruction.mnemonic(),
+                instruction.offset
+            );
+            for target in instruction.branch_targets() {
+                if !boundaries.contains(&target) {
+                    self.report(
+                        location,
+                        format!(
+                            "{at} branches to {target}, which is not the start of an instruction"
+                        ),
+                    );
+                }
+                targets.insert(target);
+            }
+
+            if let Operands::Local(index) | Operands::Iinc { index, .. } = instruction.operands {
+                if index >= code.max_locals {
+                    self.report(
+                        location,
+                        format!(
+                            "{at} uses local {index}, but max_locals is {}",
+                            code.max_locals
+                        ),
+                    );
+                }
+            }
+
+            if let Some(index) = instruction.constant_pool_index() {
+                self.check_instruction_constant(location, &at, instruction.opcode, index);
+            }
+        }
+
+        for entry in code.exception_table {
+            self.check_exception_handler(location, entry, &boundaries, length);
+            targets.insert(entry.handler_pc as u32);
+        }
+
+        if self.class_file.major_version >= 50 {
+            let violations = self.violations.len();
+            self.check_frames(location, method, code, &boundaries, &targets);
+            // Type checking needs well-formed frames, and reports only the first error
+            if self.options.type_check
+                && self.class_file.major_version >= 51
+                && self.violations.len() == violations
+            {
+                if let Err(e) = type_check(self.class_file, method) {
+                    self.report(location, e);
+                }
+            }
+        }
+    }
+
+    fn check_instruction_constant(&mut self, location: &str, at: &str, opcode: u8, index: u16) {
+        let Some(entry) = self.resolve(location, self.pool.get(index)) else {
+            return;
+        };
+        let interface_methods = self.class_file.major_version >= 52;
+        let (valid, expected) = match opcode {
+            opcodes::LDC | opcodes::LDC_W => (
+                matches!(
+                    entry,
+                    ConstantPoolInfo::Integer { .. }
+                        | ConstantPoolInfo::Float { .. }
+                        | ConstantPoolInfo::String { .. }
+                        | ConstantPoolInfo::Class { .. }
+                        | ConstantPoolInfo::MethodHandle { .. }
+                        | ConstantPoolInfo::MethodType { .. }
+                        | ConstantPoolInfo::Dynamic { .. }
+                ),
+                "a single-slot loadable constant",
+            ),
+            opcodes::LDC2_W => (
+                matches!(
+                    entry,
+                    ConstantPoolInfo::Long { .. }
+                        | ConstantPoolInfo::Double { .. }
+                        | ConstantPoolInfo::Dynamic { .. }
+                ),
+                "a Long or Double",
+            ),
+            opcodes::GETSTATIC | opcodes::PUTSTATIC | opcodes::GETFIELD | opcodes::PUTFIELD => (
+                matches!(entry, ConstantPoolInfo::Fieldref { .. }),
+                "a Fieldref",
+            ),
+            opcodes::INVOKEVIRTUAL => (
+                matches!(entry, ConstantPoolInfo::Methodref { .. }),
+                "a Methodref",
+            ),
+            opcodes::INVOKESPECIAL | opcodes::INVOKESTATIC => (
+                matches!(entry, ConstantPoolInfo::Methodref { .. })
+                    || (interface_methods
+                        && matches!(entry, ConstantPoolInfo::InterfaceMethodref { .. })),
+                "a Methodref",
+            ),
+            opcodes::INVOKEINTERFACE => (
+                matches!(entry, ConstantPoolInfo::InterfaceMethodref { .. }),
+                "an InterfaceMethodref",
+            ),
+            opcodes::INVOKEDYNAMIC => (
+                matches!(entry, ConstantPoolInfo::InvokeDynamic { .. }),
+                "an InvokeDynamic",
+            ),
+            _ => (matches!(entry, ConstantPoolInfo::Class { .. }), "a Class"),
+        };
+        if !valid {
+            self.report(
+                location,
+                format!("{at} refers to constant pool #{index}, which is not {expected}"),
+            );
+        }
+    }
+
+    fn check_exception_handler(
+        &mut self,
+        location: &str,
+        entry: &ExceptionTableEntry,
+        boundaries: &BTreeSet<u32>,
+        length: usize,
+    ) {
+        let (start, end, handler) = (
+            entry.start_pc as u32,
+            entry.end_pc as u32,
+            entry.handler_pc as u32,
+        );
+        let at = format!("exception handler [{start}, {end}) -> {handler}");
+        if start >= end {
+            self.report(location, format!("{at} has an empty range"));
+        }
+        if !boundaries.contains(&start)
+            || !(boundaries.contains(&end) || end as usize == length)
+            || !boundaries.contains(&handler)
+        {
+            self.report(
+                location,
+                format!("{at} does not start and end at instructions"),
+            );
+        }
+        if entry.catch_type != 0 {
+            self.resolve(location, self.pool.class_name(entry.catch_type));
+        }
+    }
+
+    fn check_frames(
+        &mut self,
+        location: &str,
+        method: &MethodInfo,
+        code: &CodeAttribute,
+        boundaries: &BTreeSet<u32>,
+        targets: &BTreeSet<u32>,
+    ) {
+        let (initial, frames) = match (
+            initial_frame(self.class_file, method),
+            method_frames(self.class_file, method),
+        ) {
+            (Ok(initial), Ok(frames)) => (initial, frames),
+            (Err(e), _) | (_, Err(e)) => {
+                self.report(location, format!("invalid StackMapTable: {e}"));
+                return;
+            }
+        };
+
+        for frame in std::iter::once(&initial).chain(&frames) {
+            if frame.offset != 0 && !boundaries.contains(&frame.offset) {
+                self.report(
+                    location,
+                    format!(
+                        "stack map frame at {} is not at an instruction",
+                        frame.offset
+                    ),
+                );
+            }
+            if frame.locals.len() > code.max_locals as usize {
+                self.report(
+                    location,
+                    format!(
+                        "stack map frame at {} has {} locals, but max_locals is {}",
+                        frame.offset,
+                        frame.locals.len(),
+                        code.max_locals
+                    ),
+                );
+            }
+            let stack_size: usize = frame
+                .stack
+                .iter()
+                .map(|x| if x.is_wide() { 2 } else { 1 })
+                .sum();
+            if stack_size > code.max_stack as usize {
+                self.report(
+                    location,
+                    format!(
+                        "stack map frame at {} has a stack of {stack_size}, but max_stack is {}",
+                        frame.offset, code.max_stack
+                    ),
+                );
+            }
+            if frame.stack.contains(&VerificationType::Top) {
+                self.report(
+                    location,
+                    format!("stack map frame at {} has top on the stack", frame.offset),
+                );
+            }
+        }
+
+        // Version 50 class files fall back to type inference when frames are missing
+        if self.class_file.major_version >= 51 {
+            let offsets: BTreeSet<u32> = frames.iter().map(|x| x.offset).collect();
+            for target in targets.difference(&offsets) {
+                self.report(
+                    location,
+                    format!("branch target {target} has no stack map frame"),
+                );
+            }
+        }
+    }
+}
+
+struct CodeAttribute<'a> {
+    max_stack: u16,
+    max_locals: u16,
+    code_length: u32,
+    code: &'a [u8],
+    exception_table: &'a [ExceptionTableEntry],
+}
+
+/// The name of an attribute, where it may appear and the first class file version defining it.
+fn attribute_rule(kind: &AttributeKind) -> Option<(&'static str, &'static [Context], u16)> {
+    use Context::*;
+
+    const MEMBERS: &[Context] = &[Class, Field, Method];
+    Some(match kind {
+        AttributeKind::ConstantValue { .. } => ("ConstantValue", &[Field], 45),
+        AttributeKind::Code { .. } => ("Code", &[Method], 45),
+        AttributeKind::StackMapTable { .. } => ("StackMapTable", &[Code], 50),
+        AttributeKind::BootstrapMethods { .. } => ("BootstrapMethods", &[Class], 51),
+        AttributeKind::NestHost { .. } => ("NestHost", &[Class], 55),
+        AttributeKind::NestMembers { .. } => ("NestMembers", &[Class], 55),
+        AttributeKind::PermittedSubclasses { .. } => ("PermittedSubclasses", &[Class], 61),
+        AttributeKind::Exceptions { .. } => ("Exceptions", &[Method], 45),
+        AttributeKind::InnerClasses { .. } => ("InnerClasses", &[Class], 45),
+        AttributeKind::EnclosingMethod { .. } => ("EnclosingMethod", &[Class], 49),
+        AttributeKind::Synthetic => ("Synthetic", MEMBERS, 45),
+        AttributeKind::Signature { .. } => ("Signature", MEMBERS, 49),
+        AttributeKind::Record { .. } => ("Record", &[Class], 60),
+        AttributeKind::SourceFile { .. } => ("SourceFile", &[Class], 45),
+        AttributeKind::LineNumberTable { .. } => ("LineNumberTable", &[Code], 45),
+        AttributeKind::LocalVariableTable { .. } => ("LocalVariableTable", &[Code], 45),
+        AttributeKind::LocalVariableTypeTable { .. } => ("LocalVariableTypeTable", &[Code], 49),
+        AttributeKind::SourceDebugExtension { .. } => ("SourceDebugExtension", &[Class], 49),
+        AttributeKind::Deprecated => ("Deprecated", MEMBERS, 45),
+        AttributeKind::RuntimeVisibleAnnotations { .. } => {
+            ("RuntimeVisibleAnnotations", MEMBERS, 49)
+        }
+        AttributeKind::RuntimeInvisibleAnnotations { .. } => {
+            ("RuntimeInvisibleAnnotations", MEMBERS, 49)
+        }
+        AttributeKind::RuntimeVisibleParameterAnnotations { .. } => {
+            ("RuntimeVisibleParameterAnnotations", &[Method], 49)
+        }
+        AttributeKind::RuntimeInvisibleParameterAnnotations { .. } => {
+            ("RuntimeInvisibleParameterAnnotations", &[Method], 49)
+        }
+        AttributeKind::RuntimeVisibleTypeAnnotations { .. } => (
+            "RuntimeVisibleTypeAnnotations",
+            &[Class, Field, Method, Code],
+            52,
+        ),
+        AttributeKind::RuntimeInvisibleTypeAnnotations { .. } => (
+            "RuntimeInvisibleTypeAnnotations",
+            &[Class, Field, Method, Code],
+            52,
+        ),
+        AttributeKind::AnnotationDefault { .. } => ("AnnotationDefault", &[Method], 49),
+        AttributeKind::MethodParameters { .. } => ("MethodParameters", &[Method], 52),
+        AttributeKind::Module { .. } => ("Module", &[Class], 53),
+        AttributeKind::ModulePackages { .. } => ("ModulePackages", &[Class], 53),
+        AttributeKind::ModuleMainClass { .. } => ("ModuleMainClass", &[Class], 53),
+        AttributeKind::Unknown | AttributeKind::Skipped { .. } => return None,
+    })
+}
+
+/// An unqualified name as defined in JVMS 4.2.2.
+fn is_unqualified_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains(['.', ';', '[', '/'])
+}
+
+fn is_method_name(name: &str) -> bool {
+    name == "<init>"
+        || name == "<clinit>"
+        || (is_unqualified_name(name) && !name.contains(['<', '>']))
+}
+
+/// A class name in internal form, or an array descriptor.
+fn is_class_name(name: &str) -> bool {
+    if name.starts_with('[') {
+        is_field_descriptor(name)
+    } else {
+        name.split('/').all(is_unqualified_name)
+    }
+}
+
+/// Returns the length of the field descriptor at the start of `descriptor`.
+fn field_descriptor_len(descriptor: &str) -> Option<usize> {
+    let dims = descriptor.bytes().take_while(|x| *x == b'[').count();
+    if dims > 255 {
+        return None;
+    }
+    match descriptor.as_bytes().get(dims)? {
+        b'B' | b'C' | b'D' | b'F' | b'I' | b'J' | b'S' | b'Z' => Some(dims + 1),
+        b'L' => {
+            let end = dims + 1 + descriptor[dims + 1..].find(';')?;
+            descriptor[dims + 1..end]
+                .split('/')
+                .all(is_unqualified_name)
+                .then_some(end + 1)
+        }
+        _ => None,
+    }
+}
+
+fn is_field_descriptor(descriptor: &str) -> bool {
+    field_descriptor_len(descriptor) == Some(descriptor.len())
+}
+
+/// Returns the number of local variable slots taken by the parameters of a method descriptor,
+/// or `None` if it is malformed.
+fn method_descriptor_slots(descriptor: &str) -> Option<usize> {
+    let mut rest = descriptor.strip_prefix('(')?;
+    let mut slots = 0;
+    while !rest.starts_with(')') {
+        let len = field_descriptor_len(rest)?;
+        slots += if matches!(&rest[..len], "J" | "D") {
+            2
+        } else {
+            1
+        };
+        rest = &rest[len..];
+    }
+    let ret = &rest[1..];
+    (ret == "V" || is_field_descriptor(ret)).then_some(slots)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::class_file::{FieldInfo, StackMapFrame, StackMapFrameKind};
+    use crate::test_util::utf8;
+
+    fn class_file(
+        access_flags: AccessFlags,
+        fields: Vec<FieldInfo>,
+        methods: Vec<MethodInfo>,
+    ) -> ClassFile {
+        let constant_pool = vec![
+            ConstantPoolInfo::Empty,
+            utf8("a/b/Api"),
+            ConstantPoolInfo::Class { name_index: 1 },
+            utf8("java/lang/Object"),
+            ConstantPoolInfo::Class { name_index: 3 },
+            utf8("run"),
+            utf8("()V"),
+            utf8("count"),
+            utf8("[[Lbad;name;"),
+            utf8("Code"),
+        ];
+        ClassFile {
+            minor_version: 0,
+            major_version: 61,
+            constant_pool_count: constant_pool.len() as u16,
+            constant_pool,
+            access_flags,
+            this_class: 2,
+            super_class: 4,
+            interfaces_count: 0,
+            interfaces: Vec::new(),
+            fields_count: fields.len() as u16,
+            fields,
+            methods_count: methods.len() as u16,
+            methods,
+            attributes_count: 0,
+            attributes: Vec::new(),
+        }
+    }
+
+    fn method(access_flags: MethodAccessFlags, attributes: Vec<Attribute>) -> MethodInfo {
+        MethodInfo {
+            access_flags,
+            name_index: 5,
+            descriptor_index: 6,
+            attributes_count: attributes.len() as u16,
+            attributes,
+        }
+    }
+
+    fn code(code: Vec<u8>) -> Attribute {
+        Attribute {
+            attribute_name_index: 9,
+            attribute_length: 0,
+            kind: AttributeKind::Code {
+                max_stack: 0,
+                max_locals: 1,
+                code_length: code.len() as u32,
+                code,
+                exception_table_length: 0,
+                exception_table: Vec::new(),
+                attributes_count: 0,
+                attributes: Vec::new(),
+            },
+        }
+    }
+
+    #[test]
+    fn accepts_well_formed_class() {
+        let run = method(MethodAccessFlags::PUBLIC, vec![code(vec![opcodes::RETURN])]);
+        let class_file = class_file(
+            AccessFlags::PUBLIC | AccessFlags::SUPER,
+            Vec::new(),
+            vec![run],
+        );
+        assert_eq!(validate(&class_file), Vec::new());
+    }
+
+    #[test]
+    fn reports_format_violations() {
+        let field = FieldInfo {
+            access_flags: FieldAccessFlags::PRIVATE,
+            name_index: 7,
+            descriptor_index: 8,
+            attributes_count: 0,
+            attributes: Vec::new(),
+        };
+        // goto 5 lands outside the code, and the abstract method has code
+        let run = method(
+            MethodAccessFlags::PUBLIC | MethodAccessFlags::ABSTRACT,
+            vec![code(vec![opcodes::GOTO, 0, 5, opcodes::RETURN])],
+        );
+        let class_file = class_file(
+            AccessFlags::PUBLIC | AccessFlags::INTERFACE,
+            vec![field],
+            vec![run],
+        );
+
+        let messages: Vec<String> = validate(&class_file)
+            .iter()
+            .map(|x| x.to_string())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "class: an interface must be ACC_ABSTRACT",
+                "field `count`: invalid descriptor `[[Lbad;name;`",
+                "field `count`: interface fields must be ACC_PUBLIC, ACC_STATIC and ACC_FINAL, and may only also be ACC_SYNTHETIC",
+                "method `run()V`: abstract and native methods must not have code",
+                "method `run()V`: goto at offset 0 branches to 5, which is not the start of an instruction",
+                "method `run()V`: branch target 5 has no stack map frame",
+            ]
+        );
+    }
+
+    #[test]
+    fn type_checks_code_when_enabled() {
+        let type_check = ValidateOptions { type_check: true };
+        let messages = |mut code: Attribute| {
+            if let AttributeKind::Code { max_stack, .. } = &mut code.kind {
+                *max_stack = 2;
+            }
+            let run = method(MethodAccessFlags::PUBLIC, vec![code]);
+            let class_file = class_file(
+                AccessFlags::PUBLIC | AccessFlags::SUPER,
+                Vec::new(),
+                vec![run],
+            );
+            assert_eq!(validate(&class_file), Vec::new());
+            validate_with_options(&class_file, type_check)
+                .iter()
+                .map(|x| x.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        // iconst_0, fconst_0, iadd
+        assert_eq!(
+            messages(code(vec![0x03, 0x0b, 0x60, 0x57, opcodes::RETURN])),
+            vec!["method `run()V`: iadd at offset 2 expects int on the stack, found float"]
+        );
+        // aconst_null, pop
+        assert_eq!(
+            messages(code(vec![0x01, 0x57])),
+            vec!["method `run()V`: pop at offset 1 falls off the end of the code"]
+        );
+
+        // iconst_0, iconst_0, ifeq 6, pop, return, with an empty stack in the frame at 6
+        let mut branch = code(vec![0x03, 0x03, 0x99, 0, 4, 0x57, opcodes::RETURN]);
+        if let AttributeKind::Code { attributes, .. } = &mut branch.kind {
+            attributes.push(Attribute {
+                attribute_name_index: 9,
+                attribute_length: 0,
+                kind: AttributeKind::StackMapTable {
+                    number_of_entries: 1,
+                    entries: vec![StackMapFrame {
+                        frame_type: 6,
+                        kind: StackMapFrameKind::Same,
+                    }],
+                },
+            });
+        }
+        assert_eq!(
+            messages(branch),
+            vec!["method `run()V`: ifeq at offset 2 branches to 6, but the stack has depth 1 where the frame has depth 0"]
+        );
+    }
+}